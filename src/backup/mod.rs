@@ -0,0 +1,183 @@
+use rss::Item;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{error::Error, fs, fs::OpenOptions, io::Write, path::Path, time::Duration};
+
+use crate::{
+    bookmarks::Bookmarks, history::Listen, played::Played, queue::Queue, stars::Star,
+    subscriptions::Subscriptions,
+};
+
+// Format of backups. Bump it when a change to the data can't be read from older backups.
+pub const VERSION: i64 = 2;
+
+// Position is how far into an episode playback got
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub feed: String,
+    // kept whole, as the database keeps positions along with their episode
+    pub item: Item,
+    pub position: Duration,
+}
+
+// Backup is all of the user's data in a single file, to move it to another machine. It's
+// JSON, so that it can be looked into.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Backup {
+    pub version: i64,
+    // when it was made, in seconds since the epoch
+    pub created: i64,
+    pub subscriptions: Subscriptions,
+    pub played: Played,
+    pub positions: Vec<Position>,
+    pub queue: Queue,
+    pub bookmarks: Bookmarks,
+    #[serde(default)]
+    pub stars: Vec<Star>,
+    #[serde(default)]
+    pub history: Vec<Listen>,
+}
+
+impl Backup {
+    // Private feed URLs keep their tokens, so the file is only readable by the user
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options
+            .open(path)?
+            .write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Backup, Box<dyn Error>> {
+        parse(fs::read_to_string(path)?.as_str())
+    }
+}
+
+// Read a backup, upgrading one made by an older release
+fn parse(json: &str) -> Result<Backup, Box<dyn Error>> {
+    let mut v: Value = serde_json::from_str(json)?;
+    let version = v.get("version").and_then(Value::as_i64).unwrap_or(0);
+    if version > VERSION {
+        return Err(format!(
+            "the backup was made by a newer release (version {}), update the app",
+            version
+        )
+        .into());
+    }
+    // `auto_download` went from on or off to how many episodes to download
+    if version < 2 {
+        let feeds = v
+            .pointer_mut("/subscriptions/feeds")
+            .and_then(Value::as_array_mut);
+        for f in feeds.into_iter().flatten() {
+            if let Some(on) = f.get("auto_download").and_then(Value::as_bool) {
+                f["auto_download"] = usize::from(on).into();
+            }
+        }
+    }
+    Ok(serde_json::from_value(v)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        bookmarks::Bookmarks,
+        history::Listen,
+        played::Played,
+        queue::Queue,
+        stars::Star,
+        store::db::{
+            tests::{item, FEED},
+            Db,
+        },
+        subscriptions::Subscriptions,
+    };
+
+    use super::{parse, Backup, Position, VERSION};
+
+    #[test]
+    fn moves_everything() {
+        let db = Db::open_in_memory().unwrap();
+        db.save_position(FEED, &item("1"), Duration::from_secs(90))
+            .unwrap();
+        db.save_position(FEED, &item("2"), Duration::ZERO).unwrap();
+        db.save_star(&Star {
+            item: item("1"),
+            feed: FEED.to_string(),
+            podcast: None,
+            starred: 1,
+        })
+        .unwrap();
+        db.save_listen(&Listen {
+            item: item("1"),
+            feed: FEED.to_string(),
+            podcast: None,
+            started: 1,
+            stopped: 2,
+            position: Duration::from_secs(90),
+        })
+        .unwrap();
+
+        let mut subscriptions = Subscriptions::default();
+        subscriptions.add(FEED, "Example");
+        let mut played = Played::default();
+        played.mark_all(FEED, ["3"]);
+        let b = Backup {
+            version: VERSION,
+            created: 1,
+            subscriptions,
+            played,
+            positions: db.positions().unwrap(),
+            queue: Queue::default(),
+            bookmarks: Bookmarks::default(),
+            stars: db.load_stars().unwrap(),
+            history: db.load_listens(None).unwrap(),
+        };
+        // only the episodes that were started
+        assert_eq!(
+            b.positions,
+            vec![Position {
+                feed: FEED.to_string(),
+                item: item("1"),
+                position: Duration::from_secs(90),
+            }]
+        );
+
+        let json = serde_json::to_string(&b).unwrap();
+        let restored = parse(json.as_str()).unwrap();
+        assert_eq!(restored, b);
+
+        // replaces what was in the other database
+        let mut other = Db::open_in_memory().unwrap();
+        other
+            .save_position(FEED, &item("4"), Duration::from_secs(5))
+            .unwrap();
+        other.restore(&restored).unwrap();
+        assert_eq!(other.positions().unwrap(), b.positions);
+        assert_eq!(other.load_stars().unwrap(), b.stars);
+        assert_eq!(other.load_listens(None).unwrap(), b.history);
+        assert_eq!(other.load_played().unwrap(), b.played);
+    }
+
+    #[test]
+    fn upgrades_old_backups() {
+        let old = r#"{
+            "version": 1,
+            "created": 1,
+            "subscriptions": {"feeds": [{"url": "https://example.com/feed", "title": "Example", "auto_download": true}]},
+            "played": {},
+            "positions": [],
+            "queue": {},
+            "bookmarks": {}
+        }"#;
+        let b = parse(old).unwrap();
+        assert_eq!(b.subscriptions.get(FEED).unwrap().auto_download, 1);
+
+        assert!(parse(r#"{"version": 3}"#).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use rss::Item;
+use serde::{Deserialize, Serialize};
+use std::{error::Error, time::Duration};
+
+use crate::{feed::episode_id, store, ui::fmt_duration};
+
+// Bookmark is a moment in an episode to come back to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    // kept whole, so that it can be played again without loading its feed
+    pub item: Item,
+    pub position: Duration,
+    #[serde(default)]
+    pub note: Option<String>,
+    // title of the podcast, and the URL of its feed
+    #[serde(default)]
+    pub podcast: Option<String>,
+    #[serde(default)]
+    pub feed: Option<String>,
+    // when it was made, in seconds since the epoch
+    pub created: i64,
+}
+
+impl Bookmark {
+    pub fn title(&self) -> &str {
+        self.item.title().unwrap_or("Title missing!")
+    }
+
+    // e.g. "Episode 1 at 12:34: the wine list"
+    pub fn describe(&self) -> String {
+        let mut s = format!("{} at {}", self.title(), fmt_duration(self.position));
+        if let Some(note) = self.note.as_deref() {
+            s.push_str(": ");
+            s.push_str(note);
+        }
+        s
+    }
+
+    fn is_at(&self, item: &Item, position: Duration) -> bool {
+        episode_id(&self.item) == episode_id(item) && self.position.as_secs() == position.as_secs()
+    }
+}
+
+// Bookmarks are kept in the order they were made, stored as `bookmarks` in the store
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmarks {
+    #[serde(default)]
+    bookmarks: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    // Add a bookmark. One at the same second of the same episode is replaced, so that
+    // adding a note to a bookmark just made doesn't leave a copy without it.
+    pub fn add(&mut self, b: Bookmark) {
+        self.bookmarks
+            .retain(|other| !other.is_at(&b.item, b.position));
+        self.bookmarks.push(b);
+    }
+
+    pub fn remove(&mut self, idx: usize) -> Option<Bookmark> {
+        if idx >= self.bookmarks.len() {
+            return None;
+        }
+        Some(self.bookmarks.remove(idx))
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&Bookmark> {
+        self.bookmarks.get(idx)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Bookmark> {
+        self.bookmarks.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bookmarks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty()
+    }
+
+    pub fn load() -> Result<Bookmarks, Box<dyn Error>> {
+        store::load("bookmarks")
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        store::save("bookmarks", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rss::{Guid, Item};
+    use std::time::Duration;
+
+    use super::{Bookmark, Bookmarks};
+
+    fn bookmark(id: &str, secs: f64, note: Option<&str>) -> Bookmark {
+        Bookmark {
+            item: Item {
+                title: Some(format!("Episode {}", id)),
+                guid: Some(Guid {
+                    value: id.to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            position: Duration::from_secs_f64(secs),
+            note: note.map(str::to_string),
+            podcast: Some("Wine About It".to_string()),
+            feed: Some("https://example.com/feed".to_string()),
+            created: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn replaces_bookmark_at_same_moment() {
+        let mut b = Bookmarks::default();
+        b.add(bookmark("1", 754.2, None));
+        b.add(bookmark("2", 754.2, None));
+        b.add(bookmark("1", 754.8, Some("the wine list")));
+
+        assert_eq!(b.len(), 2);
+        assert_eq!(
+            b.get(1).unwrap().describe(),
+            "Episode 1 at 12:34: the wine list"
+        );
+        assert_eq!(b.get(0).unwrap().describe(), "Episode 2 at 12:34");
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut b = Bookmarks::default();
+        b.add(bookmark("1", 60.0, Some("note")));
+        b.add(bookmark("2", 0.0, None));
+
+        let serialized = toml::to_string(&b).unwrap();
+        assert_eq!(toml::from_str::<Bookmarks>(serialized.as_str()).unwrap(), b);
+    }
+}
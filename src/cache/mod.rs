@@ -0,0 +1,99 @@
+use rss::Channel;
+use std::{
+    error::Error,
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+use url::Url;
+
+// The cache keeps the last copy of every feed fetched in `$XDG_CACHE_HOME/podcasts/feeds`,
+// so podcasts can be shown right away while they are fetched again. A feed's file is named
+// after a hash of its URL, since private feed URLs contain tokens, and the time it was last
+// written is when the feed was last refreshed.
+fn path(u: &Url) -> Option<PathBuf> {
+    dir().map(|d| {
+        d.join("feeds")
+            .join(format!("{:016x}.xml", hash(u.as_str())))
+    })
+}
+
+pub fn dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|d| d.join("podcasts"))
+}
+
+// FNV-1a, which unlike std's hasher is guaranteed to stay the same between releases
+fn hash(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+pub fn load(u: &Url) -> Option<Channel> {
+    let content = fs::read(path(u)?).ok()?;
+    Channel::read_from(&content[..]).ok()
+}
+
+// Whether the content is the same as the cached copy
+pub fn is_current(u: &Url, content: &[u8]) -> bool {
+    path(u)
+        .and_then(|p| fs::read(p).ok())
+        .map(|cached| cached == content)
+        .unwrap_or_default()
+}
+
+pub fn save(u: &Url, content: &[u8]) -> Result<(), Box<dyn Error>> {
+    let p = path(u).ok_or("no cache directory")?;
+    if let Some(dir) = p.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(p, content)?;
+    Ok(())
+}
+
+// Size of the feed's cached copy, if there is one
+pub fn size(u: &Url) -> Option<u64> {
+    Some(fs::metadata(path(u)?).ok()?.len())
+}
+
+// Forget the feed's cached copy. It's fetched again the next time it's shown.
+pub fn remove(u: &Url) -> Result<(), Box<dyn Error>> {
+    match fs::remove_file(path(u).ok_or("no cache directory")?) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+// Forget every cached feed
+pub fn clear() -> Result<(), Box<dyn Error>> {
+    match fs::remove_dir_all(dir().ok_or("no cache directory")?.join("feeds")) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+// How long ago the feed was last refreshed, if it ever was
+pub fn age(u: &Url) -> Option<Duration> {
+    let modified = fs::metadata(path(u)?).ok()?.modified().ok()?;
+    // a clock that went backwards counts as just refreshed
+    Some(
+        SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hash;
+
+    #[test]
+    fn hash_is_stable() {
+        assert_eq!(hash(""), 0xcbf29ce484222325);
+        assert_eq!(hash("a"), 0xaf63dc4c8601ec8c);
+        assert_ne!(
+            hash("https://example.com/feed"),
+            hash("https://example.com/feed2")
+        );
+    }
+}
@@ -0,0 +1,376 @@
+use rss::Item;
+use serde::Deserialize;
+use std::{error::Error, io::Cursor, time::Duration};
+use url::Url;
+
+use crate::feed::fetch;
+
+// Going back to the previous chapter this soon after a chapter started skips to the one
+// before it instead of the start of the current one, like the previous track button
+const RESTART_WINDOW: f64 = 3.0;
+
+// Words in a chapter title that mark it as an ad. Chapters files have no field for this,
+// so podcasts that mark their ads do it in the title, like "Ad break" or "Sponsor: ...".
+const AD_WORDS: [&str; 9] = [
+    "ad",
+    "ads",
+    "advert",
+    "adverts",
+    "advertisement",
+    "sponsor",
+    "sponsors",
+    "sponsored",
+    "promo",
+];
+
+// A single chapter from a Podcasting 2.0 chapters file
+// (https://github.com/Podcastindex-org/podcast-namespace/blob/main/chapters/jsonChapters.md)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Chapter {
+    // seconds from the start of the episode
+    #[serde(rename = "startTime")]
+    pub start_time: f64,
+    pub title: Option<String>,
+    // artwork for this chapter
+    pub img: Option<String>,
+    pub url: Option<String>,
+    // where the chapter ends, when it doesn't run until the next one
+    #[serde(rename = "endTime", default)]
+    pub end_time: Option<f64>,
+}
+
+impl Chapter {
+    pub fn is_ad(&self) -> bool {
+        self.title.as_deref().is_some_and(|t| {
+            t.split(|c: char| !c.is_alphanumeric())
+                .any(|w| AD_WORDS.iter().any(|ad| w.eq_ignore_ascii_case(ad)))
+        })
+    }
+}
+
+// Whether the seconds are a time that can be played from
+fn is_time(secs: f64) -> bool {
+    Duration::try_from_secs_f64(secs).is_ok()
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+pub struct Chapters {
+    #[serde(default)]
+    pub chapters: Vec<Chapter>,
+}
+
+impl Chapters {
+    pub fn parse(s: &[u8]) -> Result<Chapters, Box<dyn Error>> {
+        let mut c: Chapters = serde_json::from_slice(s)?;
+        // a start that isn't a time in the episode, like -1 or NaN, can't be played from
+        c.chapters.retain(|c| is_time(c.start_time));
+        // and an end like that is as good as none
+        for ch in c.chapters.iter_mut() {
+            ch.end_time = ch.end_time.filter(|e| is_time(*e));
+        }
+        // the spec doesn't require the chapters to be ordered
+        c.chapters
+            .sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+        Ok(c)
+    }
+
+    // Chapters embedded in the ID3 tag of an MP3 file, if it has any
+    pub fn from_id3(file: &[u8]) -> Option<Chapters> {
+        let tag = id3::Tag::read_from2(Cursor::new(file)).ok()?;
+        let mut chapters = tag
+            .chapters()
+            .map(|c| Chapter {
+                start_time: c.start_time as f64 / 1000.0,
+                title: c
+                    .frames
+                    .iter()
+                    .find(|f| f.id() == "TIT2")
+                    .and_then(|f| f.content().text())
+                    .map(str::to_string),
+                img: None,
+                url: None,
+                end_time: Some(c.end_time as f64 / 1000.0),
+            })
+            .collect::<Vec<Chapter>>();
+        if chapters.is_empty() {
+            return None;
+        }
+        chapters.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+        Some(Chapters { chapters })
+    }
+
+    pub fn len(&self) -> usize {
+        self.chapters.len()
+    }
+
+    // The chapter after the one playing at the given position
+    pub fn next(&self, position: Duration) -> Option<&Chapter> {
+        let secs = position.as_secs_f64();
+        self.chapters.iter().find(|c| c.start_time > secs)
+    }
+
+    // The start of the chapter playing at the given position, or the chapter before it
+    // if that only just started
+    pub fn previous(&self, position: Duration) -> Option<&Chapter> {
+        let secs = position.as_secs_f64();
+        let idx = self.chapters.iter().rposition(|c| c.start_time <= secs)?;
+        if secs - self.chapters[idx].start_time < RESTART_WINDOW && idx > 0 {
+            return self.chapters.get(idx - 1);
+        }
+        self.chapters.get(idx)
+    }
+
+    // The chapter that is playing at the given position
+    pub fn at(&self, position: Duration) -> Option<&Chapter> {
+        let secs = position.as_secs_f64();
+        self.chapters.iter().rev().find(|c| c.start_time <= secs)
+    }
+
+    // If an ad is playing at the given position, the ad and where it ends. Ads back to
+    // back are skipped together. None for the end means the ad runs until the end of
+    // the episode.
+    pub fn ad_at(&self, position: Duration) -> Option<(&Chapter, Option<f64>)> {
+        let secs = position.as_secs_f64();
+        let idx = self.chapters.iter().rposition(|c| c.start_time <= secs)?;
+        let ad = &self.chapters[idx];
+        if !ad.is_ad() || ad.end_time.is_some_and(|e| e <= secs) {
+            return None;
+        }
+        let mut end = ad.end_time;
+        for c in &self.chapters[idx + 1..] {
+            if end.is_some_and(|e| e < c.start_time) {
+                break;
+            }
+            if !c.is_ad() {
+                // an ad ends where the next chapter starts, unless it says otherwise
+                return Some((ad, end.or(Some(c.start_time))));
+            }
+            end = c.end_time;
+        }
+        Some((ad, end))
+    }
+
+    // The image to show at the given position. Chapters without their own image keep
+    // showing the image of the closest earlier chapter that has one.
+    pub fn image_at(&self, position: Duration) -> Option<&str> {
+        let secs = position.as_secs_f64();
+        self.chapters
+            .iter()
+            .rev()
+            .filter(|c| c.start_time <= secs)
+            .find_map(|c| c.img.as_deref())
+    }
+
+    // Distinct image URLs across all chapters, in chapter order
+    pub fn images(&self) -> Vec<&str> {
+        let mut images: Vec<&str> = vec![];
+        for img in self.chapters.iter().filter_map(|c| c.img.as_deref()) {
+            if !images.contains(&img) {
+                images.push(img);
+            }
+        }
+        images
+    }
+}
+
+// The `<podcast:chapters url="..." type="application/json+chapters" />` link of an episode
+pub fn chapters_url(item: &Item) -> Option<Url> {
+    item.extensions()
+        .get("podcast")
+        .and_then(|ns| ns.get("chapters"))
+        .and_then(|exts| exts.first())
+        .and_then(|ext| ext.attrs().get("url"))
+        .and_then(|u| Url::parse(u).ok())
+}
+
+pub async fn get_chapters(u: Url) -> Result<Chapters, Box<dyn Error>> {
+    let content = fetch(&u).await?;
+    Chapters::parse(&content[..])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, time::Duration};
+
+    use id3::TagLike;
+    use rss::{extension::Extension, Item};
+
+    use super::{chapters_url, Chapters};
+
+    const CHAPTERS: &str = r#"{
+        "version": "1.2.0",
+        "chapters": [
+            { "startTime": 120.5, "title": "Wine", "img": "https://example.com/wine.png" },
+            { "startTime": 0, "title": "Intro", "img": "https://example.com/intro.png" },
+            { "startTime": 300, "title": "No image" },
+            { "startTime": 400, "title": "Outro", "img": "https://example.com/intro.png" }
+        ]
+    }"#;
+
+    #[test]
+    fn parses_and_sorts() {
+        let c = Chapters::parse(CHAPTERS.as_bytes()).unwrap();
+        let titles = c
+            .chapters
+            .iter()
+            .map(|c| c.title.as_deref().unwrap())
+            .collect::<Vec<&str>>();
+        assert_eq!(titles, vec!["Intro", "Wine", "No image", "Outro"]);
+    }
+
+    #[test]
+    fn drops_malformed_starts() {
+        let c = Chapters::parse(
+            br#"{"chapters": [
+                { "startTime": -5, "title": "Before" },
+                { "startTime": 1e300, "title": "Far" },
+                { "startTime": 60, "title": "Wine" }
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(c.len(), 1);
+        assert_eq!(c.chapters[0].title.as_deref(), Some("Wine"));
+    }
+
+    #[test]
+    fn drops_malformed_ends() {
+        let c = Chapters::parse(
+            br#"{"chapters": [
+                { "startTime": 0, "title": "Ad", "endTime": 1e300 },
+                { "startTime": 60, "title": "Wine", "endTime": -1 }
+            ]}"#,
+        )
+        .unwrap();
+        assert!(c.chapters.iter().all(|c| c.end_time.is_none()));
+    }
+
+    #[test]
+    fn chapter_at_position() {
+        let c = Chapters::parse(CHAPTERS.as_bytes()).unwrap();
+        let title = |secs| {
+            c.at(Duration::from_secs(secs))
+                .and_then(|c| c.title.clone())
+        };
+        assert_eq!(title(0), Some("Intro".to_string()));
+        assert_eq!(title(120), Some("Intro".to_string()));
+        assert_eq!(title(121), Some("Wine".to_string()));
+        assert_eq!(title(9000), Some("Outro".to_string()));
+    }
+
+    #[test]
+    fn image_falls_back_to_earlier_chapter() {
+        let c = Chapters::parse(CHAPTERS.as_bytes()).unwrap();
+        assert_eq!(
+            c.image_at(Duration::from_secs(301)),
+            Some("https://example.com/wine.png")
+        );
+        assert_eq!(
+            c.images(),
+            vec![
+                "https://example.com/intro.png",
+                "https://example.com/wine.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn next_and_previous() {
+        let c = Chapters::parse(CHAPTERS.as_bytes()).unwrap();
+        let title = |c: Option<&super::Chapter>| c.and_then(|c| c.title.clone());
+        assert_eq!(title(c.next(Duration::ZERO)), Some("Wine".to_string()));
+        assert_eq!(title(c.next(Duration::from_secs(400))), None);
+
+        assert_eq!(
+            title(c.previous(Duration::from_secs(200))),
+            Some("Wine".to_string())
+        );
+        // just after a chapter started, go to the one before
+        assert_eq!(
+            title(c.previous(Duration::from_secs(122))),
+            Some("Intro".to_string())
+        );
+        assert_eq!(
+            title(c.previous(Duration::from_secs(1))),
+            Some("Intro".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_id3_chapters() {
+        let mut tag = id3::Tag::new();
+        for (id, start, title) in [("ch1", 60_000, "Second"), ("ch0", 0, "First")] {
+            tag.add_frame(id3::frame::Chapter {
+                element_id: id.to_string(),
+                start_time: start,
+                end_time: start + 60_000,
+                start_offset: 0xffffffff,
+                end_offset: 0xffffffff,
+                frames: vec![id3::Frame::text("TIT2", title)],
+            });
+        }
+        let mut file = vec![];
+        tag.write_to(&mut file, id3::Version::Id3v24).unwrap();
+
+        let c = Chapters::from_id3(&file).unwrap();
+        assert_eq!(c.len(), 2);
+        assert_eq!(c.chapters[0].title.as_deref(), Some("First"));
+        assert_eq!(c.chapters[1].start_time, 60.0);
+        assert_eq!(Chapters::from_id3(b"not an mp3"), None);
+    }
+
+    #[test]
+    fn finds_ads() {
+        let c = Chapters::parse(
+            br#"{
+                "chapters": [
+                    { "startTime": 0, "title": "Intro" },
+                    { "startTime": 60, "title": "Ad break" },
+                    { "startTime": 90, "title": "Sponsor: Squarespace" },
+                    { "startTime": 120, "title": "Adventures in wine" },
+                    { "startTime": 600, "title": "Sponsored", "endTime": 630 },
+                    { "startTime": 700, "title": "Promo" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let ad = |secs| c.ad_at(Duration::from_secs(secs)).map(|(_, end)| end);
+
+        assert_eq!(ad(30), None);
+        // back to back ads are skipped together
+        assert_eq!(ad(61), Some(Some(120.0)));
+        assert_eq!(ad(95), Some(Some(120.0)));
+        assert_eq!(ad(121), None);
+        assert_eq!(ad(601), Some(Some(630.0)));
+        assert_eq!(ad(640), None);
+        // the last chapter runs until the end of the episode
+        assert_eq!(ad(701), Some(None));
+    }
+
+    #[test]
+    fn no_chapters() {
+        let c = Chapters::parse(b"{}").unwrap();
+        assert_eq!(c.at(Duration::ZERO), None);
+        assert_eq!(c.image_at(Duration::ZERO), None);
+    }
+
+    #[test]
+    fn finds_chapters_url() {
+        let mut ext = Extension::default();
+        ext.set_name("podcast:chapters");
+        ext.attrs = BTreeMap::from([(
+            "url".to_string(),
+            "https://example.com/chapters.json".to_string(),
+        )]);
+        let mut item = Item::default();
+        item.set_extensions(BTreeMap::from([(
+            "podcast".to_string(),
+            BTreeMap::from([("chapters".to_string(), vec![ext])]),
+        )]));
+
+        assert_eq!(
+            chapters_url(&item).map(|u| u.to_string()),
+            Some("https://example.com/chapters.json".to_string())
+        );
+        assert_eq!(chapters_url(&Item::default()), None);
+    }
+}
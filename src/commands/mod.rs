@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+use crate::{feed::private::redact_text, store};
+
+// Most commands remembered, the oldest are forgotten first
+const MAX_COMMANDS: usize = 500;
+
+// Commands is the history of commands typed in the input box, oldest first, to recall
+// them with Up and Down
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Commands {
+    entries: Vec<String>,
+}
+
+impl Commands {
+    // Remember a command, moving it to the end if it was typed before. Ones with a private
+    // feed URL aren't, so that its token isn't written to disk.
+    pub fn push(&mut self, cmd: &str) {
+        if is_private(cmd) {
+            return;
+        }
+        self.entries.retain(|e| e != cmd);
+        self.entries.push(cmd.to_string());
+        if self.entries.len() > MAX_COMMANDS {
+            self.entries.remove(0);
+        }
+    }
+
+    // The latest command before `before`, or of all, starting with the prefix, and where
+    // it is
+    pub fn older(&self, before: Option<usize>, prefix: &str) -> Option<(usize, &str)> {
+        let end = before.unwrap_or(self.entries.len()).min(self.entries.len());
+        self.entries[..end]
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, e)| e.starts_with(prefix))
+            .map(|(i, e)| (i, e.as_str()))
+    }
+
+    // The first command after `after` starting with the prefix, and where it is
+    pub fn newer(&self, after: usize, prefix: &str) -> Option<(usize, &str)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .skip(after + 1)
+            .find(|(_, e)| e.starts_with(prefix))
+            .map(|(i, e)| (i, e.as_str()))
+    }
+
+    pub fn load() -> Result<Commands, Box<dyn Error>> {
+        store::load("commands")
+    }
+
+    // Saves the commands except ones with a private feed URL, which files from before
+    // they were left out may have
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let entries = self.entries.iter().filter(|e| !is_private(e)).cloned();
+        store::save(
+            "commands",
+            &Commands {
+                entries: entries.collect(),
+            },
+        )
+    }
+}
+
+fn is_private(cmd: &str) -> bool {
+    redact_text(cmd) != cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Commands;
+
+    #[test]
+    fn recalls_by_prefix() {
+        let mut c = Commands::default();
+        for cmd in ["/search wine", "/load x", "/search beer", "/search wine"] {
+            c.push(cmd);
+        }
+        // typed again, so it moved to the end
+        assert_eq!(c.entries.len(), 3);
+        assert_eq!(c.older(None, "/s"), Some((2, "/search wine")));
+        assert_eq!(c.older(Some(2), "/s"), Some((1, "/search beer")));
+        assert_eq!(c.older(Some(1), "/s"), None);
+        assert_eq!(c.newer(1, "/s"), Some((2, "/search wine")));
+        assert_eq!(c.newer(2, "/"), None);
+    }
+
+    #[test]
+    fn forgets_private_feeds() {
+        let mut c = Commands::default();
+        c.push("/load https://www.patreon.com/rss/wine?auth=secret");
+        c.push("/load https://example.com/feed");
+        assert_eq!(c.entries, vec!["/load https://example.com/feed"]);
+    }
+}
@@ -0,0 +1,555 @@
+use serde::Deserialize;
+use std::{collections::BTreeMap, error::Error, fs, path::PathBuf, time::Duration};
+
+use crate::{
+    downloads::{self, Retention},
+    media::{self, Kind},
+    store,
+    subscriptions::Subscription,
+};
+
+// Config holds user preferences loaded from `$XDG_CONFIG_HOME/podcasts/config.toml`.
+// Every field has a default, so a missing file or missing keys are fine.
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // Screen-reader friendly rendering: no borders or decorative symbols, and
+    // state is announced in the status line instead of only being highlighted.
+    pub accessible: bool,
+    // Name of a built-in theme, see `ui::theme::THEMES`, or of a file in `themes_dir`
+    pub theme: String,
+    pub network: NetworkConfig,
+    pub handlers: HandlersConfig,
+    // Glyphs and colors for episode states in the episode list, by state name (e.g.
+    // `played`, `in_progress`), on top of the theme
+    pub episode_states: BTreeMap<String, StateConfig>,
+    pub startup: StartupConfig,
+    pub refresh: RefreshConfig,
+    pub store: StoreConfig,
+    pub display: DisplayConfig,
+    pub playback: PlaybackConfig,
+    pub downloads: DownloadsConfig,
+    // Text-to-speech command used to read show notes aloud, `speech::default_command`
+    // when empty
+    pub tts: String,
+    // Look for a newer release on GitHub at startup
+    pub check_updates: bool,
+}
+
+// How politely to talk to podcast hosts, under `[network]`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    // minimum seconds between two requests to the same host
+    pub host_interval: f64,
+    // up to this many seconds are randomly added to the interval
+    pub jitter: f64,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            host_interval: 1.0,
+            jitter: 0.5,
+        }
+    }
+}
+
+// Which subscriptions to refresh when the app starts
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Refresh {
+    None,
+    // only the ones last refreshed longer ago than `stale_after`
+    #[default]
+    Stale,
+    All,
+}
+
+// Look of an episode state, under `[episode_states]`
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct StateConfig {
+    pub glyph: Option<String>,
+    // a color name or "#rrggbb"
+    pub color: Option<String>,
+}
+
+// What happens at launch, under `[startup]`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct StartupConfig {
+    pub refresh: Refresh,
+    // hours after which a feed is stale
+    pub stale_after: f64,
+    // go back to where the app was when it was last closed
+    pub restore: bool,
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        StartupConfig {
+            refresh: Refresh::default(),
+            stale_after: 6.0,
+            restore: true,
+        }
+    }
+}
+
+impl StartupConfig {
+    // Whether a feed last refreshed `age` ago (never, if None) should be refreshed now
+    pub fn should_refresh(&self, age: Option<Duration>) -> bool {
+        match (self.refresh, age) {
+            (Refresh::None, _) => false,
+            (Refresh::All, _) | (Refresh::Stale, None) => true,
+            (Refresh::Stale, Some(age)) => age.as_secs_f64() >= self.stale_after * 3600.0,
+        }
+    }
+}
+
+// What is shown, under `[display]`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DisplayConfig {
+    // episodes listed per page, 0 to list all of them at once
+    pub episodes: usize,
+    // list the podcasts subscribed to next to the display area, when there's room
+    pub sidebar: bool,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        DisplayConfig {
+            episodes: 100,
+            sidebar: true,
+        }
+    }
+}
+
+// How episodes are played, under `[playback]`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PlaybackConfig {
+    // speed to start at, between `player::MIN_SPEED` and `player::MAX_SPEED`
+    pub speed: f32,
+    // in percent
+    pub volume: u8,
+    // skip chapters that are ads, unless a podcast says otherwise
+    pub skip_ads: bool,
+    // seconds to fade from one episode into the next in the queue, 0 to cut to it, up to
+    // `MAX_CROSSFADE`
+    pub crossfade: f64,
+    // times in a row to pick up an episode's download again when it breaks off, before
+    // opening the episode again where it stopped
+    pub retries: u32,
+}
+
+impl Default for PlaybackConfig {
+    fn default() -> Self {
+        PlaybackConfig {
+            speed: 1.0,
+            volume: 100,
+            skip_ads: false,
+            crossfade: 0.0,
+            retries: 5,
+        }
+    }
+}
+
+// Longest fade between episodes, in seconds
+pub const MAX_CROSSFADE: f64 = 60.0;
+
+// Downloading episodes, under `[downloads]`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DownloadsConfig {
+    // directory episodes are downloaded to, `downloads` in the data directory when empty.
+    // `~/` is the home directory.
+    pub dir: String,
+    // where in the directory episodes are downloaded to, see `downloads::path_in`
+    pub template: String,
+    // how many episodes download at once, the others wait their turn
+    pub concurrent: usize,
+    // megabytes per second all downloads together stay under, 0 for no limit
+    pub max_rate: f64,
+    // downloads of each podcast to keep, the older ones are deleted. 0 keeps them all.
+    pub keep: usize,
+    // days after an episode was played to delete its download, 0 to keep it
+    pub delete_played_after: u64,
+    // list what cleaning up would delete in /cleanup instead of deleting it
+    pub review_clean_up: bool,
+    // when podcasts download their new episodes by themselves
+    pub schedule: ScheduleConfig,
+    // the schedule of each profile that has its own, by profile name
+    pub profiles: BTreeMap<String, ScheduleConfig>,
+}
+
+impl Default for DownloadsConfig {
+    fn default() -> Self {
+        DownloadsConfig {
+            dir: String::new(),
+            template: downloads::TEMPLATE.to_string(),
+            concurrent: 3,
+            max_rate: 0.0,
+            keep: 0,
+            delete_played_after: 0,
+            review_clean_up: false,
+            schedule: ScheduleConfig::default(),
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+impl DownloadsConfig {
+    // How long the podcast's downloads are kept, by its own settings or else these
+    pub fn retention(&self, s: Option<&Subscription>) -> Retention {
+        let days = s
+            .and_then(|s| s.delete_played_after)
+            .unwrap_or(self.delete_played_after);
+        Retention {
+            keep: s.and_then(|s| s.keep).unwrap_or(self.keep),
+            played_for: match days {
+                0 => None,
+                days => Some(days as i64 * 24 * 60 * 60),
+            },
+        }
+    }
+
+    pub fn dir(&self) -> Option<PathBuf> {
+        match self.dir.trim() {
+            "" => store::dir().map(|d| d.join("downloads")),
+            d => Some(crate::expand_home(d)),
+        }
+    }
+
+    // When the profile's podcasts download by themselves
+    pub fn schedule(&self, profile: Option<&str>) -> &ScheduleConfig {
+        profile
+            .and_then(|p| self.profiles.get(p))
+            .unwrap_or(&self.schedule)
+    }
+}
+
+// When podcasts download their new episodes by themselves, under `[downloads.schedule]`.
+// Episodes downloaded by hand download any time.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ScheduleConfig {
+    // hours of the day, in local time, from the first up to the second, e.g. [23, 7] for
+    // overnight. Any time when not set.
+    pub hours: Option<(u32, u32)>,
+    // wait while the connection is metered, e.g. tethered to a phone
+    pub unmetered_only: bool,
+}
+
+// Refreshing subscriptions while the app runs, under `[refresh]`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RefreshConfig {
+    // minutes between refreshes of each subscription, 0 to only refresh them at startup
+    // and on request. Podcasts can have their own interval.
+    pub interval: u64,
+}
+
+impl Default for RefreshConfig {
+    fn default() -> Self {
+        RefreshConfig { interval: 30 }
+    }
+}
+
+// How the user's data is saved, under `[store]`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct StoreConfig {
+    // seconds between saves of changes that aren't saved right away, like playback
+    // positions and the queue
+    pub autosave: u64,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        StoreConfig { autosave: 30 }
+    }
+}
+
+// Commands that open episode files, under `[handlers]`. Each is run with the file's URL as
+// its last argument. Any other key is a MIME type, e.g. `"application/pdf" = "zathura"`,
+// and takes precedence over the handler for its kind.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct HandlersConfig {
+    // empty to use the built-in player
+    pub audio: String,
+    pub video: String,
+    pub document: String,
+    #[serde(flatten)]
+    pub types: BTreeMap<String, String>,
+}
+
+impl Default for HandlersConfig {
+    fn default() -> Self {
+        HandlersConfig {
+            audio: String::new(),
+            video: media::system_opener().to_string(),
+            document: media::system_opener().to_string(),
+            types: BTreeMap::new(),
+        }
+    }
+}
+
+impl HandlersConfig {
+    // Command to open a file of the given type with, if one is configured
+    pub fn command(&self, mime: &str, kind: Kind) -> Option<&str> {
+        let by_kind = match kind {
+            Kind::Audio => self.audio.as_str(),
+            Kind::Video => self.video.as_str(),
+            Kind::Document => self.document.as_str(),
+            Kind::Unknown => "",
+        };
+        let cmd = self
+            .types
+            .get(media::essence(mime).as_str())
+            .map(String::as_str)
+            .unwrap_or(by_kind);
+        if cmd.trim().is_empty() {
+            None
+        } else {
+            Some(cmd)
+        }
+    }
+}
+
+pub fn path() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("podcasts").join("config.toml"))
+}
+
+// Where the user's own themes are, one `<name>.toml` file each
+pub fn themes_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("podcasts").join("themes"))
+}
+
+pub fn load() -> Result<Config, Box<dyn Error>> {
+    match path() {
+        Some(p) if p.exists() => parse(fs::read_to_string(p)?.as_str()),
+        _ => Ok(Config::default()),
+    }
+}
+
+pub fn parse(s: &str) -> Result<Config, Box<dyn Error>> {
+    let config: Config = toml::from_str(s)?;
+    config.validate()?;
+    Ok(config)
+}
+
+impl Config {
+    // Settings TOML can hold but that make no sense, e.g. an infinite crossfade
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        let fade = self.playback.crossfade;
+        if !(0.0..=MAX_CROSSFADE).contains(&fade) {
+            return Err(format!(
+                "crossfade must be between 0 and {} seconds, not {}",
+                MAX_CROSSFADE, fade
+            )
+            .into());
+        }
+        let d = &self.downloads;
+        let profiles = d.profiles.iter().map(|(p, s)| (Some(p), s));
+        for (profile, s) in [(None, &d.schedule)].into_iter().chain(profiles) {
+            if s.hours.is_some_and(|(from, until)| from > 23 || until > 23) {
+                let of = profile.map_or(String::new(), |p| format!(" of the {} profile", p));
+                return Err(format!("the download schedule{} has hours past 23", of).into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{parse, Config};
+    use crate::media::{self, Kind};
+
+    #[test]
+    fn parses_empty_config() {
+        assert_eq!(parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn parses_accessible() {
+        let config = parse("accessible = true").unwrap();
+        assert!(config.accessible);
+    }
+
+    #[test]
+    fn parses_theme() {
+        let config = parse("theme = \"high-contrast\"").unwrap();
+        assert_eq!(config.theme, "high-contrast");
+    }
+
+    #[test]
+    fn parses_playback() {
+        assert_eq!(parse("").unwrap().playback.speed, 1.0);
+        assert_eq!(
+            parse("[playback]\nspeed = 1.5").unwrap().playback.speed,
+            1.5
+        );
+        assert!(
+            parse("[playback]\nskip_ads = true")
+                .unwrap()
+                .playback
+                .skip_ads
+        );
+        assert_eq!(
+            parse("[playback]\ncrossfade = 5")
+                .unwrap()
+                .playback
+                .crossfade,
+            5.0
+        );
+        for fade in ["-1", "inf", "nan", "61"] {
+            assert!(parse(format!("[playback]\ncrossfade = {}", fade).as_str()).is_err());
+        }
+        assert_eq!(parse("").unwrap().playback.retries, 5);
+        assert_eq!(
+            parse("[playback]\nretries = 0").unwrap().playback.retries,
+            0
+        );
+    }
+
+    #[test]
+    fn parses_network() {
+        let config = parse("[network]\nhost_interval = 2.5").unwrap();
+        assert_eq!(config.network.host_interval, 2.5);
+        // unset keys keep their defaults
+        assert_eq!(config.network.jitter, 0.5);
+    }
+
+    #[test]
+    fn startup_refresh() {
+        let hours = |h: u64| Some(Duration::from_secs(h * 3600));
+
+        let stale = parse("[startup]\nstale_after = 2").unwrap().startup;
+        assert!(stale.should_refresh(None));
+        assert!(stale.should_refresh(hours(3)));
+        assert!(!stale.should_refresh(hours(1)));
+
+        let all = parse("[startup]\nrefresh = \"all\"").unwrap().startup;
+        assert!(all.should_refresh(hours(0)));
+
+        let none = parse("[startup]\nrefresh = \"none\"").unwrap().startup;
+        assert!(!none.should_refresh(None));
+
+        assert!(stale.restore);
+        assert!(!parse("[startup]\nrestore = false").unwrap().startup.restore);
+    }
+
+    #[test]
+    fn parses_refresh() {
+        assert_eq!(parse("").unwrap().refresh.interval, 30);
+        assert_eq!(
+            parse("[refresh]\ninterval = 0").unwrap().refresh.interval,
+            0
+        );
+    }
+
+    #[test]
+    fn parses_display() {
+        assert_eq!(parse("").unwrap().display.episodes, 100);
+        assert_eq!(
+            parse("[display]\nepisodes = 0").unwrap().display.episodes,
+            0
+        );
+        assert!(parse("").unwrap().display.sidebar);
+        assert!(!parse("[display]\nsidebar = false").unwrap().display.sidebar);
+    }
+
+    #[test]
+    fn parses_store() {
+        assert_eq!(parse("").unwrap().store.autosave, 30);
+        assert_eq!(parse("[store]\nautosave = 5").unwrap().store.autosave, 5);
+    }
+
+    #[test]
+    fn parses_downloads() {
+        assert_eq!(parse("").unwrap().downloads.dir, "");
+        assert_eq!(parse("").unwrap().downloads.concurrent, 3);
+        let config = parse("[downloads]\ndir = \"/tmp/podcasts\"\nconcurrent = 1").unwrap();
+        assert_eq!(config.downloads.concurrent, 1);
+        assert_eq!(config.downloads.template, "{podcast}/{title}.{ext}");
+        assert_eq!(
+            config.downloads.dir(),
+            Some(std::path::PathBuf::from("/tmp/podcasts"))
+        );
+        let config = parse("[downloads]\nkeep = 3\ndelete_played_after = 7").unwrap();
+        assert_eq!(config.downloads.keep, 3);
+        assert_eq!(config.downloads.delete_played_after, 7);
+        assert!(!config.downloads.review_clean_up);
+
+        // podcasts can have their own
+        let mut subs = crate::subscriptions::Subscriptions::default();
+        subs.add("https://example.com/feed", "Example");
+        let s = subs.get_mut("https://example.com/feed").unwrap();
+        s.delete_played_after = Some(0);
+        let r = config.downloads.retention(Some(s));
+        assert_eq!((r.keep, r.played_for), (3, None));
+        s.keep = Some(5);
+        assert_eq!(config.downloads.retention(Some(s)).keep, 5);
+        assert_eq!(
+            config.downloads.retention(None).played_for,
+            Some(7 * 24 * 60 * 60)
+        );
+        let config = parse("[downloads]\nmax_rate = 2.5").unwrap();
+        assert_eq!(config.downloads.max_rate, 2.5);
+
+        let config = parse(
+            "[downloads.schedule]\nhours = [23, 7]\n[downloads.profiles.kids]\nunmetered_only = true",
+        )
+        .unwrap();
+        assert_eq!(config.downloads.schedule(None).hours, Some((23, 7)));
+        assert!(parse("[downloads.schedule]\nhours = [22, 24]").is_err());
+        assert!(parse("[downloads.profiles.kids]\nhours = [25, 7]").is_err());
+        assert_eq!(config.downloads.schedule(Some("work")).hours, Some((23, 7)));
+        let kids = config.downloads.schedule(Some("kids"));
+        assert_eq!(kids.hours, None);
+        assert!(kids.unmetered_only);
+    }
+
+    #[test]
+    fn parses_tts() {
+        let config = parse("tts = \"espeak -s 200\"").unwrap();
+        assert_eq!(config.tts, "espeak -s 200");
+    }
+
+    #[test]
+    fn parses_handlers() {
+        let config = parse(
+            r#"[handlers]
+            video = "mpv --no-terminal"
+            "application/pdf" = "zathura"
+            "#,
+        )
+        .unwrap();
+        let h = &config.handlers;
+        assert_eq!(
+            h.command("video/mp4", Kind::Video),
+            Some("mpv --no-terminal")
+        );
+        assert_eq!(
+            h.command("application/pdf", Kind::Document),
+            Some("zathura")
+        );
+        assert_eq!(
+            h.command("application/epub+zip", Kind::Document),
+            Some(media::system_opener())
+        );
+        assert_eq!(h.command("audio/mpeg", Kind::Audio), None);
+        assert_eq!(h.command("application/zip", Kind::Unknown), None);
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        assert!(parse("accessible = \"yes please\"").is_err());
+    }
+}
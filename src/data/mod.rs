@@ -1,37 +1,177 @@
-use std::sync::mpsc::{Receiver, Sender};
+use rss::Channel;
+use std::{
+    sync::mpsc::{Receiver, Sender},
+    thread,
+};
+use tokio::task::JoinSet;
 
 use tracing::{debug, error, info, instrument};
 
 use crate::{
-    feed::get_feed,
+    cache,
+    chapters::get_chapters,
+    downloads,
+    feed::{
+        artwork, content_type, get_feed, get_feed_progressively, get_image,
+        private::{self, redact, redact_text},
+    },
     message::{DisplayAction, Request, Response},
-    ui::input::Command,
-    App,
+    metrics::Fetch,
+    search::search,
+    ui::{
+        input::Command,
+        theme::{self, Theme, THEMES},
+        SEARCH_ACTIONS,
+    },
+    update, App,
 };
 
 #[tokio::main]
 #[instrument]
 pub async fn handle_background_request(responder: &Sender<Response>, receiver: &Receiver<Request>) {
     if let Ok(r) = receiver.try_recv() {
-        info!("Request type: {:?}", r);
         match r {
             Request::Feed(u) => {
-                info!("received feed request");
-                if let Ok(c) = get_feed(u).await {
-                    // TODO: error handling
-                    let res = responder.send(Response::Feed(c));
-                    if res.is_err() {
-                        error!("failed to send message: {:?}", res.unwrap_err());
+                info!("received feed request for {}", redact(&u));
+                if let Err(e) = responder.send(Response::Loading(u.clone())) {
+                    error!("failed to send message: {:?}", e);
+                }
+                let on_part = |part| {
+                    if let Err(e) = responder.send(Response::FeedPart(part)) {
+                        error!("failed to send message: {:?}", e);
+                    }
+                };
+                match get_feed_progressively(u.clone(), on_part).await {
+                    Ok((c, fetch)) => {
+                        send_fetch(responder, u, &c, fetch);
+                        let art = artwork(&c).map(str::to_string);
+                        if let Err(e) = responder.send(Response::Feed(c)) {
+                            error!("failed to send message: {:?}", e);
+                        }
+                        if let Some(art) = art {
+                            send_image(responder, art).await;
+                        }
+                    }
+                    Err(e) => {
+                        let msg = format!("Couldn't load the feed: {}", e);
+                        if let Err(e) = responder.send(Response::FeedFailed(u, msg)) {
+                            error!("failed to send message: {:?}", e);
+                        }
                     }
                 }
             }
             Request::Episode(e) => {
                 info!("received episode request");
                 if let Some(i) = e {
-                    // don't need to load anything, just pass it back to the UI
-                    let res = responder.send(Response::Episode(i));
-                    if res.is_err() {
-                        error!("failed to send message {:?}", res.unwrap_err());
+                    // the UI falls back to the podcast's artwork, which was loaded with
+                    // the feed, so only the episode's own artwork needs loading
+                    let art = i.itunes_ext().and_then(|e| e.image()).map(str::to_string);
+                    if let Err(e) = responder.send(Response::Episode(i)) {
+                        error!("failed to send message {:?}", e);
+                    }
+                    if let Some(art) = art {
+                        send_image(responder, art).await;
+                    }
+                }
+            }
+            Request::Search(terms) => {
+                info!("received search request");
+                match search(terms.as_str()).await {
+                    Ok(res) => {
+                        if let Err(e) = responder.send(Response::SearchResults(res)) {
+                            error!("failed to send message {:?}", e);
+                        }
+                    }
+                    Err(e) => send_error(responder, format!("Search failed: {}", e)),
+                }
+            }
+            Request::LatestEpisode(u) => {
+                info!("received latest episode request for {}", redact(&u));
+                match get_feed(u.clone()).await {
+                    Ok((c, fetch)) => {
+                        send_fetch(responder, u.clone(), &c, fetch);
+                        // feeds list their newest episodes first
+                        if let Some(i) = c.items().first().cloned() {
+                            let podcast = c.title().to_string();
+                            if let Err(e) = responder.send(Response::LatestEpisode(u, podcast, i)) {
+                                error!("failed to send message {:?}", e);
+                            }
+                        }
+                    }
+                    Err(e) => send_error(responder, format!("Couldn't load the feed: {}", e)),
+                }
+            }
+            Request::Subscribe(u) => {
+                info!("received subscribe request for {}", redact(&u));
+                match get_feed(u.clone()).await {
+                    Ok((c, fetch)) => {
+                        send_fetch(responder, u.clone(), &c, fetch);
+                        if let Err(e) = responder.send(Response::Subscribed(u, c)) {
+                            error!("failed to send message {:?}", e);
+                        }
+                    }
+                    Err(e) => send_error(responder, format!("Couldn't subscribe: {}", e)),
+                }
+            }
+            Request::Refresh(feeds) => {
+                info!("received refresh request for {} feeds", feeds.len());
+                let responder = responder.clone();
+                thread::spawn(move || refresh_feeds(responder, feeds));
+            }
+            Request::Download(u, path, stop) => {
+                info!("received download request for {}", redact(&u));
+                let responder = responder.clone();
+                thread::spawn(move || downloads::download(responder, u, path, stop));
+            }
+            Request::ContentType(u) => {
+                info!("received content type request for {}", redact(&u));
+                let mime = content_type(&u).await.unwrap_or_else(|e| {
+                    error!("failed to get content type: {:?}", e);
+                    send_status(responder, format!("Couldn't tell the file type: {}", e));
+                    None
+                });
+                if let Err(e) = responder.send(Response::ContentType(u, mime)) {
+                    error!("failed to send message {:?}", e);
+                }
+            }
+            Request::CheckUpdate => {
+                info!("received update check request");
+                match update::check().await {
+                    Ok(Some(r)) => {
+                        if let Err(e) = responder.send(Response::Update(r)) {
+                            error!("failed to send message {:?}", e);
+                        }
+                    }
+                    Ok(None) => debug!("no newer release"),
+                    Err(e) => {
+                        error!("failed to check for updates: {:?}", e);
+                        send_status(responder, format!("Couldn't check for updates: {}", e));
+                    }
+                }
+            }
+            Request::Chapters(u) => {
+                info!("received chapters request");
+                match get_chapters(u.clone()).await {
+                    Ok(c) => {
+                        let images = c
+                            .images()
+                            .into_iter()
+                            .map(str::to_string)
+                            .collect::<Vec<String>>();
+                        if let Err(e) = responder.send(Response::Chapters(u, c)) {
+                            error!("failed to send message {:?}", e);
+                        }
+                        // load every chapter image up front, so that the UI can switch
+                        // between them as soon as a chapter starts
+                        for img in images {
+                            send_image(responder, img).await;
+                        }
+                    }
+                    Err(e) => {
+                        error!("failed to load chapters from {}: {:?}", redact(&u), e);
+                        // the error may include the URL
+                        let e = redact_text(e.to_string().as_str());
+                        send_status(responder, format!("Couldn't load the chapters: {}", e));
                     }
                 }
             }
@@ -39,6 +179,49 @@ pub async fn handle_background_request(responder: &Sender<Response>, receiver: &
     }
 }
 
+// Pass a fetched feed to the UI, along with how fetching it went
+fn send_fetch(responder: &Sender<Response>, u: url::Url, c: &Channel, fetch: Fetch) {
+    let title = c.title().to_string();
+    if let Err(e) = responder.send(Response::Fetched(u, title, fetch)) {
+        error!("failed to send message {:?}", e);
+    }
+}
+
+// Tell the user something that doesn't need their attention, like an error playback can
+// do without
+fn send_status(responder: &Sender<Response>, msg: String) {
+    if let Err(e) = responder.send(Response::Status(msg)) {
+        error!("failed to send message {:?}", e);
+    }
+}
+
+// Tell the user something went wrong
+fn send_error(responder: &Sender<Response>, msg: String) {
+    if let Err(e) = responder.send(Response::Error(msg)) {
+        error!("failed to send message {:?}", e);
+    }
+}
+
+// Load an image and pass it to the UI, which draws it once it arrives
+async fn send_image(responder: &Sender<Response>, img: String) {
+    let res = match url::Url::parse(img.as_str()) {
+        Ok(u) => get_image(u).await,
+        Err(e) => Err(e.into()),
+    };
+    match res {
+        Ok(i) => {
+            if let Err(e) = responder.send(Response::Image(img, i)) {
+                error!("failed to send message {:?}", e);
+            }
+        }
+        Err(e) => error!(
+            "failed to load image {}: {:?}",
+            redact_text(img.as_str()),
+            e
+        ),
+    }
+}
+
 #[cfg(test)]
 mod background_request {
     use std::{mem, sync::mpsc, time::Duration};
@@ -68,6 +251,11 @@ mod background_request {
 
         handle_background_request(&ui_tx, &data_rx);
 
+        // the UI is told it's loading first
+        assert!(matches!(
+            ui_rx.recv_timeout(Duration::from_secs(1)),
+            Ok(Response::Loading(_))
+        ));
         if let Ok(res) = ui_rx.recv_timeout(Duration::from_secs(1)) {
             // just make sure that it is a Feed type
             assert_eq!(
@@ -102,39 +290,251 @@ mod background_request {
     }
 }
 
-#[instrument]
+// arguments aren't recorded, they may contain private feed tokens
+#[instrument(skip_all)]
 pub fn handle_user_input(app: &mut App, sender: &Sender<Request>, i: Command) {
     match i {
         Command::FetchPodcastFeed(url) => {
-            info!("fetch podcast feed: {}", url);
+            info!("fetch podcast feed: {}", redact_text(url.as_str()));
             if let Ok(u) = url::Url::parse(url.as_str()) {
-                info!("Fetch RSS feed from {url}", url = u);
-                let res = sender.send(Request::Feed(u));
-                if res.is_err() {
-                    error!("failed to send message {:?}", res.unwrap_err());
-                }
-                app.display_action = DisplayAction::ListEpisodes;
+                load_feed(app, sender, u);
+            }
+        }
+        Command::Search(terms) => {
+            info!("search for {}", terms);
+            app.search_results.clear();
+            app.search_state.select(None);
+            app.search_menu = false;
+            if let Err(e) = sender.send(Request::Search(terms.clone())) {
+                error!("failed to send message {:?}", e);
+            }
+            app.show(DisplayAction::Search);
+            app.announce(format!("Searching for {}", terms));
+        }
+        Command::MarkAllPlayed => app.request_mark_played(true),
+        Command::FilterEpisodes(text) if app.channel.is_some() => {
+            app.show(DisplayAction::ListEpisodes);
+            app.filter_episodes(text.as_str());
+        }
+        Command::FilterEpisodes(_) => app.announce("Load a podcast first"),
+        Command::OpenLink(n) => app.open_link(n),
+        Command::MarkPlayed(played) => app.mark_selected(played),
+        Command::Speak => app.toggle_speech(),
+        Command::SetSpeed(speed) => app.set_speed(speed),
+        Command::SetPodcastSpeed(speed) => app.set_podcast_speed(speed),
+        Command::SetVolume(volume) => app.set_volume(volume),
+        Command::Sleep(after) => app.set_sleep(after),
+        Command::SkipIntro(secs) => app.set_skip(true, secs),
+        Command::SkipOutro(secs) => app.set_skip(false, secs),
+        Command::SkipAds(skip) => app.set_skip_ads(skip),
+        Command::Bookmark(note) => app.bookmark(note),
+        Command::ShowBookmarks => app.show_bookmarks(),
+        Command::Seek(to) => app.seek_to(to),
+        Command::OpenWith(handler) => app.open_with(handler),
+        Command::Subscribe(url) => app.request_subscribe(url, sender),
+        Command::SubscribePrivate(url) => app.request_subscribe_private(url, sender),
+        Command::Unsubscribe => app.request_unsubscribe(),
+        Command::Refresh => app.refresh_all(sender),
+        Command::ShowPodcastSettings => app.show_podcast_settings(),
+        Command::ShowStarred => app.show_starred(),
+        Command::ShowHistory => app.show_history(),
+        Command::ShowDownloads => app.show_downloads(),
+        Command::ShowStorage => app.show_storage(),
+        Command::ShowCleanUp => app.show_clean_up(),
+        Command::TagPodcast(tag) => app.tag_podcast(tag.as_str(), true),
+        Command::UntagPodcast(tag) => app.tag_podcast(tag.as_str(), false),
+        Command::FilterLibrary(tag) => app.filter_library(tag),
+        Command::ListTags => app.list_tags(),
+        Command::ArchivePodcast(archive) => app.archive_podcast(archive),
+        Command::ShowArchive => app.show_archive(),
+        Command::ExportOpml(path) => app.request_export_opml(path.as_str()),
+        Command::ExportEpisodes(path) => app.export_episodes(path.as_str()),
+        Command::Backup(path) => app.backup(path.as_str()),
+        Command::RestoreBackup(path) => app.request_restore_backup(path.as_str()),
+        Command::CancelSleep => match app.player.sleep.take() {
+            Some(_) => app.announce("Sleep timer cancelled"),
+            None => app.announce("No sleep timer set"),
+        },
+        Command::ToggleAccessibility => {
+            app.config.accessible = !app.config.accessible;
+            let state = if app.config.accessible { "on" } else { "off" };
+            info!("accessible mode {}", state);
+            app.announce(format!("Accessible mode {}", state));
+        }
+        Command::ShowQueue => {
+            app.show(DisplayAction::Queue);
+            let (total, _) = app.queue.remaining(1.0);
+            app.announce(format!(
+                "Queue: {} episodes, {} minutes",
+                app.queue.len(),
+                total.as_secs() / 60
+            ));
+        }
+        Command::ShowDiagnostics => {
+            app.show(DisplayAction::Diagnostics);
+            app.announce(format!(
+                "Feed fetches, sorted by {}. Press s to change the order",
+                app.metrics_sort.name()
+            ));
+        }
+        Command::ShowLibrary => {
+            app.show(DisplayAction::Library);
+            if app.subscriptions.archive {
+                app.subscriptions.archive = false;
+                app.library_state.select(None);
+            }
+            if app.library_state.selected().is_none() && app.list_len() > 0 {
+                app.library_state.select(Some(0));
             }
+            app.announce(format!(
+                "Library: {} podcasts, sorted by {}. Press s to change the order",
+                app.list_len(),
+                app.subscriptions.sort.name()
+            ));
         }
+        Command::MoveQueueEntry(from, to) => {
+            app.show(DisplayAction::Queue);
+            app.move_queue_entry(from, to);
+        }
+        Command::SetTheme(name) => match Theme::load(name.as_str()) {
+            Ok(t) => {
+                let t = t.customize(&app.config);
+                info!("switching to theme {}", t.name);
+                app.announce(format!("Theme {}", t.name));
+                app.theme = t;
+            }
+            Err(e) => {
+                let mut names = THEMES.map(String::from).to_vec();
+                names.extend(theme::user_themes());
+                app.announce(format!("{}, available themes: {}", e, names.join(", ")))
+            }
+        },
         _ => {
             debug!("no op {input:?}", input = i);
         }
     }
 }
 
+// Fetch a feed and show its preview
+pub fn load_feed(app: &mut App, sender: &Sender<Request>, u: url::Url) {
+    info!("Fetch RSS feed from {url}", url = redact(&u));
+    match private::detect(&u) {
+        Some(p) => app.announce(format!(
+            "Loading {} feed. Its access token is hidden on screen and in logs, \
+             keep the URL to yourself",
+            p.name()
+        )),
+        None => app.announce("Loading feed"),
+    }
+    app.feed_url = Some(u.clone());
+    // show the copy from last time while the feed loads, and never the previous feed
+    app.channel = None;
+    app.episode_filter = None;
+    if let Some(c) = app.stored_feed(&u).or_else(|| cache::load(&u)) {
+        app.show_feed(c);
+    }
+    if let Err(e) = sender.send(Request::Feed(u)) {
+        error!("failed to send message {:?}", e);
+    }
+    app.show(DisplayAction::Preview);
+}
+
+// Fetch the feeds all at once, reporting each as it's done. Feeds from the same host are
+// still spaced out by `feed::limit`. Meant to run on its own thread, so that refreshing
+// doesn't hold up requests made in the meantime.
+#[tokio::main]
+#[instrument(skip_all)]
+pub async fn refresh_feeds(responder: Sender<Response>, feeds: Vec<url::Url>) {
+    let mut tasks = JoinSet::new();
+    for u in feeds {
+        let responder = responder.clone();
+        tasks.spawn(async move {
+            let c = match get_feed(u.clone()).await {
+                Ok((c, fetch)) => {
+                    send_fetch(&responder, u.clone(), &c, fetch);
+                    Some(c)
+                }
+                Err(e) => {
+                    error!("failed to refresh {}: {:?}", redact(&u), e);
+                    None
+                }
+            };
+            if let Err(e) = responder.send(Response::Refreshed(u, c)) {
+                error!("failed to send message {:?}", e);
+            }
+        });
+    }
+    while let Some(res) = tasks.join_next().await {
+        if let Err(e) = res {
+            error!("refresh failed: {:?}", e);
+        }
+    }
+}
+
+// Enter on a search result opens its actions, and Enter on an action runs it
+#[instrument(skip_all)]
+pub fn handle_search_choice(app: &mut App, sender: &Sender<Request>) {
+    let Some(result) = app
+        .search_state
+        .selected()
+        .and_then(|idx| app.search_results.get(idx))
+        .cloned()
+    else {
+        return;
+    };
+
+    if !app.search_menu {
+        app.search_menu = true;
+        app.search_menu_state.select(Some(0));
+        app.announce(format!(
+            "Actions for {}: {}",
+            result.title,
+            SEARCH_ACTIONS.join(", ")
+        ));
+        return;
+    }
+
+    app.search_menu = false;
+    let Some(u) = result
+        .feed_url
+        .as_deref()
+        .and_then(|u| url::Url::parse(u).ok())
+    else {
+        app.announce(format!("{} has no valid feed", result.title));
+        return;
+    };
+    match app.search_menu_state.selected() {
+        // Subscribe
+        Some(0) => app.subscribe_to(u.as_str(), result.title.as_str()),
+        // Preview
+        Some(1) => load_feed(app, sender, u),
+        // Queue latest episode
+        _ => {
+            app.announce(format!("Queueing the latest episode of {}", result.title));
+            if let Err(e) = sender.send(Request::LatestEpisode(u)) {
+                error!("failed to send message {:?}", e);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod user_input {
     use std::{sync::mpsc, time::Duration};
 
     use url::{ParseError, Url};
 
+    use rss::{Channel, Guid, Item};
+
     use crate::{
-        message::{self, DisplayAction, Request},
+        message::{self, Confirm, DisplayAction, Request},
         ui::input::Command,
         App,
     };
 
-    use super::handle_user_input;
+    use crate::search::SearchResult;
+
+    use super::{handle_search_choice, handle_user_input};
 
     #[test]
     fn send_load_request_publishes_feed_message() -> Result<(), ParseError> {
@@ -150,15 +550,108 @@ mod user_input {
         handle_user_input(&mut app, &data_tx, input);
 
         if let Ok(res) = data_rx.recv_timeout(Duration::from_secs(1)) {
-            assert_eq!(res, Request::Feed(expected?));
+            assert_eq!(res, Request::Feed(expected.clone()?));
         } else {
             panic!("did not receive a message in time");
         }
 
-        assert_eq!(DisplayAction::ListEpisodes, app.display_action);
+        assert_eq!(DisplayAction::Preview, app.display_action);
+        assert_eq!(app.feed_url, Some(expected?));
         Ok(())
     }
 
+    #[test]
+    fn send_load_request_for_private_feed_warns() {
+        let input =
+            Command::FetchPodcastFeed("https://www.patreon.com/rss/x?auth=abc123".to_string());
+        let mut app = App::default();
+        let (data_tx, data_rx) = mpsc::channel::<message::Request>();
+
+        handle_user_input(&mut app, &data_tx, input);
+
+        // the real URL still goes to the data layer
+        if let Ok(Request::Feed(u)) = data_rx.recv_timeout(Duration::from_secs(1)) {
+            assert_eq!(u.query(), Some("auth=abc123"));
+        } else {
+            panic!("did not receive a feed request in time");
+        }
+        assert!(app.status.contains("Patreon"));
+        assert!(!app.status.contains("abc123"));
+    }
+
+    #[test]
+    fn search_publishes_search_message() {
+        let mut app = App::default();
+        let (data_tx, data_rx) = mpsc::channel::<message::Request>();
+
+        handle_user_input(&mut app, &data_tx, Command::Search("wine".to_string()));
+
+        assert_eq!(
+            data_rx.recv_timeout(Duration::from_secs(1)),
+            Ok(Request::Search("wine".to_string()))
+        );
+        assert_eq!(DisplayAction::Search, app.display_action);
+    }
+
+    #[test]
+    fn search_choice_opens_actions_then_previews() {
+        let mut app = App {
+            display_action: DisplayAction::Search,
+            search_results: vec![SearchResult {
+                title: "Wine About It".to_string(),
+                author: "Someone".to_string(),
+                feed_url: Some("https://feeds.captivate.fm/wine-about-it/".to_string()),
+                genre: None,
+            }],
+            ..Default::default()
+        };
+        app.search_state.select(Some(0));
+        let (data_tx, data_rx) = mpsc::channel::<message::Request>();
+
+        // first Enter only shows the actions
+        handle_search_choice(&mut app, &data_tx);
+        assert!(app.search_menu);
+        assert!(data_rx.try_recv().is_err());
+
+        // pick "Preview"
+        app.search_menu_state.select(Some(1));
+        handle_search_choice(&mut app, &data_tx);
+        assert!(!app.search_menu);
+        assert_eq!(DisplayAction::Preview, app.display_action);
+        assert_eq!(
+            data_rx.recv_timeout(Duration::from_secs(1)),
+            Ok(Request::Feed(
+                Url::parse("https://feeds.captivate.fm/wine-about-it/").unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn search_choice_queues_latest() {
+        let mut app = App {
+            display_action: DisplayAction::Search,
+            search_results: vec![SearchResult {
+                title: "Wine About It".to_string(),
+                author: "Someone".to_string(),
+                feed_url: Some("https://feeds.captivate.fm/wine-about-it/".to_string()),
+                genre: None,
+            }],
+            search_menu: true,
+            ..Default::default()
+        };
+        app.search_state.select(Some(0));
+        app.search_menu_state.select(Some(2));
+        let (data_tx, data_rx) = mpsc::channel::<message::Request>();
+
+        handle_search_choice(&mut app, &data_tx);
+        assert_eq!(
+            data_rx.recv_timeout(Duration::from_secs(1)),
+            Ok(Request::LatestEpisode(
+                Url::parse("https://feeds.captivate.fm/wine-about-it/").unwrap()
+            ))
+        );
+    }
+
     #[test]
     fn send_no_op_does_nothing() {
         let input = Command::NoOp;
@@ -168,11 +661,73 @@ mod user_input {
         assert_eq!(DisplayAction::Input, app.display_action);
 
         handle_user_input(&mut app, &data_tx, input);
-        if let Ok(_) = data_rx.recv_timeout(Duration::from_secs(1)) {
+        if data_rx.recv_timeout(Duration::from_secs(1)).is_ok() {
             panic!("should not have received a message")
         }
 
         // ensure state did not change
         assert_eq!(DisplayAction::Input, app.display_action);
     }
+
+    #[test]
+    fn toggle_accessibility_flips_config() {
+        let mut app = App::default();
+        let (data_tx, data_rx) = mpsc::channel::<message::Request>();
+
+        handle_user_input(&mut app, &data_tx, Command::ToggleAccessibility);
+        assert!(app.config.accessible);
+        assert!(!app.status.is_empty());
+
+        handle_user_input(&mut app, &data_tx, Command::ToggleAccessibility);
+        assert!(!app.config.accessible);
+
+        // purely a UI concern, nothing goes to the data layer
+        assert!(data_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn set_theme() {
+        let mut app = App::default();
+        let (data_tx, _data_rx) = mpsc::channel::<message::Request>();
+
+        handle_user_input(&mut app, &data_tx, Command::SetTheme("monochrome".into()));
+        assert_eq!(app.theme.name, "monochrome");
+
+        // unknown themes leave the current one in place
+        handle_user_input(&mut app, &data_tx, Command::SetTheme("nope".into()));
+        assert_eq!(app.theme.name, "monochrome");
+        assert!(app.status.contains("high-contrast"));
+    }
+
+    #[test]
+    fn mark_all_played_asks_first() {
+        let item = |id: &str| Item {
+            guid: Some(Guid {
+                value: id.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut app = App {
+            feed_url: Some(Url::parse("https://example.com/feed").unwrap()),
+            channel: Some(Channel {
+                title: "Example".to_string(),
+                items: vec![item("2"), item("1")],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let (data_tx, _data_rx) = mpsc::channel::<message::Request>();
+
+        handle_user_input(&mut app, &data_tx, Command::MarkAllPlayed);
+        assert_eq!(
+            app.confirm,
+            Some(Confirm::MarkPlayed(
+                "https://example.com/feed".to_string(),
+                vec!["2".to_string(), "1".to_string()]
+            ))
+        );
+        assert!(app.status.contains("Mark 2 episodes of Example"));
+        assert!(!app.is_played(&item("1")));
+    }
 }
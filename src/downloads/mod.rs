@@ -0,0 +1,1167 @@
+pub mod schedule;
+pub mod throttle;
+
+use reqwest::{header, StatusCode};
+use rss::Item;
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::{fs, io::AsyncWriteExt};
+use tracing::{error, info, warn};
+use url::Url;
+
+use crate::{
+    feed::{episode_id, is_client_error, private::redact, wait_turn},
+    message::Response,
+    player::Episode,
+    stars::Stars,
+    store::db::Db,
+};
+
+// How often progress is reported while a file downloads
+const REPORT_EVERY: Duration = Duration::from_millis(250);
+
+// How many times in a row a download that broke off is picked up again, and how long to
+// wait before each try
+const RETRIES: u32 = 5;
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+// Longest a podcast's or an episode's title gets in a file name, in bytes. Most file
+// systems allow 255 bytes per name.
+const MAX_NAME: usize = 100;
+
+// Longest a file or directory name made from the template gets, in bytes. It leaves room
+// in the 255 for the `.part.validator` next to a download, and numbering like ` (2)`.
+const MAX_SEGMENT: usize = 230;
+
+// How long to wait for the server to answer, or to send more of the file, before the try
+// counts as broken off
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const STALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Status of a download
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    // waiting for one of the downloads going on to finish
+    Queued,
+    // bytes downloaded so far, and the size of the file when the server said
+    Active(u64, Option<u64>),
+    // stopped by the user, with what was downloaded before kept to carry on from
+    Paused(u64, Option<u64>),
+    Done,
+    Failed(String),
+}
+
+impl Status {
+    // e.g. "42%"
+    pub fn describe(&self) -> String {
+        match self {
+            Status::Queued => "queued".to_string(),
+            Status::Active(done, Some(len)) if *len > 0 => {
+                format!("{}%", (done * 100 / len).min(100))
+            }
+            Status::Active(done, _) => format!("{} MB", done / 1_000_000),
+            Status::Paused(done, len) => {
+                format!("paused at {}", Status::Active(*done, *len).describe())
+            }
+            Status::Done => "downloaded".to_string(),
+            Status::Failed(e) => format!("failed: {}", e),
+        }
+    }
+}
+
+// Retention is how long a podcast's downloads are kept: the latest `keep` of them, unless
+// it's 0, and the played ones until `played_for` seconds after they were played
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    pub keep: usize,
+    pub played_for: Option<i64>,
+}
+
+// Why a download is due to be deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    // not one of the latest this many of its podcast
+    Old(usize),
+    // played this many seconds ago
+    Played(i64),
+}
+
+impl Expiry {
+    // e.g. "played 15 days ago"
+    pub fn describe(&self) -> String {
+        match self {
+            Expiry::Old(1) => "not the latest download".to_string(),
+            Expiry::Old(n) => format!("not one of the latest {}", n),
+            Expiry::Played(secs) => match secs / (24 * 60 * 60) {
+                0 => "played today".to_string(),
+                1 => "played yesterday".to_string(),
+                days => format!("played {} days ago", days),
+            },
+        }
+    }
+}
+
+// Progress of a download, sent back to the UI
+#[derive(Debug, Clone, PartialEq)]
+pub enum Progress {
+    // bytes downloaded so far, and the size of the file when the server said
+    Downloaded(u64, Option<u64>),
+    Done,
+    Failed(String),
+    // stopped before it finished, see `Stop`
+    Stopped,
+}
+
+// Job is an episode being downloaded, or that was
+#[derive(Debug, Clone, PartialEq)]
+pub struct Job {
+    // kept whole, so that it can be played without loading its feed
+    pub item: Item,
+    pub feed: String,
+    // title of the podcast
+    pub podcast: Option<String>,
+    // where the file goes, which also tells jobs apart
+    pub path: PathBuf,
+    pub status: Status,
+    // when it finished downloading, in seconds since the epoch
+    pub finished: Option<i64>,
+    // queued by the podcast downloading its new episodes by itself, which waits for the
+    // schedule
+    pub auto: bool,
+}
+
+impl Job {
+    pub fn title(&self) -> &str {
+        self.item.title().unwrap_or("Title missing!")
+    }
+
+    fn is(&self, feed: &str, item: &Item) -> bool {
+        self.feed == feed
+            && episode_id(&self.item).is_some()
+            && episode_id(&self.item) == episode_id(item)
+    }
+
+    // Whether the download is never deleted to make room: its episode is starred, or one
+    // of the episodes playing
+    pub fn pinned(&self, stars: &Stars, playing: &[&Episode]) -> bool {
+        stars.is_starred(self.feed.as_str(), &self.item)
+            || playing.iter().any(|e| {
+                e.feed
+                    .as_ref()
+                    .is_some_and(|f| self.is(f.as_str(), &e.item))
+            })
+    }
+}
+
+// Downloads are the episodes downloaded, and being downloaded, newest first. Finished ones
+// are kept in the database.
+#[derive(Default, Debug, PartialEq)]
+pub struct Downloads {
+    jobs: Vec<Job>,
+    // downloads going on, by their paths, until they report they're over. A paused or
+    // cancelled one may take a moment to stop, and isn't started again until it has.
+    running: HashMap<PathBuf, Stop>,
+    // episodes whose downloads were deleted, by podcast and episode id
+    deleted: HashSet<(String, String)>,
+}
+
+impl Downloads {
+    pub fn new(jobs: Vec<Job>) -> Downloads {
+        Downloads {
+            jobs,
+            ..Downloads::default()
+        }
+    }
+
+    pub fn load(db: &Db) -> Result<Downloads, Box<dyn Error>> {
+        Ok(Downloads {
+            deleted: db.deleted_downloads()?.into_iter().collect(),
+            ..Downloads::new(db.load_downloads()?)
+        })
+    }
+
+    // The download of the episode, if there is one
+    pub fn get(&self, feed: &str, item: &Item) -> Option<&Job> {
+        self.jobs.iter().find(|j| j.is(feed, item))
+    }
+
+    pub fn get_index(&self, idx: usize) -> Option<&Job> {
+        self.jobs.get(idx)
+    }
+
+    // The path for the episode's download, or one next to it numbered like `Title (2).mp3`
+    // when another episode's download has it already, e.g. one with the same title, or a
+    // file that isn't a download is there
+    pub fn free_path(&self, feed: &str, item: &Item, path: PathBuf) -> PathBuf {
+        let taken = |p: &Path| match self.jobs.iter().find(|j| j.path == p) {
+            Some(j) => !j.is(feed, item),
+            None => p.exists(),
+        };
+        if !taken(&path) {
+            return path;
+        }
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let ext = path
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default();
+        (2..)
+            .map(|n| path.with_file_name(format!("{} ({}){}", stem, n, ext)))
+            .find(|p| !taken(p))
+            .unwrap_or(path)
+    }
+
+    // Start keeping track of a download, queued until it's started. One that failed is
+    // replaced.
+    pub fn add(&mut self, job: Job) {
+        if let Some(id) = episode_id(&job.item) {
+            self.deleted.remove(&(job.feed.clone(), id.to_string()));
+        }
+        self.jobs.retain(|j| !j.is(job.feed.as_str(), &job.item));
+        self.jobs.insert(0, job);
+    }
+
+    // Start as many queued downloads as the limit on downloads at once allows, the ones
+    // queued first first, returning them with what stops them. Ones queued by themselves
+    // wait unless `auto`.
+    pub fn start(&mut self, limit: usize, auto: bool) -> Vec<(Job, Stop)> {
+        let active = self.active();
+        let mut started = vec![];
+        for job in self.jobs.iter_mut().rev() {
+            if active + started.len() >= limit {
+                break;
+            }
+            if job.status == Status::Queued
+                && (auto || !job.auto)
+                && !self.running.contains_key(&job.path)
+            {
+                job.status = Status::Active(0, None);
+                let stop = Stop::default();
+                self.running.insert(job.path.clone(), stop.clone());
+                started.push((job.clone(), stop));
+            }
+        }
+        started
+    }
+
+    // How many downloads are going on
+    pub fn active(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|j| matches!(j.status, Status::Active(..)))
+            .count()
+    }
+
+    // How many downloads are waiting for their turn
+    pub fn queued(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|j| j.status == Status::Queued)
+            .count()
+    }
+
+    // Record the progress of the download to the path, returning its job
+    pub fn update(&mut self, path: &Path, p: Progress) -> Option<&Job> {
+        if !matches!(p, Progress::Downloaded(..)) {
+            self.running.remove(path);
+        }
+        let job = self.jobs.iter_mut().find(|j| j.path == path)?;
+        job.status = match (p, &job.status) {
+            (Progress::Downloaded(done, len), Status::Active(..)) => Status::Active(done, len),
+            // paused while it was reporting
+            (Progress::Downloaded(..), _) => return Some(job),
+            (Progress::Done, _) => {
+                job.finished = Some(crate::now());
+                Status::Done
+            }
+            (Progress::Failed(e), _) => Status::Failed(e),
+            (Progress::Stopped, Status::Active(done, len)) => Status::Paused(*done, *len),
+            // paused or cancelled already
+            (Progress::Stopped, _) => return Some(job),
+        };
+        Some(job)
+    }
+
+    // Pause the download to the path, stopping it if it's going on. Returns its job,
+    // unless there is nothing to pause.
+    pub fn pause(&mut self, path: &Path) -> Option<&Job> {
+        let job = self.jobs.iter_mut().find(|j| j.path == path)?;
+        job.status = match job.status {
+            Status::Queued => Status::Paused(0, None),
+            Status::Active(done, len) => Status::Paused(done, len),
+            _ => return None,
+        };
+        if let Some(stop) = self.running.get(path) {
+            stop.stop();
+        }
+        Some(job)
+    }
+
+    // Queue the paused download to the path again, returning its job unless it isn't
+    // paused
+    pub fn resume(&mut self, path: &Path) -> Option<&Job> {
+        let job = self
+            .jobs
+            .iter_mut()
+            .find(|j| j.path == path && matches!(j.status, Status::Paused(..)))?;
+        job.status = Status::Queued;
+        Some(job)
+    }
+
+    // Stop keeping track of the download to the path, stopping it if it's going on. It
+    // isn't wanted anymore, until it's added again.
+    pub fn remove(&mut self, path: &Path) -> Option<Job> {
+        let idx = self.jobs.iter().position(|j| j.path == path)?;
+        if let Some(stop) = self.running.get(path) {
+            stop.stop();
+        }
+        let job = self.jobs.remove(idx);
+        if let Some(id) = episode_id(&job.item) {
+            self.deleted.insert((job.feed.clone(), id.to_string()));
+        }
+        Some(job)
+    }
+
+    // Downloads to delete by the retention of their podcast (see `Retention`), and why.
+    // `played` says when a download's episode was played, if it was. The pinned ones are
+    // never deleted, though they still count as one of the latest.
+    pub fn expired(
+        &self,
+        retention: impl Fn(&str) -> Retention,
+        now: i64,
+        played: impl Fn(&Job) -> Option<i64>,
+        pinned: impl Fn(&Job) -> bool,
+    ) -> Vec<(&Job, Expiry)> {
+        let mut done = self
+            .jobs
+            .iter()
+            .filter(|j| j.status == Status::Done)
+            .collect::<Vec<&Job>>();
+        done.sort_by_key(|j| std::cmp::Reverse(j.finished));
+        let mut kept: HashMap<&str, usize> = HashMap::new();
+        done.into_iter()
+            .filter_map(|j| {
+                let r = retention(j.feed.as_str());
+                let n = kept.entry(j.feed.as_str()).or_default();
+                *n += 1;
+                let old = (r.keep > 0 && *n > r.keep).then_some(Expiry::Old(r.keep));
+                let listened = r
+                    .played_for
+                    .zip(played(j))
+                    .filter(|(after, at)| now - at > *after)
+                    .map(|(_, at)| Expiry::Played(now - at));
+                let expiry = old.or(listened)?;
+                (!pinned(j)).then_some((j, expiry))
+            })
+            .collect()
+    }
+
+    // Whether the episode has yet to be downloaded: it wasn't, it failed, or its file is
+    // gone. One whose download was deleted isn't.
+    pub fn wanted(&self, feed: &str, item: &Item) -> bool {
+        match self.get(feed, item).map(|j| &j.status) {
+            None => episode_id(item)
+                .is_some_and(|id| !self.deleted.contains(&(feed.to_string(), id.to_string()))),
+            Some(Status::Failed(_)) => true,
+            Some(Status::Done) => self.file(feed, item).is_none(),
+            Some(Status::Queued | Status::Active(..) | Status::Paused(..)) => false,
+        }
+    }
+
+    // The downloaded file of the episode, if it's still there
+    pub fn file(&self, feed: &str, item: &Item) -> Option<&Path> {
+        self.get(feed, item)
+            .filter(|j| j.status == Status::Done)
+            .map(|j| j.path.as_path())
+            .filter(|p| p.exists())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+}
+
+// Where downloads go in the download directory unless the config says otherwise: a
+// directory per podcast, with a file per episode named after it
+pub const TEMPLATE: &str = "{podcast}/{title}.{ext}";
+
+// Where an episode of the podcast is downloaded to in the directory, named by the template.
+// Its placeholders are `{podcast}`, `{title}`, `{date}` (when the episode came out, e.g.
+// 2023-04-30) and `{ext}` (the file's extension), and each `/` in it starts a directory.
+pub fn path_in(dir: &Path, template: &str, podcast: Option<&str>, item: &Item) -> PathBuf {
+    let url = item.enclosure().map(|e| e.url()).unwrap_or_default();
+    let ext = Url::parse(url)
+        .ok()
+        .and_then(|u| {
+            Path::new(u.path())
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(str::to_lowercase)
+        })
+        .filter(|e| e.len() <= 4 && e.chars().all(|c| c.is_ascii_alphanumeric()))
+        .unwrap_or_else(|| "mp3".to_string());
+    let date = item
+        .pub_date()
+        .and_then(|d| chrono::DateTime::parse_from_rfc2822(d.trim()).ok())
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "undated".to_string());
+    let title = item.title().or(episode_id(item)).unwrap_or("episode");
+    let podcast = podcast.unwrap_or("Unknown podcast");
+
+    let mut path = dir.to_path_buf();
+    // a template without a file name in it still gets one
+    let template = match template.trim().trim_end_matches(['/', '\\']) {
+        "" => TEMPLATE,
+        t => t,
+    };
+    for segment in template.split(['/', '\\']).filter(|s| !s.trim().is_empty()) {
+        let name = fill(segment, |placeholder| match placeholder {
+            "podcast" => Some(clean(podcast)),
+            "title" => Some(clean(title)),
+            "date" => Some(date.clone()),
+            "ext" => Some(ext.clone()),
+            _ => None,
+        });
+        path.push(fit(file_name(name.as_str())));
+    }
+    path
+}
+
+// The name cut to `MAX_SEGMENT` bytes, keeping its extension
+fn fit(name: String) -> String {
+    if name.len() <= MAX_SEGMENT {
+        return name;
+    }
+    match name.rsplit_once('.').filter(|(_, ext)| ext.len() <= 4) {
+        Some((stem, ext)) => {
+            let stem = truncate(stem, MAX_SEGMENT - ext.len() - 1).trim_end();
+            format!("{}.{}", stem, ext)
+        }
+        None => truncate(name.as_str(), MAX_SEGMENT).trim_end().to_string(),
+    }
+}
+
+// The template with its placeholders, like `{title}`, filled in. Unknown ones are left as
+// they are.
+fn fill(template: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut filled = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after
+            .find('}')
+            .and_then(|end| Some((end, value(&after[..end])?)))
+        {
+            Some((end, v)) => {
+                filled.push_str(v.as_str());
+                rest = &after[end + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = after;
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+// A value made short enough to put in a file name, and safe to
+fn clean(value: &str) -> String {
+    file_name(truncate(value, MAX_NAME))
+}
+
+// The start of the text that fits in the bytes, without cutting a character in two
+fn truncate(s: &str, max: usize) -> &str {
+    let mut end = s.len().min(max);
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+// Characters that aren't allowed in file names on some systems are replaced
+fn safe(c: char) -> char {
+    match c {
+        '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+        c if c.is_control() => '_',
+        c => c,
+    }
+}
+
+// A part of a path made safe to use as a file or directory name
+fn file_name(name: &str) -> String {
+    let name = name.chars().map(safe).collect::<String>();
+    // no hidden files, or ones that climb out of the directory
+    let name = name.trim().trim_start_matches('.').trim();
+    if name.is_empty() {
+        "episode".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+// Stop asks a download to stop, leaving what was downloaded so far. It reports
+// `Progress::Stopped` once it has. Every start of a download gets its own, so a download
+// paused and resumed quickly can't be stopped by the pause.
+#[derive(Debug, Clone, Default)]
+pub struct Stop(Arc<AtomicBool>);
+
+impl Stop {
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    fn stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl PartialEq for Stop {
+    fn eq(&self, other: &Stop) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+// Download the file to the path, reporting progress along the way. It's written next to
+// the path first, so that a download that broke off is never taken for a whole file, and
+// can be picked up again from there.
+#[tokio::main]
+pub async fn download(responder: Sender<Response>, u: Url, path: PathBuf, stop: Stop) {
+    let report = |p| {
+        if let Err(e) = responder.send(Response::Download(path.clone(), p)) {
+            error!("failed to send message: {:?}", e);
+        }
+    };
+    match fetch(&u, &path, &stop, &report).await {
+        Ok(()) => {
+            info!("downloaded {} to {}", redact(&u), path.display());
+            report(Progress::Done);
+        }
+        Err(e) if e.is::<Stopped>() => {
+            info!("stopped downloading {}", redact(&u));
+            report(Progress::Stopped);
+        }
+        Err(e) => {
+            error!("failed to download {}: {:?}", redact(&u), e);
+            report(Progress::Failed(e.to_string()));
+        }
+    }
+}
+
+async fn fetch(
+    u: &Url,
+    path: &Path,
+    stop: &Stop,
+    report: &impl Fn(Progress),
+) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).await?;
+    }
+    let [part, validator] = part_files(path);
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()?;
+    let mut failures = 0;
+    loop {
+        // picks up where the last try, or the last run of the app, left off
+        let from = part_len(&part).await;
+        let err = match fetch_from(&client, u, &part, &validator, from, stop, report).await {
+            Ok(()) => break,
+            Err(e) if e.is::<Stopped>() => return Err(e),
+            Err(e) => e,
+        };
+        // only failures in a row count
+        if part_len(&part).await > from {
+            failures = 0;
+        }
+        failures += 1;
+        if failures > RETRIES || is_client_error(err.as_ref()) {
+            return Err(err);
+        }
+        warn!("download of {} broke off, trying again: {}", redact(u), err);
+        tokio::time::sleep(RETRY_DELAY).await;
+    }
+    fs::rename(&part, path).await?;
+    let _ = fs::remove_file(&validator).await;
+    Ok(())
+}
+
+// Where what was downloaded of the file to the path is kept until it's whole, and what
+// tells whether the file changed since, its ETag or Last-Modified header
+pub fn part_files(path: &Path) -> [PathBuf; 2] {
+    [".part", ".part.validator"].map(|ext| {
+        let mut p = path.as_os_str().to_owned();
+        p.push(ext);
+        PathBuf::from(p)
+    })
+}
+
+// What the server can tell whether the file changed by, for If-Range: a strong ETag, or
+// else when it was last changed
+fn validator(headers: &header::HeaderMap) -> Option<&header::HeaderValue> {
+    headers
+        .get(header::ETAG)
+        .filter(|e| !e.as_bytes().starts_with(b"W/"))
+        .or_else(|| headers.get(header::LAST_MODIFIED))
+}
+
+async fn part_len(part: &Path) -> u64 {
+    fs::metadata(part).await.map(|m| m.len()).unwrap_or(0)
+}
+
+// Download the file into the part, carrying on from the byte offset when the server can
+// send the rest of the file only
+async fn fetch_from(
+    client: &reqwest::Client,
+    u: &Url,
+    part: &Path,
+    validator_path: &Path,
+    from: u64,
+    stop: &Stop,
+    report: &impl Fn(Progress),
+) -> Result<(), Box<dyn Error>> {
+    wait_turn(u).await;
+    let mut req = client.get(u.as_str());
+    if from > 0 {
+        req = req.header(header::RANGE, format!("bytes={}-", from));
+        // so that the server sends all of a file that changed instead of the rest of it
+        if let Ok(v) = fs::read_to_string(validator_path).await {
+            req = req.header(header::IF_RANGE, v.trim());
+        }
+    }
+    // reqwest errors include the URL, which may contain a private feed token
+    let res = tokio::time::timeout(STALL_TIMEOUT, req.send())
+        .await
+        .map_err(|_| "the server didn't answer")?
+        .map_err(|e| e.without_url())?;
+    // the part is longer than the file now, it must have changed since
+    if from > 0 && res.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        fs::remove_file(part).await?;
+        return Err("the file changed since it started downloading".into());
+    }
+    let mut res = res.error_for_status().map_err(|e| e.without_url())?;
+    // servers that can't send part of a file, or whose file changed, send all of it, and
+    // the part is started over
+    let resumed = from > 0 && res.status() == StatusCode::PARTIAL_CONTENT;
+    if resumed
+        && res
+            .headers()
+            .get(header::CONTENT_RANGE)
+            .and_then(range_start)
+            != Some(from)
+    {
+        fs::remove_file(part).await?;
+        return Err("the server sent a different part of the file".into());
+    }
+    let mut done = if resumed { from } else { 0 };
+    if resumed {
+        info!("resuming download of {} at {} bytes", redact(u), from);
+    } else {
+        if from > 0 {
+            info!("{} can't resume downloads, starting over", redact(u));
+        }
+        match validator(res.headers()).and_then(|v| v.to_str().ok()) {
+            Some(v) => fs::write(validator_path, v).await?,
+            None => {
+                let _ = fs::remove_file(validator_path).await;
+            }
+        }
+    }
+    let len = res.content_length().map(|l| l + done);
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(part)
+        .await?;
+
+    let mut reported = Instant::now();
+    report(Progress::Downloaded(done, len));
+    while let Some(chunk) = tokio::time::timeout(STALL_TIMEOUT, res.chunk())
+        .await
+        .map_err(|_| "the server stopped sending the file")?
+        .map_err(|e| e.without_url())?
+    {
+        if stop.stopped() {
+            return Err(Box::new(Stopped));
+        }
+        file.write_all(&chunk).await?;
+        done += chunk.len() as u64;
+        let wait = throttle::throttle()
+            .lock()
+            .map(|mut t| t.reserve(chunk.len(), Instant::now()))
+            .unwrap_or_default();
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        if reported.elapsed() >= REPORT_EVERY {
+            report(Progress::Downloaded(done, len));
+            reported = Instant::now();
+        }
+    }
+    file.flush().await?;
+    if len.is_some_and(|l| done < l) {
+        return Err("the download ended early".into());
+    }
+    Ok(())
+}
+
+// The download was stopped on purpose
+#[derive(Debug)]
+struct Stopped;
+
+impl std::fmt::Display for Stopped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "stopped")
+    }
+}
+
+impl Error for Stopped {}
+
+// Where the part of the file that was sent starts, from a Content-Range header like
+// `bytes 100-199/200`
+fn range_start(h: &header::HeaderValue) -> Option<u64> {
+    let range = h.to_str().ok()?.strip_prefix("bytes ")?;
+    range.split_once('-')?.0.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use rss::{Enclosure, Guid, Item};
+    use std::path::{Path, PathBuf};
+
+    use reqwest::header::{self, HeaderMap, HeaderValue};
+
+    use url::Url;
+
+    use super::{
+        path_in, range_start, validator, Downloads, Expiry, Job, Progress, Retention, Status,
+        TEMPLATE,
+    };
+    use crate::{
+        player::Episode,
+        stars::{Star, Stars},
+        store::db::Db,
+    };
+
+    const FEED: &str = "https://example.com/feed";
+
+    // A finished download of the episode
+    fn job(id: &str, finished: i64) -> Job {
+        Job {
+            item: item(id, id, format!("https://example.com/{}.mp3", id).as_str()),
+            feed: FEED.to_string(),
+            podcast: None,
+            path: PathBuf::from(format!("/downloads/{}.mp3", id)),
+            status: Status::Done,
+            finished: Some(finished),
+            auto: false,
+        }
+    }
+
+    fn item(id: &str, title: &str, url: &str) -> Item {
+        Item {
+            title: Some(title.to_string()),
+            guid: Some(Guid {
+                value: id.to_string(),
+                ..Default::default()
+            }),
+            enclosure: Some(Enclosure {
+                url: url.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn names_files() {
+        let dir = Path::new("/downloads");
+        assert_eq!(
+            path_in(
+                dir,
+                TEMPLATE,
+                Some("Wine About It"),
+                &item(
+                    "1",
+                    "Ep. 1: Red/White?",
+                    "https://example.com/1.MP3?token=x"
+                )
+            ),
+            PathBuf::from("/downloads/Wine About It/Ep. 1_ Red_White_.mp3")
+        );
+        assert_eq!(
+            path_in(
+                dir,
+                TEMPLATE,
+                Some("../.."),
+                &item("2", "..", "https://example.com/2")
+            ),
+            PathBuf::from("/downloads/_../episode.mp3")
+        );
+        // long titles are cut to fit a file name, between characters
+        let long = path_in(
+            dir,
+            TEMPLATE,
+            None,
+            &item("4", &"日本語".repeat(50), "https://example.com/4.mp3"),
+        );
+        let name = long.file_name().unwrap().to_str().unwrap();
+        assert_eq!(name, format!("{}.mp3", "日本語".repeat(11)));
+        // and so are names the template puts several of them in
+        let long = path_in(
+            dir,
+            "{title} {title} {title}.{ext}",
+            None,
+            &item("5", &"a".repeat(100), "https://example.com/5.mp3"),
+        );
+        let name = long.file_name().unwrap().to_str().unwrap();
+        assert_eq!(name.len(), 230);
+        assert!(name.ends_with("aaa.mp3"));
+
+        let mut i = item("3", "{date}", "https://example.com/3.m4a");
+        i.pub_date = Some("Sun, 30 Apr 2023 09:00:00 +0200".to_string());
+        assert_eq!(
+            path_in(dir, "{podcast}/{date}-{title}.{ext}", Some("Wine"), &i),
+            PathBuf::from("/downloads/Wine/2023-04-30-{date}.m4a")
+        );
+        // unknown placeholders are kept, and nothing climbs out of the directory
+        assert_eq!(
+            path_in(dir, "../{show}/{title}", None, &i),
+            PathBuf::from("/downloads/episode/{show}/{date}")
+        );
+        assert_eq!(
+            path_in(dir, "/", None, &i),
+            PathBuf::from("/downloads/Unknown podcast/{date}.m4a")
+        );
+    }
+
+    #[test]
+    fn numbers_taken_paths() {
+        let mut d = Downloads::default();
+        d.add(job("1", 10));
+        let path = PathBuf::from("/downloads/1.mp3");
+        // the episode's own download keeps its path
+        assert_eq!(d.free_path(FEED, &job("1", 0).item, path.clone()), path);
+        let other = job("2", 0).item;
+        assert_eq!(
+            d.free_path(FEED, &other, path.clone()),
+            PathBuf::from("/downloads/1 (2).mp3")
+        );
+        let mut second = job("2", 10);
+        second.path = PathBuf::from("/downloads/1 (2).mp3");
+        d.add(second);
+        assert_eq!(
+            d.free_path(FEED, &job("3", 0).item, path),
+            PathBuf::from("/downloads/1 (3).mp3")
+        );
+    }
+
+    #[test]
+    fn reads_content_range() {
+        let start = |h: &'static str| range_start(&HeaderValue::from_static(h));
+        assert_eq!(start("bytes 100-199/200"), Some(100));
+        assert_eq!(start("bytes 0-99/*"), Some(0));
+        assert_eq!(start("bytes */200"), None);
+        assert_eq!(start("items 1-2/3"), None);
+    }
+
+    #[test]
+    fn picks_validator() {
+        let mut h = HeaderMap::new();
+        assert_eq!(validator(&h), None);
+        let modified = "Wed, 21 Oct 2015 07:28:00 GMT";
+        h.insert(header::LAST_MODIFIED, HeaderValue::from_static(modified));
+        // weak ones can't tell whether part of a file is the same
+        h.insert(header::ETAG, HeaderValue::from_static("W/\"1\""));
+        assert_eq!(validator(&h).unwrap(), modified);
+        h.insert(header::ETAG, HeaderValue::from_static("\"1\""));
+        assert_eq!(validator(&h).unwrap(), "\"1\"");
+    }
+
+    #[test]
+    fn limits_downloads_at_once() {
+        let mut d = Downloads::default();
+        for id in ["1", "2", "3"] {
+            d.add(Job {
+                item: item(id, id, "https://example.com/1.mp3"),
+                feed: FEED.to_string(),
+                podcast: None,
+                path: PathBuf::from(format!("/downloads/{}.mp3", id)),
+                status: Status::Queued,
+                finished: None,
+                auto: false,
+            });
+        }
+        // the oldest first
+        let started = d.start(2, true);
+        assert_eq!(
+            started
+                .iter()
+                .map(|(j, _)| j.title())
+                .collect::<Vec<&str>>(),
+            vec!["1", "2"]
+        );
+        assert_eq!((d.active(), d.queued()), (2, 1));
+        assert!(d.start(2, true).is_empty());
+
+        d.update(Path::new("/downloads/1.mp3"), Progress::Done);
+        assert_eq!(d.start(2, true)[0].0.title(), "3");
+        assert_eq!((d.active(), d.queued()), (2, 0));
+    }
+
+    #[test]
+    fn waits_for_schedule() {
+        let mut d = Downloads::default();
+        for id in ["1", "2"] {
+            d.add(Job {
+                item: item(id, id, "https://example.com/1.mp3"),
+                feed: FEED.to_string(),
+                podcast: None,
+                path: PathBuf::from(format!("/downloads/{}.mp3", id)),
+                status: Status::Queued,
+                finished: None,
+                auto: id == "1",
+            });
+        }
+        // downloaded by hand, it doesn't wait
+        let started = d.start(2, false);
+        assert_eq!(
+            started
+                .iter()
+                .map(|(j, _)| j.title())
+                .collect::<Vec<&str>>(),
+            vec!["2"]
+        );
+        assert_eq!(d.start(2, true)[0].0.title(), "1");
+    }
+
+    #[test]
+    fn expires_downloads() {
+        let job = |feed: &str, id: &str, finished: i64| Job {
+            item: item(id, id, "https://example.com/1.mp3"),
+            feed: feed.to_string(),
+            podcast: None,
+            path: PathBuf::from(format!("/downloads/{}/{}.mp3", feed, id)),
+            status: Status::Done,
+            finished: Some(finished),
+            auto: false,
+        };
+        let d = Downloads::new(vec![
+            job("a", "1", 10),
+            job("a", "3", 30),
+            job("a", "2", 20),
+            job("b", "1", 10),
+        ]);
+        let titles = |jobs: Vec<(&Job, Expiry)>| {
+            jobs.iter()
+                .map(|(j, _)| format!("{}{}", j.feed, j.title()))
+                .collect::<Vec<String>>()
+        };
+        let all = |keep, played_for| move |_: &str| Retention { keep, played_for };
+        let never = |_: &Job| None;
+        let unpinned = |_: &Job| false;
+        assert!(d.expired(all(0, None), 100, never, unpinned).is_empty());
+        // the latest of each podcast are kept
+        assert_eq!(
+            d.expired(all(2, None), 100, never, unpinned)
+                .iter()
+                .map(|(j, e)| (j.feed.as_str(), j.title(), *e))
+                .collect::<Vec<(&str, &str, Expiry)>>(),
+            vec![("a", "1", Expiry::Old(2))]
+        );
+        // episode 2 was played at 40, more than 50 seconds ago by 100
+        let played = |j: &Job| (j.title() == "2").then_some(40);
+        let expired = d.expired(all(0, Some(50)), 100, played, unpinned);
+        assert_eq!(titles(expired.clone()), vec!["a2"]);
+        assert_eq!(expired[0].1, Expiry::Played(60));
+        assert!(d
+            .expired(all(0, Some(60)), 100, played, unpinned)
+            .is_empty());
+
+        // each podcast has its own
+        let own = |feed: &str| Retention {
+            keep: if feed == "b" { 0 } else { 1 },
+            played_for: None,
+        };
+        assert_eq!(
+            titles(d.expired(own, 100, never, unpinned)),
+            vec!["a2", "a1"]
+        );
+    }
+
+    #[test]
+    fn describes_expiry() {
+        assert_eq!(Expiry::Old(3).describe(), "not one of the latest 3");
+        assert_eq!(
+            Expiry::Played(15 * 24 * 60 * 60 + 5).describe(),
+            "played 15 days ago"
+        );
+    }
+
+    #[test]
+    fn keeps_starred_downloads() {
+        let db = Db::open_in_memory().unwrap();
+        let mut stars = Stars::default();
+        let old = job("1", 10);
+        stars
+            .toggle(
+                Star {
+                    item: old.item.clone(),
+                    feed: FEED.to_string(),
+                    podcast: None,
+                    starred: 0,
+                },
+                &db,
+            )
+            .unwrap();
+        let d = Downloads::new(vec![old, job("2", 20), job("3", 30)]);
+        // 3 is the latest, and 1 is starred
+        let keep = |_: &str| Retention {
+            keep: 1,
+            played_for: None,
+        };
+        let expired = d.expired(keep, 100, |_| None, |j| j.pinned(&stars, &[]));
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0.title(), "2");
+    }
+
+    #[test]
+    fn keeps_playing_download() {
+        let played = job("1", 10);
+        let playing = Episode {
+            item: played.item.clone(),
+            podcast: None,
+            feed: Some(Url::parse(FEED).unwrap()),
+            artwork: None,
+        };
+        let d = Downloads::new(vec![played, job("2", 20)]);
+        let stars = Stars::default();
+        let retention = |_: &str| Retention {
+            keep: 0,
+            played_for: Some(10),
+        };
+        let listened = |_: &Job| Some(0);
+        assert_eq!(
+            d.expired(retention, 100, listened, |j| j.pinned(&stars, &[]))
+                .len(),
+            2
+        );
+        let expired = d.expired(retention, 100, listened, |j| j.pinned(&stars, &[&playing]));
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0.title(), "2");
+    }
+
+    #[test]
+    fn tracks_jobs() {
+        let mut d = Downloads::default();
+        let i = item("1", "Episode 1", "https://example.com/1.mp3");
+        let path = PathBuf::from("/downloads/1.mp3");
+        d.add(Job {
+            item: i.clone(),
+            feed: FEED.to_string(),
+            podcast: None,
+            path: path.clone(),
+            status: Status::Queued,
+            finished: None,
+            auto: false,
+        });
+        assert_eq!(d.start(1, true).len(), 1);
+        d.update(&path, Progress::Downloaded(50, Some(200)));
+        assert_eq!(d.get(FEED, &i).unwrap().status.describe(), "25%");
+        assert!(!d.wanted(FEED, &i));
+
+        d.update(&path, Progress::Failed("timed out".to_string()));
+        assert_eq!(
+            d.get(FEED, &i).unwrap().status,
+            Status::Failed("timed out".to_string())
+        );
+        // trying again replaces it
+        d.add(Job {
+            status: Status::Queued,
+            ..d.get(FEED, &i).unwrap().clone()
+        });
+        assert_eq!(d.len(), 1);
+        // the file isn't there
+        d.update(&path, Progress::Done);
+        assert_eq!(d.file(FEED, &i), None);
+        assert!(d.wanted(FEED, &i));
+
+        // deleted, so it's only downloaded again when asked to
+        let job = d.remove(&path).unwrap();
+        assert!(!d.wanted(FEED, &i));
+        d.add(job);
+        d.update(&path, Progress::Failed("timed out".to_string()));
+        assert!(d.wanted(FEED, &i));
+    }
+
+    #[test]
+    fn pauses_downloads() {
+        let mut d = Downloads::default();
+        let i = item("1", "Episode 1", "https://example.com/1.mp3");
+        let path = PathBuf::from("/downloads/1.mp3");
+        d.add(Job {
+            item: i.clone(),
+            feed: FEED.to_string(),
+            podcast: None,
+            path: path.clone(),
+            status: Status::Queued,
+            finished: None,
+            auto: false,
+        });
+        let (_, stop) = d.start(1, true).remove(0);
+        d.update(&path, Progress::Downloaded(50, Some(200)));
+        assert!(d.pause(&path).is_some());
+        assert!(stop.stopped());
+        // makes room for another
+        assert_eq!(d.active(), 0);
+        // progress it reported before it stopped doesn't change that it's paused
+        d.update(&path, Progress::Downloaded(60, Some(200)));
+        let status = &d.get(FEED, &i).unwrap().status;
+        assert_eq!(status.describe(), "paused at 25%");
+        assert!(!d.wanted(FEED, &i));
+        assert!(d.pause(&path).is_none());
+
+        assert!(d.resume(&path).is_some());
+        assert_eq!(d.queued(), 1);
+        assert!(d.resume(&path).is_none());
+        // resumed before it stopped, so it waits for that
+        assert!(d.start(1, true).is_empty());
+        d.update(&path, Progress::Stopped);
+        assert_eq!(d.get(FEED, &i).unwrap().status, Status::Queued);
+        let (_, next) = d.start(1, true).remove(0);
+        assert!(!next.stopped());
+
+        // stopping by itself pauses it, making room for another
+        d.update(&path, Progress::Stopped);
+        assert_eq!(d.active(), 0);
+        assert!(matches!(
+            d.get(FEED, &i).unwrap().status,
+            Status::Paused(..)
+        ));
+    }
+}
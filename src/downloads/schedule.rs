@@ -0,0 +1,107 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::ScheduleConfig;
+
+// Whether the connection was metered the last time it was looked at
+static METERED: AtomicBool = AtomicBool::new(false);
+
+// Whether podcasts may download their new episodes by themselves at the hour of the day,
+// in local time
+pub fn is_open(config: &ScheduleConfig, hour: u32, metered: bool) -> bool {
+    if config.unmetered_only && metered {
+        return false;
+    }
+    match config.hours {
+        None => true,
+        // the same hour twice means any time
+        Some((from, until)) if from % 24 == until % 24 => true,
+        Some((from, until)) if from < until => (from..until).contains(&hour),
+        // it goes past midnight
+        Some((from, until)) => hour >= from || hour < until,
+    }
+}
+
+pub fn metered() -> bool {
+    METERED.load(Ordering::Relaxed)
+}
+
+// Keep looking at whether the connection is metered in the background. NetworkManager
+// says on Linux; elsewhere connections count as unmetered.
+pub fn watch_metered() {
+    #[cfg(target_os = "linux")]
+    std::thread::spawn(network_manager::watch);
+}
+
+#[cfg(target_os = "linux")]
+mod network_manager {
+    use std::{error::Error, sync::atomic::Ordering, time::Duration};
+    use tracing::{info, warn};
+
+    use super::METERED;
+
+    // How often NetworkManager is asked
+    const CHECK_EVERY: Duration = Duration::from_secs(60);
+
+    #[tokio::main(flavor = "current_thread")]
+    pub async fn watch() {
+        // without NetworkManager there is no telling, ever
+        if let Err(e) = poll().await {
+            warn!("can't tell if the connection is metered: {:?}", e);
+        }
+    }
+
+    async fn poll() -> Result<(), Box<dyn Error>> {
+        let conn = zbus::Connection::system().await?;
+        let proxy = zbus::Proxy::new(
+            &conn,
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+        )
+        .await?;
+        loop {
+            // NMMetered: yes, or a guess that it is. Asking can fail for a while, e.g.
+            // while NetworkManager restarts, and what was known last stands until then.
+            match proxy.get_property::<u32>("Metered").await {
+                Ok(m) => {
+                    let metered = matches!(m, 1 | 3);
+                    if METERED.swap(metered, Ordering::Relaxed) != metered {
+                        let not = if metered { "" } else { "not " };
+                        info!("the connection is {}metered", not);
+                    }
+                }
+                Err(e) => warn!("failed to ask if the connection is metered: {:?}", e),
+            }
+            tokio::time::sleep(CHECK_EVERY).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_open;
+    use crate::config::ScheduleConfig;
+
+    #[test]
+    fn opens_in_hours() {
+        let any = ScheduleConfig::default();
+        assert!(is_open(&any, 12, true));
+
+        let overnight = ScheduleConfig {
+            hours: Some((23, 7)),
+            unmetered_only: true,
+        };
+        assert!(is_open(&overnight, 23, false));
+        assert!(is_open(&overnight, 3, false));
+        assert!(!is_open(&overnight, 7, false));
+        assert!(!is_open(&overnight, 12, false));
+        assert!(!is_open(&overnight, 3, true));
+
+        let day = ScheduleConfig {
+            hours: Some((9, 17)),
+            unmetered_only: false,
+        };
+        assert!(is_open(&day, 9, true));
+        assert!(!is_open(&day, 17, false));
+    }
+}
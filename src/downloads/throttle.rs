@@ -0,0 +1,97 @@
+use std::{
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use crate::config::DownloadsConfig;
+
+// Slowest rate downloads are kept under, in bytes per second. Any slower and a chunk
+// would wait for hours.
+pub const MIN_RATE: f64 = 10_000.0;
+
+// Throttle keeps downloads under a rate, all of them together, so that downloading a
+// podcast's back catalog doesn't take up the whole connection. Downloads wait after each
+// chunk until the time it should have taken at that rate.
+#[derive(Debug, Default)]
+pub struct Throttle {
+    // bytes per second, None for no limit
+    rate: Option<f64>,
+    // when the bytes downloaded so far would be done at that rate
+    next: Option<Instant>,
+}
+
+impl From<&DownloadsConfig> for Throttle {
+    fn from(config: &DownloadsConfig) -> Self {
+        let rate = Some(config.max_rate * 1_000_000.0).filter(|r| *r > 0.0);
+        Throttle::new(rate)
+    }
+}
+
+impl Throttle {
+    // A rate that isn't a number, or is infinite, is no limit
+    pub fn new(rate: Option<f64>) -> Self {
+        let rate = rate.filter(|r| r.is_finite()).map(|r| r.max(MIN_RATE));
+        Throttle { rate, next: None }
+    }
+
+    // Account for bytes that were downloaded, and return how long to wait before
+    // downloading more
+    pub fn reserve(&mut self, bytes: usize, now: Instant) -> Duration {
+        let Some(rate) = self.rate else {
+            return Duration::ZERO;
+        };
+        // time not spent downloading doesn't save up for a burst later
+        let from = self.next.filter(|t| *t > now).unwrap_or(now);
+        let next = from + Duration::from_secs_f64(bytes as f64 / rate);
+        self.next = Some(next);
+        next - now
+    }
+}
+
+static THROTTLE: OnceLock<Mutex<Throttle>> = OnceLock::new();
+
+// The throttle shared by every download
+pub fn throttle() -> &'static Mutex<Throttle> {
+    THROTTLE.get_or_init(|| Mutex::new(Throttle::default()))
+}
+
+// Apply the user's settings to the shared throttle
+pub fn configure(config: &DownloadsConfig) {
+    if let Ok(mut t) = throttle().lock() {
+        *t = Throttle::from(config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::Throttle;
+
+    #[test]
+    fn keeps_under_rate() {
+        let mut t = Throttle::new(Some(20_000.0));
+        let now = Instant::now();
+
+        assert_eq!(t.reserve(10_000, now), Duration::from_millis(500));
+        // a second download shares the rate
+        assert_eq!(t.reserve(10_000, now), Duration::from_secs(1));
+        // once caught up, no credit for the idle time
+        let later = now + Duration::from_secs(5);
+        assert_eq!(t.reserve(20_000, later), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn keeps_to_min_rate() {
+        let mut t = Throttle::new(Some(1e-300));
+        assert_eq!(t.reserve(10_000, Instant::now()), Duration::from_secs(1));
+        let mut t = Throttle::new(Some(f64::INFINITY));
+        assert_eq!(t.reserve(1_000_000, Instant::now()), Duration::ZERO);
+    }
+
+    #[test]
+    fn no_limit() {
+        let mut t = Throttle::new(None);
+        assert_eq!(t.reserve(1_000_000, Instant::now()), Duration::ZERO);
+    }
+}
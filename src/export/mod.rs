@@ -0,0 +1,151 @@
+use rss::Channel;
+use serde::Serialize;
+use std::{error::Error, fs::OpenOptions, io::Write, path::Path};
+
+use crate::{
+    feed::{duration, episode_id},
+    played::Played,
+};
+
+// Episode is what is exported about an episode, one JSON object or CSV row each
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Episode {
+    pub title: String,
+    // RFC 3339 when the feed's date can be read, as the feed has it otherwise
+    pub published: Option<String>,
+    // in seconds
+    pub duration: Option<u64>,
+    // of the episode's file
+    pub url: Option<String>,
+    pub played: bool,
+}
+
+// The loaded feed's episodes, in the feed's order
+pub fn episodes(c: &Channel, feed: &str, played: &Played) -> Vec<Episode> {
+    c.items()
+        .iter()
+        .map(|i| Episode {
+            title: i.title().unwrap_or_default().to_string(),
+            published: i.pub_date().map(|d| {
+                chrono::DateTime::parse_from_rfc2822(d.trim())
+                    .map(|d| d.to_rfc3339())
+                    .unwrap_or_else(|_| d.to_string())
+            }),
+            duration: duration(i).map(|d| d.as_secs()),
+            url: i.enclosure().map(|e| e.url().to_string()),
+            played: episode_id(i).is_some_and(|id| played.is_played(feed, id)),
+        })
+        .collect()
+}
+
+pub fn json(episodes: &[Episode]) -> Result<String, Box<dyn Error>> {
+    Ok(serde_json::to_string_pretty(episodes)? + "\n")
+}
+
+pub fn csv(episodes: &[Episode]) -> String {
+    let mut s = String::from("title,published,duration,url,played\n");
+    for e in episodes {
+        let fields = [
+            quote(e.title.as_str()),
+            quote(e.published.as_deref().unwrap_or_default()),
+            e.duration.map(|d| d.to_string()).unwrap_or_default(),
+            quote(e.url.as_deref().unwrap_or_default()),
+            e.played.to_string(),
+        ];
+        s.push_str(&fields.join(","));
+        s.push('\n');
+    }
+    s
+}
+
+// Quote a CSV field when it needs it, doubling the quotes in it
+fn quote(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+// Write the episodes to the file, as CSV when its name ends in `.csv` and as JSON
+// otherwise. File URLs of private feeds keep their tokens, so the file is only readable by
+// the user.
+pub fn write(path: &Path, episodes: &[Episode]) -> Result<(), Box<dyn Error>> {
+    let csv_file = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+    let contents = if csv_file {
+        csv(episodes)
+    } else {
+        json(episodes)?
+    };
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rss::{extension::itunes::ITunesItemExtension, Channel, Enclosure, Guid, Item};
+
+    use crate::played::Played;
+
+    use super::{csv, episodes, json};
+
+    const FEED: &str = "https://example.com/feed";
+
+    fn channel() -> Channel {
+        Channel {
+            items: vec![
+                Item {
+                    title: Some("Wine, \"cheap\"".to_string()),
+                    guid: Some(Guid {
+                        value: "2".to_string(),
+                        ..Default::default()
+                    }),
+                    pub_date: Some("Tue, 02 May 2023 10:00:00 +0000".to_string()),
+                    enclosure: Some(Enclosure {
+                        url: "https://example.com/2.mp3".to_string(),
+                        ..Default::default()
+                    }),
+                    itunes_ext: Some(ITunesItemExtension {
+                        duration: Some("01:02:03".to_string()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                Item {
+                    title: Some("Cheese".to_string()),
+                    guid: Some(Guid {
+                        value: "1".to_string(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn exports_episodes() {
+        let mut played = Played::default();
+        played.mark_all(FEED, ["1"]);
+        let e = episodes(&channel(), FEED, &played);
+
+        assert_eq!(
+            csv(&e),
+            "title,published,duration,url,played\n\
+             \"Wine, \"\"cheap\"\"\",2023-05-02T10:00:00+00:00,3723,https://example.com/2.mp3,false\n\
+             Cheese,,,,true\n"
+        );
+        let parsed: serde_json::Value = serde_json::from_str(json(&e).unwrap().as_str()).unwrap();
+        assert_eq!(parsed[0]["duration"], 3723);
+        assert_eq!(parsed[0]["published"], "2023-05-02T10:00:00+00:00");
+        assert_eq!(parsed[1]["url"], serde_json::Value::Null);
+        assert_eq!(parsed[1]["played"], true);
+    }
+}
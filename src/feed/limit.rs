@@ -0,0 +1,142 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use crate::config::NetworkConfig;
+
+// Longest interval, and longest jitter, in seconds. Longer ones are cut to this.
+const MAX_SECS: f64 = 60.0;
+
+// Limiter spaces out requests to the same host, so that refreshing dozens of feeds from
+// one provider doesn't get us throttled or blocked. Requests to different hosts are not
+// held back by each other.
+#[derive(Debug)]
+pub struct Limiter {
+    // minimum time between two requests to the same host
+    interval: Duration,
+    // up to this much extra time is randomly added to each interval
+    jitter: Duration,
+    // earliest time the next request to each host may start
+    next: HashMap<String, Instant>,
+}
+
+impl Default for Limiter {
+    fn default() -> Self {
+        Limiter::from(&NetworkConfig::default())
+    }
+}
+
+impl From<&NetworkConfig> for Limiter {
+    fn from(config: &NetworkConfig) -> Self {
+        Limiter::new(secs(config.host_interval), secs(config.jitter))
+    }
+}
+
+// Seconds from the config, up to `MAX_SECS`. Ones that aren't a number are none.
+fn secs(s: f64) -> Duration {
+    if s.is_nan() {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f64(s.clamp(0.0, MAX_SECS))
+}
+
+impl Limiter {
+    pub fn new(interval: Duration, jitter: Duration) -> Self {
+        Limiter {
+            interval,
+            jitter,
+            next: HashMap::new(),
+        }
+    }
+
+    // Claim the next slot for a request to the host, and return how long to wait before
+    // sending it
+    pub fn reserve(&mut self, host: &str, now: Instant) -> Duration {
+        let at = self
+            .next
+            .get(host)
+            .copied()
+            .filter(|t| *t > now)
+            .unwrap_or(now);
+        self.next
+            .insert(host.to_string(), at + jittered(self.interval, self.jitter));
+        at - now
+    }
+}
+
+// Add a random amount of up to `jitter` to the duration, so that scheduled work doesn't
+// fire in lockstep
+pub fn jittered(d: Duration, jitter: Duration) -> Duration {
+    d + jitter.mul_f64(rand::random::<f64>())
+}
+
+static LIMITER: OnceLock<Mutex<Limiter>> = OnceLock::new();
+
+// The limiter shared by every request the app makes
+pub fn limiter() -> &'static Mutex<Limiter> {
+    LIMITER.get_or_init(|| Mutex::new(Limiter::default()))
+}
+
+// Apply the user's settings to the shared limiter
+pub fn configure(config: &NetworkConfig) {
+    if let Ok(mut l) = limiter().lock() {
+        *l = Limiter::from(config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{jittered, secs, Limiter, MAX_SECS};
+
+    #[test]
+    fn spaces_out_same_host() {
+        let mut l = Limiter::new(Duration::from_secs(2), Duration::ZERO);
+        let now = Instant::now();
+
+        assert_eq!(l.reserve("libsyn.com", now), Duration::ZERO);
+        assert_eq!(l.reserve("libsyn.com", now), Duration::from_secs(2));
+        assert_eq!(l.reserve("libsyn.com", now), Duration::from_secs(4));
+    }
+
+    #[test]
+    fn hosts_are_independent() {
+        let mut l = Limiter::new(Duration::from_secs(2), Duration::ZERO);
+        let now = Instant::now();
+
+        assert_eq!(l.reserve("libsyn.com", now), Duration::ZERO);
+        assert_eq!(l.reserve("captivate.fm", now), Duration::ZERO);
+    }
+
+    #[test]
+    fn no_wait_once_interval_passed() {
+        let mut l = Limiter::new(Duration::from_secs(2), Duration::ZERO);
+        let now = Instant::now();
+
+        l.reserve("libsyn.com", now);
+        let later = now + Duration::from_secs(3);
+        assert_eq!(l.reserve("libsyn.com", later), Duration::ZERO);
+    }
+
+    #[test]
+    fn caps_config_secs() {
+        assert_eq!(secs(2.5), Duration::from_millis(2500));
+        assert_eq!(secs(-1.0), Duration::ZERO);
+        assert_eq!(secs(f64::NAN), Duration::ZERO);
+        assert_eq!(secs(f64::INFINITY), Duration::from_secs_f64(MAX_SECS));
+        assert_eq!(secs(1e300), Duration::from_secs_f64(MAX_SECS));
+    }
+
+    #[test]
+    fn jitter_stays_in_bounds() {
+        let d = Duration::from_secs(10);
+        let jitter = Duration::from_secs(1);
+        for _ in 0..100 {
+            let j = jittered(d, jitter);
+            assert!(j >= d && j <= d + jitter);
+        }
+    }
+}
@@ -1,9 +1,264 @@
-use rss::Channel;
-use std::error::Error;
+pub mod limit;
+pub mod private;
+pub mod progressive;
+
+use image::DynamicImage;
+use rss::{Channel, Item};
+use std::{
+    error::Error,
+    time::{Duration, Instant},
+};
+use tracing::{debug, error};
 use url::Url;
 
-pub async fn get_feed(u: Url) -> Result<Channel, Box<dyn Error>> {
-    let content = reqwest::get(u.as_str()).await?.bytes().await?;
+use crate::{cache, metrics::Fetch};
+
+use self::progressive::Progressive;
+
+// How often to show what has been parsed of a feed that is still downloading
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+// Wait for our turn if other requests went to the same host recently. Every request the
+// app makes should go through here.
+pub async fn wait_turn(u: &Url) {
+    let host = u.host_str().unwrap_or_default();
+    let wait = limit::limiter()
+        .lock()
+        .map(|mut l| l.reserve(host, Instant::now()))
+        .unwrap_or_default();
+    if !wait.is_zero() {
+        debug!("waiting {:?} before requesting {}", wait, host);
+        tokio::time::sleep(wait).await;
+    }
+}
+
+// GET the URL
+pub async fn fetch(u: &Url) -> Result<Vec<u8>, Box<dyn Error>> {
+    wait_turn(u).await;
+    get(u).await
+}
+
+// GET the URL right away, only for callers that already waited their turn
+async fn get(u: &Url) -> Result<Vec<u8>, Box<dyn Error>> {
+    // reqwest errors include the URL, which may contain a private feed token
+    let res = reqwest::get(u.as_str())
+        .await
+        .map_err(|e| e.without_url())?;
+    Ok(res.bytes().await.map_err(|e| e.without_url())?.to_vec())
+}
+
+// Whether the server turned the request down, e.g. with a 404
+pub fn is_client_error(e: &(dyn Error + 'static)) -> bool {
+    e.downcast_ref::<reqwest::Error>()
+        .and_then(|e| e.status())
+        .is_some_and(|s| s.is_client_error())
+}
+
+// Content-Type the server reports for the URL, without downloading it
+pub async fn content_type(u: &Url) -> Result<Option<String>, Box<dyn Error>> {
+    wait_turn(u).await;
+    let res = reqwest::Client::new()
+        .head(u.as_str())
+        .send()
+        .await
+        .map_err(|e| e.without_url())?;
+    Ok(res
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string))
+}
+
+// Fetch and parse a feed, keeping a copy in the cache. Also returns how the fetch went,
+// for the diagnostics.
+pub async fn get_feed(u: Url) -> Result<(Channel, Fetch), Box<dyn Error>> {
+    wait_turn(&u).await;
+    // only time the request itself, not the wait
+    let start = Instant::now();
+    let content = get(&u).await?;
+    finish(&u, content, start.elapsed())
+}
+
+// Like `get_feed`, but while the feed downloads the episodes parsed so far are passed to
+// `on_part` every now and then, in batches. Feeds that download quickly have no batches.
+pub async fn get_feed_progressively<F>(
+    u: Url,
+    mut on_part: F,
+) -> Result<(Channel, Fetch), Box<dyn Error>>
+where
+    F: FnMut(Channel),
+{
+    wait_turn(&u).await;
+    let start = Instant::now();
+    let mut res = reqwest::get(u.as_str())
+        .await
+        .map_err(|e| e.without_url())?;
+    let mut progress = Progressive::default();
+    let mut last_part = start;
+    while let Some(chunk) = res.chunk().await.map_err(|e| e.without_url())? {
+        progress.push(&chunk[..]);
+        if last_part.elapsed() >= PROGRESS_INTERVAL {
+            if let Some(part) = progress.batch() {
+                debug!("parsed {} more episodes", part.items().len());
+                on_part(part);
+            }
+            last_part = Instant::now();
+        }
+    }
+    finish(&u, progress.into_bytes(), start.elapsed())
+}
+
+// Parse and cache a downloaded feed
+fn finish(
+    u: &Url,
+    content: Vec<u8>,
+    elapsed: Duration,
+) -> Result<(Channel, Fetch), Box<dyn Error>> {
     let channel = Channel::read_from(&content[..])?;
-    Ok(channel)
+    let unchanged = cache::is_current(u, &content[..]);
+    // saved even when unchanged, to record when the feed was refreshed
+    if let Err(e) = cache::save(u, &content[..]) {
+        error!("failed to cache feed: {:?}", e);
+    }
+    let fetch = Fetch {
+        elapsed,
+        bytes: content.len(),
+        unchanged,
+    };
+    Ok((channel, fetch))
+}
+
+// Images are drawn with one character cell per two pixels, so anything larger than
+// this is wasted on a terminal
+const IMAGE_SIZE: u32 = 256;
+
+pub async fn get_image(u: Url) -> Result<DynamicImage, Box<dyn Error>> {
+    let content = fetch(&u).await?;
+    let img = image::load_from_memory(&content[..])?;
+    Ok(img.thumbnail(IMAGE_SIZE, IMAGE_SIZE))
+}
+
+// Cover art of a podcast. The iTunes image is preferred, it's usually larger.
+pub fn artwork(c: &Channel) -> Option<&str> {
+    c.itunes_ext()
+        .and_then(|e| e.image())
+        .or_else(|| c.image().map(|i| i.url()))
+}
+
+// Something that identifies an episode within its feed: the guid if it has one,
+// otherwise the audio URL or the title
+pub fn episode_id(item: &Item) -> Option<&str> {
+    item.guid()
+        .map(|g| g.value())
+        .or_else(|| item.enclosure().map(|e| e.url()))
+        .or_else(|| item.title())
+}
+
+// When the episode came out, in seconds since the epoch. Feeds use RFC 2822 dates.
+pub fn published(item: &Item) -> Option<i64> {
+    item.pub_date()
+        .and_then(|d| chrono::DateTime::parse_from_rfc2822(d.trim()).ok())
+        .map(|d| d.timestamp())
+}
+
+// Cover art of an episode, falling back to the podcast's when it doesn't have its own
+pub fn episode_artwork<'a>(item: &'a Item, channel: Option<&'a Channel>) -> Option<&'a str> {
+    item.itunes_ext()
+        .and_then(|e| e.image())
+        .or_else(|| channel.and_then(artwork))
+}
+
+// Length of an episode, from its `itunes:duration`
+pub fn duration(item: &Item) -> Option<Duration> {
+    item.itunes_ext()
+        .and_then(|e| e.duration())
+        .and_then(parse_duration)
+}
+
+// Parse an `itunes:duration`, which is either a number of seconds or HH:MM:SS / MM:SS
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let mut secs = 0.0;
+    for part in s.trim().split(':') {
+        let n = part.parse::<f64>().ok().filter(|n| *n >= 0.0)?;
+        secs = secs * 60.0 + n;
+    }
+    // too long for a Duration, or NaN or infinite, counts as unknown
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rss::{
+        extension::itunes::{ITunesChannelExtension, ITunesItemExtension},
+        Channel, Item,
+    };
+
+    use super::{episode_artwork, parse_duration, published};
+
+    #[test]
+    fn parses_publish_dates() {
+        let item = |d: &str| Item {
+            pub_date: Some(d.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            published(&item("Tue, 02 May 2023 10:00:00 GMT")),
+            Some(1683021600)
+        );
+        assert_eq!(
+            published(&item(" Tue, 02 May 2023 12:00:00 +0200 ")),
+            Some(1683021600)
+        );
+        assert_eq!(published(&item("yesterday")), None);
+        assert_eq!(published(&Item::default()), None);
+    }
+
+    #[test]
+    fn episode_artwork_falls_back_to_channel() {
+        let mut ext = ITunesChannelExtension::default();
+        ext.set_image("https://example.com/show.png".to_string());
+        let mut channel = Channel::default();
+        channel.set_itunes_ext(ext);
+
+        let mut item = Item::default();
+        assert_eq!(
+            episode_artwork(&item, Some(&channel)),
+            Some("https://example.com/show.png")
+        );
+        assert_eq!(episode_artwork(&item, None), None);
+
+        let mut ext = ITunesItemExtension::default();
+        ext.set_image("https://example.com/episode.png".to_string());
+        item.set_itunes_ext(ext);
+        assert_eq!(
+            episode_artwork(&item, Some(&channel)),
+            Some("https://example.com/episode.png")
+        );
+    }
+
+    #[test]
+    fn parses_seconds() {
+        assert_eq!(parse_duration("3600"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("90.5"), Some(Duration::from_secs_f64(90.5)));
+    }
+
+    #[test]
+    fn parses_timestamps() {
+        assert_eq!(parse_duration("1:02:03"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_duration("45:30"), Some(Duration::from_secs(2730)));
+        assert_eq!(parse_duration(" 01:00 "), Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("an hour"), None);
+        assert_eq!(parse_duration("1::00"), None);
+        assert_eq!(parse_duration("-5"), None);
+        assert_eq!(parse_duration("NaN"), None);
+        assert_eq!(parse_duration("inf"), None);
+        assert_eq!(parse_duration("1e300"), None);
+    }
 }
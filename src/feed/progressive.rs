@@ -0,0 +1,132 @@
+use rss::Channel;
+
+// Progressive parses a feed while it downloads, so that huge archive feeds can be shown
+// before all of them has arrived. Batches of complete items are parsed on their own,
+// wrapped in everything that comes before the first item (which declares the namespaces
+// extensions need) and the closing tags. Finding items by their tags can be fooled by
+// tags inside CDATA, in which case parsing fails and the items are tried again with more
+// data. The complete document is always parsed at the end anyway.
+#[derive(Default, Debug)]
+pub struct Progressive {
+    buf: Vec<u8>,
+    // where the first item starts, once known
+    header_end: Option<usize>,
+    // how far items have been parsed
+    parsed_to: usize,
+}
+
+const ITEM_START: &[u8] = b"<item";
+const ITEM_END: &[u8] = b"</item>";
+const CLOSING: &[u8] = b"</channel></rss>";
+
+impl Progressive {
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buf.extend_from_slice(chunk);
+        if self.header_end.is_none() {
+            self.header_end = find(&self.buf, ITEM_START);
+            self.parsed_to = self.header_end.unwrap_or_default();
+        }
+    }
+
+    // The channel with the items that arrived since the last batch, if there are any
+    pub fn batch(&mut self) -> Option<Channel> {
+        let header_end = self.header_end?;
+        let rest = &self.buf[self.parsed_to..];
+        // all the complete items, or failing that just the first one
+        let last = rfind(rest, ITEM_END)?;
+        let first = find(rest, ITEM_END)?;
+        let mut ends = vec![last];
+        if first != last {
+            ends.push(first);
+        }
+        for end in ends {
+            let end = self.parsed_to + end + ITEM_END.len();
+            let mut doc = Vec::with_capacity(header_end + end - self.parsed_to + CLOSING.len());
+            doc.extend_from_slice(&self.buf[..header_end]);
+            doc.extend_from_slice(&self.buf[self.parsed_to..end]);
+            doc.extend_from_slice(CLOSING);
+
+            if let Ok(channel) = Channel::read_from(&doc[..]) {
+                self.parsed_to = end;
+                return Some(channel);
+            }
+        }
+        None
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Progressive;
+
+    const FEED: &str = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+<channel>
+<title>Big</title>
+<item><title>3</title><itunes:duration>60</itunes:duration></item>
+<item><title>2</title></item>
+<item><title>1</title></item>
+</channel>
+</rss>"#;
+
+    fn titles(p: &mut Progressive) -> Vec<String> {
+        p.batch()
+            .map(|c| {
+                c.items()
+                    .iter()
+                    .map(|i| i.title().unwrap_or_default().to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn batches_complete_items() {
+        let mut p = Progressive::default();
+        let (first, rest) = FEED.split_at(FEED.find("<item><title>2").unwrap() + 10);
+
+        p.push(first.as_bytes());
+        let c = p.batch().unwrap();
+        assert_eq!(c.title(), "Big");
+        assert_eq!(c.items().len(), 1);
+        // namespaces from the header still apply
+        assert!(c.items()[0].itunes_ext().is_some());
+        // nothing new until the next item is complete
+        assert!(p.batch().is_none());
+
+        p.push(rest.as_bytes());
+        assert_eq!(titles(&mut p), ["2", "1"]);
+        assert!(p.batch().is_none());
+        assert_eq!(p.into_bytes(), FEED.as_bytes());
+    }
+
+    #[test]
+    fn retries_items_that_fail_to_parse() {
+        let mut p = Progressive::default();
+        let feed = FEED.replace(
+            "<title>2</title>",
+            "<title>2</title><description><![CDATA[a </item> b]]></description>",
+        );
+        let (first, rest) = feed.split_at(feed.find("b]]>").unwrap());
+
+        p.push(first.as_bytes());
+        assert_eq!(titles(&mut p), ["3"]);
+        // the item is cut off inside its CDATA
+        assert!(p.batch().is_none());
+
+        p.push(rest.as_bytes());
+        assert_eq!(titles(&mut p), ["2", "1"]);
+    }
+}
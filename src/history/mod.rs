@@ -0,0 +1,145 @@
+use rss::Item;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, error::Error, time::Duration};
+
+use crate::{feed::episode_id, store::db::Db};
+
+// How many listening sessions are loaded, the rest stay in the database
+const LOADED: usize = 500;
+
+// How many listening sessions the database keeps, the oldest are forgotten
+const KEPT: usize = 10_000;
+
+// Listen is one session of playing an episode, from when it started playing until it was
+// paused, stopped or finished
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Listen {
+    // kept whole, so that it can be played without loading its feed
+    pub item: Item,
+    pub feed: String,
+    // title of the podcast
+    pub podcast: Option<String>,
+    // in seconds since the epoch
+    pub started: i64,
+    pub stopped: i64,
+    // how far into the episode playback got
+    pub position: Duration,
+}
+
+impl Listen {
+    pub fn title(&self) -> &str {
+        self.item.title().unwrap_or("Title missing!")
+    }
+}
+
+// History is every listening session, newest first. Stored in the database.
+#[derive(Default, Debug, PartialEq)]
+pub struct History {
+    listens: Vec<Listen>,
+}
+
+impl History {
+    // Keep the session, in the database too
+    pub fn add(&mut self, listen: Listen, db: &Db) -> Result<(), Box<dyn Error>> {
+        db.save_listen(&listen)?;
+        db.prune_listens(KEPT)?;
+        self.listens.insert(0, listen);
+        self.listens.truncate(LOADED);
+        Ok(())
+    }
+
+    // The episodes listened to, by their latest session, most recent first
+    pub fn episodes(&self) -> Vec<&Listen> {
+        let mut seen = HashSet::new();
+        self.listens
+            .iter()
+            // episodes without an id can't be told apart, so each session is listed
+            .filter(|l| episode_id(&l.item).is_none_or(|id| seen.insert((l.feed.as_str(), id))))
+            .collect()
+    }
+
+    // When the episode was last listened to, if it was lately
+    pub fn last_listened(&self, feed: &str, item: &Item) -> Option<i64> {
+        let id = episode_id(item)?;
+        self.listens
+            .iter()
+            .find(|l| l.feed == feed && episode_id(&l.item) == Some(id))
+            .map(|l| l.stopped)
+    }
+
+    pub fn load(db: &Db) -> Result<History, Box<dyn Error>> {
+        Ok(History {
+            listens: db.load_listens(Some(LOADED))?,
+        })
+    }
+}
+
+// How long ago a time in seconds since the epoch was, e.g. "3 hours ago"
+pub fn ago(then: i64, now: i64) -> String {
+    let secs = (now - then).max(0);
+    let (n, unit) = match secs {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => (secs / 60, "minute"),
+        3600..=86399 => (secs / 3600, "hour"),
+        _ => (secs / 86400, "day"),
+    };
+    format!("{} {}{} ago", n, unit, if n == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use rss::{Guid, Item};
+    use std::time::Duration;
+
+    use crate::store::db::Db;
+
+    use super::{ago, History, Listen};
+
+    fn listen(id: &str, started: i64) -> Listen {
+        Listen {
+            item: Item {
+                title: Some(format!("Episode {}", id)),
+                guid: Some(Guid {
+                    value: id.to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            feed: "https://example.com/feed".to_string(),
+            podcast: Some("Wine About It".to_string()),
+            started,
+            stopped: started + 60,
+            position: Duration::from_secs(started as u64),
+        }
+    }
+
+    #[test]
+    fn lists_latest_session_of_each_episode() {
+        let db = Db::open_in_memory().unwrap();
+        let mut h = History::default();
+        h.add(listen("1", 100), &db).unwrap();
+        h.add(listen("2", 200), &db).unwrap();
+        h.add(listen("1", 300), &db).unwrap();
+
+        let episodes = h.episodes();
+        assert_eq!(episodes.len(), 2);
+        assert_eq!(episodes[0].started, 300);
+        assert_eq!(episodes[1].title(), "Episode 2");
+        assert_eq!(History::load(&db).unwrap(), h);
+
+        db.prune_listens(2).unwrap();
+        let kept = db.load_listens(None).unwrap();
+        assert_eq!(
+            kept.iter().map(|l| l.started).collect::<Vec<i64>>(),
+            vec![300, 200]
+        );
+    }
+
+    #[test]
+    fn says_how_long_ago() {
+        assert_eq!(ago(100, 130), "just now");
+        assert_eq!(ago(0, 60), "1 minute ago");
+        assert_eq!(ago(0, 7200), "2 hours ago");
+        assert_eq!(ago(0, 86400 * 3), "3 days ago");
+    }
+}
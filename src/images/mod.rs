@@ -0,0 +1,74 @@
+use std::{cell::Cell, collections::HashMap};
+
+use image::DynamicImage;
+
+// Most images kept loaded. They're thumbnails, but a podcast with a picture for every
+// chapter can have hundreds of them.
+pub const MAX: usize = 64;
+
+// Images is the artwork loaded so far, keyed by URL. Once it's full, the image used
+// longest ago makes room for a new one.
+#[derive(Default, Debug)]
+pub struct Images {
+    // images and when they were last used, by the clock below
+    loaded: HashMap<String, (DynamicImage, Cell<u64>)>,
+    // counts up every time an image is used
+    clock: Cell<u64>,
+}
+
+impl Images {
+    // The image from the URL, if it's loaded. Getting it counts as using it, which is why
+    // drawing it can keep it around.
+    pub fn get(&self, u: &str) -> Option<&DynamicImage> {
+        let (img, used) = self.loaded.get(u)?;
+        used.set(self.tick());
+        Some(img)
+    }
+
+    pub fn insert(&mut self, u: String, img: DynamicImage) {
+        if !self.loaded.contains_key(&u) && self.loaded.len() >= MAX {
+            let oldest = self
+                .loaded
+                .iter()
+                .min_by_key(|(_, (_, used))| used.get())
+                .map(|(u, _)| u.clone());
+            if let Some(oldest) = oldest {
+                self.loaded.remove(&oldest);
+            }
+        }
+        let used = Cell::new(self.tick());
+        self.loaded.insert(u, (img, used));
+    }
+
+    fn tick(&self) -> u64 {
+        let now = self.clock.get() + 1;
+        self.clock.set(now);
+        now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::DynamicImage;
+
+    use super::{Images, MAX};
+
+    #[test]
+    fn forgets_least_recently_used() {
+        let mut images = Images::default();
+        for i in 0..MAX {
+            images.insert(i.to_string(), DynamicImage::new_rgb8(1, 1));
+        }
+        // the first one is used again, so the second is the oldest
+        assert!(images.get("0").is_some());
+        images.insert("new".to_string(), DynamicImage::new_rgb8(1, 1));
+        assert_eq!(images.loaded.len(), MAX);
+        assert!(images.get("0").is_some());
+        assert!(images.get("1").is_none());
+        assert!(images.get("new").is_some());
+
+        // loading one again doesn't make room
+        images.insert("new".to_string(), DynamicImage::new_rgb8(1, 1));
+        assert!(images.get("2").is_some());
+    }
+}
@@ -1,23 +1,82 @@
+mod backup;
+mod bookmarks;
+mod cache;
+mod chapters;
+mod commands;
+mod config;
 mod data;
+mod downloads;
+mod export;
 mod feed;
+mod history;
+mod images;
+mod marks;
+mod media;
 mod message;
+mod metrics;
+#[cfg(target_os = "linux")]
+mod mpris;
+mod nowplaying;
+mod opml;
+mod played;
+mod player;
+mod profile;
+mod queue;
+mod search;
+mod session;
+mod speech;
+mod stars;
+mod storage;
+mod store;
+mod subscriptions;
 mod trace;
 mod ui;
+mod update;
 
+use bookmarks::{Bookmark, Bookmarks};
+use chapters::{chapters_url, Chapters};
+use chrono::Timelike;
+use commands::Commands;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
+    },
 };
-use message::DisplayAction;
+use downloads::Downloads;
+use feed::episode_id;
+use history::{History, Listen};
+use images::Images;
+use marks::{Location, Marks};
+use media::Kind;
+use message::{Confirm, DisplayAction, Remote};
+use metrics::Metrics;
+use nowplaying::NowPlaying;
+use played::Played;
+use player::Player;
+use queue::Queue;
 use rss::{Channel, Item};
+use search::SearchResult;
+use session::{Session, View};
+use speech::Speaker;
+use stars::{Star, Stars};
 use std::sync::mpsc;
 use std::thread;
 use std::{
+    collections::HashMap,
     error::Error,
     io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
     sync::mpsc::{Receiver, Sender},
-    time::Duration,
+    time::{Duration, Instant},
+};
+use storage::Storage;
+use store::{db::Db, Dirty};
+use subscriptions::{
+    settings::{Setting, SETTINGS},
+    Subscription, Subscriptions,
 };
 use tracing::{debug, error, info, instrument, span, Level};
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
@@ -29,65 +88,3474 @@ use tui::{
     widgets::ListState,
     Frame, Terminal,
 };
-use ui::draw_main_layout;
+use ui::{
+    component::{Action, Screen},
+    draw_main_layout,
+    input::Command,
+    tabs::Tab,
+    theme::{EpisodeState, Theme},
+    title::{window_title, RestoreTitle, SaveTitle},
+    toast::Toasts,
+};
+
+use crate::ui::input::parse;
+
+// How often subscriptions are checked for being due a refresh, see `[refresh]` in the config
+const REFRESH_CHECK: Duration = Duration::from_secs(60);
+
+// How often downloads the config doesn't keep anymore are deleted
+const CLEAN_UP_CHECK: Duration = Duration::from_secs(10 * 60);
+
+// App holds the state of the application
+// TODO: persist application state about podcast that is loaded.
+#[derive(Default, Debug)]
+pub struct App {
+    // Loaded podcast channel/feed, and where it was loaded from
+    channel: Option<Channel>,
+    feed_url: Option<url::Url>,
+    // Feeds the user subscribed to
+    subscriptions: Subscriptions,
+    // Choice on the feed preview screen
+    preview_state: ListState,
+    // Loaded podcast episode
+    item: Option<Item>,
+    // Chapters of the loaded episode, if it has any
+    chapters: Option<Chapters>,
+    // Loaded images, keyed by URL
+    images: Images,
+    // Playback position in the loaded episode
+    position: Duration,
+    state: ListState, // TODO: should there be multiple list states?
+    // Podcasts found by the last search, and the actions menu for the selected one
+    search_results: Vec<SearchResult>,
+    search_state: ListState,
+    search_menu: bool,
+    search_menu_state: ListState,
+    // Episodes that have been listened to
+    played: Played,
+    // podcasts and episodes loaded before, and how far into them playback got. None if
+    // the database couldn't be opened.
+    db: Option<Db>,
+    // Episodes to play next
+    queue: Queue,
+    queue_state: ListState,
+    // moments in episodes to come back to
+    bookmarks: Bookmarks,
+    bookmarks_state: ListState,
+    // favorite episodes
+    stars: Stars,
+    stars_state: ListState,
+    // every time an episode was listened to, and when the one playing started playing
+    history: History,
+    history_state: ListState,
+    listening: Option<i64>,
+    // episodes downloaded, and being downloaded
+    downloads: Downloads,
+    downloads_state: ListState,
+    // disk space taken, measured when it's shown
+    storage: Storage,
+    storage_state: ListState,
+    // downloads cleaning up would delete and why, as listed by /cleanup
+    clean_up: Vec<(downloads::Job, downloads::Expiry)>,
+    clean_up_state: ListState,
+    // how many downloads were last said to be due for cleaning up, to say so only once
+    clean_up_due: usize,
+    // keep track of what to render on the UI across ticks
+    display_action: DisplayAction,
+    // the tab on screen. Views opened by command that don't belong to a tab, like the
+    // history, show in the one they were opened from.
+    tab: Tab,
+    // what to go back to when leaving the now playing screen, and its tab
+    previous_display: DisplayAction,
+    previous_tab: Tab,
+    // the view each tab was left on, to come back to it
+    tab_views: HashMap<Tab, DisplayAction>,
+    // user preferences loaded at startup
+    config: config::Config,
+    // styles used when rendering
+    theme: Theme,
+    // last announcement shown in the status line
+    status: String,
+    // last error, shown in the status line instead until the next key press
+    error: Option<String>,
+    // named marks and the jump list
+    marks: Marks,
+    // first key of a two key shortcut, like `m` in `m a`
+    pending_key: Option<char>,
+    // where to go once the feed that is loading arrives
+    pending_jump: Option<Location>,
+    // action waiting for a yes or no
+    confirm: Option<Confirm>,
+    // what the user is asked about it, shown in a popup
+    question: String,
+    // reads show notes aloud
+    speaker: Speaker,
+    // whether playback was paused to read show notes, to pick up again once they're done
+    resume_after_speech: bool,
+    // plays episodes on its own thread
+    player: Player,
+    // Library list selection
+    library_state: ListState,
+    // feed URL of the podcast whose settings are on screen, and the selected setting
+    settings_podcast: Option<String>,
+    settings_state: ListState,
+    // how many episodes of the feed to list, None for all of them
+    episode_limit: Option<usize>,
+    // only list the episodes with this in their title, lowercased
+    episode_filter: Option<String>,
+    // the row of the episode list a range of episodes is being selected from
+    visual: Option<usize>,
+    // the feed on screen is still coming in, in parts
+    loading_parts: bool,
+    // when the feed on screen started to download, until it's in
+    loading: Option<Instant>,
+    // changes not saved yet
+    dirty: Dirty,
+    // data that failed to load, never saved over so that what couldn't be read isn't lost
+    unloaded: Dirty,
+    // how fetching feeds went, and the order to show that in
+    metrics: Metrics,
+    metrics_sort: metrics::Sort,
+    // subscriptions still being refreshed in the background, how many of them failed and
+    // how many new episodes they had
+    refreshing: usize,
+    refresh_failures: usize,
+    refresh_new: usize,
+    // new episodes of podcasts that asked to be told about them, e.g. "2 of Wine About It"
+    refresh_notices: Vec<String>,
+    // when each subscription was last refreshed while the app runs, by feed URL, whether
+    // that worked or not
+    refreshed: HashMap<String, Instant>,
+    // newer release of the app, if the update check found one
+    update: Option<update::Release>,
+    // notices of what happened in the background, in a corner of the screen
+    toasts: Toasts,
+    // tells the desktop what's playing
+    #[cfg(target_os = "linux")]
+    mpris: Option<Sender<mpris::Snapshot>>,
+}
+
+impl App {
+    // Replace the status line contents. In accessible mode this is how focus and state
+    // changes are communicated, since highlighting alone is invisible to screen readers.
+    pub fn announce<S: Into<String>>(&mut self, msg: S) {
+        self.status = msg.into();
+        debug!(status = self.status);
+    }
+
+    // Announce something that happened in the background, in a toast as well
+    pub fn toast<S: Into<String>>(&mut self, msg: S) {
+        let msg = msg.into();
+        self.toasts.push(msg.clone(), Instant::now());
+        self.announce(msg);
+    }
+
+    // Show an error until the next key press. Announcements can't push it out of the
+    // status line, so that it isn't missed.
+    pub fn show_error<S: Into<String>>(&mut self, msg: S) {
+        let msg = msg.into();
+        error!(error = msg);
+        self.error = Some(msg);
+    }
+
+    // Announce the currently selected list entry, e.g. "Episode 3 of 10: Title"
+    fn announce_selection(&mut self) {
+        let msg = match self.display_action {
+            DisplayAction::Library => self.selected_subscription().map(|s| match s.new {
+                0 => format!(
+                    "{}: {} unplayed episodes",
+                    s.title,
+                    s.unplayed(&self.played)
+                ),
+                n => format!(
+                    "{}: {} new, {} unplayed episodes",
+                    s.title,
+                    n,
+                    s.unplayed(&self.played)
+                ),
+            }),
+            DisplayAction::PodcastSettings => self.selected_setting().and_then(|setting| {
+                let s = self.subscriptions.get(self.settings_podcast.as_deref()?)?;
+                Some(format!(
+                    "{}: {}",
+                    setting.name(),
+                    setting.value(s, &self.config)
+                ))
+            }),
+            DisplayAction::Preview => self
+                .preview_state
+                .selected()
+                .and_then(|idx| ui::PREVIEW_CHOICES.get(idx))
+                .map(|choice| choice.to_string()),
+            DisplayAction::Search if self.search_menu => self
+                .search_menu_state
+                .selected()
+                .and_then(|idx| ui::SEARCH_ACTIONS.get(idx))
+                .map(|action| action.to_string()),
+            DisplayAction::Search => self.search_state.selected().and_then(|idx| {
+                self.search_results.get(idx).map(|r| {
+                    format!(
+                        "Result {} of {}: {} by {}",
+                        idx + 1,
+                        self.search_results.len(),
+                        r.title,
+                        r.author
+                    )
+                })
+            }),
+            DisplayAction::Bookmarks => self.bookmarks_state.selected().and_then(|idx| {
+                self.bookmarks.get(idx).map(|b| {
+                    format!(
+                        "Bookmark {} of {}: {}",
+                        idx + 1,
+                        self.bookmarks.len(),
+                        b.describe()
+                    )
+                })
+            }),
+            DisplayAction::History => self.history_state.selected().and_then(|idx| {
+                let episodes = self.history.episodes();
+                episodes.get(idx).map(|l| {
+                    format!(
+                        "{} of {}: {}, listened {}, up to {}",
+                        idx + 1,
+                        episodes.len(),
+                        l.title(),
+                        history::ago(l.stopped, now()),
+                        ui::fmt_duration(l.position)
+                    )
+                })
+            }),
+            DisplayAction::Starred => self.stars_state.selected().and_then(|idx| {
+                self.stars.get(idx).map(|s| {
+                    format!(
+                        "Starred episode {} of {}: {}",
+                        idx + 1,
+                        self.stars.len(),
+                        s.title()
+                    )
+                })
+            }),
+            DisplayAction::Downloads => self.downloads_state.selected().and_then(|idx| {
+                self.downloads.get_index(idx).map(|j| {
+                    format!(
+                        "Download {} of {}: {}, {}",
+                        idx + 1,
+                        self.downloads.len(),
+                        j.title(),
+                        j.status.describe()
+                    )
+                })
+            }),
+            DisplayAction::Storage => self.storage_state.selected().and_then(|idx| {
+                self.storage.get(idx).map(|u| {
+                    format!(
+                        "{}: {}, {} downloads",
+                        u.title,
+                        ui::fmt_bytes(u.total()),
+                        u.downloads
+                    )
+                })
+            }),
+            DisplayAction::CleanUp => self.clean_up_state.selected().and_then(|idx| {
+                self.clean_up
+                    .get(idx)
+                    .map(|(j, expiry)| format!("{}, {}", j.title(), expiry.describe()))
+            }),
+            DisplayAction::Queue => self.queue_state.selected().and_then(|idx| {
+                self.queue.entries().get(idx).map(|e| {
+                    format!(
+                        "Queue entry {} of {}: {}",
+                        idx + 1,
+                        self.queue.len(),
+                        e.item.title().unwrap_or("Title missing!")
+                    )
+                })
+            }),
+            _ if self.has_older_episodes()
+                && self.state.selected() == Some(self.shown_episodes()) =>
+            {
+                Some(ui::OLDER_EPISODES.to_string())
+            }
+            _ => self.channel.as_ref().and_then(|c| {
+                let idx = *self.episode_rows().get(self.state.selected()?)?;
+                c.items().get(idx).map(|item| {
+                    format!(
+                        "Episode {} of {}: {}",
+                        idx + 1,
+                        c.items().len(),
+                        item.title().unwrap_or("Title missing!")
+                    )
+                })
+            }),
+        };
+        if let Some(msg) = msg {
+            self.announce(msg);
+        }
+    }
+
+    // Number of entries in the list that is currently displayed
+    fn list_len(&self) -> usize {
+        match self.display_action {
+            DisplayAction::Library => self.subscriptions.sorted(&self.played).len(),
+            DisplayAction::Preview => ui::PREVIEW_CHOICES.len(),
+            DisplayAction::Search if self.search_menu => ui::SEARCH_ACTIONS.len(),
+            DisplayAction::Search => self.search_results.len(),
+            DisplayAction::Queue => self.queue.len(),
+            DisplayAction::Bookmarks => self.bookmarks.len(),
+            DisplayAction::PodcastSettings => SETTINGS.len(),
+            DisplayAction::Starred => self.stars.len(),
+            DisplayAction::Downloads => self.downloads.len(),
+            DisplayAction::Storage => self.storage.len(),
+            DisplayAction::CleanUp => self.clean_up.len(),
+            DisplayAction::History => self.history.episodes().len(),
+            // nothing to select
+            DisplayAction::Diagnostics | DisplayAction::NowPlaying => 0,
+            // the episodes, then the row to show older ones
+            _ => self.episode_rows().len() + usize::from(self.has_older_episodes()),
+        }
+    }
+
+    // Selection state of the list that is currently displayed
+    fn list_state(&mut self) -> &mut ListState {
+        match self.display_action {
+            DisplayAction::Library => &mut self.library_state,
+            DisplayAction::Preview => &mut self.preview_state,
+            DisplayAction::Search if self.search_menu => &mut self.search_menu_state,
+            DisplayAction::Search => &mut self.search_state,
+            DisplayAction::Queue => &mut self.queue_state,
+            DisplayAction::Bookmarks => &mut self.bookmarks_state,
+            DisplayAction::PodcastSettings => &mut self.settings_state,
+            DisplayAction::Starred => &mut self.stars_state,
+            DisplayAction::Downloads => &mut self.downloads_state,
+            DisplayAction::Storage => &mut self.storage_state,
+            DisplayAction::CleanUp => &mut self.clean_up_state,
+            DisplayAction::History => &mut self.history_state,
+            _ => &mut self.state,
+        }
+    }
+
+    // Select the next item. This will not be reflected until the widget is drawn in the
+    // `Terminal::draw` callback using `Frame::render_stateful_widget`.
+    #[instrument(skip(self))]
+    pub fn next(&mut self) {
+        let len = self.list_len();
+        if len == 0 {
+            return;
+        }
+        let state = self.list_state();
+        let i = state
+            .selected()
+            .map(|i| if i >= len - 1 { 0 } else { i + 1 })
+            .unwrap_or_default();
+        debug!(idx = i);
+        state.select(Some(i));
+        self.announce_selection();
+    }
+
+    // Select the previous item. This will not be reflected until the widget is drawn in the
+    // `Terminal::draw` callback using `Frame::render_stateful_widget`.
+    #[instrument(skip(self))]
+    pub fn previous(&mut self) {
+        let len = self.list_len();
+        if len == 0 {
+            return;
+        }
+        let state = self.list_state();
+        let i = state
+            .selected()
+            .map(|i| if i == 0 { len - 1 } else { i - 1 })
+            .unwrap_or_default();
+        debug!(idx = i);
+        state.select(Some(i));
+        self.announce_selection();
+    }
+
+    // The episode that is selected in the episode list
+    fn selected_episode(&self) -> Option<Item> {
+        let idx = *self.episode_rows().get(self.state.selected()?)?;
+        self.channel.as_ref()?.items().get(idx).cloned()
+    }
+
+    // Start selecting a range of episodes from the selected one, or stop
+    pub fn toggle_visual(&mut self) {
+        if self.visual.take().is_some() {
+            self.announce("Stopped selecting episodes");
+            return;
+        }
+        let rows = self.episode_rows().len();
+        let Some(row) = self.state.selected().filter(|r| *r < rows) else {
+            self.announce("Select an episode first");
+            return;
+        };
+        self.visual = Some(row);
+        self.announce(
+            "Selecting episodes: move to select more, then a, d, P or D acts on all of them. \
+             V stops",
+        );
+    }
+
+    // The rows of the episode list selected while selecting a range
+    pub fn visual_rows(&self) -> Option<RangeInclusive<usize>> {
+        let from = self.visual?;
+        let last = self.episode_rows().len().checked_sub(1)?;
+        let to = self.state.selected()?.min(last);
+        Some(from.min(to)..=from.max(to))
+    }
+
+    // The episodes selected while selecting a range, in the order listed, which stops
+    // selecting. None when not selecting.
+    fn take_visual_episodes(&mut self) -> Option<Vec<Item>> {
+        let rows = self.visual_rows();
+        self.visual = None;
+        if self.display_action != DisplayAction::ListEpisodes {
+            return None;
+        }
+        let c = self.channel.as_ref()?;
+        let listed = self.episode_rows();
+        let episodes = listed.get(rows?)?;
+        Some(
+            episodes
+                .iter()
+                .filter_map(|idx| c.items().get(*idx).cloned())
+                .collect(),
+        )
+    }
+
+    // The podcast that is selected in the library
+    fn selected_subscription(&self) -> Option<&Subscription> {
+        let idx = self.library_state.selected()?;
+        self.subscriptions.sorted(&self.played).get(idx).copied()
+    }
+
+    // Open the podcast at this row of the sidebar
+    pub fn open_sidebar_podcast(&mut self, idx: usize, sender: &Sender<message::Request>) {
+        let Some(u) = ui::sidebar::podcasts(&self.subscriptions)
+            .get(idx)
+            .and_then(|s| url::Url::parse(s.url.as_str()).ok())
+        else {
+            return;
+        };
+        self.go_to(
+            Location {
+                feed: u,
+                episode: None,
+            },
+            sender,
+        );
+    }
+
+    // Open the podcast selected in the library
+    pub fn open_subscription(&mut self, sender: &Sender<message::Request>) {
+        let Some(u) = self
+            .selected_subscription()
+            .and_then(|s| url::Url::parse(s.url.as_str()).ok())
+        else {
+            return;
+        };
+        self.go_to(
+            Location {
+                feed: u,
+                episode: None,
+            },
+            sender,
+        );
+    }
+
+    // Show the settings of the podcast selected in the library, or else the one playing
+    // or on screen
+    pub fn show_podcast_settings(&mut self) {
+        let feed = match self.display_action {
+            DisplayAction::Library => self.selected_subscription().map(|s| s.url.clone()),
+            _ => self.settings_feed().map(|u| u.to_string()),
+        };
+        let Some(s) = feed.and_then(|u| self.subscriptions.get(u.as_str())) else {
+            self.announce("Subscribe to the podcast to change its settings");
+            return;
+        };
+        let msg = format!(
+            "Settings for {}. Press Left or Right to change one, Backspace to reset it",
+            s.title
+        );
+        self.settings_podcast = Some(s.url.clone());
+        self.settings_state.select(Some(0));
+        self.show(DisplayAction::PodcastSettings);
+        self.announce(msg);
+    }
+
+    fn selected_setting(&self) -> Option<Setting> {
+        self.settings_state
+            .selected()
+            .and_then(|idx| SETTINGS.get(idx))
+            .copied()
+    }
+
+    // Step the selected setting of the podcast on the settings screen up or down
+    pub fn change_setting(&mut self, step: i32) {
+        let config = self.config.clone();
+        self.edit_setting(|setting, s| setting.adjust(s, step, &config));
+    }
+
+    pub fn reset_setting(&mut self) {
+        self.edit_setting(|setting, s| setting.reset(s));
+    }
+
+    fn edit_setting(&mut self, edit: impl FnOnce(Setting, &mut Subscription)) {
+        let (Some(setting), Some(feed)) = (self.selected_setting(), self.settings_podcast.clone())
+        else {
+            return;
+        };
+        let Some(s) = self.subscriptions.get_mut(feed.as_str()) else {
+            return;
+        };
+        edit(setting, s);
+        let value = setting.value(s, &self.config);
+        let speed = s.speed;
+        self.dirty.subscriptions = true;
+        if let Err(e) = self.save() {
+            error!("failed to save subscriptions: {:?}", e);
+            self.announce(format!("Failed to save the podcast's settings: {}", e));
+            return;
+        }
+        let playing = self.player.episode.as_ref().and_then(|e| e.feed.as_ref());
+        if setting == Setting::Speed && playing.map(|u| u.as_str()) == Some(feed.as_str()) {
+            self.apply_speed(speed.unwrap_or(self.config.playback.speed));
+        }
+        self.announce(format!("{}: {}", setting.name(), value));
+    }
+
+    // Change the order of the library, keeping the selected podcast selected
+    pub fn sort_library(&mut self) {
+        let selected = self.selected_subscription().map(|s| s.url.clone());
+        self.subscriptions.sort = self.subscriptions.sort.next();
+        self.dirty.subscriptions = true;
+        let idx = self
+            .subscriptions
+            .sorted(&self.played)
+            .iter()
+            .position(|s| Some(&s.url) == selected.as_ref());
+        self.library_state.select(idx.or(Some(0)));
+        self.announce(format!("Sorted by {}", self.subscriptions.sort.name()));
+    }
+
+    // Keep a copy of a feed that was fetched, to show it right away next time
+    pub fn store_feed(&mut self, url: &str, c: &Channel) {
+        if let Some(db) = self.db.as_mut() {
+            if let Err(e) = db.save_feed(url, c) {
+                error!("failed to store feed: {:?}", e);
+            }
+        }
+    }
+
+    // The copy of a feed from the last time it was fetched
+    // Cover art of the loaded podcast
+    fn artwork(&self) -> Option<String> {
+        self.channel
+            .as_ref()
+            .and_then(feed::artwork)
+            .map(str::to_string)
+    }
+
+    pub fn stored_feed(&self, u: &url::Url) -> Option<Channel> {
+        self.db.as_ref()?.load_feed(u.as_str()).unwrap_or_else(|e| {
+            error!("failed to load stored feed: {:?}", e);
+            None
+        })
+    }
+
+    // Where playback of an episode of the loaded feed was left
+    pub fn saved_position(&self, item: &Item) -> Duration {
+        let (Some(db), Some(feed), Some(id)) =
+            (self.db.as_ref(), self.feed_url.as_ref(), episode_id(item))
+        else {
+            return Duration::ZERO;
+        };
+        db.position(feed.as_str(), id).unwrap_or_else(|e| {
+            error!("failed to load position: {:?}", e);
+            Duration::ZERO
+        })
+    }
+
+    // Remember how far into the episode in the player playback got
+    fn save_position(&self) {
+        let (Some(db), Some(e)) = (self.db.as_ref(), self.player.episode.as_ref()) else {
+            return;
+        };
+        let Some(feed) = e.feed.as_ref() else {
+            return;
+        };
+        if let Err(e) = db.save_position(feed.as_str(), &e.item, self.player.status.position) {
+            error!("failed to save position: {:?}", e);
+        }
+    }
+
+    // Keep what's known about a subscribed feed up to date when it's loaded
+    pub fn remember_feed(&mut self, url: &str, c: &Channel) {
+        if self.subscriptions.update(url, c) {
+            self.dirty.subscriptions = true;
+        }
+    }
+
+    // Like `remember_feed`, for the feed on screen, whose new episodes are being seen
+    fn remember_open_feed(&mut self, c: &Channel) {
+        let Some(u) = self.feed_url.clone() else {
+            return;
+        };
+        self.remember_feed(u.as_str(), c);
+        if self.subscriptions.seen(u.as_str()) {
+            self.dirty.subscriptions = true;
+        }
+    }
+
+    // Fetch the feeds again in the background, unless a refresh is already going
+    pub fn refresh(&mut self, feeds: Vec<url::Url>, sender: &Sender<message::Request>) {
+        if self.refreshing > 0 {
+            self.announce(format!("Still refreshing {} podcasts", self.refreshing));
+            return;
+        }
+        if feeds.is_empty() {
+            return;
+        }
+        info!("refreshing {} subscriptions", feeds.len());
+        let now = Instant::now();
+        for u in &feeds {
+            self.refreshed.insert(u.to_string(), now);
+        }
+        self.refreshing = feeds.len();
+        self.refresh_failures = 0;
+        self.refresh_new = 0;
+        self.refresh_notices.clear();
+        self.announce(format!("Refreshing {} podcasts", feeds.len()));
+        if let Err(e) = sender.send(message::Request::Refresh(feeds)) {
+            error!("failed to send message {:?}", e);
+            self.refreshing = 0;
+        }
+    }
+
+    // Subscriptions that were last refreshed longer ago than their refresh interval. One
+    // that failed waits as long as one that worked. Before the app refreshed it, its cached
+    // feed tells when it last was.
+    fn due_for_refresh(&self) -> Vec<url::Url> {
+        self.subscriptions
+            .active()
+            .filter_map(|s| {
+                let minutes = s.refresh.unwrap_or(self.config.refresh.interval);
+                let u = url::Url::parse(s.url.as_str()).ok()?;
+                let age = match self.refreshed.get(s.url.as_str()) {
+                    Some(at) => Some(at.elapsed()),
+                    None => cache::age(&u),
+                };
+                let due = match age {
+                    _ if minutes == 0 => false,
+                    Some(age) => age >= Duration::from_secs(minutes.saturating_mul(60)),
+                    None => true,
+                };
+                due.then_some(u)
+            })
+            .collect()
+    }
+
+    // Check every subscription for new episodes
+    pub fn refresh_all(&mut self, sender: &Sender<message::Request>) {
+        let feeds = self
+            .subscriptions
+            .active()
+            .filter_map(|s| url::Url::parse(s.url.as_str()).ok())
+            .collect::<Vec<url::Url>>();
+        if feeds.is_empty() {
+            self.announce("No subscriptions to refresh");
+            return;
+        }
+        self.refresh(feeds, sender);
+    }
+
+    // How many of the loaded feed's episodes are listed, newest first. Huge back
+    // catalogs are listed a page at a time.
+    pub fn shown_episodes(&self) -> usize {
+        let len = self.channel.as_ref().map(|c| c.items().len()).unwrap_or(0);
+        self.episode_limit.map(|l| l.min(len)).unwrap_or(len)
+    }
+
+    // Whether there are older episodes than the ones listed. Filtering lists them all.
+    pub fn has_older_episodes(&self) -> bool {
+        let len = self.channel.as_ref().map(|c| c.items().len()).unwrap_or(0);
+        self.episode_filter.is_none() && self.shown_episodes() < len
+    }
+
+    // The episodes listed, by their index in the feed: the ones matching the filter, or
+    // else the ones shown so far
+    pub fn episode_rows(&self) -> Vec<usize> {
+        let Some(c) = self.channel.as_ref() else {
+            return vec![];
+        };
+        match self.episode_filter.as_deref() {
+            Some(f) => c
+                .items()
+                .iter()
+                .enumerate()
+                .filter(|(_, i)| i.title().is_some_and(|t| t.to_lowercase().contains(f)))
+                .map(|(idx, _)| idx)
+                .collect(),
+            None => (0..self.shown_episodes()).collect(),
+        }
+    }
+
+    // Only list the episodes with the text in their title, or all of them again when it's
+    // empty
+    pub fn filter_episodes(&mut self, text: &str) {
+        let filter = Some(text.trim().to_lowercase()).filter(|f| !f.is_empty());
+        if filter == self.episode_filter {
+            return;
+        }
+        self.episode_filter = filter;
+        self.visual = None;
+        let rows = self.episode_rows().len();
+        self.state.select(if rows > 0 { Some(0) } else { None });
+        match self.episode_filter.as_deref() {
+            Some(f) => self.announce(format!(
+                "{} episodes match {}. Esc clears the filter",
+                rows, f
+            )),
+            None => self.announce("Cleared the filter"),
+        }
+    }
+
+    // Show the details of the selected episode, or list older episodes when that row is
+    // selected
+    pub fn open_selected_episode(&mut self, sender: &Sender<message::Request>) {
+        if self.has_older_episodes() && self.state.selected() == Some(self.shown_episodes()) {
+            self.show_older_episodes();
+            return;
+        }
+        let item = self.selected_episode();
+        // TODO: clean this up
+        info!("Load podcast episode {exists}", exists = item.is_some());
+        if let Some(i) = item.as_ref() {
+            if let Some(loc) = self.location() {
+                self.marks.visit(loc);
+            }
+            self.show(DisplayAction::DescribeEpisode);
+            self.announce(format!(
+                "Showing details for {}",
+                i.title().unwrap_or("Title missing!")
+            ));
+        }
+        // forget the previous episode's chapters before loading new ones
+        self.chapters = None;
+        self.position = item
+            .as_ref()
+            .map(|i| self.saved_position(i))
+            .unwrap_or_default();
+        let chapters = item.as_ref().and_then(chapters_url);
+        if let Err(e) = sender.send(message::Request::Episode(item)) {
+            error!("failed to send message {:?}", e);
+        }
+        if let Some(u) = chapters {
+            if let Err(e) = sender.send(message::Request::Chapters(u)) {
+                error!("failed to send message {:?}", e);
+            }
+        }
+    }
+
+    // List another page of older episodes
+    pub fn show_older_episodes(&mut self) {
+        let page = self.config.display.episodes;
+        self.episode_limit = self.episode_limit.map(|l| l + page);
+        let shown = self.shown_episodes();
+        self.announce(format!("Showing {} episodes", shown));
+    }
+
+    // Where the user currently is in the library
+    fn location(&self) -> Option<Location> {
+        let feed = self.feed_url.clone()?;
+        let episode = match self.display_action {
+            DisplayAction::DescribeEpisode => self.item.clone(),
+            _ => self.selected_episode(),
+        };
+        Some(Location {
+            feed,
+            episode: episode.as_ref().and_then(episode_id).map(str::to_string),
+        })
+    }
+
+    // Select the episode with the given id in the episode list
+    fn select_episode(&mut self, id: Option<&str>) {
+        let idx = self.channel.as_ref().and_then(|c| {
+            c.items()
+                .iter()
+                .position(|i| id.is_some() && episode_id(i) == id)
+        });
+        // list as many pages as it takes to show it
+        if let (Some(idx), Some(limit)) = (idx, self.episode_limit) {
+            let page = self.config.display.episodes.max(1);
+            if idx >= limit {
+                self.episode_limit = Some((idx + 1).div_ceil(page) * page);
+            }
+        }
+        let mut rows = self.episode_rows();
+        // the filter is in the way of an episode it leaves out
+        if idx.is_some_and(|i| !rows.contains(&i)) {
+            self.episode_filter = None;
+            rows = self.episode_rows();
+        }
+        let row = idx.and_then(|i| rows.iter().position(|r| *r == i));
+        self.state.select(row.or(Some(0)));
+        self.announce_selection();
+    }
+
+    // Jump to a location, loading its feed first if it isn't the one on screen
+    pub fn go_to(&mut self, loc: Location, sender: &Sender<message::Request>) {
+        if self.feed_url.as_ref() == Some(&loc.feed) && self.channel.is_some() {
+            self.show(DisplayAction::ListEpisodes);
+            self.select_episode(loc.episode.as_deref());
+        } else {
+            // set first, the feed may be shown from the cache right away
+            self.pending_jump = Some(loc.clone());
+            data::load_feed(self, sender, loc.feed);
+            // skip the preview, the user has been here before
+            self.show(DisplayAction::ListEpisodes);
+        }
+    }
+
+    // Where the user is, to come back to on the next launch
+    fn session(&self) -> Session {
+        let loc = self.location();
+        Session {
+            view: View::of(self.display_action),
+            feed: loc.as_ref().map(|l| l.feed.to_string()),
+            episode: loc.and_then(|l| l.episode),
+            library: self.library_state.selected(),
+            queue: self.queue_state.selected(),
+            bookmarks: self.bookmarks_state.selected(),
+            starred: self.stars_state.selected(),
+            history: self.history_state.selected(),
+            downloads: self.downloads_state.selected(),
+        }
+    }
+
+    pub fn save_session(&self) {
+        if let Err(e) = self.session().save() {
+            error!("failed to save session: {:?}", e);
+        }
+    }
+
+    // Go back to where the user was when the app was last closed
+    pub fn restore(&mut self, s: Session, sender: &Sender<message::Request>) {
+        // the lists may have been changed outside the app since
+        let library = self.subscriptions.sorted(&self.played).len();
+        self.library_state
+            .select(s.library.filter(|i| *i < library));
+        self.queue_state
+            .select(s.queue.filter(|i| *i < self.queue.len()));
+        self.bookmarks_state
+            .select(s.bookmarks.filter(|i| *i < self.bookmarks.len()));
+        self.stars_state
+            .select(s.starred.filter(|i| *i < self.stars.len()));
+        let history = self.history.episodes().len();
+        self.history_state
+            .select(s.history.filter(|i| *i < history));
+        self.downloads_state
+            .select(s.downloads.filter(|i| *i < self.downloads.len()));
+
+        let feed = s.feed.and_then(|f| url::Url::parse(f.as_str()).ok());
+        match (s.view, feed) {
+            (View::Episodes, Some(feed)) => self.go_to(
+                Location {
+                    feed,
+                    episode: s.episode,
+                },
+                sender,
+            ),
+            (View::Preview, Some(feed)) => data::load_feed(self, sender, feed),
+            (View::Episodes | View::Preview, None) | (View::Input, _) => {}
+            (view, _) => {
+                self.show(view.display());
+                self.announce_selection();
+            }
+        }
+    }
+
+    // The links in the show notes of the episode shown
+    fn links(&self) -> Vec<String> {
+        self.item
+            .as_ref()
+            .and_then(|i| i.description())
+            .map(ui::notes::links)
+            .unwrap_or_default()
+    }
+
+    // Wait for the number of the link to open
+    pub fn choose_link(&mut self) {
+        match self.links().len() {
+            0 => self.announce("The show notes have no links"),
+            1 => self.open_link(1),
+            n => {
+                self.pending_key = Some('o');
+                self.announce(format!(
+                    "Press the number of the link to open, 1 to {}, or /link for the ones after 9",
+                    n.min(9)
+                ));
+            }
+        }
+    }
+
+    // Open the show notes' link with this number, from 1, in the browser
+    pub fn open_link(&mut self, n: usize) {
+        let links = self.links();
+        let Some(url) = n.checked_sub(1).and_then(|i| links.get(i)) else {
+            self.announce(format!(
+                "No link {}, the show notes have {}",
+                n,
+                links.len()
+            ));
+            return;
+        };
+        // only web pages, a link could be to anything the opener knows how to run
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            self.announce(format!("Not opening link {}, it isn't a web page", n));
+            return;
+        }
+        match media::open(media::system_opener(), url) {
+            Ok(_) => self.announce(format!("Opened link {}: {}", n, url)),
+            Err(e) => self.show_error(format!("Couldn't open link {}: {}", n, e)),
+        }
+    }
+
+    // Set a mark on the current location
+    pub fn set_mark(&mut self, name: char) {
+        match self.location() {
+            Some(loc) if name.is_ascii_alphabetic() => {
+                self.marks.set(name, loc);
+                self.announce(format!("Set mark {}", name));
+            }
+            _ => self.announce(format!("Can't set mark {}", name)),
+        }
+    }
+
+    // Jump to a mark
+    pub fn jump_to_mark(&mut self, name: char, sender: &Sender<message::Request>) {
+        match self.marks.get(name).cloned() {
+            Some(loc) => {
+                if let Some(current) = self.location() {
+                    self.marks.visit(current);
+                }
+                self.announce(format!("Jumped to mark {}", name));
+                self.go_to(loc, sender);
+            }
+            None => self.announce(format!("Mark {} is not set", name)),
+        }
+    }
+
+    // Whether the episode of the loaded feed has been listened to
+    pub fn is_played(&self, item: &Item) -> bool {
+        match (self.feed_url.as_ref(), episode_id(item)) {
+            (Some(f), Some(e)) => self.played.is_played(f.as_str(), e),
+            _ => false,
+        }
+    }
+
+    // Whether the episode of the loaded feed is starred
+    pub fn is_starred(&self, item: &Item) -> bool {
+        self.feed_url
+            .as_ref()
+            .is_some_and(|f| self.stars.is_starred(f.as_str(), item))
+    }
+
+    // The download of the episode of the loaded feed, if it's downloading or was
+    // downloaded. One whose file is gone since doesn't count.
+    pub fn download_of(&self, item: &Item) -> Option<&downloads::Job> {
+        let feed = self.feed_url.as_ref()?.as_str();
+        self.downloads.get(feed, item).filter(|j| {
+            j.status != downloads::Status::Done || self.downloads.file(feed, item).is_some()
+        })
+    }
+
+    // What has happened to an episode of the loaded feed
+    pub fn episode_state(&self, item: &Item) -> EpisodeState {
+        if self.is_played(item) {
+            return EpisodeState::Played;
+        }
+        let id = episode_id(item);
+        let started = (self.item.as_ref().and_then(episode_id) == id && !self.position.is_zero())
+            || self
+                .queue
+                .entries()
+                .iter()
+                .any(|e| episode_id(&e.item) == id && !e.position.is_zero());
+        if id.is_some() && started {
+            EpisodeState::InProgress
+        } else {
+            EpisodeState::Unplayed
+        }
+    }
+
+    // Ask to mark episodes of the loaded feed as played: either all of them, or the
+    // selected one and everything older
+    pub fn request_mark_played(&mut self, all: bool) {
+        // or the episodes selected while selecting a range
+        let range = if all {
+            None
+        } else {
+            self.take_visual_episodes()
+        };
+        let (Some(feed), Some(c)) = (self.feed_url.as_ref(), self.channel.as_ref()) else {
+            self.announce("Load a podcast first");
+            return;
+        };
+        // feeds list their newest episodes first
+        let from = match (all, range.is_some()) {
+            (false, false) => match self
+                .state
+                .selected()
+                .and_then(|row| self.episode_rows().get(row).copied())
+            {
+                Some(idx) => idx,
+                None => return,
+            },
+            _ => 0,
+        };
+        let episodes = range
+            .as_deref()
+            .unwrap_or(c.items())
+            .iter()
+            .skip(from)
+            .filter(|i| !self.is_played(i))
+            .filter_map(episode_id)
+            .map(str::to_string)
+            .collect::<Vec<String>>();
+        if episodes.is_empty() {
+            self.announce("Those episodes are already played");
+            return;
+        }
+        let question = format!(
+            "Mark {} episodes of {} as played?",
+            episodes.len(),
+            c.title()
+        );
+        self.ask(Confirm::MarkPlayed(feed.to_string(), episodes), question);
+    }
+
+    // Wait for the user to confirm a destructive action, or cancel it
+    fn ask(&mut self, confirm: Confirm, question: String) {
+        self.announce(format!(
+            "{} Press y to confirm, anything else to cancel",
+            question
+        ));
+        self.confirm = Some(confirm);
+        self.question = question;
+    }
+
+    // Mark the selected episode, or the one on screen, as played or unplayed
+    pub fn mark_selected(&mut self, played: bool) {
+        let item = match self.display_action {
+            DisplayAction::ListEpisodes => self.selected_episode(),
+            DisplayAction::DescribeEpisode => self.item.clone(),
+            _ => None,
+        };
+        let (Some(item), Some(feed)) = (item, self.feed_url.clone()) else {
+            self.announce("Select an episode first");
+            return;
+        };
+        let title = item.title().unwrap_or("Title missing!").to_string();
+        let state = if played { "played" } else { "unplayed" };
+        if self.set_played(feed.as_str(), &item, played) {
+            self.announce(format!("Marked {} as {}", title, state));
+        } else {
+            self.announce(format!("{} is already {}", title, state));
+        }
+    }
+
+    // Mark an episode as played or unplayed and save that right away, returns false if
+    // nothing changed
+    fn set_played(&mut self, feed: &str, item: &Item, played: bool) -> bool {
+        let Some(id) = episode_id(item) else {
+            return false;
+        };
+        if !self.played.set(feed, id, played) {
+            return false;
+        }
+        self.dirty.played = true;
+        if played {
+            self.subscriptions.played_at(feed, now());
+            self.dirty.subscriptions = true;
+        }
+        if let Err(e) = self.save() {
+            error!("failed to save played episodes: {:?}", e);
+            self.announce(format!("Failed to save played episodes: {}", e));
+        }
+        true
+    }
+
+    // Count the episode in the player as played once it's nearly over
+    fn check_played(&mut self) {
+        let Some(e) = self.player.episode.as_ref() else {
+            return;
+        };
+        let (Some(feed), Some(length)) = (e.feed.clone(), self.player.length()) else {
+            return;
+        };
+        if !played::listened(self.player.status.position, length) {
+            return;
+        }
+        let item = e.item.clone();
+        if self.set_played(feed.as_str(), &item, true) {
+            info!("marked {:?} as played", item.title());
+        }
+    }
+
+    // Answer no to the action waiting for confirmation, which for most is to cancel it
+    pub fn decline(&mut self) {
+        match self.confirm.take() {
+            Some(Confirm::ExportOpml(path)) => self.export_opml(path.as_str(), true),
+            _ => self.announce("Cancelled"),
+        }
+    }
+
+    // Carry out the action that was waiting for confirmation
+    pub fn confirm(&mut self) {
+        match self.confirm.take() {
+            Some(Confirm::MarkPlayed(feed, episodes)) => {
+                let n = self
+                    .played
+                    .mark_all(feed.as_str(), episodes.iter().map(String::as_str));
+                self.subscriptions.played_at(feed.as_str(), now());
+                self.dirty.played = true;
+                self.dirty.subscriptions = true;
+                match self.save() {
+                    Ok(_) => self.announce(format!("Marked {} episodes as played", n)),
+                    Err(e) => {
+                        error!("failed to save played episodes: {:?}", e);
+                        self.announce(format!(
+                            "Marked {} episodes as played, but failed to save",
+                            n
+                        ));
+                    }
+                }
+            }
+            Some(Confirm::Unsubscribe(url, title)) => self.unsubscribe(url.as_str(), &title),
+            Some(Confirm::RestoreBackup(path)) => self.restore_backup(path.as_str()),
+            Some(Confirm::ClearStorage(feed)) => self.clear_storage(feed.as_deref()),
+            Some(Confirm::DeleteDownload(path)) => {
+                if let Some(job) = self.delete_download(Path::new(path.as_str())) {
+                    self.announce(format!("Deleted the download of {}", job.title()));
+                }
+            }
+            Some(Confirm::DeleteDownloads(paths)) => {
+                let n = paths
+                    .iter()
+                    .filter_map(|p| self.delete_download(Path::new(p.as_str())))
+                    .count();
+                if self.display_action == DisplayAction::CleanUp {
+                    self.show_clean_up();
+                }
+                self.announce(format!("Deleted {} downloads", n));
+            }
+            Some(Confirm::ExportOpml(path)) => self.export_opml(path.as_str(), false),
+            None => {}
+        }
+    }
+
+    // Show a newly loaded feed. If it's already showing, from the cache, it's updated
+    // in place instead.
+    pub fn show_feed(&mut self, c: Channel) {
+        if self.channel.is_some() {
+            self.update_feed(c);
+            return;
+        }
+        self.remember_open_feed(&c);
+        self.announce(format!(
+            "Loaded {}: {} episodes. Subscribe, or just browse?",
+            c.title(),
+            c.items().len()
+        ));
+        self.channel = Some(c);
+        self.state.select(None);
+        self.visual = None;
+        self.preview_state.select(Some(0));
+        self.episode_limit = Some(self.config.display.episodes).filter(|l| *l > 0);
+        match self.pending_jump.take() {
+            Some(loc) if self.feed_url.as_ref() == Some(&loc.feed) => {
+                self.select_episode(loc.episode.as_deref());
+            }
+            _ => {
+                if let Some(loc) = self.location() {
+                    self.marks.visit(loc);
+                }
+            }
+        }
+    }
+
+    // Show the episodes of a feed that is still downloading as they arrive. A copy from
+    // the cache is better than a partial feed, so parts are ignored when one is showing.
+    pub fn show_feed_part(&mut self, part: Channel) {
+        match self.channel.as_mut() {
+            None => {
+                self.loading_parts = true;
+                self.show_feed(part);
+            }
+            Some(c) if self.loading_parts => {
+                c.items.extend(part.items);
+                let msg = format!("Loading {}: {} episodes so far", c.title(), c.items().len());
+                self.announce(msg);
+            }
+            Some(_) => {}
+        }
+    }
+
+    // Replace the showing feed with a newer copy, keeping the selected episode selected
+    pub fn update_feed(&mut self, c: Channel) {
+        self.remember_open_feed(&c);
+        let selected = self.selected_episode();
+        let id = selected.as_ref().and_then(episode_id).map(str::to_string);
+        let n = c.items().len();
+        self.channel = Some(c);
+        // the rows may have moved
+        self.visual = None;
+        if id.is_some() {
+            self.select_episode(id.as_deref());
+        }
+        self.announce(format!("Updated {}: {} episodes", self.channel_title(), n));
+    }
+
+    fn channel_title(&self) -> &str {
+        self.channel.as_ref().map(|c| c.title()).unwrap_or_default()
+    }
+
+    // Act on the choice made on the feed preview screen: either subscribe to the feed,
+    // or just browse its episodes. Both end up on the episode list.
+    #[instrument(skip(self))]
+    pub fn choose_preview(&mut self) {
+        // the first choice is "Subscribe"
+        if self.preview_state.selected() == Some(0) {
+            self.subscribe();
+        } else {
+            self.announce("Browsing without subscribing");
+        }
+        self.show(DisplayAction::ListEpisodes);
+    }
+
+    // Add the loaded feed to the library
+    fn subscribe(&mut self) {
+        let (Some(u), Some(c)) = (self.feed_url.as_ref(), self.channel.as_ref()) else {
+            return;
+        };
+        let (u, title) = (u.to_string(), c.title().to_string());
+        self.subscribe_to(u.as_str(), title.as_str());
+    }
+
+    // Subscribe to the feed at the URL, fetching it first unless it's the one loaded
+    pub fn request_subscribe(&mut self, url: Option<String>, sender: &Sender<message::Request>) {
+        let loaded = self.feed_url.as_ref().map(|u| u.to_string());
+        let Some(url) = url.or(loaded.clone()) else {
+            self.announce("Load a podcast first, or give the URL of its feed");
+            return;
+        };
+        let u = match url::Url::parse(url.as_str()) {
+            Ok(u) => u,
+            Err(e) => {
+                self.show_error(format!("Couldn't subscribe: {}", e));
+                return;
+            }
+        };
+        if Some(u.to_string()) == loaded && self.channel.is_some() {
+            self.subscribe();
+            return;
+        }
+        if let Some(s) = self.subscriptions.get(u.as_str()) {
+            self.announce(format!("Already subscribed to {}", s.title));
+            return;
+        }
+        let provider = feed::private::detect(&u);
+        if let Err(e) = sender.send(message::Request::Subscribe(u)) {
+            error!("failed to send message {:?}", e);
+        }
+        match provider {
+            Some(p) => self.announce(format!(
+                "Subscribing to {} feed. Its access token is hidden on screen and in logs",
+                p.name()
+            )),
+            None => self.announce("Subscribing…"),
+        }
+    }
+
+    // Subscribe to a private feed, checking that its URL has the access token in it. Without
+    // a URL, explain where to find one.
+    pub fn request_subscribe_private(
+        &mut self,
+        url: Option<String>,
+        sender: &Sender<message::Request>,
+    ) {
+        let Some(url) = url else {
+            self.announce(
+                "Copy the RSS link from the podcast's membership page, like Patreon's \
+                 \"Get RSS link\" or the link in Supercast's welcome email, and give it \
+                 after /private",
+            );
+            return;
+        };
+        let u = match url::Url::parse(url.as_str()) {
+            Ok(u) => u,
+            Err(e) => {
+                self.show_error(format!("Couldn't subscribe: {}", e));
+                return;
+            }
+        };
+        if feed::private::detect(&u).is_none() {
+            self.show_error(
+                "That URL has no access token in it. Copy the whole RSS link from the \
+                 membership page, or use /subscribe for a public feed",
+            );
+            return;
+        }
+        self.request_subscribe(Some(url), sender);
+    }
+
+    // The podcast selected in the library, or else the one loaded, if it's subscribed to
+    fn command_subscription(&self) -> Option<&Subscription> {
+        match self.display_action {
+            DisplayAction::Library => self.selected_subscription(),
+            _ => self
+                .feed_url
+                .as_ref()
+                .and_then(|u| self.subscriptions.get(u.as_str())),
+        }
+    }
+
+    // Ask before taking the selected podcast, or the loaded one, out of the library
+    pub fn request_unsubscribe(&mut self) {
+        let Some(s) = self.command_subscription() else {
+            self.announce("Not subscribed to that podcast");
+            return;
+        };
+        let question = format!("Unsubscribe from {}?", s.title);
+        self.ask(
+            Confirm::Unsubscribe(s.url.clone(), s.title.clone()),
+            question,
+        );
+    }
+
+    // Take a feed out of the library, keeping a podcast selected in it
+    fn unsubscribe(&mut self, url: &str, title: &str) {
+        if self.subscriptions.remove(url).is_none() {
+            return;
+        }
+        let len = self.subscriptions.sorted(&self.played).len();
+        let selected = self
+            .library_state
+            .selected()
+            .map(|i| i.min(len.saturating_sub(1)));
+        self.library_state.select(selected.filter(|_| len > 0));
+        self.dirty.subscriptions = true;
+        match self.save() {
+            Ok(_) => self.announce(format!("Unsubscribed from {}", title)),
+            Err(e) => {
+                error!("failed to save subscriptions: {:?}", e);
+                self.announce(format!(
+                    "Unsubscribed from {}, but failed to save it",
+                    title
+                ));
+            }
+        }
+    }
+
+    // Write all of the user's data to a single file, to move it to another machine
+    pub fn backup(&mut self, path: &str) {
+        // including how far into the episode playing it got
+        self.save_position();
+        let p = expand_home(path);
+        match self.make_backup().and_then(|b| b.write(&p)) {
+            Ok(_) => self.announce(format!("Backed up to {}", p.display())),
+            Err(e) => self.show_error(format!("Couldn't back up to {}: {}", p.display(), e)),
+        }
+    }
+
+    fn make_backup(&self) -> Result<backup::Backup, Box<dyn Error>> {
+        let db = self.db.as_ref().ok_or("the database couldn't be opened")?;
+        Ok(backup::Backup {
+            version: backup::VERSION,
+            created: now(),
+            subscriptions: self.subscriptions.clone(),
+            played: self.played.clone(),
+            positions: db.positions()?,
+            queue: self.queue.clone(),
+            bookmarks: self.bookmarks.clone(),
+            stars: db.load_stars()?,
+            history: db.load_listens(None)?,
+        })
+    }
+
+    // Ask before replacing everything with a backup
+    pub fn request_restore_backup(&mut self, path: &str) {
+        let p = expand_home(path);
+        match backup::Backup::read(&p) {
+            Ok(b) => {
+                let question = format!(
+                    "Replace all of your data with the backup of {} podcasts?",
+                    b.subscriptions.iter().count()
+                );
+                self.ask(Confirm::RestoreBackup(p.display().to_string()), question);
+            }
+            Err(e) => self.show_error(format!("Couldn't read {}: {}", p.display(), e)),
+        }
+    }
+
+    fn restore_backup(&mut self, path: &str) {
+        let b = match backup::Backup::read(Path::new(path)) {
+            Ok(b) => b,
+            Err(e) => {
+                self.show_error(format!("Couldn't read {}: {}", path, e));
+                return;
+            }
+        };
+        let Some(db) = self.db.as_mut() else {
+            self.show_error("Can't restore, the database couldn't be opened");
+            return;
+        };
+        if let Err(e) = db.restore(&b) {
+            self.show_error(format!("Couldn't restore {}: {}", path, e));
+            return;
+        }
+        self.stars = Stars::load(db).unwrap_or_default();
+        self.history = History::load(db).unwrap_or_default();
+        self.played = b.played;
+        self.subscriptions = b.subscriptions;
+        self.queue = b.queue;
+        self.bookmarks = b.bookmarks;
+        for s in [
+            &mut self.library_state,
+            &mut self.queue_state,
+            &mut self.bookmarks_state,
+            &mut self.stars_state,
+            &mut self.history_state,
+        ] {
+            s.select(None);
+        }
+        // the backup replaces what failed to load, so it's saved over
+        self.unloaded.subscriptions = false;
+        self.unloaded.queue = false;
+        self.unloaded.bookmarks = false;
+        self.dirty.subscriptions = true;
+        self.dirty.queue = true;
+        self.dirty.bookmarks = true;
+        match self.save() {
+            Ok(_) => self.announce(format!(
+                "Restored {} podcasts from {}",
+                self.subscriptions.iter().count(),
+                path
+            )),
+            Err(e) => self.show_error(format!("Restored {}, but failed to save it: {}", path, e)),
+        }
+    }
+
+    // Write the loaded feed's episodes to a file, for scripts and spreadsheets
+    pub fn export_episodes(&mut self, path: &str) {
+        let (Some(c), Some(feed)) = (self.channel.as_ref(), self.feed_url.as_ref()) else {
+            self.announce("Load a podcast to export its episodes");
+            return;
+        };
+        let episodes = export::episodes(c, feed.as_str(), &self.played);
+        let p = expand_home(path);
+        match export::write(&p, &episodes) {
+            Ok(_) => self.announce(format!(
+                "Exported {} episodes to {}",
+                episodes.len(),
+                p.display()
+            )),
+            Err(e) => self.show_error(format!("Couldn't export to {}: {}", p.display(), e)),
+        }
+    }
+
+    // Add the tag to the podcast selected in the library, or the one loaded, or take it off
+    pub fn tag_podcast(&mut self, tag: &str, add: bool) {
+        let Some(s) = self.command_subscription() else {
+            self.announce("Subscribe to the podcast to tag it");
+            return;
+        };
+        let (url, title) = (s.url.clone(), s.title.clone());
+        let tag = tag.trim().to_lowercase();
+        let changed = if add {
+            self.subscriptions.tag(url.as_str(), tag.as_str())
+        } else {
+            self.subscriptions.untag(url.as_str(), tag.as_str())
+        };
+        if !changed {
+            let msg = if add { "already has" } else { "doesn't have" };
+            self.announce(format!("{} {} the tag {}", title, msg, tag));
+            return;
+        }
+        // the podcast may have left the filtered library
+        let len = self.subscriptions.sorted(&self.played).len();
+        if let Some(idx) = self.library_state.selected().filter(|i| *i >= len) {
+            self.library_state.select(idx.checked_sub(1));
+        }
+        self.dirty.subscriptions = true;
+        if let Err(e) = self.save() {
+            error!("failed to save subscriptions: {:?}", e);
+        }
+        let msg = if add { "Tagged" } else { "Untagged" };
+        self.announce(format!("{} {} {}", msg, title, tag));
+    }
+
+    // Only list the podcasts with the tag in the library, or all of them when None
+    pub fn filter_library(&mut self, tag: Option<String>) {
+        let tag = tag.map(|t| t.trim().to_lowercase());
+        if let Some(t) = tag
+            .as_ref()
+            .filter(|t| !self.subscriptions.tags().contains(&t.as_str()))
+        {
+            self.announce(format!("No podcast is tagged {}", t));
+            return;
+        }
+        self.subscriptions.tag = tag;
+        self.dirty.subscriptions = true;
+        let len = self.subscriptions.sorted(&self.played).len();
+        self.library_state
+            .select(if len > 0 { Some(0) } else { None });
+        self.show(DisplayAction::Library);
+        match self.subscriptions.tag.as_ref() {
+            Some(t) => self.announce(format!("Library, tagged {}: {} podcasts", t, len)),
+            None => self.announce(format!("Library, all {} podcasts", len)),
+        }
+    }
+
+    // Archive the podcast selected in the library, or the one loaded, or bring it back
+    pub fn archive_podcast(&mut self, archive: bool) {
+        let Some(s) = self.command_subscription() else {
+            self.announce("Not subscribed to that podcast");
+            return;
+        };
+        let (url, title) = (s.url.clone(), s.title.clone());
+        if !self.subscriptions.set_archived(url.as_str(), archive) {
+            let msg = if archive { "already" } else { "isn't" };
+            self.announce(format!("{} {} archived", title, msg));
+            return;
+        }
+        let len = self.subscriptions.sorted(&self.played).len();
+        let selected = self
+            .library_state
+            .selected()
+            .map(|i| i.min(len.saturating_sub(1)));
+        self.library_state.select(selected.filter(|_| len > 0));
+        self.dirty.subscriptions = true;
+        if let Err(e) = self.save() {
+            error!("failed to save subscriptions: {:?}", e);
+        }
+        if archive {
+            self.announce(format!("Archived {}, /archived lists it", title));
+        } else {
+            self.announce(format!("{} is back in the library", title));
+        }
+    }
+
+    // List the archived podcasts in place of the library
+    pub fn show_archive(&mut self) {
+        self.subscriptions.archive = true;
+        self.show(DisplayAction::Library);
+        let len = self.list_len();
+        self.library_state
+            .select(if len > 0 { Some(0) } else { None });
+        self.announce(format!(
+            "Archive: {} podcasts. /unarchive puts one back in the library",
+            len
+        ));
+    }
+
+    // Say which tags there are, and which the podcast has
+    pub fn list_tags(&mut self) {
+        let tags = self.subscriptions.tags();
+        if tags.is_empty() {
+            self.announce("No tags yet, add one with /tag add");
+            return;
+        }
+        let mut msg = format!("Tags: {}", tags.join(", "));
+        if let Some(s) = self.command_subscription().filter(|s| !s.tags.is_empty()) {
+            msg.push_str(&format!(". {} is tagged {}", s.title, s.tags.join(", ")));
+        }
+        self.announce(msg);
+    }
+
+    // Write the library to a file as OPML, for other podcast apps to import
+    // Write the library to the file as OPML. When some podcasts are private feeds, ask
+    // first whether their access tokens go in the file.
+    pub fn request_export_opml(&mut self, path: &str) {
+        match opml::private_feeds(&self.subscriptions) {
+            0 => self.export_opml(path, false),
+            n => self.ask(
+                Confirm::ExportOpml(path.to_string()),
+                format!(
+                    "{} of the podcasts are private feeds. Include their access tokens? \
+                     Without them, they won't play where the file is imported.",
+                    n
+                ),
+            ),
+        }
+    }
+
+    fn export_opml(&mut self, path: &str, redact: bool) {
+        let p = expand_home(path);
+        let n = self.subscriptions.iter().count();
+        match opml::write(&p, &self.subscriptions, redact) {
+            Ok(_) if redact => self.announce(format!(
+                "Exported {} podcasts to {}, with the tokens of private feeds masked",
+                n,
+                p.display()
+            )),
+            Ok(_) => self.announce(format!("Exported {} podcasts to {}", n, p.display())),
+            Err(e) => self.show_error(format!("Couldn't export to {}: {}", p.display(), e)),
+        }
+    }
+
+    // Add a feed to the library
+    pub fn subscribe_to(&mut self, url: &str, title: &str) {
+        if !self.subscriptions.add(url, title) {
+            self.announce(format!("Already subscribed to {}", title));
+            return;
+        }
+        if self.feed_url.as_ref().map(|u| u.as_str()) == Some(url) {
+            if let Some(c) = self.channel.clone() {
+                self.subscriptions.update(url, &c);
+            }
+        }
+        self.dirty.subscriptions = true;
+        match self.save() {
+            Ok(_) => self.announce(format!("Subscribed to {}", title)),
+            Err(e) => {
+                error!("failed to save subscriptions: {:?}", e);
+                self.announce(format!("Subscribed to {}, but failed to save it", title));
+            }
+        }
+    }
+
+    // Save whatever changed since the last save
+    pub fn save(&mut self) -> Result<(), Box<dyn Error>> {
+        self.save_changes()?;
+        if self.dirty.any() {
+            return Err(
+                format!("not saving over data that failed to load: {:?}", self.dirty).into(),
+            );
+        }
+        Ok(())
+    }
+
+    // Save what changed, except the data that failed to load
+    fn save_changes(&mut self) -> Result<(), Box<dyn Error>> {
+        let changed = self.dirty.except(self.unloaded);
+        if changed.subscriptions {
+            self.subscriptions.save()?;
+            self.dirty.subscriptions = false;
+        }
+        // the rest is saved even when the database couldn't be opened
+        let mut missing_db = false;
+        if changed.played {
+            match self.db.as_mut() {
+                Some(db) => {
+                    self.played.save(db)?;
+                    self.dirty.played = false;
+                }
+                None => missing_db = true,
+            }
+        }
+        if changed.queue {
+            self.queue.save()?;
+            self.dirty.queue = false;
+        }
+        if changed.metrics {
+            self.metrics.save()?;
+            self.dirty.metrics = false;
+        }
+        if changed.bookmarks {
+            self.bookmarks.save()?;
+            self.dirty.bookmarks = false;
+        }
+        if missing_db {
+            return Err("the database couldn't be opened".into());
+        }
+        Ok(())
+    }
+
+    // Save changes and where playback is in the background, only bothering the user if
+    // that fails. What failed to load was already reported when the app started.
+    pub fn autosave(&mut self) {
+        if self.player.status.state == player::State::Playing {
+            self.save_position();
+        }
+        if !self.dirty.except(self.unloaded).any() {
+            return;
+        }
+        debug!("autosaving {:?}", self.dirty);
+        if let Err(e) = self.save_changes() {
+            error!("failed to autosave: {:?}", e);
+            self.announce(format!("Failed to save changes: {}", e));
+        }
+    }
+
+    // Add the selected episode to the end of the queue
+    #[instrument(skip(self))]
+    pub fn enqueue_selected(&mut self) {
+        if let Some(items) = self.take_visual_episodes() {
+            let n = items.len();
+            for item in items {
+                self.queue.push(queue::Entry {
+                    position: self.saved_position(&item),
+                    item,
+                    podcast: self.channel.as_ref().map(|c| c.title().to_string()),
+                    feed: self.feed_url.as_ref().map(|u| u.to_string()),
+                });
+            }
+            self.dirty.queue = true;
+            self.announce(format!(
+                "Added {} episodes to the queue, {} episodes queued",
+                n,
+                self.queue.len()
+            ));
+            return;
+        }
+        if let Some(item) = self.selected_episode() {
+            // pick up where we left off if this is the episode that is loaded
+            let position = if self.item.as_ref() == Some(&item) {
+                self.position
+            } else {
+                self.saved_position(&item)
+            };
+            self.enqueue(queue::Entry {
+                item,
+                position,
+                podcast: self.channel.as_ref().map(|c| c.title().to_string()),
+                feed: self.feed_url.as_ref().map(|u| u.to_string()),
+            });
+        }
+    }
+
+    // Move a queue entry to a new position, keeping it selected
+    pub fn move_queue_entry(&mut self, from: usize, to: usize) {
+        if !self.queue.move_entry(from, to) {
+            self.announce(format!(
+                "Can't move {} to {}, the queue has {} episodes",
+                from,
+                to,
+                self.queue.len()
+            ));
+            return;
+        }
+        self.queue_state.select(Some(to));
+        let title = self.queue.entries()[to]
+            .item
+            .title()
+            .unwrap_or("Title missing!")
+            .to_string();
+        self.dirty.queue = true;
+        self.announce(format!("Moved {} to position {}", title, to));
+    }
+
+    // Bookmark the moment playing, with a note if given. Saved right away.
+    pub fn bookmark(&mut self, note: Option<String>) {
+        let Some(e) = self.player.episode.as_ref() else {
+            self.announce("Nothing is playing to bookmark");
+            return;
+        };
+        let b = Bookmark {
+            item: e.item.clone(),
+            position: self.player.status.position,
+            note,
+            podcast: e.podcast.clone(),
+            feed: e.feed.as_ref().map(|u| u.to_string()),
+            created: now(),
+        };
+        let msg = format!("Bookmarked {}", b.describe());
+        self.bookmarks.add(b);
+        self.dirty.bookmarks = true;
+        match self.save() {
+            Ok(_) => self.announce(msg),
+            Err(e) => {
+                error!("failed to save bookmarks: {:?}", e);
+                self.announce(format!("Failed to save bookmarks: {}", e));
+            }
+        }
+    }
+
+    // Show a view, moving to the tab it belongs to if it does
+    pub fn show(&mut self, view: DisplayAction) {
+        self.display_action = view;
+        if let Some(t) = Tab::of(&view) {
+            self.tab = t;
+        }
+    }
+
+    // Switch to one of the tabs, on the view it was left on
+    pub fn switch_tab(&mut self, tab: Tab, sender: &Sender<message::Request>) {
+        if self.tab == tab {
+            return;
+        }
+        self.tab_views.insert(self.tab, self.display_action);
+        if tab == Tab::NowPlaying {
+            self.toggle_now_playing();
+            return;
+        }
+        if let Some(view) = self.tab_views.get(&tab).copied() {
+            self.display_action = view;
+            self.tab = tab;
+            self.announce(format!("{} tab", tab.name()));
+            return;
+        }
+        match tab {
+            Tab::Podcasts => data::handle_user_input(self, sender, Command::ShowLibrary),
+            Tab::Episodes if self.channel.is_some() => {
+                self.show(DisplayAction::ListEpisodes);
+                self.announce("Episodes tab");
+            }
+            Tab::Episodes => self.announce("No podcast loaded yet, open one from the library"),
+            Tab::Queue => data::handle_user_input(self, sender, Command::ShowQueue),
+            Tab::Downloads => self.show_downloads(),
+            Tab::NowPlaying => {}
+        }
+    }
+
+    // Switch to the next tab, or the previous one
+    pub fn cycle_tab(&mut self, back: bool, sender: &Sender<message::Request>) {
+        self.switch_tab(self.tab.cycle(back), sender);
+    }
+
+    // Switch to the now playing screen, or back to where it was opened from
+    pub fn toggle_now_playing(&mut self) {
+        if self.display_action == DisplayAction::NowPlaying {
+            self.display_action = self.previous_display;
+            self.tab = self.previous_tab;
+            self.announce("Closed now playing");
+            return;
+        }
+        self.previous_display = self.display_action;
+        self.previous_tab = self.tab;
+        self.show(DisplayAction::NowPlaying);
+        let msg = match (self.player.external.as_ref(), self.player.episode.as_ref()) {
+            (Some(e), _) => format!("Now playing externally: {}", e.episode.title()),
+            (None, Some(e)) => format!(
+                "Now playing: {}, {}. Press v to go back",
+                e.title(),
+                self.player.status.name()
+            ),
+            (None, None) => "Nothing playing. Press v to go back".to_string(),
+        };
+        self.announce(msg);
+    }
+
+    pub fn show_bookmarks(&mut self) {
+        self.show(DisplayAction::Bookmarks);
+        if self.bookmarks_state.selected().is_none() && !self.bookmarks.is_empty() {
+            self.bookmarks_state.select(Some(0));
+        }
+        self.announce(format!(
+            "Bookmarks: {}. Enter plays from one, d deletes it",
+            self.bookmarks.len()
+        ));
+    }
+
+    // Go back to the selected bookmark, playing its episode if it isn't in the player
+    fn play_selected_bookmark(&mut self) {
+        let Some(b) = self
+            .bookmarks_state
+            .selected()
+            .and_then(|idx| self.bookmarks.get(idx))
+            .cloned()
+        else {
+            return;
+        };
+        if self.player.has(&b.item) && self.player.status.state != player::State::Stopped {
+            self.seek_to(b.position);
+            return;
+        }
+        self.play_entry(queue::Entry {
+            item: b.item,
+            position: b.position,
+            podcast: b.podcast,
+            feed: b.feed,
+        });
+    }
+
+    fn delete_selected_bookmark(&mut self) {
+        let Some(idx) = self.bookmarks_state.selected() else {
+            return;
+        };
+        let Some(b) = self.bookmarks.remove(idx) else {
+            return;
+        };
+        if self.bookmarks.is_empty() {
+            self.bookmarks_state.select(None);
+        } else {
+            self.bookmarks_state
+                .select(Some(idx.min(self.bookmarks.len() - 1)));
+        }
+        self.dirty.bookmarks = true;
+        match self.save() {
+            Ok(_) => self.announce(format!("Deleted bookmark {}", b.describe())),
+            Err(e) => {
+                error!("failed to save bookmarks: {:?}", e);
+                self.announce(format!("Failed to save bookmarks: {}", e));
+            }
+        }
+    }
+
+    // Star the selected episode, or the one on screen, or unstar it if it was starred
+    pub fn toggle_star(&mut self) {
+        let star = match self.display_action {
+            DisplayAction::Starred => self
+                .stars_state
+                .selected()
+                .and_then(|idx| self.stars.get(idx))
+                .cloned(),
+            _ => {
+                let item = match self.display_action {
+                    DisplayAction::ListEpisodes => self.selected_episode(),
+                    DisplayAction::DescribeEpisode => self.item.clone(),
+                    _ => None,
+                };
+                item.zip(self.feed_url.as_ref()).map(|(item, feed)| Star {
+                    item,
+                    feed: feed.to_string(),
+                    podcast: self.channel.as_ref().map(|c| c.title().to_string()),
+                    starred: now(),
+                })
+            }
+        };
+        let Some(star) = star else {
+            return;
+        };
+        let Some(db) = self.db.as_ref() else {
+            self.show_error("Can't star episodes, the database couldn't be opened");
+            return;
+        };
+        let title = star.title().to_string();
+        match self.stars.toggle(star, db) {
+            Ok(true) => self.announce(format!("Starred {}", title)),
+            Ok(false) => {
+                self.announce(format!("Unstarred {}", title));
+                let len = self.stars.len();
+                if let Some(idx) = self.stars_state.selected().filter(|i| *i >= len) {
+                    self.stars_state.select(idx.checked_sub(1));
+                }
+            }
+            Err(e) => self.show_error(format!("Couldn't save the star: {}", e)),
+        }
+    }
+
+    pub fn show_starred(&mut self) {
+        self.show(DisplayAction::Starred);
+        if self.stars_state.selected().is_none() && !self.stars.is_empty() {
+            self.stars_state.select(Some(0));
+        }
+        self.announce(format!(
+            "Starred episodes: {}. Enter plays one, * unstars it",
+            self.stars.len()
+        ));
+    }
+
+    // Download the selected episode, or the one on screen, in the background. In the
+    // downloads, try the selected one again.
+    pub fn download_selected(&mut self, sender: &Sender<message::Request>) {
+        if let Some(items) = self.take_visual_episodes() {
+            self.download_episodes(items, sender);
+            return;
+        }
+        let episode = match self.display_action {
+            DisplayAction::ListEpisodes => self.selected_episode().zip(self.feed_url.clone()),
+            DisplayAction::DescribeEpisode => self.item.clone().zip(self.feed_url.clone()),
+            DisplayAction::Downloads => self
+                .downloads_state
+                .selected()
+                .and_then(|idx| self.downloads.get_index(idx))
+                .and_then(|j| Some((j.item.clone(), url::Url::parse(j.feed.as_str()).ok()?))),
+            _ => None,
+        };
+        let Some((item, feed)) = episode else {
+            return;
+        };
+        let title = item.title().unwrap_or("Title missing!").to_string();
+        match self
+            .downloads
+            .get(feed.as_str(), &item)
+            .map(|j| (&j.status, j.auto))
+        {
+            // one waiting for the schedule starts now instead
+            Some((downloads::Status::Queued, true)) => {}
+            Some((downloads::Status::Active(..) | downloads::Status::Queued, _)) => {
+                self.announce(format!("Already downloading {}", title));
+                return;
+            }
+            Some((downloads::Status::Paused(..), _)) => {
+                self.announce(format!("{} is paused, P carries on downloading it", title));
+                return;
+            }
+            Some((downloads::Status::Done, _))
+                if self.downloads.file(feed.as_str(), &item).is_some() =>
+            {
+                self.announce(format!("Already downloaded {}", title));
+                return;
+            }
+            _ => {}
+        }
+        if !matches!(
+            item.enclosure().map(|e| url::Url::parse(e.url())),
+            Some(Ok(_))
+        ) {
+            self.announce(format!("Can't download {}, it has no valid file", title));
+            return;
+        }
+        let podcast = match self.downloads.get(feed.as_str(), &item) {
+            Some(j) => j.podcast.clone(),
+            None => self.channel.as_ref().map(|c| c.title().to_string()),
+        };
+        if !self.queue_download(feed.as_str(), podcast, item, false) {
+            self.show_error("Can't download, there is no directory to download to");
+            return;
+        }
+        // the list is newest first, so the one tried again is at the top now
+        if self.display_action == DisplayAction::Downloads {
+            self.downloads_state.select(Some(0));
+        }
+        self.start_downloads(sender);
+        match self.downloads.queued() {
+            0 => self.announce(format!("Downloading {}", title)),
+            n => self.announce(format!("Queued {}, {} waiting to download", title, n)),
+        }
+    }
+
+    // Download episodes of the loaded feed, leaving out the ones downloaded or downloading
+    // already and the ones without a file
+    fn download_episodes(&mut self, items: Vec<Item>, sender: &Sender<message::Request>) {
+        let Some(feed) = self.feed_url.clone() else {
+            return;
+        };
+        let podcast = self.channel.as_ref().map(|c| c.title().to_string());
+        let mut n = 0;
+        for item in items {
+            let busy = match self
+                .downloads
+                .get(feed.as_str(), &item)
+                .map(|j| (&j.status, j.auto))
+            {
+                // one waiting for the schedule starts now instead
+                Some((downloads::Status::Queued, true)) => false,
+                Some((
+                    downloads::Status::Active(..)
+                    | downloads::Status::Queued
+                    | downloads::Status::Paused(..),
+                    _,
+                )) => true,
+                Some((downloads::Status::Done, _)) => {
+                    self.downloads.file(feed.as_str(), &item).is_some()
+                }
+                _ => false,
+            };
+            let file = matches!(
+                item.enclosure().map(|e| url::Url::parse(e.url())),
+                Some(Ok(_))
+            );
+            if busy || !file {
+                continue;
+            }
+            if !self.queue_download(feed.as_str(), podcast.clone(), item, false) {
+                self.show_error("Can't download, there is no directory to download to");
+                return;
+            }
+            n += 1;
+        }
+        self.start_downloads(sender);
+        match n {
+            0 => self.announce("Those episodes are downloaded or downloading already"),
+            n => self.announce(format!("Downloading {} episodes", n)),
+        }
+    }
+
+    // Ask before deleting the downloads of the selected episode, or the episodes selected
+    // while selecting a range
+    pub fn request_delete_episode_downloads(&mut self) {
+        let items = match self.take_visual_episodes() {
+            Some(items) => items,
+            None => self.selected_episode().into_iter().collect(),
+        };
+        let jobs = items
+            .iter()
+            .filter_map(|i| self.download_of(i))
+            .map(|j| (j.path.display().to_string(), j.title().to_string()))
+            .collect::<Vec<(String, String)>>();
+        match jobs.as_slice() {
+            [] => self.announce("Those episodes aren't downloaded"),
+            [(path, title)] => {
+                let question = format!("Delete the download of {}?", title);
+                self.ask(Confirm::DeleteDownload(path.clone()), question);
+            }
+            _ => {
+                let question = format!("Delete {} downloads?", jobs.len());
+                let paths = jobs.into_iter().map(|(p, _)| p).collect();
+                self.ask(Confirm::DeleteDownloads(paths), question);
+            }
+        }
+    }
+
+    // Queue the episode to download, false if there is nowhere to download it to
+    fn queue_download(
+        &mut self,
+        feed: &str,
+        podcast: Option<String>,
+        item: Item,
+        auto: bool,
+    ) -> bool {
+        let Some(dir) = self.config.downloads.dir() else {
+            return false;
+        };
+        let path = downloads::path_in(
+            &dir,
+            self.config.downloads.template.as_str(),
+            podcast.as_deref(),
+            &item,
+        );
+        let job = downloads::Job {
+            path: self.downloads.free_path(feed, &item, path),
+            item,
+            feed: feed.to_string(),
+            podcast,
+            status: downloads::Status::Queued,
+            finished: None,
+            auto,
+        };
+        // so that it carries on the next time the app starts, if it's closed before then
+        if let Some(Err(e)) = self.db.as_ref().map(|db| db.save_download(&job, None)) {
+            error!("failed to save download: {:?}", e);
+        }
+        self.downloads.add(job);
+        true
+    }
+
+    // Queue the latest episodes of a podcast that downloads them by itself, the ones that
+    // weren't downloaded or played yet. They start downloading with the next tick.
+    fn auto_download(&mut self, feed: &str, c: &Channel) {
+        let latest = self.subscriptions.get(feed).map_or(0, |s| s.auto_download);
+        let wanted = c
+            .items()
+            .iter()
+            .take(latest)
+            .filter(|i| matches!(i.enclosure().map(|e| url::Url::parse(e.url())), Some(Ok(_))))
+            .filter(|i| episode_id(i).is_some_and(|id| !self.played.is_played(feed, id)))
+            .filter(|i| self.downloads.wanted(feed, i))
+            .cloned()
+            .collect::<Vec<Item>>();
+        for item in wanted {
+            info!("downloading {:?} of {}", item.title(), c.title());
+            self.queue_download(feed, Some(c.title().to_string()), item, true);
+        }
+    }
+
+    // Ask before deleting the selected download's file
+    pub fn request_delete_download(&mut self) {
+        let Some(j) = self
+            .downloads_state
+            .selected()
+            .and_then(|idx| self.downloads.get_index(idx))
+        else {
+            return;
+        };
+        let question = format!("Delete the download of {}?", j.title());
+        self.ask(
+            Confirm::DeleteDownload(j.path.display().to_string()),
+            question,
+        );
+    }
+
+    // Pause the selected download, or carry on with it when it's paused. It starts again
+    // with the next tick, when there's room.
+    pub fn pause_selected_download(&mut self) {
+        let Some(j) = self
+            .downloads_state
+            .selected()
+            .and_then(|idx| self.downloads.get_index(idx))
+            .cloned()
+        else {
+            return;
+        };
+        if self.downloads.resume(&j.path).is_some() {
+            self.announce(format!("Resumed {}", j.title()));
+        } else if self.downloads.pause(&j.path).is_some() {
+            self.announce(format!("Paused {}", j.title()));
+        } else {
+            self.announce(format!("{} isn't downloading", j.title()));
+        }
+    }
+
+    // Stop the selected download and delete what was downloaded of it. Finished ones are
+    // deleted with D.
+    pub fn cancel_selected_download(&mut self) {
+        let Some(j) = self
+            .downloads_state
+            .selected()
+            .and_then(|idx| self.downloads.get_index(idx))
+        else {
+            return;
+        };
+        if j.status == downloads::Status::Done {
+            let msg = format!("{} finished downloading, D deletes it", j.title());
+            self.announce(msg);
+            return;
+        }
+        let path = j.path.clone();
+        if let Some(job) = self.delete_download(&path) {
+            self.announce(format!("Cancelled the download of {}", job.title()));
+        }
+    }
+
+    // Delete a download's file, along with what's left of it if it didn't finish, and
+    // forget about it. One that's going on is stopped.
+    fn delete_download(&mut self, path: &Path) -> Option<downloads::Job> {
+        let job = self.downloads.remove(path)?;
+        let len = self.downloads.len();
+        if let Some(idx) = self.downloads_state.selected().filter(|i| *i >= len) {
+            self.downloads_state.select(idx.checked_sub(1));
+        }
+        let [part, validator] = downloads::part_files(path);
+        for p in [path, part.as_path(), validator.as_path()] {
+            match std::fs::remove_file(p) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                    error!("failed to delete {}: {:?}", p.display(), e);
+                }
+                _ => {}
+            }
+        }
+        if let Some(Err(e)) = self
+            .db
+            .as_ref()
+            .map(|db| db.delete_download(job.feed.as_str(), &job.item))
+        {
+            error!("failed to forget download: {:?}", e);
+        }
+        Some(job)
+    }
+
+    // Delete the downloads their podcast doesn't keep, by its settings or the
+    // `[downloads]` config. With `review_clean_up`, only say how many there are, for
+    // /cleanup to list.
+    pub fn clean_up_downloads(&mut self) {
+        let expired = self.expired_downloads();
+        if self.config.downloads.review_clean_up {
+            let n = expired.len();
+            if n > 0 && n != self.clean_up_due {
+                self.toast(format!("{} downloads can be cleaned up, see /cleanup", n));
+            }
+            self.clean_up_due = n;
+            return;
+        }
+        for (job, expiry) in expired {
+            if let Some(job) = self.delete_download(&job.path) {
+                info!(
+                    "deleted the download of {}, {}",
+                    job.title(),
+                    expiry.describe()
+                );
+            }
+        }
+    }
+
+    // List the downloads cleaning up would delete, and why, without deleting them
+    pub fn show_clean_up(&mut self) {
+        self.clean_up = self.expired_downloads();
+        self.show(DisplayAction::CleanUp);
+        let len = self.clean_up.len();
+        self.clean_up_state.select((len > 0).then(|| {
+            self.clean_up_state
+                .selected()
+                .unwrap_or_default()
+                .min(len - 1)
+        }));
+        match len {
+            0 => self.announce("Nothing to clean up"),
+            n => self.announce(format!(
+                "{} downloads are due to be deleted. D deletes them now",
+                n
+            )),
+        }
+    }
+
+    // Ask before deleting every download /cleanup lists
+    pub fn request_clean_up(&mut self) {
+        if self.clean_up.is_empty() {
+            self.announce("Nothing to clean up");
+            return;
+        }
+        let paths = self
+            .clean_up
+            .iter()
+            .map(|(j, _)| j.path.to_string_lossy().to_string())
+            .collect::<Vec<String>>();
+        let question = format!("Delete {} downloads?", paths.len());
+        self.ask(Confirm::DeleteDownloads(paths), question);
+    }
+
+    // Downloads their podcast's retention doesn't keep, and why. Starred episodes and the
+    // ones playing are kept regardless.
+    fn expired_downloads(&self) -> Vec<(downloads::Job, downloads::Expiry)> {
+        let config = &self.config.downloads;
+        let playing = [
+            self.player.episode.as_ref(),
+            self.player.external.as_ref().map(|e| &e.episode),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<&player::Episode>>();
+        self.downloads
+            .expired(
+                |feed| config.retention(self.subscriptions.get(feed)),
+                now(),
+                |j| {
+                    let id = episode_id(&j.item)?;
+                    if !self.played.is_played(j.feed.as_str(), id) {
+                        return None;
+                    }
+                    // played without listening to it here, e.g. marked as played
+                    self.history
+                        .last_listened(j.feed.as_str(), &j.item)
+                        .or(j.finished)
+                },
+                |j| j.pinned(&self.stars, &playing),
+            )
+            .into_iter()
+            .map(|(j, expiry)| (j.clone(), expiry))
+            .collect()
+    }
+
+    pub fn show_storage(&mut self) {
+        self.storage = Storage::measure(&self.downloads, &self.subscriptions);
+        self.show(DisplayAction::Storage);
+        let len = self.storage.len();
+        if self.storage_state.selected().is_none_or(|i| i >= len) {
+            self.storage_state.select(Some(0));
+        }
+        self.announce(format!(
+            "Storage: {} in all. D deletes the downloads and cached feed of one",
+            ui::fmt_bytes(self.storage.all.total() + self.storage.data_size)
+        ));
+    }
+
+    // Ask before deleting the downloads and cached feed of the selected podcast, or every
+    // podcast's
+    pub fn request_clear_storage(&mut self) {
+        let Some(u) = self
+            .storage_state
+            .selected()
+            .and_then(|idx| self.storage.get(idx))
+        else {
+            return;
+        };
+        let question = format!(
+            "Delete {} of downloads and cached feeds of {}?",
+            ui::fmt_bytes(u.total()),
+            u.title
+        );
+        self.ask(Confirm::ClearStorage(u.feed.clone()), question);
+    }
+
+    // Delete the downloads and cached feed of the podcast, or of all of them. The ones
+    // downloading are left alone.
+    fn clear_storage(&mut self, feed: Option<&str>) {
+        let before = self.storage.all.total();
+        let paths = self
+            .downloads
+            .iter()
+            .filter(|j| feed.is_none_or(|f| f == j.feed))
+            .filter(|j| !matches!(j.status, downloads::Status::Active(..)))
+            .map(|j| j.path.clone())
+            .collect::<Vec<PathBuf>>();
+        for p in paths {
+            self.delete_download(&p);
+        }
+        // refreshes are timed by the cached feeds until the app refreshes them, so keep
+        // when that was before they're gone
+        let now = Instant::now();
+        for s in self.subscriptions.iter() {
+            if feed.is_some_and(|f| f != s.url) || self.refreshed.contains_key(&s.url) {
+                continue;
+            }
+            let age = url::Url::parse(s.url.as_str())
+                .ok()
+                .and_then(|u| cache::age(&u));
+            if let Some(at) = age.and_then(|age| now.checked_sub(age)) {
+                self.refreshed.insert(s.url.clone(), at);
+            }
+        }
+        let res = match feed.map(url::Url::parse) {
+            Some(Ok(u)) => cache::remove(&u),
+            Some(Err(_)) => Ok(()),
+            None => cache::clear(),
+        };
+        if let Err(e) = res {
+            error!("failed to clear the cache: {:?}", e);
+        }
+        self.show_storage();
+        let freed = before.saturating_sub(self.storage.all.total());
+        self.announce(format!("Freed {}", ui::fmt_bytes(freed)));
+    }
+
+    // Start the queued downloads there is room for
+    pub fn start_downloads(&mut self, sender: &Sender<message::Request>) {
+        let limit = self.config.downloads.concurrent.max(1);
+        for (job, stop) in self.downloads.start(limit, self.download_window_open()) {
+            let Some(Ok(u)) = job.item.enclosure().map(|e| url::Url::parse(e.url())) else {
+                continue;
+            };
+            if let Err(e) = sender.send(message::Request::Download(u, job.path, stop)) {
+                error!("failed to send download request: {:?}", e);
+            }
+        }
+    }
+
+    // Whether podcasts may download new episodes by themselves now, by the profile's
+    // schedule
+    fn download_window_open(&self) -> bool {
+        let schedule = self.config.downloads.schedule(profile::current());
+        let hour = chrono::Local::now().hour();
+        downloads::schedule::is_open(schedule, hour, downloads::schedule::metered())
+    }
+
+    pub fn show_downloads(&mut self) {
+        self.show(DisplayAction::Downloads);
+        if self.downloads_state.selected().is_none() && !self.downloads.is_empty() {
+            self.downloads_state.select(Some(0));
+        }
+        let waiting = if self.download_window_open() {
+            ""
+        } else {
+            ", new episodes wait for the schedule"
+        };
+        self.announce(format!(
+            "Downloads: {} active, {} queued{}. P pauses or resumes one, c cancels it, d tries \
+             a failed one again",
+            self.downloads.active(),
+            self.downloads.queued(),
+            waiting
+        ));
+    }
+
+    // Play the selected download, from where it was left
+    fn play_selected_download(&mut self) {
+        let Some(j) = self
+            .downloads_state
+            .selected()
+            .and_then(|idx| self.downloads.get_index(idx))
+            .cloned()
+        else {
+            return;
+        };
+        if j.status != downloads::Status::Done {
+            self.announce(format!("{} isn't downloaded yet", j.title()));
+            return;
+        }
+        let position = self.position_in(j.feed.as_str(), &j.item);
+        self.play_entry(queue::Entry {
+            item: j.item,
+            position,
+            podcast: j.podcast,
+            feed: Some(j.feed),
+        });
+    }
+
+    // Keep track of how a download is going, and remember it once it's done
+    fn download_progress(&mut self, path: &Path, p: downloads::Progress) {
+        let Some(job) = self.downloads.update(path, p.clone()).cloned() else {
+            return;
+        };
+        match p {
+            downloads::Progress::Done => {
+                let size = std::fs::metadata(path).ok().map(|m| m.len());
+                if let Some(Err(e)) = self.db.as_ref().map(|db| db.save_download(&job, size)) {
+                    error!("failed to save download: {:?}", e);
+                }
+                self.toast(format!("Downloaded {}", job.title()));
+            }
+            downloads::Progress::Failed(e) => {
+                // what was downloaded is kept, to carry on from when it's tried again
+                if let Some(Err(e)) = self
+                    .db
+                    .as_ref()
+                    .map(|db| db.remove_download(job.feed.as_str(), &job.item))
+                {
+                    error!("failed to forget download: {:?}", e);
+                }
+                self.show_error(format!("Couldn't download {}: {}", job.title(), e))
+            }
+            downloads::Progress::Downloaded(..) | downloads::Progress::Stopped => {}
+        }
+    }
+
+    // The downloaded file of an episode to play instead of streaming it, if there is one
+    fn local_file(&self, feed: Option<&str>, item: &Item) -> Option<url::Url> {
+        let path = self.downloads.file(feed?, item)?;
+        url::Url::from_file_path(path).ok()
+    }
+
+    // The downloaded file of the open episode, if there is one
+    fn local_path(&self) -> Option<PathBuf> {
+        let item = self.item.as_ref()?;
+        let feed = self.feed_url.as_ref()?;
+        self.downloads
+            .file(feed.as_str(), item)
+            .map(Path::to_path_buf)
+    }
+
+    // Where playback of an episode of any feed was left
+    fn position_in(&self, feed: &str, item: &Item) -> Duration {
+        match (self.db.as_ref(), episode_id(item)) {
+            (Some(db), Some(id)) => db.position(feed, id).unwrap_or_default(),
+            _ => Duration::ZERO,
+        }
+    }
+
+    // Play the selected starred episode, from where it was left
+    fn play_selected_star(&mut self) {
+        let Some(s) = self
+            .stars_state
+            .selected()
+            .and_then(|idx| self.stars.get(idx))
+            .cloned()
+        else {
+            return;
+        };
+        let position = self.position_in(s.feed.as_str(), &s.item);
+        self.play_entry(queue::Entry {
+            item: s.item,
+            position,
+            podcast: s.podcast,
+            feed: Some(s.feed),
+        });
+    }
+
+    pub fn show_history(&mut self) {
+        self.show(DisplayAction::History);
+        let len = self.history.episodes().len();
+        self.history_state
+            .select(if len > 0 { Some(0) } else { None });
+        self.announce(format!("History: {} episodes. Enter resumes one", len));
+    }
+
+    // Resume the selected episode of the history where it was left
+    fn resume_selected_listen(&mut self) {
+        let Some(l) = self
+            .history_state
+            .selected()
+            .and_then(|idx| self.history.episodes().get(idx).copied())
+            .cloned()
+        else {
+            return;
+        };
+        // the position saved since, e.g. zero once it finished
+        let position = self.position_in(l.feed.as_str(), &l.item);
+        self.play_entry(queue::Entry {
+            item: l.item,
+            position,
+            podcast: l.podcast,
+            feed: Some(l.feed),
+        });
+    }
+
+    // The episode in the player stopped playing, keep the session in the history
+    fn record_listen(&mut self) {
+        let Some(started) = self.listening.take() else {
+            return;
+        };
+        let Some(e) = self.player.episode.as_ref() else {
+            return;
+        };
+        let Some(feed) = e.feed.as_ref() else {
+            return;
+        };
+        let listen = Listen {
+            item: e.item.clone(),
+            feed: feed.to_string(),
+            podcast: e.podcast.clone(),
+            started,
+            stopped: now(),
+            position: self.player.status.position,
+        };
+        let Some(db) = self.db.as_ref() else {
+            return;
+        };
+        if let Err(e) = self.history.add(listen, db) {
+            error!("failed to save listening history: {:?}", e);
+        }
+    }
+
+    // Move the selected queue entry to the position computed from its current one
+    pub fn move_selected_queue_entry(&mut self, to: impl Fn(usize, usize) -> usize) {
+        if let Some(from) = self.queue_state.selected() {
+            let to = to(from, self.queue.len());
+            if to != from {
+                self.move_queue_entry(from, to);
+            }
+        }
+    }
+
+    // Start reading the open episode's show notes aloud, or stop if already reading
+    pub fn toggle_speech(&mut self) {
+        if self.speaker.is_speaking() {
+            self.speaker.stop();
+            self.announce("Stopped reading");
+            self.check_speech();
+            return;
+        }
+        let Some(item) = self.item.as_ref() else {
+            self.announce("Open an episode to read its show notes");
+            return;
+        };
+        let text = speech::text(item);
+        let command = if self.config.tts.trim().is_empty() {
+            speech::default_command()
+        } else {
+            self.config.tts.as_str()
+        };
+        let program = media::program(command).to_string();
+        match self.speaker.speak(command, text.as_str()) {
+            Ok(_) => {
+                self.announce("Reading the show notes, press s to stop");
+                // so the two don't talk over each other
+                if self.player.status.state == player::State::Playing {
+                    self.player.send(player::Control::TogglePause);
+                    self.resume_after_speech = true;
+                }
+            }
+            Err(e) => {
+                error!("failed to run {}: {:?}", program, e);
+                self.announce(format!("Failed to run {}: {}", program, e));
+            }
+        }
+    }
+
+    // Pick playback up again once the show notes it was paused for are read, unless it was
+    // stopped or started again in the meantime
+    pub fn check_speech(&mut self) {
+        if !self.resume_after_speech || self.speaker.is_speaking() {
+            return;
+        }
+        self.resume_after_speech = false;
+        if self.player.status.state == player::State::Paused {
+            self.player.send(player::Control::TogglePause);
+        }
+    }
+
+    // Open the file attached to the open episode with the handler for its type. When
+    // neither the feed nor the URL say what the file is, the server is asked first.
+    pub fn open_enclosure(&mut self, sender: &Sender<message::Request>) {
+        let Some(enclosure) = self.item.as_ref().and_then(|i| i.enclosure()) else {
+            self.announce("This episode has no file to open");
+            return;
+        };
+        let url = enclosure.url().to_string();
+        // a downloaded file tells what it is by its name, without asking the server
+        let local = self.local_path().map(|p| p.display().to_string());
+        let mime = Some(enclosure.mime_type())
+            .filter(|m| !media::is_generic(m))
+            .or_else(|| media::guess(url.as_str()))
+            .or_else(|| local.as_deref().and_then(media::guess))
+            .map(str::to_string);
+        match mime {
+            Some(m) => self.open_with_handler(url.as_str(), m.as_str()),
+            None => match url::Url::parse(url.as_str()) {
+                Ok(u) => {
+                    self.announce("Checking what kind of file this is");
+                    if let Err(e) = sender.send(message::Request::ContentType(u)) {
+                        error!("failed to send message {:?}", e);
+                    }
+                }
+                Err(e) => {
+                    error!("invalid enclosure URL: {:?}", e);
+                    self.announce("This episode's file has an invalid URL");
+                }
+            },
+        }
+    }
+
+    pub fn open_with_handler(&mut self, url: &str, mime: &str) {
+        let kind = media::kind(mime);
+        let Some(handler) = self.config.handlers.command(mime, kind) else {
+            match kind {
+                Kind::Audio => self.play(url),
+                _ => self.announce(format!(
+                    "Don't know how to open {} files, they can only be downloaded",
+                    media::essence(mime)
+                )),
+            }
+            return;
+        };
+        // handlers open the downloaded file when there is one
+        let local = self.local_path().map(|p| p.display().to_string());
+        let url = local.as_deref().unwrap_or(url);
+        if kind == Kind::Audio {
+            let handler = handler.to_string();
+            self.play_externally(handler.as_str(), url);
+            return;
+        }
+        let program = media::program(handler).to_string();
+        info!("opening {} file with {}", kind.name(), program);
+        match media::open(handler, url) {
+            Ok(_) => self.announce(format!("Opened {} with {}", kind.name(), program)),
+            Err(e) => {
+                error!("failed to run {}: {:?}", program, e);
+                self.announce(format!("Failed to run {}: {}", program, e));
+            }
+        }
+    }
+
+    // Hand the open episode's file over to another program to play, keeping track of it
+    // until it exits. Only one episode plays at a time, so anything playing is stopped.
+    fn play_externally(&mut self, handler: &str, url: &str) {
+        let Some(item) = self.item.clone() else {
+            return;
+        };
+        let u = match url::Url::parse(url) {
+            Ok(u) => u,
+            Err(e) => {
+                error!("invalid enclosure URL: {:?}", e);
+                self.announce("This episode's file has an invalid URL");
+                return;
+            }
+        };
+        if self.player.is_active() {
+            self.player.send(player::Control::Stop);
+        }
+        if let Some(e) = self.player.external.take() {
+            e.stop();
+        }
+        let episode = player::Episode {
+            item,
+            podcast: self.channel.as_ref().map(|c| c.title().to_string()),
+            feed: self.feed_url.clone(),
+            artwork: self.artwork(),
+        };
+        let program = media::program(handler).to_string();
+        info!("playing externally with {}", program);
+        match player::External::spawn(handler, &u, episode) {
+            Ok(e) => {
+                self.announce(format!("Playing {} in {}", e.episode.title(), program));
+                self.player.external = Some(e);
+            }
+            Err(e) => {
+                error!("failed to run {}: {:?}", program, e);
+                self.announce(format!("Failed to run {}: {}", program, e));
+            }
+        }
+    }
+
+    // Notice the program playing an episode exiting
+    fn check_external(&mut self) {
+        let Some(e) = self.player.external.as_mut() else {
+            return;
+        };
+        match e.exited() {
+            Ok(None) => return,
+            Ok(Some(status)) if status.success() => {
+                let msg = format!("{} finished playing {}", e.program, e.episode.title());
+                self.announce(msg);
+            }
+            Ok(Some(status)) => {
+                let msg = format!("{} exited with {}", e.program, status);
+                self.announce(msg);
+            }
+            Err(err) => error!("failed to check on {}: {:?}", e.program, err),
+        }
+        self.player.external = None;
+    }
+
+    // Choose the program to play episodes with for this session, None for the app
+    pub fn open_with(&mut self, handler: Option<String>) {
+        match handler {
+            Some(h) => {
+                self.announce(format!("Playing episodes with {}", media::program(&h)));
+                self.config.handlers.audio = h;
+            }
+            None => {
+                self.announce("Playing episodes in the app");
+                self.config.handlers.audio.clear();
+            }
+        }
+    }
+
+    // Play the open episode's file in the app, resuming where it was left
+    fn play(&mut self, url: &str) {
+        let Some(item) = self.item.clone() else {
+            return;
+        };
+        let u = match url::Url::parse(url) {
+            Ok(u) => u,
+            Err(e) => {
+                error!("invalid enclosure URL: {:?}", e);
+                self.announce("This episode's file has an invalid URL");
+                return;
+            }
+        };
+        let episode = player::Episode {
+            item,
+            podcast: self.channel.as_ref().map(|c| c.title().to_string()),
+            feed: self.feed_url.clone(),
+            artwork: self.artwork(),
+        };
+        self.start(episode, u, self.position);
+    }
+
+    // Start playing an episode, taking it out of the queue if it was waiting there
+    fn start(&mut self, episode: player::Episode, mut u: url::Url, mut position: Duration) {
+        if let Some(file) =
+            self.local_file(episode.feed.as_ref().map(|f| f.as_str()), &episode.item)
+        {
+            u = file;
+        }
+        if self.queue.remove_episode(&episode.item) {
+            self.dirty.queue = true;
+        }
+        if let Some(e) = self.player.external.take() {
+            e.stop();
+        }
+        let speed = self
+            .podcast_speed(episode.feed.as_ref())
+            .unwrap_or(self.config.playback.speed);
+        if player::clamp_speed(speed) != self.player.status.speed {
+            self.apply_speed(speed);
+        }
+        let intro = self.podcast(episode.feed.as_ref()).map(|s| s.skip_intro);
+        if let Some(intro) = intro.map(Duration::from_secs) {
+            position = position.max(intro);
+        }
+        self.save_position();
+        self.record_listen();
+        self.announce(format!("Loading {}", episode.title()));
+        let open = self.item.as_ref() == Some(&episode.item);
+        self.player.play(episode, u, position);
+        if open {
+            self.player.chapters = self.chapters.clone();
+        }
+    }
 
-use crate::ui::input::parse;
+    // Play a queue entry. Whatever was playing goes back to the front of the queue, to be
+    // picked up again later.
+    fn play_entry(&mut self, e: queue::Entry) {
+        let u = match e.item.enclosure().map(|e| url::Url::parse(e.url())) {
+            Some(Ok(u)) => u,
+            _ => {
+                self.announce(format!(
+                    "Can't play {}, it has no valid file",
+                    e.item.title().unwrap_or("Title missing!")
+                ));
+                return;
+            }
+        };
+        if let Some(current) = self.player.episode.clone() {
+            if matches!(
+                self.player.status.state,
+                player::State::Playing | player::State::Paused
+            ) {
+                self.queue.insert(
+                    0,
+                    queue::Entry {
+                        item: current.item,
+                        position: self.player.status.position,
+                        podcast: current.podcast,
+                        feed: current.feed.map(|u| u.to_string()),
+                    },
+                );
+            }
+        }
+        let feed = e.feed.and_then(|f| url::Url::parse(f.as_str()).ok());
+        // only the loaded podcast's artwork is known
+        let artwork = self
+            .artwork()
+            .filter(|_| feed.is_some() && feed == self.feed_url);
+        let episode = player::Episode {
+            item: e.item,
+            podcast: e.podcast,
+            feed,
+            artwork,
+        };
+        self.start(episode, u, e.position);
+    }
 
-// App holds the state of the application
-// TODO: persist application state about podcast that is loaded.
-#[derive(Default, Debug)]
-pub struct App {
-    // Current value of the input box
-    input: String,
-    // Loaded podcast channel/feed
-    channel: Option<Channel>,
-    // Loaded podcast episode
-    item: Option<Item>,
-    state: ListState, // TODO: should there be multiple list states?
-    // keep track of what to render on the UI across ticks
-    display_action: DisplayAction,
-}
+    // Play the selected queue entry
+    fn play_selected_entry(&mut self) {
+        let Some(e) = self
+            .queue_state
+            .selected()
+            .and_then(|idx| self.queue.remove(idx))
+        else {
+            return;
+        };
+        self.dirty.queue = true;
+        if self.queue.is_empty() {
+            self.queue_state.select(None);
+        } else if let Some(idx) = self.queue_state.selected() {
+            self.queue_state.select(Some(idx.min(self.queue.len() - 1)));
+        }
+        self.play_entry(e);
+    }
 
-impl App {
-    // Select the next item. This will not be reflected until the widget is drawn in the
-    // `Terminal::draw` callback using `Frame::render_stateful_widget`.
-    #[instrument]
-    pub fn next(&mut self) {
-        let i = self
-            .channel
+    // Pause or carry on, playing the episode again if it was stopped
+    fn toggle_pause(&mut self) {
+        let Some(e) = self.player.episode.clone() else {
+            return;
+        };
+        // playing or pausing by hand wins over picking up after speech
+        self.resume_after_speech = false;
+        match self.player.status.state {
+            player::State::Playing => {
+                self.player.send(player::Control::TogglePause);
+                self.announce(format!("Paused {}", e.title()));
+            }
+            player::State::Paused => {
+                self.player.send(player::Control::TogglePause);
+                self.announce(format!("Playing {}", e.title()));
+            }
+            player::State::Stopped => match e.item.enclosure().map(|e| url::Url::parse(e.url())) {
+                Some(Ok(u)) => {
+                    let feed = e.feed.as_ref().map(|f| f.to_string());
+                    let u = self.local_file(feed.as_deref(), &e.item).unwrap_or(u);
+                    self.announce(format!("Loading {}", e.title()));
+                    let position = self.player.status.position;
+                    self.player.play(e, u, position);
+                }
+                _ => self.announce("This episode's file has an invalid URL"),
+            },
+            player::State::Loading => {}
+        }
+    }
+
+    // Skip ahead, or back when negative, by some seconds
+    fn seek_by(&mut self, by: i64) {
+        if self.player.episode.is_none() || self.player.status.state == player::State::Loading {
+            return;
+        }
+        let to = self.player.seek_by(by);
+        self.announce(format!("At {}", ui::fmt_duration(to)));
+    }
+
+    // Go to the next chapter of the episode playing, or back through its chapters
+    fn jump_chapter(&mut self, forward: bool) {
+        if !self.player.is_active() || self.player.status.state == player::State::Loading {
+            return;
+        }
+        match self.player.jump_chapter(forward) {
+            Some(title) if title.is_empty() => self.announce("Chapter"),
+            Some(title) => self.announce(format!("Chapter: {}", title)),
+            None if self.player.chapters.is_none() => self.announce("This episode has no chapters"),
+            None => self.announce("No more chapters"),
+        }
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        let speed = self.apply_speed(speed);
+        // the speed carries on to the next episodes, unless this podcast has its own
+        let feed = self.player.episode.as_ref().and_then(|e| e.feed.clone());
+        if self.podcast_speed(feed.as_ref()).is_none() {
+            self.config.playback.speed = speed;
+        }
+        self.announce(format!("Speed {:.1}x", speed));
+    }
+
+    fn apply_speed(&mut self, speed: f32) -> f32 {
+        let speed = player::clamp_speed(speed);
+        self.player.send(player::Control::SetSpeed(speed));
+        // shown right away, rather than once the player says so
+        self.player.status.speed = speed;
+        speed
+    }
+
+    // The subscription of a feed, for its settings
+    fn podcast(&self, feed: Option<&url::Url>) -> Option<&Subscription> {
+        feed.and_then(|u| self.subscriptions.get(u.as_str()))
+    }
+
+    // Speed saved for the podcast, if it's subscribed to and has one
+    fn podcast_speed(&self, feed: Option<&url::Url>) -> Option<f32> {
+        self.podcast(feed).and_then(|s| s.speed)
+    }
+
+    // Podcast whose settings are changed: the one playing, or else the one on screen
+    fn settings_feed(&self) -> Option<url::Url> {
+        self.player
+            .episode
             .as_ref()
-            .map(|c| c.items())
-            .and_then(|items| {
-                self.state
-                    .selected()
-                    .map(|i| if i >= items.len() - 1 { 0 } else { i + 1 })
-            })
-            .unwrap_or_default();
-        debug!(idx = i);
-        self.state.select(Some(i));
+            .and_then(|e| e.feed.clone())
+            .or_else(|| self.feed_url.clone())
     }
 
-    // Select the previous item. This will not be reflected until the widget is drawn in the
-    // `Terminal::draw` callback using `Frame::render_stateful_widget`.
-    #[instrument]
-    pub fn previous(&mut self) {
-        let i: usize = self
-            .channel
+    // Save how many seconds to skip at the start of the podcast's episodes, or before
+    // their end when not `intro`. 0 skips nothing.
+    pub fn set_skip(&mut self, intro: bool, secs: u64) {
+        let Some(feed) = self.settings_feed() else {
+            self.announce("Load a podcast to set what to skip");
+            return;
+        };
+        let Some(s) = self.subscriptions.get_mut(feed.as_str()) else {
+            self.announce("Subscribe to the podcast to save what to skip");
+            return;
+        };
+        let part = if intro {
+            s.skip_intro = secs;
+            "first"
+        } else {
+            s.skip_outro = secs;
+            "last"
+        };
+        self.dirty.subscriptions = true;
+        if let Err(e) = self.save() {
+            error!("failed to save subscriptions: {:?}", e);
+            self.announce(format!("Failed to save what to skip: {}", e));
+            return;
+        }
+        match secs {
+            0 => self.announce(format!(
+                "Not skipping the {} seconds of this podcast's episodes",
+                part
+            )),
+            n => self.announce(format!(
+                "Skipping the {} {} seconds of this podcast's episodes",
+                part, n
+            )),
+        }
+    }
+
+    // Stop the episode playing once it reaches its podcast's outro, as if it had finished
+    fn check_outro(&mut self) {
+        let Some(e) = self.player.episode.as_ref() else {
+            return;
+        };
+        let outro = self.podcast(e.feed.as_ref()).map(|s| s.skip_outro);
+        let (Some(outro), Some(length)) = (outro.filter(|o| *o > 0), self.player.length()) else {
+            return;
+        };
+        let status = self.player.status;
+        if status.state == player::State::Playing
+            && status.position.saturating_add(Duration::from_secs(outro)) >= length
+        {
+            info!("skipping the outro");
+            self.player.send(player::Control::Stop);
+            self.finish_episode();
+        }
+    }
+
+    pub fn set_skip_ads(&mut self, skip: bool) {
+        let Some(feed) = self.settings_feed() else {
+            self.announce("Load a podcast to set what to skip");
+            return;
+        };
+        let Some(s) = self.subscriptions.get_mut(feed.as_str()) else {
+            self.announce("Subscribe to the podcast to save what to skip");
+            return;
+        };
+        s.skip_ads = Some(skip);
+        self.dirty.subscriptions = true;
+        if let Err(e) = self.save() {
+            error!("failed to save subscriptions: {:?}", e);
+            self.announce(format!("Failed to save what to skip: {}", e));
+            return;
+        }
+        if skip {
+            self.announce("Skipping ads in this podcast's episodes");
+        } else {
+            self.announce("Not skipping ads in this podcast's episodes");
+        }
+    }
+
+    // Skip past the ad chapter playing, if its podcast skips ads
+    fn check_ads(&mut self) {
+        let Some(e) = self.player.episode.as_ref() else {
+            return;
+        };
+        let skip = self
+            .podcast(e.feed.as_ref())
+            .and_then(|s| s.skip_ads)
+            .unwrap_or(self.config.playback.skip_ads);
+        if !skip || self.player.status.state != player::State::Playing {
+            return;
+        }
+        let Some((ad, end)) = self
+            .player
+            .chapters
             .as_ref()
-            .map(|c| c.items())
-            .and_then(|items| {
-                self.state
-                    .selected()
-                    .map(|i| if i == 0 { items.len() - 1 } else { i - 1 })
-            })
-            .unwrap_or_default();
-        debug!(idx = i);
-        self.state.select(Some(i));
+            .and_then(|c| c.ad_at(self.player.status.position))
+        else {
+            return;
+        };
+        let title = ad.title.clone().unwrap_or_default();
+        info!("skipping the ad {}", title);
+        self.announce(format!("Skipped {}", title));
+        match end {
+            Some(end) => {
+                let to = self.player.seek_to(Duration::from_secs_f64(end));
+                // the player only reports the new position once it got there
+                self.player.status.position = to;
+            }
+            None => {
+                self.player.send(player::Control::Stop);
+                self.finish_episode();
+            }
+        }
+    }
+
+    // The file of the episode that plays once the one playing finishes, if any
+    fn up_next(&self) -> Option<url::Url> {
+        if self.player.sleep == Some(player::Sleep::EndOfEpisode) || self.player.stop_after {
+            return None;
+        }
+        let current = self
+            .player
+            .episode
+            .as_ref()
+            .map(|e| (&e.item, e.feed.as_ref().map(|f| f.to_string())));
+        let first = self
+            .queue
+            .entries()
+            .first()
+            .map(|e| (&e.item, e.feed.clone()));
+        let (item, feed) = match self.player.repeat {
+            player::Repeat::One => current,
+            player::Repeat::Queue => first.or(current),
+            player::Repeat::Off => first,
+        }?;
+        self.local_file(feed.as_deref(), item)
+            .or_else(|| url::Url::parse(item.enclosure()?.url()).ok())
+    }
+
+    // Get the next episode ready as the one playing nears its end, and fade into it if
+    // set to
+    fn check_next(&mut self) {
+        if self.player.status.state != player::State::Playing {
+            return;
+        }
+        let (Some(length), Some(next)) = (self.player.length(), self.up_next()) else {
+            return;
+        };
+        let left = length.saturating_sub(self.player.status.position);
+        let fade = Duration::from_secs_f64(self.config.playback.crossfade.max(0.0));
+        if left <= fade + player::PRELOAD && self.player.preloaded.as_ref() != Some(&next) {
+            self.player.send(player::Control::Preload(next.clone()));
+            self.player.preloaded = Some(next);
+        }
+        if !fade.is_zero() && left <= fade {
+            self.player.crossfade = Some(left);
+            self.finish_episode();
+            // in case the next episode couldn't be played
+            self.player.crossfade = None;
+        }
+    }
+
+    // The episode in the player is over: count it as played, and carry on with the
+    // queue unless it's time to sleep
+    // The episode stopped playing because its file couldn't be read. When its download
+    // gave up partway through, it's opened again where it stopped, once.
+    fn playback_failed(&mut self, msg: String) {
+        let at = self.player.status.position;
+        self.save_position();
+        self.player.status = self.player.status.stopped();
+        let Some(e) = self.player.episode.take() else {
+            self.show_error(format!("Couldn't play the episode: {}", msg));
+            return;
+        };
+        match self.player.url.clone() {
+            Some(u) if !self.player.reopened && !at.is_zero() && u.scheme() != "file" => {
+                info!("reopening {:?} at {:?} after: {}", e.title(), at, msg);
+                self.toast(format!(
+                    "Lost the connection, reopening {} at {}",
+                    e.title(),
+                    ui::fmt_duration(at)
+                ));
+                self.player.play(e, u, at);
+                self.player.reopened = true;
+            }
+            _ => self.show_error(format!("Couldn't play {}: {}", e.title(), msg)),
+        }
+    }
+
+    fn finish_episode(&mut self) {
+        self.record_listen();
+        // the next time it's played, it starts over
+        self.player.status.position = Duration::ZERO;
+        self.save_position();
+        let finished = self.player.episode.take();
+        if let Some(e) = finished.as_ref() {
+            if let Some(feed) = e.feed.as_ref() {
+                self.set_played(feed.as_str(), &e.item, true);
+            }
+            self.announce(format!("Finished {}", e.title()));
+        }
+        self.player.status = self.player.status.stopped();
+        // what was just played may have been the last reason to keep a download
+        self.clean_up_downloads();
+        if self.player.sleep == Some(player::Sleep::EndOfEpisode) {
+            self.fall_asleep();
+            return;
+        }
+        // only this once
+        if self.player.stop_after {
+            self.player.stop_after = false;
+            self.announce("Stopped after the episode");
+            return;
+        }
+        if let Some(e) = finished {
+            let again = queue::Entry {
+                item: e.item,
+                position: Duration::ZERO,
+                podcast: e.podcast,
+                feed: e.feed.map(|u| u.to_string()),
+            };
+            match self.player.repeat {
+                player::Repeat::Off => {}
+                player::Repeat::One => self.queue.insert(0, again),
+                player::Repeat::Queue => self.queue.push(again),
+            }
+        }
+        if let Some(next) = self.queue.remove(0) {
+            self.dirty.queue = true;
+            self.play_entry(next);
+        }
+    }
+
+    // Save a playback speed for the podcast playing, or else the one on screen. None goes
+    // back to the global speed.
+    pub fn set_podcast_speed(&mut self, speed: Option<f32>) {
+        let Some(feed) = self.settings_feed() else {
+            self.announce("Load a podcast to set its speed");
+            return;
+        };
+        let speed = speed.map(player::clamp_speed);
+        if !self.subscriptions.set_speed(feed.as_str(), speed) {
+            self.announce("Subscribe to the podcast to save its speed");
+            return;
+        }
+        self.dirty.subscriptions = true;
+        if let Err(e) = self.save() {
+            error!("failed to save subscriptions: {:?}", e);
+            self.announce(format!("Failed to save the podcast's speed: {}", e));
+            return;
+        }
+        let playing = self.player.episode.as_ref().and_then(|e| e.feed.as_ref()) == Some(&feed);
+        if playing {
+            self.apply_speed(speed.unwrap_or(self.config.playback.speed));
+        }
+        match speed {
+            Some(s) => self.announce(format!("Episodes of this podcast play at {:.1}x", s)),
+            None => self.announce(format!(
+                "Episodes of this podcast play at the global speed, {:.1}x",
+                self.config.playback.speed
+            )),
+        }
+    }
+
+    fn toggle_skip_silence(&mut self) {
+        let skip = !self.player.status.skip_silence;
+        self.player.send(player::Control::SkipSilence(skip));
+        self.player.status.skip_silence = skip;
+        if skip {
+            self.announce("Skipping silences");
+        } else {
+            self.announce(format!(
+                "Stopped skipping silences, {} saved",
+                ui::fmt_duration(self.player.status.saved)
+            ));
+        }
+    }
+
+    // Mark point A of a loop, then point B to start looping, then stop looping
+    fn mark_segment(&mut self) {
+        if !self.player.is_active() || self.player.status.state == player::State::Loading {
+            self.announce("Nothing is playing");
+            return;
+        }
+        let position = self.player.status.position;
+        match self.player.segment {
+            None => {
+                let segment = player::Segment {
+                    start: position,
+                    end: None,
+                };
+                self.player.segment = Some(segment);
+                self.announce(format!(
+                    "Marked the start of the {}, press l at its end",
+                    segment.describe()
+                ));
+            }
+            Some(s) if s.end.is_none() && position <= s.start => {
+                self.announce("The end of the loop has to come after its start")
+            }
+            Some(s) if s.end.is_none() => {
+                let segment = player::Segment {
+                    end: Some(position),
+                    ..s
+                };
+                self.player.segment = Some(segment);
+                self.player.seek_to(s.start);
+                self.player.status.position = s.start;
+                self.announce(format!("Playing the {}", segment.describe()));
+            }
+            Some(_) => {
+                self.player.segment = None;
+                self.announce("Stopped looping");
+            }
+        }
+    }
+
+    // Go back to the start of the loop once playback reaches its end
+    fn check_segment(&mut self) {
+        let Some(player::Segment {
+            start,
+            end: Some(end),
+        }) = self.player.segment
+        else {
+            return;
+        };
+        if self.player.status.state == player::State::Playing && self.player.status.position >= end
+        {
+            let to = self.player.seek_to(start);
+            // the player only reports the new position once it got there
+            self.player.status.position = to;
+        }
+    }
+
+    fn toggle_stop_after(&mut self) {
+        self.player.stop_after = !self.player.stop_after;
+        if self.player.stop_after {
+            self.announce("Stopping after this episode");
+        } else {
+            self.announce("Carrying on after this episode");
+        }
+    }
+
+    fn cycle_repeat(&mut self) {
+        self.set_repeat(self.player.repeat.next());
+    }
+
+    fn set_repeat(&mut self, repeat: player::Repeat) {
+        self.player.repeat = repeat;
+        let mut s = repeat.describe().to_string();
+        // capitalized for the status line
+        s[..1].make_ascii_uppercase();
+        self.announce(s);
+    }
+
+    pub fn set_volume(&mut self, volume: u8) {
+        let volume = volume.min(100);
+        self.player.send(player::Control::SetVolume(volume));
+        self.player.status.volume = volume;
+        self.announce(format!("Volume {}%", volume));
+    }
+
+    // Stop playback after a while, or at the end of the episode when None
+    pub fn set_sleep(&mut self, after: Option<Duration>) {
+        let sleep = match after.map(|d| Instant::now().checked_add(d)) {
+            Some(Some(at)) => player::Sleep::At(at),
+            Some(None) => {
+                self.announce("That's too long for a sleep timer");
+                return;
+            }
+            None => player::Sleep::EndOfEpisode,
+        };
+        self.announce(format!("Sleep timer set, {}", sleep.describe()));
+        self.player.sleep = Some(sleep);
+    }
+
+    // The sleep timer went off
+    fn fall_asleep(&mut self) {
+        self.player.sleep = None;
+        self.player.stop_after = false;
+        if self.player.is_active() {
+            self.player.send(player::Control::Stop);
+        }
+        if let Some(e) = self.player.external.take() {
+            e.stop();
+        }
+        self.announce("Sleep timer went off, playback stopped");
+    }
+
+    // Go to a position in the episode playing
+    pub fn seek_to(&mut self, to: Duration) {
+        if self.player.episode.is_none() {
+            self.announce("Nothing is playing");
+            return;
+        }
+        if self.player.status.state == player::State::Loading {
+            return;
+        }
+        let to = self.player.seek_to(to);
+        self.announce(format!("At {}", ui::fmt_duration(to)));
+    }
+
+    // Act on playback control from outside the app
+    fn remote(&mut self, r: Remote) {
+        let state = self.player.status.state;
+        match r {
+            Remote::PlayPause => self.toggle_pause(),
+            Remote::Play if matches!(state, player::State::Paused | player::State::Stopped) => {
+                self.toggle_pause()
+            }
+            Remote::Pause if state == player::State::Playing => self.toggle_pause(),
+            Remote::Play | Remote::Pause => {}
+            Remote::Stop if self.player.is_active() => self.stop(),
+            Remote::Stop => {}
+            Remote::Next => self.seek_by(player::SEEK_FORWARD),
+            Remote::Previous => self.seek_by(-player::SEEK_BACK),
+            Remote::SeekTo(to) => self.seek_to(to),
+            Remote::SetSpeed(speed) => self.set_speed(speed),
+            Remote::SetVolume(volume) => self.set_volume(volume),
+            Remote::SetRepeat(repeat) => self.set_repeat(repeat),
+        }
+    }
+
+    fn stop(&mut self) {
+        // stopping early is the stop X asked for
+        self.player.stop_after = false;
+        if let Some(e) = self.player.external.take() {
+            self.announce(format!("Stopped {}", e.program));
+            e.stop();
+            return;
+        }
+        self.player.send(player::Control::Stop);
+        if let Some(e) = self.player.episode.as_ref() {
+            self.announce(format!("Stopped {}", e.title()));
+        }
+    }
+
+    // Add an episode to the end of the queue, it's saved with the next autosave
+    pub fn enqueue(&mut self, e: queue::Entry) {
+        let msg = format!(
+            "Added {} to the queue, {} episodes queued",
+            e.item.title().unwrap_or("Title missing!"),
+            self.queue.len() + 1
+        );
+        self.queue.push(e);
+        self.dirty.queue = true;
+        self.announce(msg);
     }
 }
 
+// The path typed in, with `~/` standing for the home directory as the shell isn't there
+// to expand it
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+// Seconds since the epoch
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(text) = profile::info(&args) {
+        println!("{}", text);
+        return Ok(());
+    }
+    // before anything is loaded, to load the profile's data
+    if let Some(p) = profile::from_args(args)? {
+        profile::set(p);
+    }
+
     // set up logging
     let file_appender = RollingFileAppender::new(Rotation::HOURLY, "/tmp", "podcasts.log");
     let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
@@ -100,15 +3568,155 @@ async fn main() -> Result<(), Box<dyn Error>> {
     tracing_subscriber::registry()
         .with(fmt_layer.with_filter(span_filter))
         .init();
+    if let Some(p) = profile::current() {
+        info!("using profile {}", p);
+    }
+
+    // load user configuration
+    let config = config::load().unwrap_or_else(|e| {
+        error!("failed to load config, using defaults: {:?}", e);
+        config::Config::default()
+    });
+
+    feed::limit::configure(&config.network);
+    player::stream::configure(&config.playback);
+    downloads::throttle::configure(&config.downloads);
+    if config.downloads.schedule(profile::current()).unmetered_only {
+        downloads::schedule::watch_metered();
+    }
+
+    // what fails to load is left alone on disk, and reported once the app is up
+    let mut unloaded = Dirty::default();
+    let mut load_errors = vec![];
+    let subscriptions = Subscriptions::load().unwrap_or_else(|e| {
+        error!("failed to load subscriptions: {:?}", e);
+        unloaded.subscriptions = true;
+        load_errors.push(format!("subscriptions: {}", e));
+        Subscriptions::default()
+    });
+    let db = Db::open()
+        .map_err(|e| error!("failed to open the database: {:?}", e))
+        .ok();
+    let played = match db.as_ref().map(Played::load) {
+        Some(Ok(p)) => p,
+        Some(Err(e)) => {
+            error!("failed to load played episodes: {:?}", e);
+            Played::default()
+        }
+        None => Played::default(),
+    };
+    let stars = match db.as_ref().map(Stars::load) {
+        Some(Ok(s)) => s,
+        Some(Err(e)) => {
+            error!("failed to load stars: {:?}", e);
+            Stars::default()
+        }
+        None => Stars::default(),
+    };
+    let history = match db.as_ref().map(History::load) {
+        Some(Ok(h)) => h,
+        Some(Err(e)) => {
+            error!("failed to load listening history: {:?}", e);
+            History::default()
+        }
+        None => History::default(),
+    };
+    let downloads = match db.as_ref().map(Downloads::load) {
+        Some(Ok(d)) => d,
+        Some(Err(e)) => {
+            error!("failed to load downloads: {:?}", e);
+            Downloads::default()
+        }
+        None => Downloads::default(),
+    };
+    let bookmarks = Bookmarks::load().unwrap_or_else(|e| {
+        error!("failed to load bookmarks: {:?}", e);
+        unloaded.bookmarks = true;
+        load_errors.push(format!("bookmarks: {}", e));
+        Bookmarks::default()
+    });
+    let queue = Queue::load().unwrap_or_else(|e| {
+        error!("failed to load queue: {:?}", e);
+        unloaded.queue = true;
+        load_errors.push(format!("queue: {}", e));
+        Queue::default()
+    });
+    let metrics = Metrics::load().unwrap_or_else(|e| {
+        error!("failed to load metrics: {:?}", e);
+        unloaded.metrics = true;
+        load_errors.push(format!("metrics: {}", e));
+        Metrics::default()
+    });
+
+    // refresh subscriptions in the background, per the startup config
+    let stale = subscriptions
+        .active()
+        .filter_map(|s| url::Url::parse(s.url.as_str()).ok())
+        .filter(|u| config.startup.should_refresh(cache::age(u)))
+        .collect::<Vec<url::Url>>();
 
     // create app
-    let app = App::default();
+    let mut app = App {
+        subscriptions,
+        played,
+        db,
+        stars,
+        history,
+        downloads,
+        queue,
+        bookmarks,
+        metrics,
+        unloaded,
+        theme: Theme::from_config(&config),
+        config,
+        ..Default::default()
+    };
+    if !load_errors.is_empty() {
+        app.show_error(format!(
+            "Failed to load {}. Changes to it won't be saved.",
+            load_errors.join(", ")
+        ));
+    }
 
     // channel for publishing messages from the UI to the data thread
     let (data_tx, data_rx) = mpsc::channel::<message::Request>();
     // channel for publishing messages from the data thread to the UI
     let (ui_tx, ui_rx) = mpsc::channel::<message::Response>();
 
+    app.refresh(stale, &data_tx);
+
+    if app.config.startup.restore {
+        match Session::load() {
+            Ok(s) => app.restore(s, &data_tx),
+            Err(e) => error!("failed to load session: {:?}", e),
+        }
+    }
+
+    if app.config.check_updates {
+        data_tx.send(message::Request::CheckUpdate)?;
+    }
+
+    // spawn player thread
+    let (player_tx, player_rx) = mpsc::channel::<player::Control>();
+    app.player = Player::new(player_tx);
+    app.player
+        .send(player::Control::SetSpeed(app.config.playback.speed));
+    app.player.status.speed = player::clamp_speed(app.config.playback.speed);
+    app.player
+        .send(player::Control::SetVolume(app.config.playback.volume));
+    app.player.status.volume = app.config.playback.volume.min(100);
+    let responder = ui_tx.clone();
+    thread::spawn(move || player::run(responder, player_rx));
+
+    // spawn MPRIS thread, for media keys and the desktop's now playing
+    #[cfg(target_os = "linux")]
+    {
+        let (mpris_tx, mpris_rx) = mpsc::channel::<mpris::Snapshot>();
+        app.mpris = Some(mpris_tx);
+        let responder = ui_tx.clone();
+        thread::spawn(move || mpris::run(responder, mpris_rx));
+    }
+
     // spawn data thread
     thread::spawn(move || loop {
         data::handle_background_request(&ui_tx, &data_rx);
@@ -118,7 +3726,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, SaveTitle, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
@@ -130,10 +3738,15 @@ async fn main() -> Result<(), Box<dyn Error>> {
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        RestoreTitle
     )?;
     terminal.show_cursor()?;
 
+    if let Err(e) = nowplaying::clear() {
+        error!("failed to clear now playing: {:?}", e);
+    }
+
     if let Err(err) = res {
         eprintln!("{:?}", err)
     }
@@ -147,66 +3760,159 @@ fn run_app<B: Backend>(
     data_tx: &Sender<message::Request>,
     ui_rx: &Receiver<message::Response>,
 ) -> io::Result<()> {
+    let mut screen = Screen::default();
+    screen.input.commands = Commands::load().unwrap_or_else(|e| {
+        error!("failed to load command history: {:?}", e);
+        Commands::default()
+    });
+    let mut title = String::new();
+    let mut now_playing = None;
+    #[cfg(target_os = "linux")]
+    let mut mpris_snapshot = None;
+    let autosave = Duration::from_secs(app.config.store.autosave);
+    let mut last_save = Instant::now();
+    let mut last_refresh = Instant::now();
+    app.clean_up_downloads();
+    let mut last_clean_up = Instant::now();
     loop {
+        if last_save.elapsed() >= autosave {
+            app.autosave();
+            last_save = Instant::now();
+        }
+        if last_clean_up.elapsed() >= CLEAN_UP_CHECK {
+            app.clean_up_downloads();
+            last_clean_up = Instant::now();
+        }
+        if last_refresh.elapsed() >= REFRESH_CHECK {
+            // quietly skipped while a refresh is still going
+            if app.refreshing == 0 {
+                let due = app.due_for_refresh();
+                app.refresh(due, data_tx);
+            }
+            last_refresh = Instant::now();
+        }
+        if matches!(app.player.sleep, Some(player::Sleep::At(t)) if t <= Instant::now()) {
+            app.fall_asleep();
+        }
+        app.check_external();
+        app.check_speech();
+        app.start_downloads(data_tx);
+
         let span = span!(Level::TRACE, "draw");
         let _enter = span.enter();
-        terminal.draw(|f| display(f, &mut app, ui_rx))?;
+        terminal.draw(|f| display(f, &mut app, &mut screen, ui_rx))?;
+
+        let t = window_title(&app);
+        if t != title {
+            execute!(io::stdout(), SetTitle(t.as_str()))?;
+            title = t;
+        }
+
+        let np = NowPlaying::from_app(&app);
+        if now_playing.as_ref() != Some(&np) {
+            if let Err(e) = np.write() {
+                error!("failed to export now playing: {:?}", e);
+            }
+            now_playing = Some(np);
+        }
+
+        #[cfg(target_os = "linux")]
+        if let Some(tx) = app.mpris.as_ref() {
+            let s = mpris::Snapshot::from_app(&app);
+            if mpris_snapshot.as_ref() != Some(&s) {
+                // the MPRIS thread is gone if there's no session bus, nothing to do then
+                if tx.send(s.clone()).is_err() {
+                    app.mpris = None;
+                }
+                mpris_snapshot = Some(s);
+            }
+        }
 
         if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
+            let ev = event::read()?;
+            // clicks and the scroll wheel, while nothing waits for a key
+            if let Event::Mouse(mouse) = ev {
+                if (&app.confirm, app.pending_key) == (&None, None) {
+                    if let Some(action) = screen.handle_mouse(mouse, &app.display_action) {
+                        handle_action(&mut app, data_tx, action);
+                    }
+                }
+                continue;
+            }
+            if let Event::Key(key) = ev {
+                // the error was seen
+                app.error = None;
+                // the parts of the screen get the key first, unless the app waits for an
+                // answer or the second key of a shortcut
+                let action = match (&app.confirm, app.pending_key) {
+                    (None, None) => screen.handle_event(key, &app.display_action),
+                    _ => None,
+                };
+                if let Some(action) = action {
+                    if matches!(action, Action::Submit(_)) {
+                        if let Err(e) = screen.input.commands.save() {
+                            error!("failed to save command history: {:?}", e);
+                        }
+                    }
+                    handle_action(&mut app, data_tx, action);
+                    // the episodes are filtered as the filter is typed
+                    if let Some(f) = screen.input.filter() {
+                        if app.display_action == DisplayAction::ListEpisodes {
+                            app.filter_episodes(f);
+                        }
+                    }
+                    continue;
+                }
                 match key.code {
+                    // answer a pending confirmation before anything else
+                    KeyCode::Char('y') if app.confirm.is_some() => app.confirm(),
+                    _ if app.confirm.is_some() => app.decline(),
+                    // close the search result actions
+                    KeyCode::Esc if app.search_menu => {
+                        app.search_menu = false;
+                        app.announce("Closed actions");
+                    }
+                    KeyCode::Esc if app.visual.is_some() => app.toggle_visual(),
+                    // along with the filter being typed
+                    KeyCode::Esc
+                        if app.display_action == DisplayAction::ListEpisodes
+                            && app.episode_filter.is_some() =>
+                    {
+                        screen.input.take();
+                        app.filter_episodes("");
+                    }
+                    // any other key cancels a half typed two key shortcut
+                    _ if app.pending_key.is_some() && !matches!(key.code, KeyCode::Char(_)) => {
+                        app.pending_key = None;
+                    }
                     KeyCode::Esc => {
                         info!("Closing application");
+                        app.save_position();
+                        app.record_listen();
+                        app.save_session();
+                        app.autosave();
                         return Ok(());
                     }
-                    // submit data
-                    KeyCode::Enter => {
-                        info!(
-                            "Submitting request for display mode {display:?}",
-                            display = app.display_action
-                        );
-                        match app.display_action {
-                            DisplayAction::Input => {
-                                // submit a message to data layer
-                                let msg = app.input.drain(..).collect::<String>();
-                                data::handle_user_input(&mut app, data_tx, parse(msg.as_str()))
-                            }
-                            DisplayAction::ListEpisodes => {
-                                let item: Option<Item> =
-                                    app.channel.as_ref().map(|c| c.items()).and_then(|items| {
-                                        app.state.selected().and_then(|idx| items.get(idx)).cloned()
-                                        // TODO: there must be a more idiomatic way
-                                    });
-                                // TODO: clean this up
-                                info!("Load podcast episode {exists}", exists = item.is_some());
-                                if item.is_some() {
-                                    app.display_action = DisplayAction::DescribeEpisode;
-                                }
-                                let res = data_tx.send(message::Request::Episode(item));
-                                if res.is_err() {
-                                    error!("failed to send message {:?}", res.unwrap_err());
-                                }
-                            }
-                            DisplayAction::DescribeEpisode => {
-                                // TODO: idk what should happen here yet. probably need to have another list of options.
-                                info!("Load episode");
-                            }
+                    // jump list. Going forward is Ctrl-F, as vim's Ctrl-I is indistinguishable
+                    // from Tab in most terminals.
+                    KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        let current = app.location();
+                        match app.marks.back(current).cloned() {
+                            Some(loc) => app.go_to(loc, data_tx),
+                            None => app.announce("Already at the oldest jump"),
                         }
                     }
-                    // user input
-                    KeyCode::Char(c) => {
-                        app.input.push(c);
-                    }
-                    KeyCode::Backspace => {
-                        app.input.pop();
-                    }
-                    // list selection
-                    KeyCode::Up => {
-                        app.previous();
-                    }
-                    KeyCode::Down => {
-                        app.next();
+                    KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        match app.marks.forward().cloned() {
+                            Some(loc) => app.go_to(loc, data_tx),
+                            None => app.announce("Already at the latest jump"),
+                        }
                     }
+                    KeyCode::Tab => app.cycle_tab(false, data_tx),
+                    KeyCode::BackTab => app.cycle_tab(true, data_tx),
+                    // single key shortcuts the parts of the screen left, the input box
+                    // takes keys once a command is being typed
+                    KeyCode::Char(c) => handle_hotkey(&mut app, data_tx, c),
                     _ => {}
                 }
             }
@@ -214,21 +3920,286 @@ fn run_app<B: Backend>(
     }
 }
 
-fn display<B: Backend>(f: &mut Frame<B>, app: &mut App, rx: &Receiver<message::Response>) {
+fn handle_hotkey(app: &mut App, data_tx: &Sender<message::Request>, c: char) {
+    // second key of a two key shortcut
+    if let Some(first) = app.pending_key.take() {
+        match first {
+            'm' => app.set_mark(c),
+            '\'' => app.jump_to_mark(c, data_tx),
+            'o' => match c.to_digit(10) {
+                Some(n) => app.open_link(n as usize),
+                None => app.announce("Cancelled"),
+            },
+            _ => {}
+        }
+        return;
+    }
+
+    // the keys of each view are taken by its component first
+    match c {
+        '1'..='5' => {
+            if let Some(tab) = c.to_digit(10).and_then(Tab::nth) {
+                app.switch_tab(tab, data_tx);
+            }
+        }
+        '\'' => app.pending_key = Some(c),
+        'b' if app.player.episode.is_some() => app.bookmark(None),
+        'v' => app.toggle_now_playing(),
+        'S' => app.toggle_skip_silence(),
+        'r' => app.cycle_repeat(),
+        'l' => app.mark_segment(),
+        'x' if app.player.is_active() || app.player.external.is_some() => app.stop(),
+        'X' => app.toggle_stop_after(),
+        'n' => app.jump_chapter(true),
+        'N' => app.jump_chapter(false),
+        _ => debug!(
+            "no hotkey for {c:?} in {display:?}",
+            display = app.display_action
+        ),
+    }
+}
+
+// Carry out what a key asked for, by way of a part of the screen
+fn handle_action(app: &mut App, data_tx: &Sender<message::Request>, action: Action) {
+    match action {
+        Action::Consumed => {}
+        Action::OpenPodcast(idx) => app.open_sidebar_podcast(idx, data_tx),
+        Action::Select(idx) => {
+            app.state.select(Some(idx));
+            app.announce_selection();
+        }
+        Action::SeekTo(ratio) => match app.player.length() {
+            Some(d) => app.seek_to(d.mul_f64(ratio)),
+            None => app.announce("The episode's length isn't known, can't seek to there"),
+        },
+        // commands can be submitted from any screen
+        Action::Submit(msg) => data::handle_user_input(app, data_tx, parse(msg.as_str())),
+        Action::TogglePause => app.toggle_pause(),
+        Action::SeekBack => app.seek_by(-player::SEEK_BACK),
+        Action::SeekForward => app.seek_by(player::SEEK_FORWARD),
+        Action::Faster => app.set_speed(app.player.status.speed + player::SPEED_STEP),
+        Action::Slower => app.set_speed(app.player.status.speed - player::SPEED_STEP),
+        Action::Louder => app.set_volume(app.player.status.volume + player::VOLUME_STEP),
+        Action::Quieter => {
+            app.set_volume(app.player.status.volume.saturating_sub(player::VOLUME_STEP))
+        }
+        Action::Previous => app.previous(),
+        Action::Next => app.next(),
+        Action::Open => app.open_selected_episode(data_tx),
+        Action::OpenFile => app.open_enclosure(data_tx),
+        Action::Enqueue => app.enqueue_selected(),
+        Action::MarkPlayed => app.request_mark_played(false),
+        Action::Download => app.download_selected(data_tx),
+        Action::DeleteDownload => app.request_delete_episode_downloads(),
+        Action::Visual => app.toggle_visual(),
+        Action::Star => app.toggle_star(),
+        Action::SetMark => app.pending_key = Some('m'),
+        Action::ChooseLink => app.choose_link(),
+        Action::Speak => app.toggle_speech(),
+        Action::PodcastSettings => app.show_podcast_settings(),
+        Action::ChoosePreview => app.choose_preview(),
+        Action::ChooseResult => data::handle_search_choice(app, data_tx),
+        Action::OpenSubscription => app.open_subscription(data_tx),
+        Action::SortLibrary => app.sort_library(),
+        Action::ChangeSetting(by) => app.change_setting(by),
+        Action::ResetSetting => app.reset_setting(),
+        Action::PlayEntry => app.play_selected_entry(),
+        Action::MoveUp => app.move_selected_queue_entry(|i, _| i.saturating_sub(1)),
+        Action::MoveDown => {
+            app.move_selected_queue_entry(|i, len| (i + 1).min(len.saturating_sub(1)))
+        }
+        Action::MoveToTop => app.move_selected_queue_entry(|_, _| 0),
+        Action::MoveToBottom => app.move_selected_queue_entry(|_, len| len.saturating_sub(1)),
+        Action::PlayBookmark => app.play_selected_bookmark(),
+        Action::DeleteBookmark => app.delete_selected_bookmark(),
+        Action::PlayStar => app.play_selected_star(),
+        Action::ResumeListen => app.resume_selected_listen(),
+        Action::PlayDownload => app.play_selected_download(),
+        Action::RemoveDownload => app.request_delete_download(),
+        Action::PauseDownload => app.pause_selected_download(),
+        Action::CancelDownload => app.cancel_selected_download(),
+        Action::ClearStorage => app.request_clear_storage(),
+        Action::CleanUp => app.request_clean_up(),
+        Action::SortMetrics => {
+            app.metrics_sort = app.metrics_sort.next();
+            app.announce(format!("Sorted by {}", app.metrics_sort.name()));
+        }
+    }
+}
+
+fn display<B: Backend>(
+    f: &mut Frame<B>,
+    app: &mut App,
+    screen: &mut Screen,
+    rx: &Receiver<message::Response>,
+) {
     if let Ok(r) = rx.try_recv() {
         update_app_state(app, r)
     }
 
-    draw_main_layout(f, app);
+    draw_main_layout(f, app, screen);
 }
 
 fn update_app_state(app: &mut App, msg: message::Response) {
     match msg {
+        message::Response::Loading(u) => {
+            if app.feed_url.as_ref() == Some(&u) {
+                app.loading = Some(Instant::now());
+            }
+        }
         message::Response::Feed(c) => {
-            app.channel = Some(c);
+            app.loading_parts = false;
+            app.loading = None;
+            if let Some(u) = app.feed_url.clone() {
+                app.store_feed(u.as_str(), &c);
+            }
+            app.show_feed(c);
         }
+        message::Response::FeedPart(part) => app.show_feed_part(part),
         message::Response::Episode(e) => {
             app.item = Some(e);
         }
+        message::Response::Chapters(u, c) => {
+            // ignore chapters that arrive after the user moved on to another episode
+            if app.item.as_ref().and_then(chapters_url) == Some(u) {
+                if app.item.as_ref().is_some_and(|i| app.player.has(i)) {
+                    app.player.chapters = Some(c.clone());
+                }
+                app.chapters = Some(c);
+            }
+        }
+        message::Response::Image(u, i) => {
+            app.images.insert(u, i);
+        }
+        message::Response::SearchResults(r) => {
+            app.announce(format!("Found {} podcasts", r.len()));
+            app.search_state
+                .select(if r.is_empty() { None } else { Some(0) });
+            app.search_results = r;
+        }
+        message::Response::LatestEpisode(u, podcast, item) => app.enqueue(queue::Entry {
+            item,
+            position: Duration::ZERO,
+            podcast: Some(podcast),
+            feed: Some(u.to_string()),
+        }),
+        message::Response::Refreshed(u, c) => {
+            app.refreshing = app.refreshing.saturating_sub(1);
+            if let Some(c) = c.as_ref() {
+                app.store_feed(u.as_str(), c);
+                let new = app.subscriptions.new_in(u.as_str(), c);
+                app.refresh_new += new;
+                if new > 0 {
+                    app.auto_download(u.as_str(), c);
+                }
+                if new > 0 && app.subscriptions.get(u.as_str()).is_some_and(|s| s.notify) {
+                    app.refresh_notices
+                        .push(format!("{} of {}", new, c.title()));
+                }
+            }
+            match c {
+                Some(c) if app.feed_url.as_ref() == Some(&u) => app.update_feed(c),
+                Some(c) => app.remember_feed(u.as_str(), &c),
+                None => app.refresh_failures += 1,
+            }
+            if app.refreshing == 0 {
+                let mut new = match app.refresh_new {
+                    0 => "no new episodes".to_string(),
+                    1 => "1 new episode".to_string(),
+                    n => format!("{} new episodes", n),
+                };
+                if !app.refresh_notices.is_empty() {
+                    new = format!("{} ({})", new, app.refresh_notices.join(", "));
+                }
+                match app.refresh_failures {
+                    0 => app.toast(format!("Podcasts refreshed, {}", new)),
+                    n => app.toast(format!("Podcasts refreshed, {}, {} failed", new, n)),
+                }
+                app.clean_up_downloads();
+            }
+        }
+        message::Response::Subscribed(u, c) => {
+            app.store_feed(u.as_str(), &c);
+            app.subscribe_to(u.as_str(), c.title());
+            app.remember_feed(u.as_str(), &c);
+        }
+        message::Response::Fetched(u, title, fetch) => {
+            app.metrics.record(u.as_str(), title.as_str(), &fetch);
+            app.dirty.metrics = true;
+        }
+        message::Response::Remote(r) => app.remote(r),
+        message::Response::Player(e) => match e {
+            player::Event::Status(s) => {
+                let title = app
+                    .player
+                    .episode
+                    .as_ref()
+                    .map(|e| e.title().to_string())
+                    .unwrap_or_default();
+                if s.state != app.player.status.state && s.state == player::State::Playing {
+                    app.announce(format!("Playing {}", title));
+                    app.listening = Some(now());
+                }
+                if let Some(n) = s
+                    .retrying
+                    .filter(|n| app.player.status.retrying != Some(*n))
+                {
+                    app.announce(format!(
+                        "Lost the connection, reconnecting (try {} of {})",
+                        n, app.config.playback.retries
+                    ));
+                }
+                // where it was paused or stopped, before a stop forgets the position
+                if app.player.status.state == player::State::Playing
+                    && s.state != player::State::Playing
+                {
+                    app.save_position();
+                    app.record_listen();
+                }
+                app.player.status = s;
+                // keep the details of the episode in sync with the player
+                if app.item.as_ref().is_some_and(|i| app.player.has(i)) {
+                    app.position = s.position;
+                }
+                app.check_played();
+                app.check_segment();
+                app.check_outro();
+                app.check_ads();
+                app.check_next();
+            }
+            // chapters in the file only fill in for an episode that has none in its feed
+            player::Event::Chapters(c) => {
+                if app.player.chapters.is_none() {
+                    if app.item.as_ref().is_some_and(|i| app.player.has(i)) {
+                        app.chapters = Some(c.clone());
+                    }
+                    app.player.chapters = Some(c);
+                }
+            }
+            player::Event::Finished => app.finish_episode(),
+            player::Event::Failed(msg) => app.playback_failed(msg),
+        },
+        message::Response::Download(path, p) => app.download_progress(&path, p),
+        message::Response::Status(msg) => app.announce(msg),
+        message::Response::FeedFailed(u, msg) => {
+            // a feed that failed partway through won't send any more of itself
+            if app.feed_url.as_ref() == Some(&u) {
+                app.loading_parts = false;
+                app.loading = None;
+            }
+            app.show_error(msg);
+        }
+        message::Response::Error(msg) => app.show_error(msg),
+        message::Response::Update(r) => {
+            match r.summary() {
+                Some(s) => app.announce(format!("{} is available: {}", r.version, s)),
+                None => app.announce(format!("{} is available", r.version)),
+            }
+            app.update = Some(r);
+        }
+        message::Response::ContentType(u, mime) => {
+            let mime = mime.unwrap_or_default();
+            app.open_with_handler(u.as_str(), mime.as_str());
+        }
     }
 }
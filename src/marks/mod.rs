@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use url::Url;
+
+// Maximum number of entries kept in the jump list
+const MAX_JUMPS: usize = 100;
+
+// Location is a place in the library to jump back to: a podcast, and optionally one of
+// its episodes (see `feed::episode_id`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub feed: Url,
+    pub episode: Option<String>,
+}
+
+// Marks holds vim-like named marks (`m a` / `' a`) and the jump list of recently
+// visited locations (Ctrl-O / Ctrl-I)
+#[derive(Default, Debug)]
+pub struct Marks {
+    marks: HashMap<char, Location>,
+    jumps: Vec<Location>,
+    // position in the jump list, equal to its length when not currently jumping
+    cursor: usize,
+}
+
+impl Marks {
+    pub fn set(&mut self, name: char, loc: Location) {
+        self.marks.insert(name, loc);
+    }
+
+    pub fn get(&self, name: char) -> Option<&Location> {
+        self.marks.get(&name)
+    }
+
+    // Record a visit. Visiting somewhere new while partway back through the list drops
+    // the locations that were ahead, like browser history.
+    pub fn visit(&mut self, loc: Location) {
+        // while jumping, the cursor is on the location being visited from
+        if self.cursor < self.jumps.len() {
+            self.jumps.truncate(self.cursor + 1);
+        }
+        if self.jumps.last() != Some(&loc) {
+            self.jumps.push(loc);
+        }
+        if self.jumps.len() > MAX_JUMPS {
+            self.jumps.remove(0);
+        }
+        self.cursor = self.jumps.len();
+    }
+
+    // Go back to the previous location. The current location is remembered when starting
+    // to go back, so that `forward` can return to it.
+    pub fn back(&mut self, current: Option<Location>) -> Option<&Location> {
+        if self.cursor == self.jumps.len() {
+            if let Some(c) = current {
+                if self.jumps.last() != Some(&c) {
+                    self.jumps.push(c);
+                }
+            }
+            self.cursor = self.jumps.len().saturating_sub(1);
+        }
+        if self.cursor == 0 {
+            return None;
+        }
+        self.cursor -= 1;
+        self.jumps.get(self.cursor)
+    }
+
+    // Go forward again after going back
+    pub fn forward(&mut self) -> Option<&Location> {
+        if self.cursor + 1 >= self.jumps.len() {
+            return None;
+        }
+        self.cursor += 1;
+        self.jumps.get(self.cursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::{Location, Marks};
+
+    fn loc(episode: &str) -> Location {
+        Location {
+            feed: Url::parse("https://example.com/feed").unwrap(),
+            episode: Some(episode.to_string()),
+        }
+    }
+
+    #[test]
+    fn marks() {
+        let mut m = Marks::default();
+        m.set('a', loc("1"));
+        m.set('b', loc("2"));
+        m.set('a', loc("3"));
+        assert_eq!(m.get('a'), Some(&loc("3")));
+        assert_eq!(m.get('b'), Some(&loc("2")));
+        assert_eq!(m.get('c'), None);
+    }
+
+    #[test]
+    fn back_and_forward() {
+        let mut m = Marks::default();
+        m.visit(loc("1"));
+        m.visit(loc("2"));
+
+        // moved on to 3 without visiting it yet
+        assert_eq!(m.back(Some(loc("3"))), Some(&loc("2")));
+        assert_eq!(m.back(Some(loc("2"))), Some(&loc("1")));
+        assert_eq!(m.back(Some(loc("1"))), None);
+        assert_eq!(m.forward(), Some(&loc("2")));
+        assert_eq!(m.forward(), Some(&loc("3")));
+        assert_eq!(m.forward(), None);
+    }
+
+    #[test]
+    fn visiting_drops_forward_history() {
+        let mut m = Marks::default();
+        m.visit(loc("1"));
+        m.visit(loc("2"));
+        m.visit(loc("3"));
+
+        assert_eq!(m.back(None), Some(&loc("2")));
+        m.visit(loc("4"));
+        assert_eq!(m.forward(), None);
+        assert_eq!(m.back(None), Some(&loc("2")));
+        assert_eq!(m.back(None), Some(&loc("1")));
+    }
+
+    #[test]
+    fn repeated_visits_are_collapsed() {
+        let mut m = Marks::default();
+        m.visit(loc("1"));
+        m.visit(loc("1"));
+        assert_eq!(m.back(None), None);
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    io,
+    process::{Child, Command, Stdio},
+    thread,
+};
+
+use tracing::error;
+
+// What an enclosure is, which decides how it gets opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Audio,
+    Video,
+    // bonus content such as PDFs and ebooks
+    Document,
+    Unknown,
+}
+
+impl Kind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Audio => "audio",
+            Kind::Video => "video",
+            Kind::Document => "document",
+            Kind::Unknown => "unknown",
+        }
+    }
+}
+
+pub fn kind(mime: &str) -> Kind {
+    let mime = essence(mime);
+    match mime.split_once('/') {
+        Some(("audio", _)) => Kind::Audio,
+        Some(("video", _)) => Kind::Video,
+        Some(("text", _)) => Kind::Document,
+        _ => match mime.as_str() {
+            "application/pdf" | "application/epub+zip" => Kind::Document,
+            // HLS playlists
+            "application/vnd.apple.mpegurl" | "application/x-mpegurl" => Kind::Audio,
+            _ => Kind::Unknown,
+        },
+    }
+}
+
+// The type without parameters, e.g. `audio/mpeg` for `Audio/MPEG; charset=binary`
+pub fn essence(mime: &str) -> String {
+    mime.split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+// Types that don't say anything about the content. Feeds and servers often use these
+// when they don't know better.
+pub fn is_generic(mime: &str) -> bool {
+    matches!(
+        essence(mime).as_str(),
+        "" | "application/octet-stream" | "binary/octet-stream" | "application/x-download"
+    )
+}
+
+// Guess the type from the file extension in the URL
+pub fn guess(url: &str) -> Option<&'static str> {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let (_, ext) = path.rsplit_once('.')?;
+    let mime = match ext.to_ascii_lowercase().as_str() {
+        "mp3" => "audio/mpeg",
+        "m4a" | "aac" => "audio/mp4",
+        "ogg" | "oga" | "opus" => "audio/ogg",
+        "flac" => "audio/flac",
+        "wav" => "audio/wav",
+        "mp4" | "m4v" => "video/mp4",
+        "mov" => "video/quicktime",
+        "webm" => "video/webm",
+        "pdf" => "application/pdf",
+        "epub" => "application/epub+zip",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+// The program that opens files with their default application
+pub fn system_opener() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else {
+        "xdg-open"
+    }
+}
+
+// Run a handler command, e.g. `mpv --no-terminal`, with the URL as its last argument.
+// The handler runs on its own; it is only waited on so that it doesn't linger as a zombie.
+pub fn open(handler: &str, url: &str) -> io::Result<()> {
+    let mut child = spawn(handler, url)?;
+    let program = program(handler).to_string();
+    thread::spawn(move || {
+        if let Err(e) = child.wait() {
+            error!("failed to wait for {}: {:?}", program, e);
+        }
+    });
+    Ok(())
+}
+
+// Start a handler command with the URL as its last argument, without waiting for it. Its
+// output is thrown away so that it doesn't draw over the UI.
+pub fn spawn(handler: &str, url: &str) -> io::Result<Child> {
+    command(handler)?.arg(url).stdin(Stdio::null()).spawn()
+}
+
+// A handler command split into its program and arguments, e.g. `mpv --no-video`, with its
+// output thrown away
+pub fn command(handler: &str) -> io::Result<Command> {
+    let mut parts = handler.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty handler command"))?;
+    let mut c = Command::new(program);
+    c.args(parts).stdout(Stdio::null()).stderr(Stdio::null());
+    Ok(c)
+}
+
+// The program a handler command runs, e.g. `mpv` for `mpv --no-video`
+pub fn program(handler: &str) -> &str {
+    handler.split_whitespace().next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{command, guess, is_generic, kind, Kind};
+
+    #[test]
+    fn kinds() {
+        assert_eq!(kind("audio/mpeg"), Kind::Audio);
+        assert_eq!(kind("Audio/MP4; codecs=mp4a"), Kind::Audio);
+        assert_eq!(kind("video/mp4"), Kind::Video);
+        assert_eq!(kind("application/pdf"), Kind::Document);
+        assert_eq!(kind("application/epub+zip"), Kind::Document);
+        assert_eq!(kind("application/zip"), Kind::Unknown);
+        assert_eq!(kind(""), Kind::Unknown);
+    }
+
+    #[test]
+    fn generic_types() {
+        assert!(is_generic(""));
+        assert!(is_generic("application/octet-stream"));
+        assert!(!is_generic("audio/mpeg"));
+    }
+
+    #[test]
+    fn guesses_from_extension() {
+        assert_eq!(
+            guess("https://example.com/ep1.MP3?token=abc"),
+            Some("audio/mpeg")
+        );
+        assert_eq!(
+            guess("https://example.com/notes.pdf#p2"),
+            Some("application/pdf")
+        );
+        assert_eq!(guess("https://example.com/episode"), None);
+        assert_eq!(guess("https://example.com/ep1.xyz"), None);
+    }
+
+    #[test]
+    fn splits_command() {
+        let c = command("espeak  -s 200").unwrap();
+        assert_eq!(c.get_program(), "espeak");
+        assert_eq!(c.get_args().collect::<Vec<_>>(), ["-s", "200"]);
+        assert!(command("  ").is_err());
+    }
+}
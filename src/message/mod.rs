@@ -1,22 +1,146 @@
+use image::DynamicImage;
 use rss::{Channel, Item};
+use std::{path::PathBuf, time::Duration};
 use url::Url;
 
-#[derive(Default, Debug, PartialEq)]
+use crate::{
+    chapters::Chapters,
+    downloads,
+    metrics::Fetch,
+    player::{self, Event},
+    search::SearchResult,
+    update::Release,
+};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
 pub enum DisplayAction {
     #[default]
     Input, // TODO: this needs to change
+    // look at a feed before deciding whether to subscribe to it
+    Preview,
     ListEpisodes,
     DescribeEpisode,
+    Queue,
+    Search,
+    // how fetching feeds went
+    Diagnostics,
+    // the podcasts subscribed to
+    Library,
+    // a podcast's own settings
+    PodcastSettings,
+    // starred episodes of every podcast
+    Starred,
+    // episodes listened to, most recent first
+    History,
+    // episodes downloaded, and being downloaded
+    Downloads,
+    // disk space taken by downloads and caches
+    Storage,
+    // downloads that cleaning up deletes, before it does
+    CleanUp,
+    // moments saved in episodes
+    Bookmarks,
+    // full screen view of the episode playing
+    NowPlaying,
+}
+
+// Destructive action waiting for the user to confirm it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Confirm {
+    // mark these episodes of the feed as played
+    MarkPlayed(String, Vec<String>),
+    // take the feed out of the library, along with its title
+    Unsubscribe(String, String),
+    // replace all of the user's data with the backup in this file
+    RestoreBackup(String),
+    // delete the downloaded file
+    DeleteDownload(String),
+    // delete these downloaded files
+    DeleteDownloads(Vec<String>),
+    // delete the downloads and cached feed of the podcast, or of all of them
+    ClearStorage(Option<String>),
+    // write the library as OPML to this file with the tokens of private feeds, or masked
+    // when declined
+    ExportOpml(String),
+}
+
+// Playback control from outside the app, like the desktop's media keys
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Remote {
+    Play,
+    Pause,
+    PlayPause,
+    Stop,
+    // skip ahead or back, like the seek keys
+    Next,
+    Previous,
+    SeekTo(Duration),
+    SetSpeed(f32),
+    // in percent
+    SetVolume(u8),
+    SetRepeat(player::Repeat),
 }
 
 #[derive(Debug, PartialEq)]
+#[allow(clippy::large_enum_variant)]
 pub enum Request {
     Feed(Url),
     Episode(Option<Item>),
+    // fetch the chapters file, and the artwork of every chapter
+    Chapters(Url),
+    // look up podcasts in the directory
+    Search(String),
+    // fetch the feed, only to get its newest episode
+    LatestEpisode(Url),
+    // ask the server what kind of file an episode is
+    ContentType(Url),
+    // look for a newer release of the app
+    CheckUpdate,
+    // fetch a feed to add it to the library
+    Subscribe(Url),
+    // fetch these subscriptions again, all at once
+    Refresh(Vec<Url>),
+    // download an episode's file to the path
+    Download(Url, PathBuf, downloads::Stop),
 }
 
 #[derive(Debug, PartialEq)]
+#[allow(clippy::large_enum_variant)]
 pub enum Response {
+    // the feed started to download
+    Loading(Url),
     Feed(Channel),
+    // the feed couldn't be loaded, and why
+    FeedFailed(Url, String),
+    // episodes of a feed that is still downloading, in batches
+    FeedPart(Channel),
     Episode(Item),
+    // chapters file, along with the URL it was loaded from
+    Chapters(Url, Chapters),
+    // image keyed by the URL it was loaded from
+    Image(String, DynamicImage),
+    SearchResults(Vec<SearchResult>),
+    // newest episode of the feed, along with the podcast's title
+    LatestEpisode(Url, String, Item),
+    // Content-Type of the URL, if the server said
+    ContentType(Url, Option<String>),
+    // a subscription was refreshed in the background, None if that failed
+    Refreshed(Url, Option<Channel>),
+    // a feed to add to the library was fetched
+    Subscribed(Url, Channel),
+    // a feed was fetched, along with its title
+    Fetched(Url, String, Fetch),
+    // something happened in the player
+    Player(Event),
+    // a newer release of the app is out
+    Update(Release),
+    // playback control from outside the app
+    Remote(Remote),
+    // how the download to the path is going
+    Download(PathBuf, downloads::Progress),
+    // something the user may want to know, shown in the status line
+    Status(String),
+    // something went wrong that the user should know about
+    Error(String),
 }
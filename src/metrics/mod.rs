@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, error::Error, time::Duration};
+
+use crate::store;
+
+// Fetch describes one download of a feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fetch {
+    pub elapsed: Duration,
+    pub bytes: usize,
+    // the feed was the same as the cached copy
+    pub unchanged: bool,
+}
+
+// FeedStats sums up the fetches of one feed
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedStats {
+    pub title: String,
+    pub fetches: u32,
+    pub last_ms: u64,
+    pub total_ms: u64,
+    pub slowest_ms: u64,
+    // size of the last fetch
+    pub bytes: u64,
+    pub unchanged: u32,
+}
+
+impl FeedStats {
+    pub fn average_ms(&self) -> u64 {
+        self.total_ms.checked_div(self.fetches.into()).unwrap_or(0)
+    }
+}
+
+// How the diagnostics table is sorted
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sort {
+    // slowest on average first
+    #[default]
+    Slowest,
+    Last,
+    Largest,
+    Name,
+}
+
+impl Sort {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sort::Slowest => "slowest on average",
+            Sort::Last => "slowest last time",
+            Sort::Largest => "largest",
+            Sort::Name => "name",
+        }
+    }
+
+    pub fn next(&self) -> Sort {
+        match self {
+            Sort::Slowest => Sort::Last,
+            Sort::Last => Sort::Largest,
+            Sort::Largest => Sort::Name,
+            Sort::Name => Sort::Slowest,
+        }
+    }
+}
+
+// Metrics records how fetching every feed went, by feed URL, stored as `metrics` in the
+// store. Helps find the subscriptions that make refreshing slow.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metrics {
+    #[serde(default)]
+    feeds: BTreeMap<String, FeedStats>,
+}
+
+impl Metrics {
+    pub fn record(&mut self, url: &str, title: &str, fetch: &Fetch) {
+        let stats = self.feeds.entry(url.to_string()).or_default();
+        let ms = u64::try_from(fetch.elapsed.as_millis()).unwrap_or(u64::MAX);
+        stats.title = title.to_string();
+        stats.fetches += 1;
+        stats.last_ms = ms;
+        stats.total_ms = stats.total_ms.saturating_add(ms);
+        stats.slowest_ms = stats.slowest_ms.max(ms);
+        stats.bytes = fetch.bytes as u64;
+        if fetch.unchanged {
+            stats.unchanged += 1;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.feeds.is_empty()
+    }
+
+    pub fn sorted(&self, sort: Sort) -> Vec<(&str, &FeedStats)> {
+        let mut feeds = self
+            .feeds
+            .iter()
+            .map(|(u, s)| (u.as_str(), s))
+            .collect::<Vec<(&str, &FeedStats)>>();
+        match sort {
+            Sort::Slowest => feeds.sort_by_key(|(_, s)| std::cmp::Reverse(s.average_ms())),
+            Sort::Last => feeds.sort_by_key(|(_, s)| std::cmp::Reverse(s.last_ms)),
+            Sort::Largest => feeds.sort_by_key(|(_, s)| std::cmp::Reverse(s.bytes)),
+            Sort::Name => feeds.sort_by_key(|(_, s)| s.title.to_lowercase()),
+        }
+        feeds
+    }
+
+    pub fn load() -> Result<Metrics, Box<dyn Error>> {
+        store::load("metrics")
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        store::save("metrics", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Fetch, Metrics, Sort};
+
+    fn fetch(ms: u64, bytes: usize, unchanged: bool) -> Fetch {
+        Fetch {
+            elapsed: Duration::from_millis(ms),
+            bytes,
+            unchanged,
+        }
+    }
+
+    #[test]
+    fn records_fetches() {
+        let mut m = Metrics::default();
+        m.record("https://a.com/feed", "A", &fetch(300, 1000, false));
+        m.record("https://a.com/feed", "A", &fetch(100, 1200, true));
+
+        let (_, s) = m.sorted(Sort::Slowest)[0];
+        assert_eq!(s.fetches, 2);
+        assert_eq!(s.last_ms, 100);
+        assert_eq!(s.average_ms(), 200);
+        assert_eq!(s.slowest_ms, 300);
+        assert_eq!(s.bytes, 1200);
+        assert_eq!(s.unchanged, 1);
+    }
+
+    #[test]
+    fn sorts() {
+        let mut m = Metrics::default();
+        m.record("https://a.com/feed", "b", &fetch(300, 10, false));
+        m.record("https://b.com/feed", "A", &fetch(100, 20, false));
+        m.record("https://b.com/feed", "A", &fetch(900, 20, false));
+        m.record("https://c.com/feed", "c", &fetch(600, 5, false));
+
+        let hosts = |sort| {
+            m.sorted(sort)
+                .into_iter()
+                .map(|(u, _)| &u[8..9])
+                .collect::<Vec<&str>>()
+        };
+        assert_eq!(hosts(Sort::Slowest), ["c", "b", "a"]);
+        assert_eq!(hosts(Sort::Last), ["b", "c", "a"]);
+        assert_eq!(hosts(Sort::Largest), ["b", "a", "c"]);
+        assert_eq!(hosts(Sort::Name), ["b", "a", "c"]);
+    }
+}
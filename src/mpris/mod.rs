@@ -0,0 +1,539 @@
+use rss::Item;
+use std::{
+    collections::hash_map::DefaultHasher,
+    collections::HashMap,
+    error::Error,
+    hash::{Hash, Hasher},
+    sync::mpsc::{Receiver, Sender},
+    time::Duration,
+};
+use tracing::{debug, error, info, instrument};
+use zbus::{
+    fdo,
+    zvariant::{ObjectPath, OwnedValue, Value},
+    SignalContext,
+};
+
+use crate::{
+    feed::{duration, episode_artwork, episode_id},
+    message::{Remote, Response},
+    player::{self, MAX_SPEED, MIN_SPEED},
+    App,
+};
+
+// Where both interfaces live, per the MPRIS spec
+const PATH: &str = "/org/mpris/MediaPlayer2";
+
+// Tracks are identified by object paths under this one
+const TRACK_PATH: &str = "/org/saxypandabear/podcasts/episode";
+
+// Position moving on by more than this between two snapshots, at normal speed, is a seek
+const SEEK_THRESHOLD: Duration = Duration::from_secs(2);
+
+// Track is the episode the desktop is told about
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    pub id: String,
+    pub title: String,
+    pub podcast: Option<String>,
+    pub length: Option<Duration>,
+    // audio file
+    pub url: Option<String>,
+    pub art: Option<String>,
+}
+
+impl Track {
+    fn new(item: &Item, podcast: Option<String>, art: Option<&str>) -> Track {
+        Track {
+            id: episode_id(item).unwrap_or_default().to_string(),
+            title: item.title().unwrap_or("Title missing!").to_string(),
+            podcast,
+            length: duration(item),
+            url: item.enclosure().map(|e| e.url().to_string()),
+            art: art.map(str::to_string),
+        }
+    }
+
+    // Object path identifying the episode. Episode ids are URLs or guids, which can't be
+    // used in a path as they are.
+    fn path(&self) -> String {
+        let mut h = DefaultHasher::new();
+        self.id.hash(&mut h);
+        format!("{}/{:016x}", TRACK_PATH, h.finish())
+    }
+
+    fn metadata(&self) -> HashMap<String, OwnedValue> {
+        let mut m = HashMap::new();
+        let mut insert = |k: &str, v: Value| {
+            if let Ok(v) = OwnedValue::try_from(v) {
+                m.insert(k.to_string(), v);
+            }
+        };
+        if let Ok(p) = ObjectPath::try_from(self.path()) {
+            insert("mpris:trackid", Value::from(p));
+        }
+        insert("xesam:title", Value::from(self.title.clone()));
+        if let Some(p) = self.podcast.as_ref() {
+            insert("xesam:album", Value::from(p.clone()));
+            insert("xesam:artist", Value::from(vec![p.clone()]));
+        }
+        if let Some(d) = self.length {
+            insert("mpris:length", Value::from(d.as_micros() as i64));
+        }
+        if let Some(u) = self.url.as_ref() {
+            insert("xesam:url", Value::from(u.clone()));
+        }
+        if let Some(a) = self.art.as_ref() {
+            insert("mpris:artUrl", Value::from(a.clone()));
+        }
+        m
+    }
+}
+
+// Snapshot is what the desktop is shown of the app, sent over whenever it changes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub state: player::State,
+    pub position: Duration,
+    pub speed: f32,
+    // in percent
+    pub volume: u8,
+    pub repeat: player::Repeat,
+    pub track: Option<Track>,
+}
+
+impl Default for Snapshot {
+    fn default() -> Self {
+        let status = player::Status::default();
+        Snapshot {
+            state: status.state,
+            position: status.position,
+            speed: status.speed,
+            volume: status.volume,
+            repeat: player::Repeat::Off,
+            track: None,
+        }
+    }
+}
+
+impl Snapshot {
+    pub fn from_app(app: &App) -> Snapshot {
+        let status = app.player.status;
+        // the episode in the player wins over the one on screen
+        let track = match app.player.episode.as_ref() {
+            Some(e) => {
+                let channel = app.channel.as_ref().filter(|_| e.feed == app.feed_url);
+                let art = episode_artwork(&e.item, channel);
+                let mut t = Track::new(&e.item, e.podcast.clone(), art);
+                t.length = app.player.length();
+                Some(t)
+            }
+            None => app.item.as_ref().map(|i| {
+                Track::new(
+                    i,
+                    app.channel.as_ref().map(|c| c.title().to_string()),
+                    episode_artwork(i, app.channel.as_ref()),
+                )
+            }),
+        };
+        Snapshot {
+            state: status.state,
+            position: if app.player.episode.is_some() {
+                status.position
+            } else {
+                app.position
+            },
+            speed: status.speed,
+            volume: status.volume,
+            repeat: app.player.repeat,
+            track,
+        }
+    }
+
+    fn playback_status(&self) -> &'static str {
+        match self.state {
+            player::State::Stopped => "Stopped",
+            // about to play
+            player::State::Loading | player::State::Playing => "Playing",
+            player::State::Paused => "Paused",
+        }
+    }
+
+    // Whether the position moved other than by playing along since the last snapshot,
+    // which comes in at least twice a second while playing
+    fn seeked_from(&self, before: &Snapshot) -> bool {
+        if self.track != before.track || self.state == player::State::Loading {
+            return false;
+        }
+        let played = if before.state == player::State::Playing {
+            SEEK_THRESHOLD.mul_f32(self.speed.max(1.0))
+        } else {
+            Duration::ZERO
+        };
+        self.position < before.position || self.position > before.position + played
+    }
+}
+
+// The org.mpris.MediaPlayer2 interface, about the app itself
+struct Root;
+
+#[zbus::interface(name = "org.mpris.MediaPlayer2")]
+impl Root {
+    fn raise(&self) {}
+
+    fn quit(&self) {}
+
+    #[zbus(property)]
+    fn can_quit(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn can_raise(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn has_track_list(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn identity(&self) -> &str {
+        "Podcasts"
+    }
+
+    #[zbus(property)]
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        vec![]
+    }
+
+    #[zbus(property)]
+    fn supported_mime_types(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+// The org.mpris.MediaPlayer2.Player interface. Calls are passed on to the UI, which acts
+// on them as if the matching key had been pressed.
+struct Controls {
+    responder: Sender<Response>,
+    snapshot: Snapshot,
+}
+
+impl Controls {
+    fn send(&self, r: Remote) -> fdo::Result<()> {
+        debug!("remote control {:?}", r);
+        self.responder
+            .send(Response::Remote(r))
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    fn has_track(&self) -> bool {
+        self.snapshot.track.is_some()
+    }
+}
+
+#[zbus::interface(name = "org.mpris.MediaPlayer2.Player")]
+impl Controls {
+    fn next(&self) -> fdo::Result<()> {
+        self.send(Remote::Next)
+    }
+
+    fn previous(&self) -> fdo::Result<()> {
+        self.send(Remote::Previous)
+    }
+
+    fn pause(&self) -> fdo::Result<()> {
+        self.send(Remote::Pause)
+    }
+
+    fn play_pause(&self) -> fdo::Result<()> {
+        self.send(Remote::PlayPause)
+    }
+
+    fn stop(&self) -> fdo::Result<()> {
+        self.send(Remote::Stop)
+    }
+
+    fn play(&self) -> fdo::Result<()> {
+        self.send(Remote::Play)
+    }
+
+    // offset in microseconds, backwards when negative
+    fn seek(&self, offset: i64) -> fdo::Result<()> {
+        let to = if offset < 0 {
+            self.snapshot
+                .position
+                .saturating_sub(Duration::from_micros(offset.unsigned_abs()))
+        } else {
+            self.snapshot.position + Duration::from_micros(offset as u64)
+        };
+        self.send(Remote::SeekTo(to))
+    }
+
+    // ignored unless it's for the current track, per the spec
+    fn set_position(&self, track_id: ObjectPath<'_>, position: i64) -> fdo::Result<()> {
+        match self.snapshot.track.as_ref() {
+            Some(t) if t.path() == track_id.as_str() && position >= 0 => {
+                self.send(Remote::SeekTo(Duration::from_micros(position as u64)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn open_uri(&self, _uri: &str) -> fdo::Result<()> {
+        Err(fdo::Error::NotSupported(
+            "load a feed with /load instead".to_string(),
+        ))
+    }
+
+    #[zbus(signal)]
+    async fn seeked(ctxt: &SignalContext<'_>, position: i64) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn playback_status(&self) -> &str {
+        self.snapshot.playback_status()
+    }
+
+    #[zbus(property)]
+    fn rate(&self) -> f64 {
+        self.snapshot.speed as f64
+    }
+
+    #[zbus(property)]
+    fn set_rate(&mut self, rate: f64) {
+        if !rate.is_finite() {
+            return;
+        }
+        // a rate of 0 means pause, per the spec
+        if rate <= 0.0 {
+            let _ = self.send(Remote::Pause);
+            return;
+        }
+        self.snapshot.speed = player::clamp_speed(rate as f32);
+        let _ = self.send(Remote::SetSpeed(self.snapshot.speed));
+    }
+
+    #[zbus(property)]
+    fn metadata(&self) -> HashMap<String, OwnedValue> {
+        self.snapshot
+            .track
+            .as_ref()
+            .map(Track::metadata)
+            .unwrap_or_default()
+    }
+
+    #[zbus(property)]
+    fn volume(&self) -> f64 {
+        self.snapshot.volume as f64 / 100.0
+    }
+
+    #[zbus(property)]
+    fn set_volume(&mut self, volume: f64) {
+        self.snapshot.volume = (volume.clamp(0.0, 1.0) * 100.0).round() as u8;
+        let _ = self.send(Remote::SetVolume(self.snapshot.volume));
+    }
+
+    #[zbus(property)]
+    fn loop_status(&self) -> &str {
+        match self.snapshot.repeat {
+            player::Repeat::Off => "None",
+            player::Repeat::One => "Track",
+            player::Repeat::Queue => "Playlist",
+        }
+    }
+
+    #[zbus(property)]
+    fn set_loop_status(&mut self, status: &str) -> fdo::Result<()> {
+        self.snapshot.repeat = match status {
+            "None" => player::Repeat::Off,
+            "Track" => player::Repeat::One,
+            "Playlist" => player::Repeat::Queue,
+            _ => {
+                return Err(fdo::Error::InvalidArgs(format!(
+                    "no loop status {}",
+                    status
+                )))
+            }
+        };
+        self.send(Remote::SetRepeat(self.snapshot.repeat))
+    }
+
+    // clients ask for the position when they need it, changes to it aren't signalled
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn position(&self) -> i64 {
+        self.snapshot.position.as_micros() as i64
+    }
+
+    #[zbus(property)]
+    fn minimum_rate(&self) -> f64 {
+        MIN_SPEED as f64
+    }
+
+    #[zbus(property)]
+    fn maximum_rate(&self) -> f64 {
+        MAX_SPEED as f64
+    }
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> bool {
+        self.has_track()
+    }
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> bool {
+        self.has_track()
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        self.has_track()
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        self.has_track()
+    }
+
+    #[zbus(property)]
+    fn can_seek(&self) -> bool {
+        self.has_track()
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_control(&self) -> bool {
+        true
+    }
+}
+
+// Serve the MPRIS interfaces on the session bus until the UI goes away. Meant to run on
+// its own thread, passing calls from the desktop on over `responder`.
+#[tokio::main]
+#[instrument(skip_all)]
+pub async fn run(responder: Sender<Response>, snapshots: Receiver<Snapshot>) {
+    if let Err(e) = serve(responder, snapshots).await {
+        error!("MPRIS stopped: {:?}", e);
+    }
+}
+
+async fn serve(
+    responder: Sender<Response>,
+    snapshots: Receiver<Snapshot>,
+) -> Result<(), Box<dyn Error>> {
+    let controls = Controls {
+        responder,
+        snapshot: Snapshot::default(),
+    };
+    // the pid tells apart several copies of the app, as the spec suggests
+    let name = format!(
+        "org.mpris.MediaPlayer2.podcasts.instance{}",
+        std::process::id()
+    );
+    let conn = zbus::connection::Builder::session()?
+        .name(name.as_str())?
+        .serve_at(PATH, Root)?
+        .serve_at(PATH, controls)?
+        .build()
+        .await?;
+    info!("serving MPRIS as {}", name);
+    let iface = conn.object_server().interface::<_, Controls>(PATH).await?;
+
+    while let Ok(snapshot) = snapshots.recv() {
+        let ctxt = iface.signal_context();
+        let mut controls = iface.get_mut().await;
+        let before = std::mem::replace(&mut controls.snapshot, snapshot);
+        let now = &controls.snapshot;
+        if now.state != before.state {
+            controls.playback_status_changed(ctxt).await?;
+        }
+        if now.track != before.track {
+            controls.metadata_changed(ctxt).await?;
+            if now.track.is_some() != before.track.is_some() {
+                controls.can_play_changed(ctxt).await?;
+                controls.can_pause_changed(ctxt).await?;
+                controls.can_seek_changed(ctxt).await?;
+                controls.can_go_next_changed(ctxt).await?;
+                controls.can_go_previous_changed(ctxt).await?;
+            }
+        }
+        if now.speed != before.speed {
+            controls.rate_changed(ctxt).await?;
+        }
+        if now.volume != before.volume {
+            controls.volume_changed(ctxt).await?;
+        }
+        if now.repeat != before.repeat {
+            controls.loop_status_changed(ctxt).await?;
+        }
+        if now.seeked_from(&before) {
+            Controls::seeked(ctxt, now.position.as_micros() as i64).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rss::{Enclosure, Guid, Item};
+    use std::time::Duration;
+
+    use super::{Snapshot, Track};
+    use crate::player;
+
+    fn track() -> Track {
+        Track::new(
+            &Item {
+                title: Some("Episode 1".to_string()),
+                guid: Some(Guid {
+                    value: "https://example.com/episodes/1".to_string(),
+                    ..Default::default()
+                }),
+                enclosure: Some(Enclosure {
+                    url: "https://example.com/1.mp3".to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            Some("Wine About It".to_string()),
+            None,
+        )
+    }
+
+    #[test]
+    fn describes_track() {
+        let t = track();
+        assert!(zbus::zvariant::ObjectPath::try_from(t.path()).is_ok());
+
+        let m = t.metadata();
+        assert_eq!(
+            String::try_from(m["xesam:title"].try_clone().unwrap()).unwrap(),
+            "Episode 1"
+        );
+        assert_eq!(
+            String::try_from(m["xesam:album"].try_clone().unwrap()).unwrap(),
+            "Wine About It"
+        );
+        assert!(m.contains_key("mpris:trackid"));
+        assert!(!m.contains_key("mpris:length"));
+    }
+
+    #[test]
+    fn notices_seeks() {
+        let before = Snapshot {
+            state: player::State::Playing,
+            position: Duration::from_secs(60),
+            track: Some(track()),
+            ..Default::default()
+        };
+        let at = |secs| Snapshot {
+            position: Duration::from_secs(secs),
+            ..before.clone()
+        };
+
+        assert!(!at(60).seeked_from(&before));
+        assert!(!at(61).seeked_from(&before));
+        assert!(at(90).seeked_from(&before));
+        assert!(at(45).seeked_from(&before));
+    }
+}
@@ -0,0 +1,205 @@
+use serde::Serialize;
+use std::{error::Error, fs, path::PathBuf, time::Duration};
+
+use crate::{feed::duration, player, ui::fmt_duration, App};
+
+// What the player is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum State {
+    // no episode open
+    Idle,
+    // an episode is open but not playing
+    Stopped,
+    // the episode's file is downloading
+    Loading,
+    Playing,
+    Paused,
+}
+
+// NowPlaying is the playback state exported for status bars (tmux, polybar, waybar...).
+// It is written as `now-playing.json` and as a one line `now-playing.txt` in
+// `$XDG_RUNTIME_DIR/podcasts` whenever it changes. The JSON fields are a stable format:
+// only ever add to them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NowPlaying {
+    pub state: State,
+    pub episode: Option<String>,
+    pub podcast: Option<String>,
+    // whole seconds, so that the files don't change more than once a second
+    pub elapsed: u64,
+    pub duration: Option<u64>,
+}
+
+impl NowPlaying {
+    pub fn from_app(app: &App) -> NowPlaying {
+        // another program doesn't say how far along it is
+        if let Some(e) = app.player.external.as_ref() {
+            return NowPlaying {
+                state: State::Playing,
+                episode: e.episode.item.title().map(str::to_string),
+                podcast: e.episode.podcast.clone(),
+                elapsed: 0,
+                duration: duration(&e.episode.item).map(|d| d.as_secs()),
+            };
+        }
+        // the episode in the player wins over the one on screen
+        if let Some(e) = app.player.episode.as_ref() {
+            let status = app.player.status;
+            return NowPlaying {
+                state: match status.state {
+                    player::State::Stopped => State::Stopped,
+                    player::State::Loading => State::Loading,
+                    player::State::Playing => State::Playing,
+                    player::State::Paused => State::Paused,
+                },
+                episode: e.item.title().map(str::to_string),
+                podcast: e.podcast.clone(),
+                elapsed: status.position.as_secs(),
+                duration: app.player.length().map(|d| d.as_secs()),
+            };
+        }
+        NowPlaying {
+            state: if app.item.is_some() {
+                State::Stopped
+            } else {
+                State::Idle
+            },
+            episode: app
+                .item
+                .as_ref()
+                .and_then(|i| i.title().map(str::to_string)),
+            podcast: app.channel.as_ref().map(|c| c.title().to_string()),
+            elapsed: app.position.as_secs(),
+            duration: app.item.as_ref().and_then(duration).map(|d| d.as_secs()),
+        }
+    }
+
+    // One line summary, e.g. "Episode 1 — Wine About It 12:34/45:00"
+    pub fn line(&self) -> String {
+        let Some(episode) = self.episode.as_deref() else {
+            return String::new();
+        };
+        let mut line = match self.podcast.as_deref() {
+            Some(p) => format!("{} — {}", episode, p),
+            None => episode.to_string(),
+        };
+        line.push(' ');
+        line.push_str(fmt_duration(Duration::from_secs(self.elapsed)).as_str());
+        if let Some(d) = self.duration {
+            line.push('/');
+            line.push_str(fmt_duration(Duration::from_secs(d)).as_str());
+        }
+        line
+    }
+
+    pub fn write(&self) -> Result<(), Box<dyn Error>> {
+        let d = dir().ok_or("no runtime or cache directory")?;
+        fs::create_dir_all(&d)?;
+        write_atomic(d.join("now-playing.json"), serde_json::to_string(self)?)?;
+        write_atomic(d.join("now-playing.txt"), format!("{}\n", self.line()))?;
+        Ok(())
+    }
+}
+
+// Remove the exported files, so status bars don't show a stale episode after quitting
+pub fn clear() -> Result<(), Box<dyn Error>> {
+    if let Some(d) = dir() {
+        for name in ["now-playing.json", "now-playing.txt"] {
+            let p = d.join(name);
+            if p.exists() {
+                fs::remove_file(p)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn dir() -> Option<PathBuf> {
+    dirs::runtime_dir()
+        .or_else(dirs::cache_dir)
+        .map(|d| d.join("podcasts"))
+}
+
+// Status bars poll these files, write them whole so a half written one is never read
+fn write_atomic(p: PathBuf, contents: String) -> Result<(), Box<dyn Error>> {
+    let tmp = p.with_extension("tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(tmp, p)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rss::{extension::itunes::ITunesItemExtension, Channel, Item};
+    use std::time::Duration;
+
+    use super::{NowPlaying, State};
+    use crate::{player, App};
+
+    #[test]
+    fn idle() {
+        let np = NowPlaying::from_app(&App::default());
+        assert_eq!(np.state, State::Idle);
+        assert_eq!(np.line(), "");
+        assert_eq!(
+            serde_json::to_string(&np).unwrap(),
+            r#"{"state":"idle","episode":null,"podcast":null,"elapsed":0,"duration":null}"#
+        );
+    }
+
+    #[test]
+    fn open_episode() {
+        let mut ext = ITunesItemExtension::default();
+        ext.set_duration("45:00".to_string());
+        let app = App {
+            channel: Some(Channel {
+                title: "Wine About It".to_string(),
+                ..Default::default()
+            }),
+            item: Some(Item {
+                title: Some("Episode 1".to_string()),
+                itunes_ext: Some(ext),
+                ..Default::default()
+            }),
+            position: Duration::from_millis(754_900),
+            ..Default::default()
+        };
+
+        let np = NowPlaying::from_app(&app);
+        assert_eq!(np.state, State::Stopped);
+        assert_eq!(np.elapsed, 754);
+        assert_eq!(np.duration, Some(2700));
+        assert_eq!(np.line(), "Episode 1 — Wine About It 12:34/45:00");
+    }
+
+    #[test]
+    fn playing_episode() {
+        let mut app = App {
+            item: Some(Item {
+                title: Some("Open".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        app.player.episode = Some(player::Episode {
+            item: Item {
+                title: Some("Playing".to_string()),
+                ..Default::default()
+            },
+            podcast: Some("Wine About It".to_string()),
+            feed: None,
+            artwork: None,
+        });
+        app.player.status = player::Status {
+            state: player::State::Playing,
+            position: Duration::from_secs(61),
+            duration: Some(Duration::from_secs(120)),
+            ..Default::default()
+        };
+
+        let np = NowPlaying::from_app(&app);
+        assert_eq!(np.state, State::Playing);
+        assert_eq!(np.line(), "Playing — Wine About It 1:01/2:00");
+    }
+}
@@ -0,0 +1,115 @@
+use std::{error::Error, fs::OpenOptions, io::Write, path::Path};
+use url::Url;
+
+use crate::{feed::private, subscriptions::Subscriptions};
+
+// How many of the podcasts are private feeds, with an access token in their URL
+pub fn private_feeds(subscriptions: &Subscriptions) -> usize {
+    subscriptions
+        .iter()
+        .filter_map(|s| Url::parse(s.url.as_str()).ok())
+        .filter(|u| private::detect(u).is_some())
+        .count()
+}
+
+// Write the library as an OPML 2.0 subscription list, which other podcast apps can import.
+// With `redact`, the tokens of private feeds are masked (see `feed::private::redact`).
+pub fn export(subscriptions: &Subscriptions, redact: bool) -> String {
+    let mut s = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <opml version=\"2.0\">\n  \
+         <head>\n    <title>Podcast subscriptions</title>\n  </head>\n  \
+         <body>\n",
+    );
+    for sub in subscriptions.iter() {
+        let url = match Url::parse(sub.url.as_str()) {
+            Ok(u) if redact => private::redact(&u),
+            _ => sub.url.clone(),
+        };
+        s.push_str(&format!(
+            "    <outline type=\"rss\" text=\"{title}\" title=\"{title}\" xmlUrl=\"{url}\"/>\n",
+            title = escape(sub.title.as_str()),
+            url = escape(url.as_str())
+        ));
+    }
+    s.push_str("  </body>\n</opml>\n");
+    s
+}
+
+// Write the library to the file as OPML. Unless redacted, private feed URLs keep their
+// tokens, so that the feeds still work wherever they are imported, and the file is only
+// readable by the user.
+pub fn write(
+    path: &Path,
+    subscriptions: &Subscriptions,
+    redact: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(path)?
+        .write_all(export(subscriptions, redact).as_bytes())?;
+    Ok(())
+}
+
+// Escape text for an XML attribute
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // not allowed in XML at all
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::subscriptions::Subscriptions;
+
+    use super::{export, private_feeds};
+
+    #[test]
+    fn exports_subscriptions() {
+        let mut s = Subscriptions::default();
+        s.add("https://example.com/feed?a=1&b=2", "Wine & \"Cheese\"");
+        s.add("https://example.com/other", "Other");
+
+        assert_eq!(
+            export(&s, true),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <opml version=\"2.0\">\n  \
+             <head>\n    <title>Podcast subscriptions</title>\n  </head>\n  \
+             <body>\n    \
+             <outline type=\"rss\" text=\"Wine &amp; &quot;Cheese&quot;\" \
+             title=\"Wine &amp; &quot;Cheese&quot;\" \
+             xmlUrl=\"https://example.com/feed?a=1&amp;b=2\"/>\n    \
+             <outline type=\"rss\" text=\"Other\" title=\"Other\" \
+             xmlUrl=\"https://example.com/other\"/>\n  \
+             </body>\n</opml>\n"
+        );
+    }
+
+    #[test]
+    fn redacts_private_feeds() {
+        let mut s = Subscriptions::default();
+        s.add("https://www.patreon.com/rss/show?auth=abc123", "Members");
+        s.add("https://example.com/other", "Other");
+        assert_eq!(private_feeds(&s), 1);
+
+        assert!(export(&s, false).contains("auth=abc123"));
+        let redacted = export(&s, true);
+        assert!(!redacted.contains("abc123"));
+        assert!(redacted.contains("xmlUrl=\"https://www.patreon.com/rss/show?auth=***\""));
+        assert!(redacted.contains("https://example.com/other"));
+    }
+}
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    time::Duration,
+};
+
+use crate::store::db::Db;
+
+// Share of an episode that has to be played for it to count as played, the rest is often
+// outros and ads
+const PLAYED_AT: f64 = 0.9;
+
+// Whether playback got far enough into an episode of this length to count it as played
+pub fn listened(position: Duration, length: Duration) -> bool {
+    !length.is_zero() && position.as_secs_f64() >= length.as_secs_f64() * PLAYED_AT
+}
+
+// Played keeps track of which episodes have been listened to, by feed URL and episode id
+// (see `feed::episode_id`). Stored in the database, a row per episode, and only the rows
+// that changed are saved.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct Played {
+    #[serde(default)]
+    feeds: BTreeMap<String, BTreeSet<String>>,
+    // episodes marked as played (true) or unplayed (false) since the last save
+    #[serde(skip)]
+    changes: BTreeMap<(String, String), bool>,
+}
+
+impl PartialEq for Played {
+    fn eq(&self, other: &Self) -> bool {
+        self.feeds == other.feeds
+    }
+}
+
+impl FromIterator<(String, String)> for Played {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        let mut feeds = BTreeMap::<String, BTreeSet<String>>::new();
+        for (feed, episode) in iter {
+            feeds.entry(feed).or_default().insert(episode);
+        }
+        Played {
+            feeds,
+            ..Default::default()
+        }
+    }
+}
+
+impl Played {
+    pub fn is_played(&self, feed: &str, episode: &str) -> bool {
+        self.feeds
+            .get(feed)
+            .map(|e| e.contains(episode))
+            .unwrap_or_default()
+    }
+
+    // Mark an episode as played or not, returns false if it already was
+    pub fn set(&mut self, feed: &str, episode: &str, played: bool) -> bool {
+        if played {
+            return self.mark_all(feed, [episode]) > 0;
+        }
+        let Some(e) = self.feeds.get_mut(feed) else {
+            return false;
+        };
+        let removed = e.remove(episode);
+        if e.is_empty() {
+            self.feeds.remove(feed);
+        }
+        if removed {
+            self.changes
+                .insert((feed.to_string(), episode.to_string()), false);
+        }
+        removed
+    }
+
+    // Mark every given episode of the feed as played, and return how many weren't already
+    pub fn mark_all<'a, I>(&mut self, feed: &str, episodes: I) -> usize
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let played = self.feeds.entry(feed.to_string()).or_default();
+        let mut n = 0;
+        for e in episodes {
+            if played.insert(e.to_string()) {
+                self.changes.insert((feed.to_string(), e.to_string()), true);
+                n += 1;
+            }
+        }
+        n
+    }
+
+    // Every played episode, as (feed, episode id)
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.feeds
+            .iter()
+            .flat_map(|(f, e)| e.iter().map(move |e| (f.as_str(), e.as_str())))
+    }
+
+    pub fn load(db: &Db) -> Result<Played, Box<dyn Error>> {
+        db.load_played()
+    }
+
+    // Save the episodes marked or unmarked since the last save
+    pub fn save(&mut self, db: &mut Db) -> Result<(), Box<dyn Error>> {
+        db.save_played(
+            self.changes
+                .iter()
+                .map(|((f, e), played)| (f.as_str(), e.as_str(), *played)),
+        )?;
+        self.changes.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{listened, Played};
+
+    const FEED: &str = "https://example.com/feed";
+
+    #[test]
+    fn is_played_per_feed() {
+        let mut p = Played::default();
+        assert!(!p.is_played(FEED, "1"));
+
+        p.mark_all(FEED, ["1"]);
+        assert!(p.is_played(FEED, "1"));
+        assert!(!p.is_played("https://example.com/other", "1"));
+    }
+
+    #[test]
+    fn mark_all_counts_new() {
+        let mut p = Played::default();
+        p.mark_all(FEED, ["2"]);
+
+        assert_eq!(p.mark_all(FEED, ["1", "2", "3"]), 2);
+        assert!(p.is_played(FEED, "1") && p.is_played(FEED, "2") && p.is_played(FEED, "3"));
+        assert_eq!(p.mark_all(FEED, ["1", "2", "3"]), 0);
+    }
+
+    #[test]
+    fn set_and_unset() {
+        let mut p = Played::default();
+        assert!(p.set(FEED, "1", true));
+        assert!(!p.set(FEED, "1", true));
+        assert!(p.is_played(FEED, "1"));
+
+        assert!(p.set(FEED, "1", false));
+        assert!(!p.set(FEED, "1", false));
+        assert!(!p.is_played(FEED, "1"));
+        assert_eq!(p, Played::default());
+    }
+
+    #[test]
+    fn keeps_changes() {
+        let mut p = [(FEED.to_string(), "1".to_string())]
+            .into_iter()
+            .collect::<Played>();
+        assert!(p.changes.is_empty());
+
+        p.set(FEED, "1", false);
+        p.mark_all(FEED, ["1", "2"]);
+        p.set(FEED, "3", false);
+        let changes = p
+            .changes
+            .iter()
+            .map(|((_, e), played)| (e.as_str(), *played))
+            .collect::<Vec<(&str, bool)>>();
+        assert_eq!(changes, [("1", true), ("2", true)]);
+    }
+
+    #[test]
+    fn listened_near_the_end() {
+        let length = Duration::from_secs(100);
+        assert!(!listened(Duration::from_secs(89), length));
+        assert!(listened(Duration::from_secs(90), length));
+        assert!(!listened(Duration::from_secs(10), Duration::ZERO));
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut p = Played::default();
+        p.mark_all(FEED, ["1", "2"]);
+
+        let serialized = toml::to_string(&p).unwrap();
+        assert_eq!(toml::from_str::<Played>(serialized.as_str()).unwrap(), p);
+    }
+}
@@ -0,0 +1,758 @@
+pub mod stream;
+mod tracked;
+
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+use rss::Item;
+use std::{
+    error::Error,
+    io,
+    process::{Child, ExitStatus},
+    sync::{
+        mpsc::{Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tracing::{debug, error, info, instrument};
+use url::Url;
+
+use crate::{
+    chapters::Chapters,
+    feed::{duration, private::redact},
+    media,
+    message::Response,
+    ui::fmt_duration,
+};
+
+use self::{
+    stream::Download,
+    tracked::{Progress, Tracked},
+};
+
+// How often the position is reported while playing
+const TICK: Duration = Duration::from_millis(500);
+
+// Levels of the last few ticks kept for the play bar's meter, the newest last
+pub const LEVELS: usize = 16;
+
+// Quietest a level goes down to on the meter, in dB
+const LEVEL_FLOOR: f32 = -48.0;
+
+// How often the volume steps down while fading out of an episode
+const FADE_STEP: Duration = Duration::from_millis(50);
+
+// How long before the end of an episode the next one starts downloading, so that it can
+// start right away
+pub const PRELOAD: Duration = Duration::from_secs(60);
+
+// Seconds skipped by the seek keys
+pub const SEEK_BACK: i64 = 15;
+pub const SEEK_FORWARD: i64 = 30;
+
+// Playback speeds that can be set, and the step of the speed keys
+pub const MIN_SPEED: f32 = 0.5;
+pub const MAX_SPEED: f32 = 3.0;
+pub const SPEED_STEP: f32 = 0.1;
+
+// Step of the volume keys, in percent
+pub const VOLUME_STEP: u8 = 5;
+
+// Control is something for the player to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum Control {
+    // play the file at the URL, starting at the position. What's playing fades out over
+    // the second duration while the file fades in, or stops right away when it's zero.
+    Play(Url, Duration, Duration),
+    // start downloading the file at the URL, to be played next
+    Preload(Url),
+    // pause if playing, or carry on if paused
+    TogglePause,
+    // go to the position in the episode
+    Seek(Duration),
+    // play at this many times the normal speed
+    SetSpeed(f32),
+    // in percent, up to 100
+    SetVolume(u8),
+    // skip long silences, or stop skipping them
+    SkipSilence(bool),
+    Stop,
+}
+
+// What the player is doing
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    #[default]
+    Stopped,
+    // the file is starting to download
+    Loading,
+    Playing,
+    Paused,
+}
+
+impl State {
+    pub fn name(&self) -> &'static str {
+        match self {
+            State::Stopped => "Stopped",
+            State::Loading => "Loading",
+            State::Playing => "Playing",
+            State::Paused => "Paused",
+        }
+    }
+}
+
+// Status is reported to the UI whenever it changes, and regularly while playing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Status {
+    pub state: State,
+    pub position: Duration,
+    // from the file, when its format says
+    pub duration: Option<Duration>,
+    pub speed: f32,
+    // in percent
+    pub volume: u8,
+    pub skip_silence: bool,
+    // listening time saved by skipping silences this session
+    pub saved: Duration,
+    // how much of the file has downloaded in percent, while it still downloads
+    pub buffered: Option<u8>,
+    // playback caught up with the download, and waits for more of it
+    pub buffering: bool,
+    // the download broke off, and this is the try to pick it up again under way
+    pub retrying: Option<u32>,
+    // how loud it was playing each of the last ticks, in percent of full scale in dB
+    pub levels: [u8; LEVELS],
+}
+
+impl Default for Status {
+    fn default() -> Self {
+        Status {
+            state: State::default(),
+            position: Duration::ZERO,
+            duration: None,
+            speed: 1.0,
+            volume: 100,
+            skip_silence: false,
+            saved: Duration::ZERO,
+            buffered: None,
+            buffering: false,
+            retrying: None,
+            levels: [0; LEVELS],
+        }
+    }
+}
+
+impl Status {
+    // e.g. "Playing", or "Buffering" while waiting for the download
+    pub fn name(&self) -> &'static str {
+        match self.state {
+            State::Playing if self.retrying.is_some() => "Reconnecting",
+            State::Playing if self.buffering => "Buffering",
+            state => state.name(),
+        }
+    }
+
+    // Add the loudest sample of the last tick, from 0 to 1, to the levels
+    pub fn push_level(&mut self, peak: f32) {
+        let level = match peak {
+            p if p > 0.0 => ((20.0 * p.log10() - LEVEL_FLOOR) / -LEVEL_FLOOR).clamp(0.0, 1.0),
+            _ => 0.0,
+        };
+        self.levels.rotate_left(1);
+        self.levels[LEVELS - 1] = (level * 100.0).round() as u8;
+    }
+
+    // Nothing playing, with the same settings
+    pub fn stopped(&self) -> Status {
+        Status {
+            speed: self.speed,
+            volume: self.volume,
+            skip_silence: self.skip_silence,
+            saved: self.saved,
+            ..Default::default()
+        }
+    }
+}
+
+// Keep a speed within what can be set, rounded to the step of the speed keys. A speed
+// that isn't a number is taken as the normal one.
+pub fn clamp_speed(speed: f32) -> f32 {
+    if !speed.is_finite() {
+        return 1.0;
+    }
+    let steps = (1.0 / SPEED_STEP).round();
+    ((speed * steps).round() / steps).clamp(MIN_SPEED, MAX_SPEED)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Status(Status),
+    // chapters found in the episode's file
+    Chapters(Chapters),
+    // the episode played to the end
+    Finished,
+    Failed(String),
+}
+
+// When the sleep timer stops playback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sleep {
+    At(Instant),
+    EndOfEpisode,
+}
+
+impl Sleep {
+    // e.g. "sleep in 29:59" or "sleep at end of episode"
+    pub fn describe(&self) -> String {
+        match self {
+            Sleep::At(t) => format!(
+                "sleep in {}",
+                fmt_duration(t.saturating_duration_since(Instant::now()))
+            ),
+            Sleep::EndOfEpisode => "sleep at end of episode".to_string(),
+        }
+    }
+}
+
+// What plays once an episode finishes
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    // the next episode in the queue
+    #[default]
+    Off,
+    // the same episode again
+    One,
+    // the next episode in the queue, with the finished one going to the back of it
+    Queue,
+}
+
+impl Repeat {
+    pub fn next(&self) -> Repeat {
+        match self {
+            Repeat::Off => Repeat::One,
+            Repeat::One => Repeat::Queue,
+            Repeat::Queue => Repeat::Off,
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Repeat::Off => "repeat off",
+            Repeat::One => "repeat episode",
+            Repeat::Queue => "repeat queue",
+        }
+    }
+}
+
+// Segment is a part of the episode played over and over, from point A to point B
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub start: Duration,
+    // None until point B is marked
+    pub end: Option<Duration>,
+}
+
+impl Segment {
+    // e.g. "loop 1:00-1:30" or "loop from 1:00"
+    pub fn describe(&self) -> String {
+        match self.end {
+            Some(end) => format!("loop {}-{}", fmt_duration(self.start), fmt_duration(end)),
+            None => format!("loop from {}", fmt_duration(self.start)),
+        }
+    }
+}
+
+// Episode is what's in the player
+#[derive(Debug, Clone, PartialEq)]
+pub struct Episode {
+    pub item: Item,
+    pub podcast: Option<String>,
+    pub feed: Option<Url>,
+    // the podcast's cover art, shown for episodes without their own
+    pub artwork: Option<String>,
+}
+
+impl Episode {
+    pub fn title(&self) -> &str {
+        self.item.title().unwrap_or("Title missing!")
+    }
+}
+
+// External is an episode handed over to another program to play, like mpv
+#[derive(Debug)]
+pub struct External {
+    pub episode: Episode,
+    // e.g. `mpv`
+    pub program: String,
+    child: Child,
+}
+
+impl External {
+    pub fn spawn(handler: &str, u: &Url, episode: Episode) -> io::Result<External> {
+        Ok(External {
+            episode,
+            program: media::program(handler).to_string(),
+            child: media::spawn(handler, u.as_str())?,
+        })
+    }
+
+    // How the program exited, once it has
+    pub fn exited(&mut self) -> io::Result<Option<ExitStatus>> {
+        self.child.try_wait()
+    }
+
+    pub fn stop(mut self) {
+        if let Err(e) = self.child.kill().and_then(|_| self.child.wait()) {
+            error!("failed to stop {}: {:?}", self.program, e);
+        }
+    }
+}
+
+// Player is the UI's handle on the player thread, along with what it last reported
+#[derive(Default, Debug)]
+pub struct Player {
+    controls: Option<Sender<Control>>,
+    pub episode: Option<Episode>,
+    pub status: Status,
+    pub sleep: Option<Sleep>,
+    pub repeat: Repeat,
+    // stop once the episode playing finishes, instead of moving on to the next one
+    pub stop_after: bool,
+    // fade into the next episode played over this long, rather than cutting to it
+    pub crossfade: Option<Duration>,
+    // the file of the next episode, downloading ahead of time
+    pub preloaded: Option<Url>,
+    // looped in the episode playing
+    pub segment: Option<Segment>,
+    // of the episode in the player, from its feed or its file
+    pub chapters: Option<Chapters>,
+    // playing in another program instead of the app
+    pub external: Option<External>,
+    // the file of the episode playing
+    pub url: Option<Url>,
+    // the episode was opened again where its download gave up, which is only tried once
+    pub reopened: bool,
+}
+
+impl Player {
+    pub fn new(controls: Sender<Control>) -> Player {
+        Player {
+            controls: Some(controls),
+            ..Default::default()
+        }
+    }
+
+    pub fn play(&mut self, episode: Episode, u: Url, start: Duration) {
+        let fade = self.crossfade.take().unwrap_or_default();
+        self.send(Control::Play(u.clone(), start, fade));
+        self.url = Some(u);
+        self.reopened = false;
+        self.preloaded = None;
+        if !self.has(&episode.item) {
+            self.chapters = None;
+            self.segment = None;
+        }
+        self.episode = Some(episode);
+    }
+
+    pub fn send(&self, c: Control) {
+        let Some(controls) = self.controls.as_ref() else {
+            error!("no player to send {:?} to", c);
+            return;
+        };
+        if let Err(e) = controls.send(c) {
+            error!("failed to send message {:?}", e);
+        }
+    }
+
+    // Move the position by `by` seconds, backwards when negative. Returns where it ended
+    // up, which is never past the start or the end of the episode.
+    pub fn seek_by(&mut self, by: i64) -> Duration {
+        let offset = Duration::from_secs(by.unsigned_abs());
+        let to = if by < 0 {
+            self.status.position.saturating_sub(offset)
+        } else {
+            self.status.position + offset
+        };
+        self.seek_to(to)
+    }
+
+    pub fn seek_to(&mut self, mut to: Duration) -> Duration {
+        if let Some(d) = self.length() {
+            to = to.min(d);
+        }
+        // a stopped episode starts from there when played again
+        if self.status.state == State::Stopped {
+            self.status.position = to;
+        } else {
+            self.send(Control::Seek(to));
+        }
+        to
+    }
+
+    // Go to the next chapter, or back through the chapters. Returns the chapter's title.
+    pub fn jump_chapter(&mut self, forward: bool) -> Option<String> {
+        let chapters = self.chapters.as_ref()?;
+        let chapter = if forward {
+            chapters.next(self.status.position)
+        } else {
+            chapters.previous(self.status.position)
+        }?;
+        let title = chapter.title.clone().unwrap_or_default();
+        self.seek_to(Duration::from_secs_f64(chapter.start_time));
+        Some(title)
+    }
+
+    // How long the episode is, from the file or else the feed
+    pub fn length(&self) -> Option<Duration> {
+        self.status
+            .duration
+            .or_else(|| self.episode.as_ref().and_then(|e| duration(&e.item)))
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status.state != State::Stopped
+    }
+
+    // Whether the item is the episode in the player
+    pub fn has(&self, item: &Item) -> bool {
+        self.episode.as_ref().map(|e| &e.item) == Some(item)
+    }
+}
+
+// Audio output, opened when something is first played so that the app works without a
+// sound device until then
+struct Output {
+    // the sink stops making sound once the stream is dropped
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+    sink: Sink,
+}
+
+impl Output {
+    fn open() -> Result<Output, Box<dyn Error>> {
+        let (stream, handle) = OutputStream::try_default()?;
+        let sink = Sink::try_new(&handle)?;
+        Ok(Output {
+            _stream: stream,
+            handle,
+            sink,
+        })
+    }
+
+    // Play on a new sink from now on, returning the old one. Both play at the same time
+    // until the old one is dropped.
+    fn new_sink(&mut self) -> Result<Sink, Box<dyn Error>> {
+        let sink = Sink::try_new(&self.handle)?;
+        sink.set_volume(self.sink.volume());
+        Ok(std::mem::replace(&mut self.sink, sink))
+    }
+}
+
+// An episode fading out while the next one starts
+struct Fading {
+    sink: Sink,
+    // kept until it's done, so that the end of the file can still be read
+    _download: Option<Download>,
+    started: Instant,
+    length: Duration,
+}
+
+impl Fading {
+    // Turn the volume down for how far along the fade is, false once it's over
+    fn step(&self, volume: u8) -> bool {
+        let done = self.started.elapsed().as_secs_f32() / self.length.as_secs_f32();
+        if done >= 1.0 || self.sink.empty() {
+            return false;
+        }
+        self.sink.set_volume(volume as f32 / 100.0 * (1.0 - done));
+        true
+    }
+}
+
+// Run the player until the UI goes away. Meant to run on its own thread, reporting back
+// over `responder`.
+#[tokio::main]
+#[instrument(skip_all)]
+pub async fn run(responder: Sender<Response>, controls: Receiver<Control>) {
+    let send = |e: Event| responder.send(Response::Player(e)).is_ok();
+    let mut output: Option<Output> = None;
+    let mut download: Option<Download> = None;
+    let mut preloaded: Option<(Url, Download)> = None;
+    let mut fading: Option<Fading> = None;
+    let mut progress = Arc::new(Progress::default());
+    let mut status = Status::default();
+    let mut reported = status;
+    loop {
+        let timeout = if fading.is_some() { FADE_STEP } else { TICK };
+        match controls.recv_timeout(timeout) {
+            Ok(Control::Play(u, start, fade)) => {
+                info!("playing {}", redact(&u));
+                fading = None;
+                if let Some(o) = output
+                    .as_mut()
+                    .filter(|_| !fade.is_zero() && status.state == State::Playing)
+                {
+                    match o.new_sink() {
+                        Ok(sink) => {
+                            fading = Some(Fading {
+                                sink,
+                                _download: download.take(),
+                                started: Instant::now(),
+                                length: fade,
+                            })
+                        }
+                        Err(e) => error!("failed to fade out: {:?}", e),
+                    }
+                }
+                if fading.is_none() {
+                    // stop the download first, the sink waits for a read of it to finish
+                    download = None;
+                    if let Some(o) = output.as_ref() {
+                        o.sink.clear();
+                    }
+                }
+                // the episode fading out keeps counting on its own
+                progress = Arc::new(progress.carry_on());
+                let preload = preloaded.take().filter(|(p, _)| *p == u).map(|(_, d)| d);
+                status = Status {
+                    state: State::Loading,
+                    position: start,
+                    ..status.stopped()
+                };
+                send(Event::Status(status));
+                reported = status;
+                let fade_in = if fading.is_some() {
+                    fade
+                } else {
+                    Duration::ZERO
+                };
+                match play(
+                    &mut output,
+                    download.insert(preload.unwrap_or_else(|| Download::start(&u))),
+                    &progress,
+                    start,
+                    fade_in,
+                    status.volume,
+                ) {
+                    Ok((duration, chapters)) => {
+                        // from when the next episode actually starts
+                        if let Some(f) = fading.as_mut() {
+                            f.started = Instant::now();
+                        }
+                        status.state = State::Playing;
+                        status.duration = duration;
+                        if let Some(c) = chapters {
+                            send(Event::Chapters(c));
+                        }
+                    }
+                    Err(e) => {
+                        error!("failed to play {}: {:?}", redact(&u), e);
+                        download = None;
+                        fading = None;
+                        status = status.stopped();
+                        send(Event::Failed(e.to_string()));
+                    }
+                }
+            }
+            Ok(Control::TogglePause) => match (output.as_ref(), status.state) {
+                (Some(o), State::Playing) => {
+                    o.sink.pause();
+                    status.state = State::Paused;
+                }
+                (Some(o), State::Paused) => {
+                    o.sink.play();
+                    status.state = State::Playing;
+                }
+                _ => debug!("nothing to pause"),
+            },
+            Ok(Control::Seek(to)) => match status.state {
+                State::Playing | State::Paused => {
+                    progress.seek(to);
+                    status.position = to;
+                }
+                _ => debug!("nothing to seek in"),
+            },
+            Ok(Control::SetSpeed(speed)) => {
+                let speed = clamp_speed(speed);
+                debug!("playing at {}x", speed);
+                progress.set_speed(speed);
+                status.speed = speed;
+            }
+            Ok(Control::SetVolume(volume)) => {
+                status.volume = volume.min(100);
+                if let Some(o) = output.as_ref() {
+                    o.sink.set_volume(status.volume as f32 / 100.0);
+                }
+            }
+            Ok(Control::SkipSilence(skip)) => {
+                debug!("skipping silences: {}", skip);
+                progress.set_skip_silence(skip);
+                status.skip_silence = skip;
+            }
+            Ok(Control::Preload(u)) => {
+                if preloaded.as_ref().is_none_or(|(p, _)| *p != u) {
+                    debug!("preloading {}", redact(&u));
+                    preloaded = Some((u.clone(), Download::start(&u)));
+                }
+            }
+            Ok(Control::Stop) => {
+                debug!("stopping");
+                fading = None;
+                download = None;
+                if let Some(o) = output.as_ref() {
+                    o.sink.clear();
+                }
+                status.state = State::Stopped;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        if fading.as_ref().is_some_and(|f| !f.step(status.volume)) {
+            fading = None;
+        }
+
+        if let Some(o) = output.as_ref().filter(|_| status.state == State::Playing) {
+            if o.sink.empty() {
+                status = status.stopped();
+                reported = status;
+                // the file may have stopped early because it couldn't all be downloaded
+                let event = match download.take().and_then(|d| d.error()) {
+                    Some(e) => Event::Failed(e),
+                    None => Event::Finished,
+                };
+                if !send(event) {
+                    return;
+                }
+                continue;
+            }
+            status.position = progress.position();
+            status.saved = progress.saved();
+            status.push_level(progress.take_peak());
+        }
+        if let Some(d) = download.as_ref() {
+            status.buffered = d.buffered();
+            status.buffering = d.buffering();
+            status.retrying = d.retrying();
+        }
+        if status != reported {
+            if !send(Event::Status(status)) {
+                return;
+            }
+            reported = status;
+        }
+    }
+}
+
+// Play the file as it downloads, returns how long it is if the format says and any
+// chapters in its tags. Waits for enough of the file to tell what format it is in.
+fn play(
+    output: &mut Option<Output>,
+    d: &Download,
+    progress: &Arc<Progress>,
+    start: Duration,
+    fade_in: Duration,
+    volume: u8,
+) -> Result<(Option<Duration>, Option<Chapters>), Box<dyn Error>> {
+    if output.is_none() {
+        let o = Output::open()?;
+        o.sink.set_volume(volume as f32 / 100.0);
+        *output = Some(o);
+    }
+    let source = Decoder::new(d.stream())?;
+    let duration = source.total_duration();
+    // the decoder read past the tags at the start of the file to find the audio
+    let chapters = d.tag().and_then(|t| Chapters::from_id3(&t));
+
+    let Some(o) = output.as_ref() else {
+        return Err("no audio output".into());
+    };
+    let tracked = Tracked::new(source, progress.clone());
+    if fade_in.is_zero() {
+        o.sink.append(tracked);
+    } else {
+        o.sink.append(tracked.fade_in(fade_in));
+    }
+    if !start.is_zero() {
+        progress.seek(start);
+    }
+    o.sink.play();
+    Ok((duration, chapters))
+}
+
+#[cfg(test)]
+mod tests {
+    use rss::{extension::itunes::ITunesItemExtension, Item};
+    use std::time::Duration;
+
+    use super::{clamp_speed, Episode, Player, Segment, State, Status, LEVELS};
+
+    #[test]
+    fn keeps_levels() {
+        let mut s = Status::default();
+        s.push_level(1.0);
+        s.push_level(0.0);
+        // -24 dB
+        s.push_level(0.0631);
+        assert_eq!(s.levels[LEVELS - 3..], [100, 0, 50]);
+        s.push_level(0.0001);
+        assert_eq!(s.levels[LEVELS - 1], 0);
+        assert_eq!(s.stopped().levels, [0; LEVELS]);
+    }
+
+    #[test]
+    fn names_state() {
+        let mut s = Status {
+            state: State::Playing,
+            buffering: true,
+            ..Default::default()
+        };
+        assert_eq!(s.name(), "Buffering");
+        s.retrying = Some(2);
+        assert_eq!(s.name(), "Reconnecting");
+        assert_eq!(s.stopped().retrying, None);
+    }
+
+    #[test]
+    fn seeks_within_episode() {
+        let mut ext = ITunesItemExtension::default();
+        ext.set_duration("1:00".to_string());
+        let mut p = Player {
+            episode: Some(Episode {
+                item: Item {
+                    itunes_ext: Some(ext),
+                    ..Default::default()
+                },
+                podcast: None,
+                feed: None,
+                artwork: None,
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(p.seek_by(-15), Duration::ZERO);
+        assert_eq!(p.seek_by(30), Duration::from_secs(30));
+        assert_eq!(p.seek_by(-15), Duration::from_secs(15));
+        assert_eq!(p.seek_by(90), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn describes_segment() {
+        let mut s = Segment {
+            start: Duration::from_secs(60),
+            end: None,
+        };
+        assert_eq!(s.describe(), "loop from 1:00");
+        s.end = Some(Duration::from_secs(90));
+        assert_eq!(s.describe(), "loop 1:00-1:30");
+    }
+
+    #[test]
+    fn clamps_speed() {
+        assert_eq!(clamp_speed(1.5), 1.5);
+        assert_eq!(clamp_speed(1.0 + 5.0 * 0.1), 1.5);
+        assert_eq!(clamp_speed(0.2), 0.5);
+        assert_eq!(clamp_speed(4.0), 3.0);
+        assert_eq!(clamp_speed(f32::NAN), 1.0);
+        assert_eq!(clamp_speed(f32::INFINITY), 1.0);
+    }
+}
@@ -0,0 +1,394 @@
+use reqwest::{header, StatusCode};
+use std::{
+    error::Error,
+    io::{self, Read, Seek, SeekFrom},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    time::Duration,
+};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::{
+    config::PlaybackConfig,
+    feed::{is_client_error, private::redact, wait_turn},
+};
+
+// Once playback caught up with the download, it waits for this much more before carrying
+// on, rather than stuttering along a chunk at a time on a slow connection
+const REBUFFER: usize = 256 * 1024;
+
+// Most memory set aside ahead of time for the rest of a file. Servers may say anything for
+// its size, which grows the buffer as the file comes in either way.
+const MAX_RESERVE: u64 = 64 * 1024 * 1024;
+
+// How many times in a row a download that broke off is picked up again, `retries` in the
+// config, and how long to wait before each try
+static RETRIES: AtomicU32 = AtomicU32::new(5);
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+// Apply the user's settings to every download played from here on
+pub fn configure(config: &PlaybackConfig) {
+    RETRIES.store(config.retries, Ordering::Relaxed);
+}
+
+// What has been downloaded so far. The whole file is kept rather than a window of it:
+// the MP3 decoder seeks back by reading from the start of the file again, so a window
+// would mean downloading the episode over for every seek back.
+#[derive(Default)]
+struct State {
+    data: Vec<u8>,
+    // size of the file, when the server says
+    len: Option<u64>,
+    done: bool,
+    // why the download gave up
+    error: Option<String>,
+    // the download broke off, and this is the try to pick it up again under way, until
+    // more of the file comes in
+    retry: Option<u32>,
+    // playback caught up with the download and waits for it
+    waiting: bool,
+    cancelled: bool,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    // signalled whenever the state changes
+    changed: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn update(&self, f: impl FnOnce(&mut State)) {
+        f(&mut self.lock());
+        self.changed.notify_all();
+    }
+}
+
+// Download is an episode's file downloading in the background while it plays. It stops
+// when dropped.
+pub struct Download {
+    shared: Arc<Shared>,
+    task: JoinHandle<()>,
+}
+
+impl Download {
+    // Start downloading the file. Has to be called from within the runtime.
+    pub fn start(u: &Url) -> Download {
+        let shared = Arc::new(Shared::default());
+        let task = tokio::spawn(download(u.clone(), shared.clone()));
+        Download { shared, task }
+    }
+
+    // A reader over the file, which waits for the parts that haven't downloaded yet
+    pub fn stream(&self) -> Stream {
+        Stream {
+            shared: self.shared.clone(),
+            pos: 0,
+        }
+    }
+
+    // How much of the file has downloaded, in percent. None once it all has, or when
+    // the server didn't say how big it is.
+    pub fn buffered(&self) -> Option<u8> {
+        let s = self.shared.lock();
+        if s.done {
+            return None;
+        }
+        let len = s.len.filter(|l| *l > 0)?;
+        Some((s.data.len() as u64 * 100 / len).min(100) as u8)
+    }
+
+    // Whether playback is waiting for the download
+    pub fn buffering(&self) -> bool {
+        self.shared.lock().waiting
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.shared.lock().error.clone()
+    }
+
+    // Which try at picking up the download again is under way, if it broke off
+    pub fn retrying(&self) -> Option<u32> {
+        self.shared.lock().retry
+    }
+
+    // The ID3 tag at the start of the file, once it has downloaded
+    pub fn tag(&self) -> Option<Vec<u8>> {
+        let s = self.shared.lock();
+        let len = tag_len(s.data.get(..10)?)?;
+        s.data.get(..len).map(<[u8]>::to_vec)
+    }
+}
+
+impl Drop for Download {
+    fn drop(&mut self) {
+        self.task.abort();
+        // let go of a reader waiting for more
+        self.shared.update(|s| s.cancelled = true);
+    }
+}
+
+// Length of the ID3v2 tag that the header starts, including the header
+fn tag_len(header: &[u8]) -> Option<usize> {
+    if header.len() < 10 || &header[..3] != b"ID3" {
+        return None;
+    }
+    // the size is stored in 7 bits per byte
+    let size = header[6..10]
+        .iter()
+        .fold(0usize, |size, b| (size << 7) | (*b & 0x7f) as usize);
+    // with a footer after the tag
+    let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+    Some(10 + size + footer)
+}
+
+async fn download(u: Url, shared: Arc<Shared>) {
+    // downloaded episodes are read from disk, all at once
+    if u.scheme() == "file" {
+        let res = match u.to_file_path() {
+            Ok(p) => tokio::fs::read(p).await.map_err(|e| e.to_string()),
+            Err(_) => Err("invalid file URL".to_string()),
+        };
+        shared.update(|s| match res {
+            Ok(data) => {
+                s.len = Some(data.len() as u64);
+                s.data = data;
+                s.done = true;
+            }
+            Err(e) => s.error = Some(e),
+        });
+        return;
+    }
+    let client = reqwest::Client::new();
+    let mut failures = 0;
+    loop {
+        let from = shared.lock().data.len() as u64;
+        let (err, missing) = match fetch_from(&client, &u, from, &shared).await {
+            Ok(()) => {
+                debug!("downloaded {}", redact(&u));
+                shared.update(|s| {
+                    s.done = true;
+                    s.retry = None;
+                });
+                return;
+            }
+            // not kept as an error, which can't be held across the retry delay
+            Err(e) => (e.to_string(), is_client_error(e.as_ref())),
+        };
+        // only failures in a row count
+        if shared.lock().data.len() as u64 > from {
+            failures = 0;
+        }
+        failures += 1;
+        // asking again won't make a missing file appear
+        if failures > RETRIES.load(Ordering::Relaxed) || missing {
+            shared.update(|s| {
+                s.error = Some(err);
+                s.retry = None;
+            });
+            return;
+        }
+        warn!(
+            "download of {} broke off, trying again: {}",
+            redact(&u),
+            err
+        );
+        shared.update(|s| s.retry = Some(failures));
+        tokio::time::sleep(RETRY_DELAY).await;
+    }
+}
+
+// Download the file from the byte offset on, adding it to what was downloaded already
+async fn fetch_from(
+    client: &reqwest::Client,
+    u: &Url,
+    from: u64,
+    shared: &Shared,
+) -> Result<(), Box<dyn Error>> {
+    wait_turn(u).await;
+    let mut req = client.get(u.as_str());
+    if from > 0 {
+        req = req.header(header::RANGE, format!("bytes={}-", from));
+    }
+    // reqwest errors include the URL, which may contain a private feed token
+    let mut res = req
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.without_url())?;
+    // servers that can't send part of a file send all of it
+    let mut skip = match res.status() {
+        StatusCode::PARTIAL_CONTENT => 0,
+        _ => from,
+    };
+    // a length too big to add up is as good as none
+    let len = res
+        .content_length()
+        .and_then(|l| l.checked_add(from - skip));
+    shared.update(|s| {
+        s.len = s.len.or(len);
+        if let Some(l) = len {
+            s.data
+                .reserve(l.saturating_sub(from).min(MAX_RESERVE) as usize);
+        }
+    });
+
+    while let Some(chunk) = res.chunk().await.map_err(|e| e.without_url())? {
+        let n = skip.min(chunk.len() as u64);
+        skip -= n;
+        shared.update(|s| {
+            s.data.extend_from_slice(&chunk[n as usize..]);
+            s.retry = None;
+        });
+    }
+    match len {
+        Some(l) if (shared.lock().data.len() as u64) < l => Err("the download ended early".into()),
+        _ => Ok(()),
+    }
+}
+
+// Stream reads an episode's file as it downloads
+pub struct Stream {
+    shared: Arc<Shared>,
+    pos: u64,
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut s = self.shared.lock();
+        loop {
+            if s.cancelled {
+                return Err(io::Error::other("stopped"));
+            }
+            let available = (s.data.len() as u64).saturating_sub(self.pos) as usize;
+            let ready = if s.waiting {
+                available >= REBUFFER
+            } else {
+                available > 0
+            };
+            if ready || s.done || s.error.is_some() {
+                break;
+            }
+            s.waiting = true;
+            s = self
+                .shared
+                .changed
+                .wait(s)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+        s.waiting = false;
+
+        let start = self.pos.min(s.data.len() as u64) as usize;
+        let n = buf.len().min(s.data.len() - start);
+        if n == 0 {
+            if let Some(e) = s.error.as_ref() {
+                return Err(io::Error::other(e.clone()));
+            }
+        }
+        buf[..n].copy_from_slice(&s.data[start..start + n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for Stream {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(p) => Some(p),
+            SeekFrom::Current(d) => self.pos.checked_add_signed(d),
+            SeekFrom::End(d) => {
+                let s = self.shared.lock();
+                let len = match (s.len, s.done) {
+                    (Some(l), _) => l,
+                    (None, true) => s.data.len() as u64,
+                    (None, false) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::Unsupported,
+                            "the size of the file isn't known yet",
+                        ))
+                    }
+                };
+                len.checked_add_signed(d)
+            }
+        };
+        self.pos = pos
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "seek before the start"))?;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Seek, SeekFrom},
+        sync::Arc,
+        thread,
+        time::Duration,
+    };
+
+    use super::{tag_len, Shared, Stream, REBUFFER};
+
+    #[test]
+    fn reads_as_it_downloads() {
+        let shared = Arc::new(Shared::default());
+        shared.update(|s| s.len = Some(REBUFFER as u64 * 2));
+        let mut stream = Stream {
+            shared: shared.clone(),
+            pos: 0,
+        };
+
+        let downloader = {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                for _ in 0..4 {
+                    thread::sleep(Duration::from_millis(10));
+                    shared.update(|s| s.data.extend(vec![1; REBUFFER / 2]));
+                }
+                shared.update(|s| s.done = true);
+            })
+        };
+        // waits for a full rebuffer before starting
+        let mut buf = vec![0; REBUFFER * 3];
+        let n = stream.read(&mut buf).unwrap();
+        assert!(n >= REBUFFER);
+
+        let mut rest = vec![];
+        stream.read_to_end(&mut rest).unwrap();
+        assert_eq!(n + rest.len(), REBUFFER * 2);
+        downloader.join().unwrap();
+
+        assert_eq!(
+            stream.seek(SeekFrom::End(-10)).unwrap(),
+            REBUFFER as u64 * 2 - 10
+        );
+        assert_eq!(stream.read(&mut buf).unwrap(), 10);
+    }
+
+    #[test]
+    fn stops_waiting_when_cancelled() {
+        let shared = Arc::new(Shared::default());
+        let mut stream = Stream {
+            shared: shared.clone(),
+            pos: 0,
+        };
+        let reader = thread::spawn(move || stream.read(&mut [0; 16]));
+        thread::sleep(Duration::from_millis(10));
+        shared.update(|s| s.cancelled = true);
+        assert!(reader.join().unwrap().is_err());
+    }
+
+    #[test]
+    fn measures_id3_tag() {
+        assert_eq!(tag_len(b"ID3\x04\x00\x00\x00\x00\x02\x01"), Some(10 + 257));
+        assert_eq!(tag_len(b"ID3\x04\x00\x10\x00\x00\x00\x0a"), Some(30));
+        assert_eq!(tag_len(b"\xff\xfb\x90\x00\x00\x00\x00\x00\x00\x00"), None);
+    }
+}
@@ -0,0 +1,317 @@
+use rodio::{source::SeekError, Sample, Source};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tracing::error;
+
+// Samples quieter than this count as silence, about -36 dB. Podcasts recorded in a room
+// rarely go all the way to zero between sentences.
+const SILENCE: f32 = 0.016;
+
+// How much of a silence is kept when skipping silences, so that speech still has pauses
+const KEEP_SILENCE: Duration = Duration::from_millis(250);
+
+// No seek waiting to happen
+const NO_SEEK: u64 = u64::MAX;
+
+// Progress is shared between the player and the audio output thread, which is where the
+// samples are actually pulled
+#[derive(Debug)]
+pub struct Progress {
+    // samples played so far, counting from the start of the episode
+    samples: AtomicU64,
+    // samples per second, across channels
+    rate: AtomicU64,
+    // f32 bits
+    speed: AtomicU32,
+    // skip long silences, and how many nanoseconds were skipped so far this session. Kept
+    // as time rather than samples, since episodes of the session can differ in rate.
+    skip_silence: AtomicBool,
+    skipped: AtomicU64,
+    // nanoseconds into the episode to seek to, done on the audio output thread so that
+    // seeking into a part still downloading doesn't hold up the player
+    seek: AtomicU64,
+    // f32 bits of the loudest sample since the level was last taken. Bits of positive
+    // floats order like the floats, so the loudest is kept with `fetch_max`.
+    peak: AtomicU32,
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Progress {
+            samples: AtomicU64::new(0),
+            rate: AtomicU64::new(0),
+            speed: AtomicU32::new(1.0f32.to_bits()),
+            skip_silence: AtomicBool::new(false),
+            skipped: AtomicU64::new(0),
+            seek: AtomicU64::new(NO_SEEK),
+            peak: AtomicU32::new(0),
+        }
+    }
+}
+
+impl Progress {
+    // Position in the episode, whatever the speed it was played at
+    pub fn position(&self) -> Duration {
+        if let Some(to) = self.seeking() {
+            return to;
+        }
+        match self.rate.load(Ordering::Relaxed) {
+            0 => Duration::ZERO,
+            rate => {
+                Duration::from_secs_f64(self.samples.load(Ordering::Relaxed) as f64 / rate as f64)
+            }
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
+        f32::from_bits(self.speed.load(Ordering::Relaxed))
+    }
+
+    pub fn set_speed(&self, speed: f32) {
+        self.speed.store(speed.to_bits(), Ordering::Relaxed);
+    }
+
+    // A new progress for the next episode, with the same settings
+    pub fn carry_on(&self) -> Progress {
+        Progress {
+            speed: AtomicU32::new(self.speed.load(Ordering::Relaxed)),
+            skip_silence: AtomicBool::new(self.skip_silence.load(Ordering::Relaxed)),
+            skipped: AtomicU64::new(self.skipped.load(Ordering::Relaxed)),
+            ..Default::default()
+        }
+    }
+
+    // Go to the position once the next samples are played
+    pub fn seek(&self, to: Duration) {
+        let nanos = to.as_nanos().min(NO_SEEK as u128 - 1) as u64;
+        self.seek.store(nanos, Ordering::Relaxed);
+    }
+
+    fn seeking(&self) -> Option<Duration> {
+        match self.seek.load(Ordering::Relaxed) {
+            NO_SEEK => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
+
+    // The loudest sample played since the last time, from 0 to 1
+    pub fn take_peak(&self) -> f32 {
+        f32::from_bits(self.peak.swap(0, Ordering::Relaxed))
+    }
+
+    pub fn set_skip_silence(&self, skip: bool) {
+        self.skip_silence.store(skip, Ordering::Relaxed);
+    }
+
+    // Listening time saved by skipping silences this session
+    pub fn saved(&self) -> Duration {
+        Duration::from_nanos(self.skipped.load(Ordering::Relaxed))
+    }
+}
+
+// Tracked wraps a decoded episode to count how far into it playback is, and to play it
+// at the speed set on its progress. Sink::get_pos can't be used for the position, it
+// counts time played rather than time into the episode once the speed changes.
+//
+// It also skips silences when asked to. Samples are read a frame (one per channel) at a
+// time so that skipping never swaps the channels around.
+pub struct Tracked<S: Source>
+where
+    S::Item: Sample,
+{
+    input: S,
+    progress: Arc<Progress>,
+    // the frame being played, and how much of it was
+    frame: Vec<S::Item>,
+    played: usize,
+    // samples of silence in a row so far
+    quiet: u64,
+}
+
+impl<S: Source> Tracked<S>
+where
+    S::Item: Sample,
+{
+    pub fn new(input: S, progress: Arc<Progress>) -> Tracked<S> {
+        progress.samples.store(0, Ordering::Relaxed);
+        progress.seek.store(NO_SEEK, Ordering::Relaxed);
+        progress.rate.store(
+            input.sample_rate() as u64 * input.channels() as u64,
+            Ordering::Relaxed,
+        );
+        Tracked {
+            input,
+            progress,
+            frame: vec![],
+            played: 0,
+            quiet: 0,
+        }
+    }
+
+    // Read the next frame that isn't part of a silence being skipped
+    fn next_frame(&mut self) -> bool {
+        let to = self.progress.seek.swap(NO_SEEK, Ordering::Relaxed);
+        if to != NO_SEEK {
+            let to = Duration::from_nanos(to);
+            if let Err(e) = self.try_seek(to) {
+                error!("failed to seek to {:?}: {:?}", to, e);
+            }
+        }
+        let channels = self.input.channels().max(1) as usize;
+        loop {
+            self.frame.clear();
+            self.played = 0;
+            self.frame.extend(self.input.by_ref().take(channels));
+            if self.frame.is_empty() {
+                return false;
+            }
+            let n = self.frame.len() as u64;
+            self.progress.samples.fetch_add(n, Ordering::Relaxed);
+
+            let loudest = self
+                .frame
+                .iter()
+                .map(|s| s.to_f32().abs())
+                .fold(0.0, f32::max);
+            if loudest >= SILENCE {
+                self.progress
+                    .peak
+                    .fetch_max(loudest.min(1.0).to_bits(), Ordering::Relaxed);
+                self.quiet = 0;
+                return true;
+            }
+            self.quiet += n;
+            let rate = self.progress.rate.load(Ordering::Relaxed);
+            let keep = (KEEP_SILENCE.as_secs_f64() * rate as f64) as u64;
+            if !self.progress.skip_silence.load(Ordering::Relaxed) || self.quiet <= keep {
+                return true;
+            }
+            let nanos = n as u128 * 1_000_000_000 / rate.max(1) as u128;
+            self.progress
+                .skipped
+                .fetch_add(nanos as u64, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<S: Source> Iterator for Tracked<S>
+where
+    S::Item: Sample,
+{
+    type Item = S::Item;
+
+    fn next(&mut self) -> Option<S::Item> {
+        if self.played >= self.frame.len() && !self.next_frame() {
+            return None;
+        }
+        let item = self.frame.get(self.played).copied();
+        self.played += 1;
+        item
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (low, high) = self.input.size_hint();
+        let buffered = self.frame.len() - self.played;
+        (low + buffered, high.map(|h| h + buffered))
+    }
+}
+
+impl<S: Source> Source for Tracked<S>
+where
+    S::Item: Sample,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        let buffered = self.frame.len() - self.played;
+        self.input.current_frame_len().map(|n| n + buffered)
+    }
+
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    // Claiming a higher sample rate plays the samples faster, like `Source::speed`
+    fn sample_rate(&self) -> u32 {
+        (self.input.sample_rate() as f32 * self.progress.speed()) as u32
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.frame.clear();
+        self.played = 0;
+        self.quiet = 0;
+        let rate = self.progress.rate.load(Ordering::Relaxed);
+        self.progress
+            .samples
+            .store((pos.as_secs_f64() * rate as f64) as u64, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rodio::{buffer::SamplesBuffer, source::SineWave, Source};
+    use std::{sync::Arc, time::Duration};
+
+    use super::{Progress, Tracked};
+
+    #[test]
+    fn counts_position_at_any_speed() {
+        let progress = Arc::new(Progress::default());
+        let mut t = Tracked::new(SineWave::new(440.0), progress.clone());
+
+        progress.set_speed(2.0);
+        assert_eq!(t.sample_rate(), 96000);
+        // one second of the wave, played in half a second
+        t.by_ref().take(48000).for_each(drop);
+        assert_eq!(progress.position(), Duration::from_secs(1));
+
+        progress.seek(Duration::from_secs(10));
+        assert_eq!(progress.position(), Duration::from_secs(10));
+        t.by_ref().take(24000).for_each(drop);
+        assert_eq!(progress.position(), Duration::from_millis(10500));
+    }
+
+    #[test]
+    fn skips_silences() {
+        // a second of sound, two seconds of silence, and another second of sound
+        let sound = (0..1000).map(|i| if i % 2 == 0 { 0.5 } else { -0.5 });
+        let samples = sound
+            .clone()
+            .chain(std::iter::repeat_n(0.0, 2000))
+            .chain(sound)
+            .collect::<Vec<f32>>();
+
+        let progress = Arc::new(Progress::default());
+        progress.set_skip_silence(true);
+        let played = Tracked::new(SamplesBuffer::new(1, 1000, samples), progress.clone()).count();
+
+        // a quarter of a second of the silence is kept
+        assert_eq!(played, 4000 - 1750);
+        assert_eq!(progress.saved(), Duration::from_millis(1750));
+        assert_eq!(progress.position(), Duration::from_secs(4));
+        // still counted for the next episode, before its rate is known
+        assert_eq!(progress.carry_on().saved(), Duration::from_millis(1750));
+    }
+
+    #[test]
+    fn keeps_peak() {
+        let samples = vec![0.1, -0.6, 0.3, 0.0];
+        let progress = Arc::new(Progress::default());
+        let mut t = Tracked::new(SamplesBuffer::new(1, 1000, samples), progress.clone());
+        t.by_ref().take(3).for_each(drop);
+        assert_eq!(progress.take_peak(), 0.6);
+        // taken until more is played
+        assert_eq!(progress.take_peak(), 0.0);
+        t.for_each(drop);
+        assert_eq!(progress.take_peak(), 0.0);
+    }
+}
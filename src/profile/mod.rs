@@ -0,0 +1,100 @@
+use std::{error::Error, sync::OnceLock};
+
+// Profile whose data is used, set once at startup. Without one, the data is kept where it
+// was before there were profiles.
+static CURRENT: OnceLock<String> = OnceLock::new();
+
+const USAGE: &str = "Usage: podcasts [--profile <name>]
+
+Options:
+  --profile <name>  keep the data of the profile apart, see docs/profiles.md
+  -h, --help        print this and quit
+  -V, --version     print the version and quit";
+
+// What to print instead of starting, when the command line asks for help or the version
+pub fn info(args: &[String]) -> Option<String> {
+    args.iter().find_map(|arg| match arg.as_str() {
+        "-h" | "--help" => Some(USAGE.to_string()),
+        "-V" | "--version" => Some(format!("podcasts {}", env!("CARGO_PKG_VERSION"))),
+        _ => None,
+    })
+}
+
+// The profile asked for on the command line, with `--profile work` or `--profile=work`
+pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Option<String>, Box<dyn Error>> {
+    let mut profile = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let name = match arg.strip_prefix("--profile") {
+            Some("") => args.next().ok_or("--profile needs a name")?,
+            Some(rest) if rest.starts_with('=') => rest[1..].to_string(),
+            _ => return Err(format!("unknown argument {}, see --help", arg).into()),
+        };
+        validate(name.as_str())?;
+        profile = Some(name);
+    }
+    Ok(profile)
+}
+
+// Names become directory names, so only letters, digits, `-` and `_` are allowed
+fn validate(name: &str) -> Result<(), Box<dyn Error>> {
+    let ok = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if !ok {
+        return Err(format!(
+            "invalid profile name {:?}, use letters, digits, - and _",
+            name
+        )
+        .into());
+    }
+    Ok(())
+}
+
+pub fn set(name: String) {
+    let _ = CURRENT.set(name);
+}
+
+pub fn current() -> Option<&'static str> {
+    CURRENT.get().map(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_args, info, USAGE};
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn parses_profile() {
+        assert_eq!(from_args(args("")).unwrap(), None);
+        assert_eq!(
+            from_args(args("--profile work")).unwrap(),
+            Some("work".to_string())
+        );
+        assert_eq!(
+            from_args(args("--profile=kids_2")).unwrap(),
+            Some("kids_2".to_string())
+        );
+        assert!(from_args(args("--profile")).is_err());
+        assert!(from_args(args("--profile ../work")).is_err());
+        assert!(from_args(args("--verbose")).is_err());
+    }
+
+    #[test]
+    fn prints_help_and_version() {
+        assert_eq!(info(&args("--profile work")), None);
+        assert_eq!(
+            info(&args("--profile work --help")),
+            Some(USAGE.to_string())
+        );
+        assert_eq!(info(&args("-h")), Some(USAGE.to_string()));
+        assert_eq!(
+            info(&args("-V")),
+            Some(format!("podcasts {}", env!("CARGO_PKG_VERSION")))
+        );
+    }
+}
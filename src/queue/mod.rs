@@ -0,0 +1,226 @@
+use rss::Item;
+use serde::{Deserialize, Serialize};
+use std::{error::Error, time::Duration};
+
+use crate::{
+    feed::{duration, episode_id},
+    store,
+};
+
+// Entry is an episode waiting to be played
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub item: Item,
+    // how far into the episode playback already got
+    pub position: Duration,
+    // title of the podcast, and the URL of its feed
+    #[serde(default)]
+    pub podcast: Option<String>,
+    #[serde(default)]
+    pub feed: Option<String>,
+}
+
+impl Entry {
+    pub fn new(item: Item, position: Duration) -> Entry {
+        Entry {
+            item,
+            position,
+            podcast: None,
+            feed: None,
+        }
+    }
+
+    // Listening time left for this episode at the given playback speed, if the feed
+    // says how long the episode is
+    pub fn remaining(&self, speed: f32) -> Option<Duration> {
+        duration(&self.item).map(|d| d.saturating_sub(self.position).div_f32(speed))
+    }
+}
+
+// Queue is the ordered list of episodes to play next, stored as `queue` in the store so
+// that it survives restarts
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Queue {
+    #[serde(default)]
+    entries: Vec<Entry>,
+}
+
+impl Queue {
+    pub fn push(&mut self, e: Entry) {
+        self.entries.push(e);
+    }
+
+    pub fn insert(&mut self, idx: usize, e: Entry) {
+        self.entries.insert(idx.min(self.entries.len()), e);
+    }
+
+    pub fn remove(&mut self, idx: usize) -> Option<Entry> {
+        if idx >= self.entries.len() {
+            return None;
+        }
+        Some(self.entries.remove(idx))
+    }
+
+    // Take the episode out of the queue, returns false if it wasn't queued
+    pub fn remove_episode(&mut self, item: &Item) -> bool {
+        let len = self.entries.len();
+        let id = episode_id(item);
+        self.entries
+            .retain(|e| id.is_none() || episode_id(&e.item) != id);
+        self.entries.len() != len
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    // Move the entry at `from` so that it ends up at `to`, shifting the ones in between.
+    // Returns false if either position is past the end of the queue.
+    pub fn move_entry(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.entries.len() || to >= self.entries.len() {
+            return false;
+        }
+        let e = self.entries.remove(from);
+        self.entries.insert(to, e);
+        true
+    }
+
+    // Total listening time left across the queue at the given playback speed, along with
+    // the number of episodes that could not be counted because their length is unknown
+    pub fn remaining(&self, speed: f32) -> (Duration, usize) {
+        self.entries
+            .iter()
+            .fold((Duration::ZERO, 0), |(total, unknown), e| {
+                match e.remaining(speed) {
+                    Some(d) => (total + d, unknown),
+                    None => (total, unknown + 1),
+                }
+            })
+    }
+
+    pub fn load() -> Result<Queue, Box<dyn Error>> {
+        store::load("queue")
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        store::save("queue", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rss::{extension::itunes::ITunesItemExtension, Guid, Item};
+
+    use super::{Entry, Queue};
+
+    fn episode(duration: Option<&str>) -> Item {
+        let mut item = Item::default();
+        if let Some(d) = duration {
+            let mut ext = ITunesItemExtension::default();
+            ext.set_duration(d.to_string());
+            item.set_itunes_ext(ext);
+        }
+        item
+    }
+
+    #[test]
+    fn remaining_accounts_for_position_and_speed() {
+        let mut q = Queue::default();
+        q.push(Entry::new(
+            episode(Some("1:00:00")),
+            Duration::from_secs(30 * 60),
+        ));
+
+        let e = &q.entries()[0];
+        assert_eq!(e.remaining(1.0), Some(Duration::from_secs(30 * 60)));
+        assert_eq!(e.remaining(1.5), Some(Duration::from_secs(20 * 60)));
+    }
+
+    #[test]
+    fn remaining_never_negative() {
+        let mut q = Queue::default();
+        q.push(Entry::new(episode(Some("60")), Duration::from_secs(120)));
+        assert_eq!(q.entries()[0].remaining(1.0), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn total_skips_unknown_durations() {
+        let mut q = Queue::default();
+        q.push(Entry::new(episode(Some("30:00")), Duration::ZERO));
+        q.push(Entry::new(episode(None), Duration::ZERO));
+        q.push(Entry::new(episode(Some("1800")), Duration::from_secs(600)));
+
+        assert_eq!(q.len(), 3);
+        assert_eq!(q.remaining(1.0), (Duration::from_secs(3000), 1));
+        assert_eq!(q.remaining(2.0), (Duration::from_secs(1500), 1));
+    }
+
+    #[test]
+    fn move_entry() {
+        let mut q = Queue::default();
+        for t in ["a", "b", "c", "d"] {
+            let mut item = episode(None);
+            item.set_title(t.to_string());
+            q.push(Entry::new(item, Duration::ZERO));
+        }
+        let titles = |q: &Queue| {
+            q.entries()
+                .iter()
+                .map(|e| e.item.title().unwrap_or_default().to_string())
+                .collect::<Vec<String>>()
+        };
+
+        assert!(q.move_entry(3, 0));
+        assert_eq!(titles(&q), ["d", "a", "b", "c"]);
+        assert!(q.move_entry(1, 2));
+        assert_eq!(titles(&q), ["d", "b", "a", "c"]);
+        assert!(!q.move_entry(0, 4));
+        assert!(!q.move_entry(4, 0));
+        assert_eq!(titles(&q), ["d", "b", "a", "c"]);
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut q = Queue::default();
+        let mut item = episode(Some("30:00"));
+        item.set_title("Episode 2".to_string());
+        q.push(Entry::new(item, Duration::from_secs(90)));
+        q.push(Entry::new(episode(None), Duration::ZERO));
+
+        let serialized = toml::to_string(&q).unwrap();
+        assert_eq!(toml::from_str::<Queue>(serialized.as_str()).unwrap(), q);
+    }
+
+    #[test]
+    fn empty_queue() {
+        let q = Queue::default();
+        assert!(q.is_empty());
+        assert_eq!(q.remaining(1.0), (Duration::ZERO, 0));
+    }
+
+    #[test]
+    fn removes_episode() {
+        let mut q = Queue::default();
+        let mut item = episode(None);
+        item.set_guid(Guid {
+            value: "1".to_string(),
+            ..Default::default()
+        });
+        q.push(Entry::new(item.clone(), Duration::ZERO));
+        q.push(Entry::new(episode(None), Duration::ZERO));
+
+        assert!(q.remove_episode(&item));
+        assert!(!q.remove_episode(&item));
+        assert_eq!(q.len(), 1);
+    }
+}
@@ -0,0 +1,100 @@
+use serde::Deserialize;
+use std::error::Error;
+use url::Url;
+
+use crate::feed::fetch;
+
+// Podcast directory used to look up feeds by name
+const SEARCH_URL: &str = "https://itunes.apple.com/search";
+
+// Maximum number of results to ask for
+const LIMIT: usize = 25;
+
+// SearchResult is a podcast found in the directory
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SearchResult {
+    #[serde(rename = "collectionName")]
+    pub title: String,
+    #[serde(rename = "artistName", default)]
+    pub author: String,
+    #[serde(rename = "feedUrl")]
+    pub feed_url: Option<String>,
+    #[serde(rename = "primaryGenreName")]
+    pub genre: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    #[serde(default)]
+    results: Vec<SearchResult>,
+}
+
+// Parse a response from the directory. Results without a feed can't be subscribed to,
+// so they are left out.
+pub fn parse(s: &[u8]) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+    let res: Response = serde_json::from_slice(s)?;
+    Ok(res
+        .results
+        .into_iter()
+        .filter(|r| r.feed_url.is_some())
+        .collect())
+}
+
+pub async fn search(terms: &str) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+    let u = Url::parse_with_params(
+        SEARCH_URL,
+        &[
+            ("media", "podcast"),
+            ("term", terms),
+            ("limit", LIMIT.to_string().as_str()),
+        ],
+    )?;
+    let content = fetch(&u).await?;
+    parse(&content[..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn parses_results() {
+        let res = parse(
+            br#"{
+                "resultCount": 2,
+                "results": [
+                    {
+                        "kind": "podcast",
+                        "collectionName": "Wine About It",
+                        "artistName": "Someone",
+                        "feedUrl": "https://feeds.captivate.fm/wine-about-it/",
+                        "primaryGenreName": "Comedy"
+                    },
+                    { "kind": "podcast", "collectionName": "No Feed" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].title, "Wine About It");
+        assert_eq!(res[0].author, "Someone");
+        assert_eq!(
+            res[0].feed_url.as_deref(),
+            Some("https://feeds.captivate.fm/wine-about-it/")
+        );
+        assert_eq!(res[0].genre.as_deref(), Some("Comedy"));
+    }
+
+    #[test]
+    fn parses_no_results() {
+        assert!(parse(br#"{"resultCount": 0, "results": []}"#)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(parse(b"<html>").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+use crate::{message::DisplayAction, store};
+
+// Session is where the user was when the app was last closed, stored as `session` in the
+// store so that the next launch picks up there
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(default)]
+    pub view: View,
+    // the feed that was loaded, and the id of the episode selected in it
+    #[serde(default)]
+    pub feed: Option<String>,
+    #[serde(default)]
+    pub episode: Option<String>,
+    // rows selected in the other lists, which the lists scroll to
+    #[serde(default)]
+    pub library: Option<usize>,
+    #[serde(default)]
+    pub queue: Option<usize>,
+    #[serde(default)]
+    pub bookmarks: Option<usize>,
+    #[serde(default)]
+    pub starred: Option<usize>,
+    #[serde(default)]
+    pub history: Option<usize>,
+    #[serde(default)]
+    pub downloads: Option<usize>,
+}
+
+impl Session {
+    pub fn load() -> Result<Session, Box<dyn Error>> {
+        store::load("session")
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        store::save("session", self)
+    }
+}
+
+// The screens that can be come back to. The others show something that doesn't outlive
+// the app, like search results or the episode playing.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum View {
+    #[default]
+    Input,
+    Preview,
+    Episodes,
+    Library,
+    Queue,
+    Bookmarks,
+    Starred,
+    History,
+    Downloads,
+}
+
+impl View {
+    pub fn of(d: DisplayAction) -> View {
+        match d {
+            DisplayAction::Preview => View::Preview,
+            // the description is a key press away from its episode, selected in the list
+            DisplayAction::ListEpisodes | DisplayAction::DescribeEpisode => View::Episodes,
+            DisplayAction::Library => View::Library,
+            DisplayAction::Queue => View::Queue,
+            DisplayAction::Bookmarks => View::Bookmarks,
+            DisplayAction::Starred => View::Starred,
+            DisplayAction::History => View::History,
+            DisplayAction::Downloads => View::Downloads,
+            _ => View::Input,
+        }
+    }
+
+    pub fn display(&self) -> DisplayAction {
+        match self {
+            View::Input => DisplayAction::Input,
+            View::Preview => DisplayAction::Preview,
+            View::Episodes => DisplayAction::ListEpisodes,
+            View::Library => DisplayAction::Library,
+            View::Queue => DisplayAction::Queue,
+            View::Bookmarks => DisplayAction::Bookmarks,
+            View::Starred => DisplayAction::Starred,
+            View::History => DisplayAction::History,
+            View::Downloads => DisplayAction::Downloads,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::DisplayAction;
+
+    use super::{Session, View};
+
+    #[test]
+    fn round_trips() {
+        let s = Session {
+            view: View::of(DisplayAction::DescribeEpisode),
+            feed: Some("https://example.com/feed".to_string()),
+            episode: Some("1".to_string()),
+            library: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(s.view.display(), DisplayAction::ListEpisodes);
+
+        let serialized = toml::to_string(&s).unwrap();
+        assert_eq!(toml::from_str::<Session>(serialized.as_str()).unwrap(), s);
+        assert_eq!(toml::from_str::<Session>("").unwrap(), Session::default());
+    }
+}
@@ -0,0 +1,105 @@
+use html2text::render::text_renderer::TrivialDecorator;
+use rss::Item;
+use std::{
+    io::{self, Write},
+    process::{Child, Stdio},
+    thread,
+};
+
+use crate::media;
+
+// Lines are joined back up anyway, this just keeps html2text from wrapping them
+const WIDTH: usize = 10_000;
+
+// Text-to-speech program used when none is configured
+pub fn default_command() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "say"
+    } else {
+        "espeak"
+    }
+}
+
+// What to read out for an episode: its title, then the show notes as plain text
+pub fn text(item: &Item) -> String {
+    let notes = html2text::from_read_with_decorator(
+        item.description().unwrap_or_default().as_bytes(),
+        WIDTH,
+        TrivialDecorator::new(),
+    );
+    let notes = notes.split_whitespace().collect::<Vec<&str>>().join(" ");
+    match item.title() {
+        Some(t) => format!("{}. {}", t, notes),
+        None => notes,
+    }
+}
+
+// Speaker reads text aloud with an external text-to-speech command, one text at a time
+#[derive(Default, Debug)]
+pub struct Speaker {
+    child: Option<Child>,
+}
+
+impl Speaker {
+    // Start reading the text with the command (e.g. `espeak -s 200`), which gets the text
+    // on its standard input, so long show notes don't run into the limit on arguments.
+    // Anything still being read is stopped first.
+    pub fn speak(&mut self, command: &str, text: &str) -> io::Result<()> {
+        self.stop();
+        let mut child = media::command(command)?.stdin(Stdio::piped()).spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            let text = text.to_string();
+            // the command may read slower than the pipe holds, and closing it ends the text
+            thread::spawn(move || {
+                let _ = stdin.write_all(text.as_bytes());
+            });
+        }
+        self.child = Some(child);
+        Ok(())
+    }
+
+    pub fn is_speaking(&mut self) -> bool {
+        match self.child.as_mut().map(Child::try_wait) {
+            Some(Ok(None)) => true,
+            Some(_) => {
+                self.child = None;
+                false
+            }
+            None => false,
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(mut c) = self.child.take() {
+            // it may have finished already
+            let _ = c.kill();
+            let _ = c.wait();
+        }
+    }
+}
+
+impl Drop for Speaker {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rss::Item;
+
+    use super::text;
+
+    #[test]
+    fn reads_title_and_plain_notes() {
+        let item = Item {
+            title: Some("Episode 1".to_string()),
+            description: Some(
+                "<p>We talk about <a href=\"https://example.com\">wine</a>.</p><p>Cheers!</p>"
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+        assert_eq!(text(&item), "Episode 1. We talk about wine. Cheers!");
+    }
+}
@@ -0,0 +1,129 @@
+use rss::Item;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+use crate::{feed::episode_id, store::db::Db};
+
+// Star marks an episode as a favorite
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Star {
+    // kept whole, so that it can be played without loading its feed
+    pub item: Item,
+    pub feed: String,
+    // title of the podcast
+    pub podcast: Option<String>,
+    // when it was starred, in seconds since the epoch
+    pub starred: i64,
+}
+
+impl Star {
+    pub fn title(&self) -> &str {
+        self.item.title().unwrap_or("Title missing!")
+    }
+
+    fn is(&self, feed: &str, item: &Item) -> bool {
+        self.feed == feed
+            && episode_id(&self.item).is_some()
+            && episode_id(&self.item) == episode_id(item)
+    }
+}
+
+// Stars are the starred episodes of every podcast, newest first. Stored in the database.
+#[derive(Default, Debug, PartialEq)]
+pub struct Stars {
+    stars: Vec<Star>,
+}
+
+impl Stars {
+    pub fn is_starred(&self, feed: &str, item: &Item) -> bool {
+        self.stars.iter().any(|s| s.is(feed, item))
+    }
+
+    // Star the episode, or unstar it if it already was, returning whether it's starred
+    // now. Nothing changes unless the database does.
+    pub fn toggle(&mut self, star: Star, db: &Db) -> Result<bool, Box<dyn Error>> {
+        match self
+            .stars
+            .iter()
+            .position(|s| s.is(star.feed.as_str(), &star.item))
+        {
+            Some(idx) => {
+                db.remove_star(star.feed.as_str(), &star.item)?;
+                self.stars.remove(idx);
+                Ok(false)
+            }
+            None => {
+                db.save_star(&star)?;
+                self.stars.insert(0, star);
+                Ok(true)
+            }
+        }
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&Star> {
+        self.stars.get(idx)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Star> {
+        self.stars.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.stars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stars.is_empty()
+    }
+
+    pub fn load(db: &Db) -> Result<Stars, Box<dyn Error>> {
+        Ok(Stars {
+            stars: db.load_stars()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rss::{Guid, Item};
+
+    use crate::store::db::Db;
+
+    use super::{Star, Stars};
+
+    const FEED: &str = "https://example.com/feed";
+
+    fn star(id: &str, starred: i64) -> Star {
+        Star {
+            item: Item {
+                title: Some(format!("Episode {}", id)),
+                guid: Some(Guid {
+                    value: id.to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            feed: FEED.to_string(),
+            podcast: Some("Wine About It".to_string()),
+            starred,
+        }
+    }
+
+    #[test]
+    fn toggles_and_keeps_stars() {
+        let db = Db::open_in_memory().unwrap();
+        let mut stars = Stars::default();
+        assert!(stars.toggle(star("1", 1), &db).unwrap());
+        assert!(stars.toggle(star("2", 2), &db).unwrap());
+        assert!(stars.is_starred(FEED, &star("1", 0).item));
+        assert!(!stars.is_starred("https://example.com/other", &star("1", 0).item));
+
+        // newest first
+        assert_eq!(stars.get(0).unwrap().title(), "Episode 2");
+        assert_eq!(Stars::load(&db).unwrap(), stars);
+
+        assert!(!stars.toggle(star("1", 3), &db).unwrap());
+        assert!(!stars.is_starred(FEED, &star("1", 0).item));
+        assert_eq!(Stars::load(&db).unwrap(), stars);
+    }
+}
@@ -0,0 +1,213 @@
+use std::{fs, path::Path};
+use url::Url;
+
+use crate::{
+    cache,
+    downloads::{Downloads, Job},
+    store,
+    subscriptions::Subscriptions,
+};
+
+// Usage is the disk space taken by a podcast's downloads and its cached feed, or by all of
+// them when it has no feed
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Usage {
+    pub feed: Option<String>,
+    pub title: String,
+    pub downloads: usize,
+    // in bytes
+    pub download_size: u64,
+    pub cache_size: u64,
+}
+
+impl Usage {
+    pub fn total(&self) -> u64 {
+        self.download_size + self.cache_size
+    }
+}
+
+// Storage is the disk space the app takes, in total and by podcast
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Storage {
+    // every podcast, the cached feeds of the ones not subscribed to anymore too
+    pub all: Usage,
+    // the database and the rest of the user's data
+    pub data_size: u64,
+    // biggest first
+    pub podcasts: Vec<Usage>,
+}
+
+impl Storage {
+    // Look at how big the files are on disk
+    pub fn measure(downloads: &Downloads, subscriptions: &Subscriptions) -> Storage {
+        let mut s = tally(
+            downloads.iter(),
+            subscriptions,
+            |j| file_size(&j.path) + file_size(&part(&j.path)),
+            |feed| {
+                Url::parse(feed)
+                    .ok()
+                    .and_then(|u| cache::size(&u))
+                    .unwrap_or(0)
+            },
+        );
+        s.all.cache_size = cache::dir().map_or(0, |d| dir_size(&d));
+        s.data_size = store::dir().map_or(0, |d| data_size(&d));
+        s
+    }
+
+    // The row of the list, the first being every podcast
+    pub fn get(&self, idx: usize) -> Option<&Usage> {
+        match idx {
+            0 => Some(&self.all),
+            i => self.podcasts.get(i - 1),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.podcasts.len() + 1
+    }
+}
+
+// Add up the podcasts' downloads and cached feeds, by the sizes of their files
+fn tally<'a>(
+    jobs: impl Iterator<Item = &'a Job>,
+    subscriptions: &Subscriptions,
+    download_size: impl Fn(&Job) -> u64,
+    cache_size: impl Fn(&str) -> u64,
+) -> Storage {
+    let mut podcasts: Vec<Usage> = subscriptions
+        .iter()
+        .map(|s| Usage {
+            feed: Some(s.url.clone()),
+            title: s.title.clone(),
+            ..Default::default()
+        })
+        .collect();
+    for j in jobs {
+        let idx = match podcasts
+            .iter()
+            .position(|u| u.feed.as_deref() == Some(j.feed.as_str()))
+        {
+            Some(idx) => idx,
+            None => {
+                podcasts.push(Usage {
+                    feed: Some(j.feed.clone()),
+                    title: j.podcast.clone().unwrap_or_else(|| j.feed.clone()),
+                    ..Default::default()
+                });
+                podcasts.len() - 1
+            }
+        };
+        podcasts[idx].downloads += 1;
+        podcasts[idx].download_size += download_size(j);
+    }
+    for u in podcasts.iter_mut() {
+        u.cache_size = u.feed.as_deref().map_or(0, &cache_size);
+    }
+    podcasts.sort_by_key(|u| std::cmp::Reverse(u.total()));
+
+    let all = Usage {
+        feed: None,
+        title: "All podcasts".to_string(),
+        downloads: podcasts.iter().map(|u| u.downloads).sum(),
+        download_size: podcasts.iter().map(|u| u.download_size).sum(),
+        cache_size: podcasts.iter().map(|u| u.cache_size).sum(),
+    };
+    Storage {
+        all,
+        data_size: 0,
+        podcasts,
+    }
+}
+
+fn part(path: &Path) -> std::path::PathBuf {
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    part.into()
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map_or(0, |m| m.len())
+}
+
+// Size of the files in the directory and the ones in it
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|e| match e.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&e.path()),
+            Ok(_) => e.metadata().map_or(0, |m| m.len()),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+// Size of the user's data, leaving out other profiles and the downloads kept there
+fn data_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+        .map(|e| e.metadata().map_or(0, |m| m.len()))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use rss::Item;
+    use std::path::PathBuf;
+
+    use crate::{
+        downloads::{Job, Status},
+        subscriptions::Subscriptions,
+    };
+
+    use super::tally;
+
+    #[test]
+    fn adds_up_by_podcast() {
+        let mut subs = Subscriptions::default();
+        subs.add("a", "Podcast A");
+        subs.add("b", "Podcast B");
+        let job = |feed: &str, path: &str| Job {
+            item: Item::default(),
+            feed: feed.to_string(),
+            podcast: Some("Podcast C".to_string()),
+            path: PathBuf::from(path),
+            status: Status::Done,
+            finished: None,
+            auto: false,
+        };
+        let jobs = [job("b", "1"), job("b", "2"), job("c", "3")];
+        let s = tally(
+            jobs.iter(),
+            &subs,
+            |_| 100,
+            |feed| u64::from(feed != "c") * 10,
+        );
+
+        let rows = s
+            .podcasts
+            .iter()
+            .map(|u| (u.title.as_str(), u.downloads, u.total()))
+            .collect::<Vec<(&str, usize, u64)>>();
+        // biggest first, downloads of podcasts not subscribed to anymore too
+        assert_eq!(
+            rows,
+            vec![
+                ("Podcast B", 2, 210),
+                ("Podcast C", 1, 100),
+                ("Podcast A", 0, 10)
+            ]
+        );
+        assert_eq!(s.all.downloads, 3);
+        assert_eq!(s.all.total(), 320);
+        assert_eq!(s.get(1).map(|u| u.title.as_str()), Some("Podcast B"));
+    }
+}
@@ -0,0 +1,768 @@
+use rss::{Channel, Item};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::{error::Error, fs, path::PathBuf, time::Duration};
+use tracing::{error, info};
+
+use crate::{
+    backup::{Backup, Position},
+    downloads::{Job, Status},
+    feed::{episode_id, published},
+    history::Listen,
+    played::Played,
+    stars::Star,
+};
+
+use super::{dir, load};
+
+// Tables of the database as first released. Episodes keep their whole feed entry as JSON,
+// so a feed can be shown again exactly as it was. An episode keeps its row, and with it its
+// position, after its feed stops listing it; `idx` is its place in the feed, NULL once it
+// isn't listed.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS podcasts (
+        url TEXT PRIMARY KEY,
+        title TEXT NOT NULL,
+        channel TEXT NOT NULL,
+        fetched INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS episodes (
+        podcast TEXT NOT NULL,
+        id TEXT NOT NULL,
+        idx INTEGER,
+        title TEXT,
+        published INTEGER,
+        item TEXT NOT NULL,
+        position REAL NOT NULL DEFAULT 0,
+        PRIMARY KEY (podcast, id)
+    );
+    CREATE TABLE IF NOT EXISTS played (
+        podcast TEXT NOT NULL,
+        episode TEXT NOT NULL,
+        PRIMARY KEY (podcast, episode)
+    );
+    CREATE TABLE IF NOT EXISTS stars (
+        podcast TEXT NOT NULL,
+        episode TEXT NOT NULL,
+        podcast_title TEXT,
+        item TEXT NOT NULL,
+        starred INTEGER NOT NULL,
+        PRIMARY KEY (podcast, episode)
+    );
+    CREATE TABLE IF NOT EXISTS history (
+        podcast TEXT NOT NULL,
+        podcast_title TEXT,
+        item TEXT NOT NULL,
+        started INTEGER NOT NULL,
+        stopped INTEGER NOT NULL,
+        position REAL NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS downloads (
+        podcast TEXT NOT NULL,
+        episode TEXT NOT NULL,
+        path TEXT NOT NULL,
+        size INTEGER,
+        finished INTEGER,
+        PRIMARY KEY (podcast, episode)
+    );
+";
+
+// Changes to the database, in order. Its `user_version` is how many of them it went
+// through. When a release changes the tables, add the SQL doing it at the end; released
+// migrations never change.
+const MIGRATIONS: &[&str] = &[
+    SCHEMA,
+    // downloads keep their episode, so that they can be listed and played without the feed
+    "ALTER TABLE downloads ADD COLUMN item TEXT;
+     ALTER TABLE downloads ADD COLUMN podcast_title TEXT;",
+    // downloads queued by the podcast downloading new episodes by itself wait for the
+    // schedule
+    "ALTER TABLE downloads ADD COLUMN auto INTEGER NOT NULL DEFAULT 0;",
+    // downloads that were deleted, so that podcasts don't download them again by themselves
+    "CREATE TABLE deleted_downloads (
+        podcast TEXT NOT NULL,
+        episode TEXT NOT NULL,
+        PRIMARY KEY (podcast, episode)
+     );",
+    // what was brought over from the TOML files of older releases. Databases from before
+    // this imported the played episodes when they were made, unless that failed and there
+    // are none.
+    "CREATE TABLE imports (name TEXT PRIMARY KEY);
+     INSERT INTO imports SELECT 'played' WHERE EXISTS (SELECT 1 FROM played);",
+];
+
+// Db is the SQLite database in the store, `podcasts.db`, which holds the podcasts and
+// episodes that were loaded, which of them were played or starred, how far into them
+// playback got, every time they were listened to and where they were downloaded to
+#[derive(Debug)]
+pub struct Db {
+    conn: Connection,
+}
+
+impl Db {
+    pub fn open() -> Result<Db, Box<dyn Error>> {
+        let d = dir().ok_or("no data directory")?;
+        fs::create_dir_all(&d)?;
+        let p = d.join("podcasts.db");
+        let mut db = Db::with(Connection::open(&p)?)?;
+        // private feed URLs contain access tokens, so nothing is readable by other users
+        #[cfg(unix)]
+        fs::set_permissions(&p, std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+        // tried again the next time the app starts
+        if let Err(e) = db.import() {
+            error!("failed to import played episodes: {:?}", e);
+        }
+        Ok(db)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Db, Box<dyn Error>> {
+        Db::with(Connection::open_in_memory()?)
+    }
+
+    fn with(conn: Connection) -> Result<Db, Box<dyn Error>> {
+        let mut db = Db { conn };
+        db.migrate(MIGRATIONS)?;
+        Ok(db)
+    }
+
+    // Run the migrations the database didn't go through yet, each all or nothing
+    fn migrate(&mut self, migrations: &[&str]) -> Result<(), Box<dyn Error>> {
+        let version: usize = self
+            .conn
+            .pragma_query_value(None, "user_version", |r| r.get(0))?;
+        // tables changed by a newer release may not be understood, so don't touch them
+        if version > migrations.len() {
+            return Err(format!(
+                "the database was made by a newer release (version {}), update the app",
+                version
+            )
+            .into());
+        }
+        for (idx, m) in migrations.iter().enumerate().skip(version) {
+            info!("migrating the database to version {}", idx + 1);
+            let tx = self.conn.transaction()?;
+            tx.execute_batch(m)?;
+            tx.pragma_update(None, "user_version", idx + 1)?;
+            tx.commit()?;
+        }
+        Ok(())
+    }
+
+    // Bring over what older releases kept in TOML files, unless it was already
+    fn import(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.imported("played")? {
+            return Ok(());
+        }
+        let played = load::<Played>("played")?;
+        self.import_played(&played)
+    }
+
+    fn imported(&self, name: &str) -> Result<bool, Box<dyn Error>> {
+        let found = self
+            .conn
+            .query_row("SELECT 1 FROM imports WHERE name = ?1", [name], |_| Ok(()))
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    fn import_played(&mut self, played: &Played) -> Result<(), Box<dyn Error>> {
+        if played != &Played::default() {
+            info!("importing played episodes into the database");
+            self.save_played(played.iter().map(|(p, e)| (p, e, true)))?;
+        }
+        self.conn
+            .execute("INSERT OR IGNORE INTO imports (name) VALUES ('played')", [])?;
+        Ok(())
+    }
+
+    // Keep a copy of the feed, along with every episode it lists
+    pub fn save_feed(&mut self, url: &str, c: &Channel) -> Result<(), Box<dyn Error>> {
+        let podcast = Channel {
+            items: vec![],
+            ..c.clone()
+        };
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO podcasts (url, title, channel, fetched)
+             VALUES (?1, ?2, ?3, strftime('%s', 'now'))
+             ON CONFLICT (url) DO UPDATE SET
+                title = excluded.title, channel = excluded.channel, fetched = excluded.fetched",
+            params![url, c.title(), serde_json::to_string(&podcast)?],
+        )?;
+        tx.execute(
+            "UPDATE episodes SET idx = NULL WHERE podcast = ?1",
+            params![url],
+        )?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO episodes (podcast, id, idx, title, published, item)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (podcast, id) DO UPDATE SET
+                    idx = excluded.idx, title = excluded.title,
+                    published = excluded.published, item = excluded.item",
+            )?;
+            for (idx, item) in c.items().iter().enumerate() {
+                // an episode without an id can't be told apart from the others
+                let Some(id) = episode_id(item) else {
+                    continue;
+                };
+                insert.execute(params![
+                    url,
+                    id,
+                    idx,
+                    item.title(),
+                    published(item),
+                    serde_json::to_string(item)?
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    // The copy of the feed from the last time it was loaded
+    pub fn load_feed(&self, url: &str) -> Result<Option<Channel>, Box<dyn Error>> {
+        let channel = self
+            .conn
+            .query_row(
+                "SELECT channel FROM podcasts WHERE url = ?1",
+                params![url],
+                |r| r.get::<_, String>(0),
+            )
+            .optional()?;
+        let Some(channel) = channel else {
+            return Ok(None);
+        };
+        let mut c: Channel = serde_json::from_str(channel.as_str())?;
+        let mut stmt = self.conn.prepare(
+            "SELECT item FROM episodes WHERE podcast = ?1 AND idx IS NOT NULL ORDER BY idx",
+        )?;
+        let items = stmt.query_map(params![url], |r| r.get::<_, String>(0))?;
+        for item in items {
+            c.items.push(serde_json::from_str::<Item>(item?.as_str())?);
+        }
+        Ok(Some(c))
+    }
+
+    // Remember how far into the episode playback got
+    pub fn save_position(
+        &self,
+        podcast: &str,
+        item: &Item,
+        position: Duration,
+    ) -> Result<(), Box<dyn Error>> {
+        let Some(id) = episode_id(item) else {
+            return Ok(());
+        };
+        self.conn.execute(
+            "INSERT INTO episodes (podcast, id, title, published, item, position)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT (podcast, id) DO UPDATE SET position = excluded.position",
+            params![
+                podcast,
+                id,
+                item.title(),
+                published(item),
+                serde_json::to_string(item)?,
+                position.as_secs_f64()
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Where playback of the episode was left, zero if it never started
+    pub fn position(&self, podcast: &str, id: &str) -> Result<Duration, Box<dyn Error>> {
+        let secs = self
+            .conn
+            .query_row(
+                "SELECT position FROM episodes WHERE podcast = ?1 AND id = ?2",
+                params![podcast, id],
+                |r| r.get::<_, f64>(0),
+            )
+            .optional()?;
+        Ok(Duration::from_secs_f64(secs.unwrap_or_default().max(0.0)))
+    }
+
+    pub fn load_played(&self) -> Result<Played, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare("SELECT podcast, episode FROM played")?;
+        let rows = stmt.query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)))?;
+        Ok(rows.collect::<Result<Played, _>>()?)
+    }
+
+    // Mark episodes as played or unplayed, as (podcast, episode id, played)
+    pub fn save_played<'a, I>(&mut self, changes: I) -> Result<(), Box<dyn Error>>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str, bool)>,
+    {
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO played (podcast, episode) VALUES (?1, ?2) ON CONFLICT DO NOTHING",
+            )?;
+            let mut delete =
+                tx.prepare("DELETE FROM played WHERE podcast = ?1 AND episode = ?2")?;
+            for (podcast, episode, played) in changes {
+                match played {
+                    true => insert.execute(params![podcast, episode])?,
+                    false => delete.execute(params![podcast, episode])?,
+                };
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    // Starred episodes, newest first
+    pub fn load_stars(&self) -> Result<Vec<Star>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT podcast, item, starred, podcast_title FROM stars ORDER BY starred DESC, rowid DESC",
+        )?;
+        let rows = stmt.query_map([], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, String>(1)?,
+                r.get::<_, i64>(2)?,
+                r.get::<_, Option<String>>(3)?,
+            ))
+        })?;
+        let mut stars = vec![];
+        for row in rows {
+            let (feed, item, starred, podcast) = row?;
+            stars.push(Star {
+                item: serde_json::from_str(item.as_str())?,
+                feed,
+                podcast,
+                starred,
+            });
+        }
+        Ok(stars)
+    }
+
+    pub fn save_star(&self, star: &Star) -> Result<(), Box<dyn Error>> {
+        let id = episode_id(&star.item).ok_or("the episode has no id")?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO stars (podcast, episode, podcast_title, item, starred)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                star.feed,
+                id,
+                star.podcast,
+                serde_json::to_string(&star.item)?,
+                star.starred
+            ],
+        )?;
+        Ok(())
+    }
+
+    // The latest listening sessions, newest first, or all of them when the limit is None
+    pub fn load_listens(&self, limit: Option<usize>) -> Result<Vec<Listen>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT podcast, podcast_title, item, started, stopped, position FROM history
+             ORDER BY started DESC, rowid DESC LIMIT ?1",
+        )?;
+        // a negative limit is no limit
+        let limit = limit.map_or(-1, |l| l as i64);
+        let rows = stmt.query_map(params![limit], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, Option<String>>(1)?,
+                r.get::<_, String>(2)?,
+                r.get::<_, i64>(3)?,
+                r.get::<_, i64>(4)?,
+                r.get::<_, f64>(5)?,
+            ))
+        })?;
+        let mut listens = vec![];
+        for row in rows {
+            let (feed, podcast, item, started, stopped, position) = row?;
+            listens.push(Listen {
+                item: serde_json::from_str(item.as_str())?,
+                feed,
+                podcast,
+                started,
+                stopped,
+                position: Duration::from_secs_f64(position.max(0.0)),
+            });
+        }
+        Ok(listens)
+    }
+
+    pub fn save_listen(&self, l: &Listen) -> Result<(), Box<dyn Error>> {
+        self.conn.execute(
+            "INSERT INTO history (podcast, podcast_title, item, started, stopped, position)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                l.feed,
+                l.podcast,
+                serde_json::to_string(&l.item)?,
+                l.started,
+                l.stopped,
+                l.position.as_secs_f64()
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Forget all but the latest listening sessions
+    pub fn prune_listens(&self, keep: usize) -> Result<(), Box<dyn Error>> {
+        self.conn.execute(
+            "DELETE FROM history WHERE rowid NOT IN (
+                SELECT rowid FROM history ORDER BY started DESC, rowid DESC LIMIT ?1
+             )",
+            params![keep as i64],
+        )?;
+        Ok(())
+    }
+
+    // Every episode that playback got into
+    pub fn positions(&self) -> Result<Vec<Position>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT podcast, item, position FROM episodes WHERE position > 0
+             ORDER BY podcast, id",
+        )?;
+        let rows = stmt.query_map([], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, String>(1)?,
+                r.get::<_, f64>(2)?,
+            ))
+        })?;
+        let mut positions = vec![];
+        for row in rows {
+            let (feed, item, position) = row?;
+            positions.push(Position {
+                feed,
+                item: serde_json::from_str(item.as_str())?,
+                position: Duration::from_secs_f64(position),
+            });
+        }
+        Ok(positions)
+    }
+
+    // Replace the played episodes, positions, stars and history with the backup's, all
+    // or nothing
+    pub fn restore(&mut self, b: &Backup) -> Result<(), Box<dyn Error>> {
+        let tx = self.conn.transaction()?;
+        tx.execute_batch(
+            "DELETE FROM played; DELETE FROM stars; DELETE FROM history;
+             UPDATE episodes SET position = 0;",
+        )?;
+        for (podcast, episode) in b.played.iter() {
+            tx.execute(
+                "INSERT INTO played (podcast, episode) VALUES (?1, ?2)",
+                params![podcast, episode],
+            )?;
+        }
+        for p in b.positions.iter() {
+            let Some(id) = episode_id(&p.item) else {
+                continue;
+            };
+            tx.execute(
+                "INSERT INTO episodes (podcast, id, title, published, item, position)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (podcast, id) DO UPDATE SET position = excluded.position",
+                params![
+                    p.feed,
+                    id,
+                    p.item.title(),
+                    published(&p.item),
+                    serde_json::to_string(&p.item)?,
+                    p.position.as_secs_f64()
+                ],
+            )?;
+        }
+        for s in b.stars.iter() {
+            let Some(id) = episode_id(&s.item) else {
+                continue;
+            };
+            tx.execute(
+                "INSERT OR REPLACE INTO stars (podcast, episode, podcast_title, item, starred)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    s.feed,
+                    id,
+                    s.podcast,
+                    serde_json::to_string(&s.item)?,
+                    s.starred
+                ],
+            )?;
+        }
+        // oldest first, so that they come back in the same order
+        for l in b.history.iter().rev() {
+            tx.execute(
+                "INSERT INTO history (podcast, podcast_title, item, started, stopped, position)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    l.feed,
+                    l.podcast,
+                    serde_json::to_string(&l.item)?,
+                    l.started,
+                    l.stopped,
+                    l.position.as_secs_f64()
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    // Downloads, newest first. The ones that didn't finish before the app was closed are
+    // queued again.
+    pub fn load_downloads(&self) -> Result<Vec<Job>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT podcast, podcast_title, item, path, finished, auto FROM downloads
+             WHERE item IS NOT NULL ORDER BY finished IS NOT NULL, finished DESC, rowid DESC",
+        )?;
+        let rows = stmt.query_map([], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, Option<String>>(1)?,
+                r.get::<_, String>(2)?,
+                r.get::<_, String>(3)?,
+                r.get::<_, Option<i64>>(4)?,
+                r.get::<_, bool>(5)?,
+            ))
+        })?;
+        let mut jobs = vec![];
+        for row in rows {
+            let (feed, podcast, item, path, finished, auto) = row?;
+            jobs.push(Job {
+                item: serde_json::from_str(item.as_str())?,
+                feed,
+                podcast,
+                path: PathBuf::from(path),
+                status: match finished {
+                    Some(_) => Status::Done,
+                    None => Status::Queued,
+                },
+                finished,
+                auto,
+            });
+        }
+        Ok(jobs)
+    }
+
+    // Remember a download, as finished once it has finished
+    pub fn save_download(&self, job: &Job, size: Option<u64>) -> Result<(), Box<dyn Error>> {
+        let id = episode_id(&job.item).ok_or("the episode has no id")?;
+        self.conn.execute(
+            "DELETE FROM deleted_downloads WHERE podcast = ?1 AND episode = ?2",
+            params![job.feed, id],
+        )?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO downloads (podcast, episode, path, size, finished, item, podcast_title, auto)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                job.feed,
+                id,
+                job.path.to_string_lossy(),
+                size,
+                job.finished,
+                serde_json::to_string(&job.item)?,
+                job.podcast,
+                job.auto
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_download(&self, podcast: &str, item: &Item) -> Result<(), Box<dyn Error>> {
+        let Some(id) = episode_id(item) else {
+            return Ok(());
+        };
+        self.conn.execute(
+            "DELETE FROM downloads WHERE podcast = ?1 AND episode = ?2",
+            params![podcast, id],
+        )?;
+        Ok(())
+    }
+
+    // Forget a download that was deleted, remembering that it was
+    pub fn delete_download(&self, podcast: &str, item: &Item) -> Result<(), Box<dyn Error>> {
+        self.remove_download(podcast, item)?;
+        let Some(id) = episode_id(item) else {
+            return Ok(());
+        };
+        self.conn.execute(
+            "INSERT OR IGNORE INTO deleted_downloads (podcast, episode) VALUES (?1, ?2)",
+            params![podcast, id],
+        )?;
+        Ok(())
+    }
+
+    // Episodes whose downloads were deleted, by podcast and episode id
+    pub fn deleted_downloads(&self) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT podcast, episode FROM deleted_downloads")?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    pub fn remove_star(&self, podcast: &str, item: &Item) -> Result<(), Box<dyn Error>> {
+        let Some(id) = episode_id(item) else {
+            return Ok(());
+        };
+        self.conn.execute(
+            "DELETE FROM stars WHERE podcast = ?1 AND episode = ?2",
+            params![podcast, id],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use rss::{Channel, Guid, Item};
+    use std::{path::PathBuf, time::Duration};
+
+    use crate::{
+        downloads::{Job, Status},
+        played::Played,
+    };
+
+    use super::{Db, MIGRATIONS};
+
+    pub const FEED: &str = "https://example.com/feed";
+
+    // An episode with the id as its guid, shared with other tests of what's stored
+    pub fn item(id: &str) -> Item {
+        Item {
+            title: Some(format!("Episode {}", id)),
+            guid: Some(Guid {
+                value: id.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn channel(ids: &[&str]) -> Channel {
+        Channel {
+            title: "Wine About It".to_string(),
+            items: ids.iter().map(|id| item(id)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn keeps_feeds() {
+        let mut db = Db::open_in_memory().unwrap();
+        assert_eq!(db.load_feed(FEED).unwrap(), None);
+
+        db.save_feed(FEED, &channel(&["2", "1"])).unwrap();
+        assert_eq!(db.load_feed(FEED).unwrap(), Some(channel(&["2", "1"])));
+
+        // episodes the feed stopped listing aren't shown, but keep their position
+        db.save_position(FEED, &item("1"), Duration::from_secs(90))
+            .unwrap();
+        db.save_feed(FEED, &channel(&["3", "2"])).unwrap();
+        assert_eq!(db.load_feed(FEED).unwrap(), Some(channel(&["3", "2"])));
+        assert_eq!(db.position(FEED, "1").unwrap(), Duration::from_secs(90));
+    }
+
+    #[test]
+    fn keeps_positions() {
+        let mut db = Db::open_in_memory().unwrap();
+        assert_eq!(db.position(FEED, "1").unwrap(), Duration::ZERO);
+
+        db.save_position(FEED, &item("1"), Duration::from_millis(1500))
+            .unwrap();
+        // loading the feed again doesn't lose it
+        db.save_feed(FEED, &channel(&["1"])).unwrap();
+        assert_eq!(db.position(FEED, "1").unwrap(), Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn migrates() {
+        let mut db = Db::open_in_memory().unwrap();
+        db.save_feed(FEED, &channel(&["1"])).unwrap();
+
+        let next = [MIGRATIONS, &["ALTER TABLE podcasts ADD COLUMN author TEXT"]].concat();
+        db.migrate(&next).unwrap();
+        // again is a no-op
+        db.migrate(&next).unwrap();
+        assert_eq!(db.load_feed(FEED).unwrap(), Some(channel(&["1"])));
+
+        // a failed migration leaves the database as it was
+        let broken = [&next[..], &["DELETE FROM podcasts; BROKEN"]].concat();
+        assert!(db.migrate(&broken).is_err());
+        assert_eq!(db.load_feed(FEED).unwrap(), Some(channel(&["1"])));
+        // and the database is newer than this list
+        assert!(db.migrate(MIGRATIONS).is_err());
+    }
+
+    #[test]
+    fn keeps_downloads() {
+        let db = Db::open_in_memory().unwrap();
+        let job = Job {
+            item: item("1"),
+            feed: FEED.to_string(),
+            podcast: Some("Wine About It".to_string()),
+            path: PathBuf::from("/downloads/Wine About It/Episode 1.mp3"),
+            status: Status::Done,
+            finished: Some(1),
+            auto: false,
+        };
+        db.save_download(&job, Some(1000)).unwrap();
+        // downloading it again replaces it
+        db.save_download(&job, Some(1000)).unwrap();
+        assert_eq!(db.load_downloads().unwrap(), vec![job.clone()]);
+
+        // one that didn't finish comes back queued, before the finished ones
+        let unfinished = Job {
+            item: item("2"),
+            path: PathBuf::from("/downloads/Wine About It/Episode 2.mp3"),
+            status: Status::Active(10, Some(1000)),
+            finished: None,
+            auto: true,
+            ..job.clone()
+        };
+        db.save_download(&unfinished, None).unwrap();
+        let loaded = db.load_downloads().unwrap();
+        assert_eq!(loaded[0].status, Status::Queued);
+        assert!(loaded[0].auto);
+        assert_eq!(loaded[1], job);
+
+        db.remove_download(FEED, &item("2")).unwrap();
+        assert_eq!(db.load_downloads().unwrap(), vec![job.clone()]);
+
+        // deleting one is remembered until it's downloaded again
+        db.delete_download(FEED, &item("1")).unwrap();
+        assert!(db.load_downloads().unwrap().is_empty());
+        let deleted = (FEED.to_string(), "1".to_string());
+        assert_eq!(db.deleted_downloads().unwrap(), vec![deleted]);
+        db.save_download(&job, None).unwrap();
+        assert!(db.deleted_downloads().unwrap().is_empty());
+    }
+
+    #[test]
+    fn keeps_played() {
+        let mut db = Db::open_in_memory().unwrap();
+        let mut p = Played::default();
+        p.mark_all(FEED, ["1", "2"]);
+        p.save(&mut db).unwrap();
+        assert_eq!(db.load_played().unwrap(), p);
+
+        p.set(FEED, "1", false);
+        p.save(&mut db).unwrap();
+        assert_eq!(db.load_played().unwrap(), p);
+
+        // only what changed is written, what others marked in the meantime stays
+        db.save_played([(FEED, "3", true)]).unwrap();
+        p.set(FEED, "4", true);
+        p.save(&mut db).unwrap();
+        assert_eq!(db.load_played().unwrap().iter().count(), 3);
+    }
+
+    #[test]
+    fn imports_played_once() {
+        let mut db = Db::open_in_memory().unwrap();
+        assert!(!db.imported("played").unwrap());
+        let mut p = Played::default();
+        p.mark_all(FEED, ["1"]);
+        db.import_played(&p).unwrap();
+        assert!(db.imported("played").unwrap());
+        assert_eq!(db.load_played().unwrap(), p);
+    }
+}
@@ -0,0 +1,270 @@
+use serde::{de::DeserializeOwned, Serialize};
+pub mod db;
+
+use std::{
+    error::Error,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+use crate::profile;
+
+// The store keeps the user's data as TOML files in `$XDG_DATA_HOME/podcasts`, one file per
+// kind of data (e.g. `subscriptions.toml`), and what grows with every feed loaded in a
+// SQLite database next to them (see `db`)
+pub fn dir() -> Option<PathBuf> {
+    let d = dirs::data_dir()?.join("podcasts");
+    // each profile keeps its own data, see `profile`
+    Some(match profile::current() {
+        Some(p) => d.join("profiles").join(p),
+        None => d,
+    })
+}
+
+pub fn path(name: &str) -> Option<PathBuf> {
+    dir().map(|d| d.join(format!("{}.toml", name)))
+}
+
+// Which kinds of data changed since they were last saved
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dirty {
+    pub subscriptions: bool,
+    pub played: bool,
+    pub queue: bool,
+    pub metrics: bool,
+    pub bookmarks: bool,
+}
+
+impl Dirty {
+    pub fn any(&self) -> bool {
+        self.subscriptions || self.played || self.queue || self.metrics || self.bookmarks
+    }
+
+    // The kinds that changed, leaving out those in `other`
+    pub fn except(&self, other: Dirty) -> Dirty {
+        Dirty {
+            subscriptions: self.subscriptions && !other.subscriptions,
+            played: self.played && !other.played,
+            queue: self.queue && !other.queue,
+            metrics: self.metrics && !other.metrics,
+            bookmarks: self.bookmarks && !other.bookmarks,
+        }
+    }
+}
+
+// Version of the files' format, saved in each of them as `version`. Files saved before
+// there was one are version 0.
+const VERSION: i64 = 2;
+
+// Migration upgrades the named data to the version `to`, from the one before
+struct Migration {
+    name: &'static str,
+    to: i64,
+    run: fn(&mut toml::Table),
+}
+
+// Changes to the files' format, in order. When a release changes how some data is saved,
+// add a migration for it here and bump VERSION. Released migrations never change.
+const MIGRATIONS: &[Migration] = &[Migration {
+    name: "subscriptions",
+    to: 2,
+    run: auto_download_count,
+}];
+
+// `auto_download` went from on or off to how many of the latest episodes to download
+fn auto_download_count(t: &mut toml::Table) {
+    let Some(feeds) = t.get_mut("feeds").and_then(|f| f.as_array_mut()) else {
+        return;
+    };
+    for f in feeds.iter_mut().filter_map(|f| f.as_table_mut()) {
+        if let Some(on) = f.get("auto_download").and_then(|a| a.as_bool()) {
+            f.insert("auto_download".to_string(), i64::from(on).into());
+        }
+    }
+}
+
+// Load the named data, or its default if nothing was saved yet. Files saved by older
+// releases are upgraded, keeping a copy of how they were.
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> Result<T, Box<dyn Error>> {
+    let p = match path(name) {
+        Some(p) if p.exists() => p,
+        _ => return Ok(T::default()),
+    };
+    let mut t: toml::Table = toml::from_str(fs::read_to_string(&p)?.as_str())?;
+    let from = migrate(name, &mut t, MIGRATIONS, VERSION)?;
+    if from < VERSION && MIGRATIONS.iter().any(|m| m.name == name && m.to > from) {
+        info!("upgraded {} from version {}", name, from);
+        fs::copy(&p, p.with_extension(format!("v{}.toml", from)))?;
+    }
+    Ok(t.try_into()?)
+}
+
+// Run the migrations of the named data that it didn't go through yet, returning the
+// version it was at
+fn migrate(
+    name: &str,
+    t: &mut toml::Table,
+    migrations: &[Migration],
+    version: i64,
+) -> Result<i64, Box<dyn Error>> {
+    let from = t
+        .remove("version")
+        .and_then(|v| v.as_integer())
+        .unwrap_or(0);
+    // what a newer release saved may not be understood, and saving over it would lose it
+    if from > version {
+        return Err(format!(
+            "{} was saved by a newer release (version {}), update the app",
+            name, from
+        )
+        .into());
+    }
+    for m in migrations.iter().filter(|m| m.name == name && m.to > from) {
+        (m.run)(t);
+    }
+    Ok(from)
+}
+
+// The data as TOML, with the version of the format
+fn serialize<T: Serialize>(value: &T) -> Result<String, Box<dyn Error>> {
+    let mut t = toml::Table::try_from(value)?;
+    t.insert("version".to_string(), toml::Value::Integer(VERSION));
+    Ok(toml::to_string(&t)?)
+}
+
+pub fn save<T: Serialize>(name: &str, value: &T) -> Result<(), Box<dyn Error>> {
+    let p = path(name).ok_or("no data directory")?;
+    if let Some(dir) = p.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    write(&p, serialize(value)?.as_str())
+}
+
+// Replace the file's contents all at once: they're written next to it first, so a crash
+// or a full disk never leaves it half written
+fn write(p: &Path, contents: &str) -> Result<(), Box<dyn Error>> {
+    let tmp = p.with_extension("toml.tmp");
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // private feed URLs contain access tokens, so nothing is readable by other users
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut f = options.open(&tmp)?;
+    f.write_all(contents.as_bytes())?;
+    f.sync_all()?;
+    fs::rename(&tmp, p)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::subscriptions::Subscriptions;
+
+    use super::{migrate, serialize, Dirty, Migration, VERSION};
+
+    #[test]
+    fn saves_version() {
+        let mut s = Subscriptions::default();
+        s.add("https://example.com/feed", "Example");
+        let mut t: toml::Table = toml::from_str(serialize(&s).unwrap().as_str()).unwrap();
+        assert_eq!(t["version"].as_integer(), Some(VERSION));
+
+        assert_eq!(
+            migrate("subscriptions", &mut t, &[], VERSION).unwrap(),
+            VERSION
+        );
+        assert_eq!(t.try_into::<Subscriptions>().unwrap(), s);
+    }
+
+    #[test]
+    fn replaces_whole_file() {
+        let dir = std::env::temp_dir().join(format!("podcasts-store-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let p = dir.join("queue.toml");
+        std::fs::write(&p, "a much longer file than what replaces it").unwrap();
+        super::write(&p, "short").unwrap();
+        assert_eq!(std::fs::read_to_string(&p).unwrap(), "short");
+        assert!(!p.with_extension("toml.tmp").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn leaves_out_kinds() {
+        let dirty = Dirty {
+            subscriptions: true,
+            queue: true,
+            ..Default::default()
+        };
+        let failed = Dirty {
+            queue: true,
+            metrics: true,
+            ..Default::default()
+        };
+        let left = dirty.except(failed);
+        assert!(left.subscriptions && !left.queue && !left.metrics);
+        assert!(!failed.except(failed).any());
+    }
+
+    #[test]
+    fn counts_auto_downloads() {
+        let mut t: toml::Table = toml::from_str(
+            "[[feeds]]\nurl = \"a\"\nauto_download = true\n\
+             [[feeds]]\nurl = \"b\"\nauto_download = false\n\
+             [[feeds]]\nurl = \"c\"",
+        )
+        .unwrap();
+        super::auto_download_count(&mut t);
+        let counts = t["feeds"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f.get("auto_download").and_then(|a| a.as_integer()))
+            .collect::<Vec<Option<i64>>>();
+        assert_eq!(counts, vec![Some(1), Some(0), None]);
+    }
+
+    #[test]
+    fn runs_migrations() {
+        let rename = |t: &mut toml::Table| {
+            if let Some(v) = t.remove("feeds") {
+                t.insert("podcasts".to_string(), v);
+            }
+        };
+        let count = |t: &mut toml::Table| {
+            let n = t.get("podcasts").and_then(|v| v.as_array()).map(Vec::len);
+            t.insert("count".to_string(), (n.unwrap_or(0) as i64).into());
+        };
+        let migrations = [
+            Migration {
+                name: "subscriptions",
+                to: 2,
+                run: rename,
+            },
+            Migration {
+                name: "queue",
+                to: 2,
+                run: |t| t.clear(),
+            },
+            Migration {
+                name: "subscriptions",
+                to: 3,
+                run: count,
+            },
+        ];
+
+        // files without a version went through none of them
+        let mut t: toml::Table = toml::from_str("feeds = [1, 2]").unwrap();
+        assert_eq!(migrate("subscriptions", &mut t, &migrations, 3).unwrap(), 0);
+        assert_eq!(t, toml::from_str("podcasts = [1, 2]\ncount = 2").unwrap());
+
+        // only the ones after the file's version run
+        let mut t: toml::Table = toml::from_str("version = 2\npodcasts = [1]").unwrap();
+        assert_eq!(migrate("subscriptions", &mut t, &migrations, 3).unwrap(), 2);
+        assert_eq!(t, toml::from_str("podcasts = [1]\ncount = 1").unwrap());
+
+        let mut t: toml::Table = toml::from_str("version = 4").unwrap();
+        assert!(migrate("subscriptions", &mut t, &migrations, 3).is_err());
+    }
+}
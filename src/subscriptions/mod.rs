@@ -0,0 +1,507 @@
+pub mod settings;
+
+use rss::Channel;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+use crate::{
+    feed::{episode_id, published},
+    played::Played,
+    store,
+};
+
+// Subscription is a podcast in the user's library
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subscription {
+    // feed URL. Private feeds keep their token here, so the file is only readable by
+    // the user and the URL must go through `feed::private::redact` before being shown.
+    pub url: String,
+    pub title: String,
+    // when the newest episode came out, in seconds since the epoch
+    #[serde(default)]
+    pub updated: Option<i64>,
+    // ids of the episodes the last time the feed was loaded (see `feed::episode_id`)
+    #[serde(default)]
+    pub episodes: Vec<String>,
+    // when an episode was last played, in seconds since the epoch
+    #[serde(default)]
+    pub last_played: Option<i64>,
+    // playback speed for the podcast's episodes, instead of the global one
+    #[serde(default)]
+    pub speed: Option<f32>,
+    // seconds skipped at the start of each episode, and before its end
+    #[serde(default)]
+    pub skip_intro: u64,
+    #[serde(default)]
+    pub skip_outro: u64,
+    // skip ad chapters, or follow `skip_ads` in the config when None
+    #[serde(default)]
+    pub skip_ads: Option<bool>,
+    // episodes that came out since the podcast was last opened
+    #[serde(default)]
+    pub new: usize,
+    // how many of the latest episodes to download when a refresh finds new ones, 0 for
+    // none
+    #[serde(default)]
+    pub auto_download: usize,
+    // downloads to keep and days to keep played ones, or the config's `[downloads]` ones
+    // when None. See `downloads::Retention`.
+    #[serde(default)]
+    pub keep: Option<usize>,
+    #[serde(default)]
+    pub delete_played_after: Option<u64>,
+    // minutes between refreshes while the app runs, or the config's `[refresh]` interval
+    // when None. 0 only refreshes at startup and with /refresh.
+    #[serde(default)]
+    pub refresh: Option<u64>,
+    // say so in the status line when a refresh finds new episodes
+    #[serde(default)]
+    pub notify: bool,
+    // labels to filter the library by, e.g. "news", lowercase and sorted
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // put away: not refreshed or listed in the library, but kept with its settings
+    #[serde(default)]
+    pub archived: bool,
+}
+
+impl Subscription {
+    pub fn unplayed(&self, played: &Played) -> usize {
+        self.episodes
+            .iter()
+            .filter(|e| !played.is_played(self.url.as_str(), e))
+            .count()
+    }
+}
+
+// How the library is sorted
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sort {
+    // newest episode first
+    #[default]
+    Updated,
+    // most unplayed episodes first
+    Unplayed,
+    Alphabetical,
+    // most recently played first
+    Played,
+}
+
+impl Sort {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sort::Updated => "recently updated",
+            Sort::Unplayed => "unplayed episodes",
+            Sort::Alphabetical => "title",
+            Sort::Played => "recently played",
+        }
+    }
+
+    pub fn next(&self) -> Sort {
+        match self {
+            Sort::Updated => Sort::Unplayed,
+            Sort::Unplayed => Sort::Alphabetical,
+            Sort::Alphabetical => Sort::Played,
+            Sort::Played => Sort::Updated,
+        }
+    }
+}
+
+// Subscriptions is the user's library, stored as `subscriptions` in the store along with
+// how it's sorted and filtered
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subscriptions {
+    #[serde(default)]
+    pub sort: Sort,
+    // only podcasts with this tag are listed
+    #[serde(default)]
+    pub tag: Option<String>,
+    // list the archived podcasts instead
+    #[serde(skip)]
+    pub archive: bool,
+    #[serde(default)]
+    feeds: Vec<Subscription>,
+}
+
+impl Subscriptions {
+    pub fn iter(&self) -> impl Iterator<Item = &Subscription> {
+        self.feeds.iter()
+    }
+
+    // The podcasts that aren't archived, which are the ones kept up to date
+    pub fn active(&self) -> impl Iterator<Item = &Subscription> {
+        self.feeds.iter().filter(|s| !s.archived)
+    }
+
+    pub fn contains(&self, url: &str) -> bool {
+        self.feeds.iter().any(|s| s.url == url)
+    }
+
+    // Add a feed to the library, returns false if it was already there
+    pub fn add(&mut self, url: &str, title: &str) -> bool {
+        if self.contains(url) {
+            return false;
+        }
+        self.feeds.push(Subscription {
+            url: url.to_string(),
+            title: title.to_string(),
+            updated: None,
+            episodes: vec![],
+            last_played: None,
+            speed: None,
+            skip_intro: 0,
+            skip_outro: 0,
+            skip_ads: None,
+            new: 0,
+            auto_download: 0,
+            keep: None,
+            delete_played_after: None,
+            refresh: None,
+            notify: false,
+            tags: vec![],
+            archived: false,
+        });
+        true
+    }
+
+    // How many of the feed's episodes weren't there the last time it was loaded, by id.
+    // None are new the first time.
+    pub fn new_in(&self, url: &str, c: &Channel) -> usize {
+        let Some(s) = self.get(url).filter(|s| !s.episodes.is_empty()) else {
+            return 0;
+        };
+        c.items()
+            .iter()
+            .filter_map(episode_id)
+            .filter(|id| !s.episodes.iter().any(|e| e == id))
+            .count()
+    }
+
+    // Remember what the feed looked like when it was loaded, counting the episodes that
+    // are new. Returns false if it isn't subscribed to or nothing changed.
+    pub fn update(&mut self, url: &str, c: &Channel) -> bool {
+        let new = self.new_in(url, c);
+        let Some(s) = self.feeds.iter_mut().find(|s| s.url == url) else {
+            return false;
+        };
+        let before = s.clone();
+        s.new += new;
+        s.title = c.title().to_string();
+        s.updated = c.items().iter().filter_map(published).max();
+        s.episodes = c
+            .items()
+            .iter()
+            .filter_map(episode_id)
+            .map(str::to_string)
+            .collect();
+        *s != before
+    }
+
+    // Take a feed out of the library
+    pub fn remove(&mut self, url: &str) -> Option<Subscription> {
+        let idx = self.feeds.iter().position(|s| s.url == url)?;
+        Some(self.feeds.remove(idx))
+    }
+
+    // The podcast was opened, so its new episodes were seen. Returns false if there were
+    // none.
+    pub fn seen(&mut self, url: &str) -> bool {
+        match self.get_mut(url) {
+            Some(s) if s.new > 0 => {
+                s.new = 0;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn get(&self, url: &str) -> Option<&Subscription> {
+        self.feeds.iter().find(|s| s.url == url)
+    }
+
+    pub fn get_mut(&mut self, url: &str) -> Option<&mut Subscription> {
+        self.feeds.iter_mut().find(|s| s.url == url)
+    }
+
+    // Save the podcast's own playback speed, None to go back to the global one. Returns
+    // false if it isn't subscribed to.
+    pub fn set_speed(&mut self, url: &str, speed: Option<f32>) -> bool {
+        let Some(s) = self.get_mut(url) else {
+            return false;
+        };
+        s.speed = speed;
+        true
+    }
+
+    // Archive the podcast, or bring it back. Returns false if it isn't subscribed to or
+    // already was.
+    pub fn set_archived(&mut self, url: &str, archived: bool) -> bool {
+        match self.get_mut(url) {
+            Some(s) if s.archived != archived => {
+                s.archived = archived;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Tag the podcast, returns false if it isn't subscribed to or already had the tag
+    pub fn tag(&mut self, url: &str, tag: &str) -> bool {
+        let tag = tag.trim().to_lowercase();
+        match self.get_mut(url) {
+            Some(s) if !tag.is_empty() && !s.tags.contains(&tag) => {
+                s.tags.push(tag);
+                s.tags.sort();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Take the tag off the podcast, returns false if it didn't have it
+    pub fn untag(&mut self, url: &str, tag: &str) -> bool {
+        let tag = tag.trim().to_lowercase();
+        let Some(s) = self.get_mut(url) else {
+            return false;
+        };
+        let before = s.tags.len();
+        s.tags.retain(|t| *t != tag);
+        s.tags.len() != before
+    }
+
+    // Every tag in the library, each once and sorted
+    pub fn tags(&self) -> Vec<&str> {
+        let mut tags = self
+            .feeds
+            .iter()
+            .flat_map(|s| s.tags.iter().map(String::as_str))
+            .collect::<Vec<&str>>();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    pub fn played_at(&mut self, url: &str, when: i64) {
+        if let Some(s) = self.feeds.iter_mut().find(|s| s.url == url) {
+            s.last_played = Some(when);
+        }
+    }
+
+    // The library, or the archive, in the order of its sort and without the podcasts its
+    // tag filters out
+    pub fn sorted(&self, played: &Played) -> Vec<&Subscription> {
+        let mut feeds = self
+            .feeds
+            .iter()
+            .filter(|s| s.archived == self.archive)
+            .filter(|s| self.tag.as_ref().is_none_or(|t| s.tags.contains(t)))
+            .collect::<Vec<&Subscription>>();
+        match self.sort {
+            // newest first, and None sorts before Some so it goes last
+            Sort::Updated => feeds.sort_by_key(|s| std::cmp::Reverse(s.updated)),
+            Sort::Unplayed => feeds.sort_by_key(|s| std::cmp::Reverse(s.unplayed(played))),
+            Sort::Alphabetical => feeds.sort_by_key(|s| s.title.to_lowercase()),
+            Sort::Played => feeds.sort_by_key(|s| std::cmp::Reverse(s.last_played)),
+        }
+        feeds
+    }
+
+    pub fn load() -> Result<Subscriptions, Box<dyn Error>> {
+        store::load("subscriptions")
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        store::save("subscriptions", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rss::{Channel, Guid, Item};
+
+    use super::{Sort, Subscriptions};
+    use crate::played::Played;
+
+    fn episode(id: &str, date: &str) -> Item {
+        Item {
+            guid: Some(Guid {
+                value: id.to_string(),
+                ..Default::default()
+            }),
+            pub_date: Some(date.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn add_is_idempotent() {
+        let mut s = Subscriptions::default();
+        assert!(s.add("https://example.com/feed", "Example"));
+        assert!(!s.add("https://example.com/feed", "Example again"));
+        assert_eq!(s.feeds.len(), 1);
+        assert!(s.contains("https://example.com/feed"));
+        assert!(!s.contains("https://example.com/other"));
+    }
+
+    #[test]
+    fn removes() {
+        let mut s = Subscriptions::default();
+        s.add("https://a.com/feed", "a");
+        s.add("https://b.com/feed", "b");
+        assert_eq!(s.remove("https://a.com/feed").unwrap().title, "a");
+        assert!(s.remove("https://a.com/feed").is_none());
+        assert!(!s.contains("https://a.com/feed"));
+        assert!(s.contains("https://b.com/feed"));
+    }
+
+    #[test]
+    fn counts_new_episodes() {
+        let u = "https://example.com/feed";
+        let feed = |ids: &[&str]| Channel {
+            items: ids
+                .iter()
+                .map(|id| episode(id, "Mon, 01 May 2023 00:00:00 +0000"))
+                .collect(),
+            ..Default::default()
+        };
+        let mut s = Subscriptions::default();
+        s.add(u, "Example");
+        s.update(u, &feed(&["1", "2"]));
+        assert_eq!(s.get(u).unwrap().new, 0);
+
+        assert_eq!(s.new_in(u, &feed(&["4", "3", "2"])), 2);
+        s.update(u, &feed(&["4", "3", "2"]));
+        s.update(u, &feed(&["5", "4", "3"]));
+        assert_eq!(s.get(u).unwrap().new, 3);
+
+        assert!(s.seen(u));
+        assert!(!s.seen(u));
+        assert_eq!(s.get(u).unwrap().new, 0);
+    }
+
+    #[test]
+    fn sorts() {
+        let mut s = Subscriptions::default();
+        s.add("https://a.com/feed", "b");
+        s.add("https://b.com/feed", "A");
+        s.add("https://c.com/feed", "c");
+        let a = Channel {
+            title: "b".to_string(),
+            items: vec![episode("1", "Tue, 02 May 2023 10:00:00 GMT")],
+            ..Default::default()
+        };
+        let b = Channel {
+            title: "A".to_string(),
+            items: vec![
+                episode("2", "Wed, 03 May 2023 10:00:00 GMT"),
+                episode("1", "Mon, 01 May 2023 10:00:00 GMT"),
+            ],
+            ..Default::default()
+        };
+        assert!(s.update("https://a.com/feed", &a));
+        assert!(!s.update("https://a.com/feed", &a));
+        assert!(s.update("https://b.com/feed", &b));
+        s.played_at("https://c.com/feed", 100);
+        s.played_at("https://a.com/feed", 50);
+
+        let mut played = Played::default();
+        played.mark_all("https://b.com/feed", ["1", "2"]);
+
+        let mut titles = |sort| {
+            s.sort = sort;
+            s.sorted(&played)
+                .into_iter()
+                .map(|s| s.title.clone())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(titles(Sort::Updated), ["A", "b", "c"]);
+        assert_eq!(titles(Sort::Unplayed), ["b", "A", "c"]);
+        assert_eq!(titles(Sort::Alphabetical), ["A", "b", "c"]);
+        assert_eq!(titles(Sort::Played), ["c", "b", "A"]);
+    }
+
+    #[test]
+    fn filters_by_tag() {
+        let mut s = Subscriptions::default();
+        s.add("https://example.com/feed", "Example");
+        s.add("https://example.com/other", "Other");
+        assert!(s.tag("https://example.com/feed", "News"));
+        assert!(!s.tag("https://example.com/feed", "news"));
+        assert!(s.tag("https://example.com/other", "tech"));
+        assert!(s.tag("https://example.com/other", "news"));
+        assert_eq!(s.tags(), vec!["news", "tech"]);
+
+        s.tag = Some("tech".to_string());
+        let titles = |s: &Subscriptions| {
+            s.sorted(&Played::default())
+                .iter()
+                .map(|s| s.title.clone())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(titles(&s), vec!["Other"]);
+        s.tag = Some("news".to_string());
+        assert_eq!(titles(&s).len(), 2);
+
+        assert!(s.untag("https://example.com/feed", "news"));
+        assert!(!s.untag("https://example.com/feed", "news"));
+        assert_eq!(titles(&s), vec!["Other"]);
+    }
+
+    #[test]
+    fn archives() {
+        let mut s = Subscriptions::default();
+        s.add("https://example.com/feed", "Example");
+        s.add("https://example.com/other", "Other");
+        assert!(s.set_archived("https://example.com/other", true));
+        assert!(!s.set_archived("https://example.com/other", true));
+
+        let titles = |s: &Subscriptions| {
+            s.sorted(&Played::default())
+                .iter()
+                .map(|s| s.title.clone())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(titles(&s), vec!["Example"]);
+        assert_eq!(s.active().count(), 1);
+        s.archive = true;
+        assert_eq!(titles(&s), vec!["Other"]);
+
+        assert!(s.set_archived("https://example.com/other", false));
+        assert_eq!(titles(&s), Vec::<String>::new());
+        assert_eq!(s.active().count(), 2);
+    }
+
+    #[test]
+    fn sets_speed() {
+        let mut s = Subscriptions::default();
+        s.add("https://example.com/feed", "Example");
+        assert!(s.set_speed("https://example.com/feed", Some(1.5)));
+        assert!(!s.set_speed("https://example.com/other", Some(1.5)));
+        assert_eq!(s.get("https://example.com/feed").unwrap().speed, Some(1.5));
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut s = Subscriptions::default();
+        s.add("https://example.com/feed", "Example");
+        s.add("https://example.com/other", "Other");
+        s.set_speed("https://example.com/other", Some(1.2));
+        s.tag("https://example.com/other", "news");
+        s.tag = Some("news".to_string());
+
+        let serialized = toml::to_string(&s).unwrap();
+        assert_eq!(
+            toml::from_str::<Subscriptions>(serialized.as_str()).unwrap(),
+            s
+        );
+    }
+
+    #[test]
+    fn parses_empty() {
+        assert_eq!(
+            toml::from_str::<Subscriptions>("").unwrap(),
+            Subscriptions::default()
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use crate::{
+    config::Config,
+    player::{clamp_speed, SPEED_STEP},
+};
+
+use super::Subscription;
+
+// How much the settings change with each step on the settings screen
+const SKIP_STEP: u64 = 5;
+const REFRESH_STEP: u64 = 15;
+const DAYS_STEP: u64 = 7;
+
+// Setting is one of a podcast's own settings, as listed on its settings screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Speed,
+    SkipIntro,
+    SkipOutro,
+    SkipAds,
+    AutoDownload,
+    Keep,
+    DeletePlayed,
+    Refresh,
+    Notify,
+}
+
+pub const SETTINGS: [Setting; 9] = [
+    Setting::Speed,
+    Setting::SkipIntro,
+    Setting::SkipOutro,
+    Setting::SkipAds,
+    Setting::AutoDownload,
+    Setting::Keep,
+    Setting::DeletePlayed,
+    Setting::Refresh,
+    Setting::Notify,
+];
+
+impl Setting {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Setting::Speed => "Playback speed",
+            Setting::SkipIntro => "Skip intro",
+            Setting::SkipOutro => "Skip outro",
+            Setting::SkipAds => "Skip ads",
+            Setting::AutoDownload => "Download new episodes",
+            Setting::Keep => "Downloads to keep",
+            Setting::DeletePlayed => "Delete played downloads",
+            Setting::Refresh => "Refresh",
+            Setting::Notify => "Tell me about new episodes",
+        }
+    }
+
+    // The podcast's value, e.g. "1.5x", or "1.0x (global)" when it follows the config
+    pub fn value(&self, s: &Subscription, config: &Config) -> String {
+        let global = |v: String, own: bool| if own { v } else { format!("{} (global)", v) };
+        let on_off = |b: bool| if b { "on" } else { "off" }.to_string();
+        match self {
+            Setting::Speed => global(
+                format!("{:.1}x", s.speed.unwrap_or(config.playback.speed)),
+                s.speed.is_some(),
+            ),
+            Setting::SkipIntro => skip(s.skip_intro),
+            Setting::SkipOutro => skip(s.skip_outro),
+            Setting::SkipAds => global(
+                on_off(s.skip_ads.unwrap_or(config.playback.skip_ads)),
+                s.skip_ads.is_some(),
+            ),
+            Setting::AutoDownload => match s.auto_download {
+                0 => "off".to_string(),
+                1 => "the latest episode".to_string(),
+                n => format!("the latest {} episodes", n),
+            },
+            Setting::Keep => global(
+                match s.keep.unwrap_or(config.downloads.keep) {
+                    0 => "all".to_string(),
+                    n => format!("the latest {}", n),
+                },
+                s.keep.is_some(),
+            ),
+            Setting::DeletePlayed => global(
+                match s
+                    .delete_played_after
+                    .unwrap_or(config.downloads.delete_played_after)
+                {
+                    0 => "never".to_string(),
+                    1 => "a day after".to_string(),
+                    n => format!("{} days after", n),
+                },
+                s.delete_played_after.is_some(),
+            ),
+            Setting::Refresh => global(
+                match s.refresh.unwrap_or(config.refresh.interval) {
+                    0 => "only at startup".to_string(),
+                    m => format!("every {} minutes", m),
+                },
+                s.refresh.is_some(),
+            ),
+            Setting::Notify => on_off(s.notify),
+        }
+    }
+
+    // Step the value up, or down for a negative step. Settings that are on or off are
+    // switched either way.
+    pub fn adjust(&self, s: &mut Subscription, step: i32, config: &Config) {
+        match self {
+            Setting::Speed => {
+                let speed = s.speed.unwrap_or(config.playback.speed);
+                s.speed = Some(clamp_speed(speed + SPEED_STEP * step as f32));
+            }
+            Setting::SkipIntro => s.skip_intro = stepped(s.skip_intro, SKIP_STEP, step),
+            Setting::SkipOutro => s.skip_outro = stepped(s.skip_outro, SKIP_STEP, step),
+            Setting::SkipAds => {
+                s.skip_ads = Some(!s.skip_ads.unwrap_or(config.playback.skip_ads));
+            }
+            Setting::AutoDownload => {
+                s.auto_download = stepped(s.auto_download as u64, 1, step) as usize;
+            }
+            Setting::Keep => {
+                let keep = s.keep.unwrap_or(config.downloads.keep);
+                s.keep = Some(stepped(keep as u64, 1, step) as usize);
+            }
+            Setting::DeletePlayed => {
+                let days = s
+                    .delete_played_after
+                    .unwrap_or(config.downloads.delete_played_after);
+                s.delete_played_after = Some(stepped(days, DAYS_STEP, step));
+            }
+            Setting::Refresh => {
+                let minutes = s.refresh.unwrap_or(config.refresh.interval);
+                s.refresh = Some(stepped(minutes, REFRESH_STEP, step));
+            }
+            Setting::Notify => s.notify = !s.notify,
+        }
+    }
+
+    // Go back to the default, which for some settings is to follow the config
+    pub fn reset(&self, s: &mut Subscription) {
+        match self {
+            Setting::Speed => s.speed = None,
+            Setting::SkipIntro => s.skip_intro = 0,
+            Setting::SkipOutro => s.skip_outro = 0,
+            Setting::SkipAds => s.skip_ads = None,
+            Setting::AutoDownload => s.auto_download = 0,
+            Setting::Keep => s.keep = None,
+            Setting::DeletePlayed => s.delete_played_after = None,
+            Setting::Refresh => s.refresh = None,
+            Setting::Notify => s.notify = false,
+        }
+    }
+}
+
+fn skip(secs: u64) -> String {
+    match secs {
+        0 => "off".to_string(),
+        n => format!("{} seconds", n),
+    }
+}
+
+// Move the value by a step, onto a multiple of the step
+fn stepped(value: u64, size: u64, step: i32) -> u64 {
+    // a value between two steps goes to the nearest one in that direction first
+    let steps = if step < 0 {
+        value.div_ceil(size)
+    } else {
+        value / size
+    };
+    (steps as i64 + i64::from(step)).max(0) as u64 * size
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::Config, subscriptions::Subscriptions};
+
+    use super::{stepped, Setting};
+
+    #[test]
+    fn adjusts_settings() {
+        let config = Config::default();
+        let mut subs = Subscriptions::default();
+        subs.add("https://example.com/feed", "Example");
+        let s = subs.get_mut("https://example.com/feed").unwrap();
+
+        assert_eq!(Setting::Speed.value(s, &config), "1.0x (global)");
+        Setting::Speed.adjust(s, 1, &config);
+        assert_eq!(Setting::Speed.value(s, &config), "1.1x");
+        Setting::Speed.reset(s);
+        assert_eq!(s.speed, None);
+
+        Setting::SkipIntro.adjust(s, -1, &config);
+        assert_eq!(Setting::SkipIntro.value(s, &config), "off");
+        Setting::SkipIntro.adjust(s, 1, &config);
+        assert_eq!(Setting::SkipIntro.value(s, &config), "5 seconds");
+
+        Setting::SkipAds.adjust(s, -1, &config);
+        assert_eq!(Setting::SkipAds.value(s, &config), "on");
+
+        Setting::AutoDownload.adjust(s, -1, &config);
+        assert_eq!(Setting::AutoDownload.value(s, &config), "off");
+        Setting::AutoDownload.adjust(s, 1, &config);
+        Setting::AutoDownload.adjust(s, 1, &config);
+        assert_eq!(
+            Setting::AutoDownload.value(s, &config),
+            "the latest 2 episodes"
+        );
+
+        assert_eq!(Setting::Keep.value(s, &config), "all (global)");
+        Setting::Keep.adjust(s, 1, &config);
+        assert_eq!(Setting::Keep.value(s, &config), "the latest 1");
+        Setting::DeletePlayed.adjust(s, 1, &config);
+        Setting::DeletePlayed.adjust(s, 1, &config);
+        assert_eq!(Setting::DeletePlayed.value(s, &config), "14 days after");
+        Setting::DeletePlayed.reset(s);
+        assert_eq!(Setting::DeletePlayed.value(s, &config), "never (global)");
+
+        assert_eq!(
+            Setting::Refresh.value(s, &config),
+            "every 30 minutes (global)"
+        );
+        Setting::Refresh.adjust(s, -1, &config);
+        Setting::Refresh.adjust(s, -1, &config);
+        assert_eq!(Setting::Refresh.value(s, &config), "only at startup");
+    }
+
+    #[test]
+    fn steps_onto_multiples() {
+        assert_eq!(stepped(0, 5, 1), 5);
+        assert_eq!(stepped(0, 5, -1), 0);
+        assert_eq!(stepped(12, 5, 1), 15);
+        assert_eq!(stepped(12, 5, -1), 10);
+        assert_eq!(stepped(10, 5, -1), 5);
+    }
+}
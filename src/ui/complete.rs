@@ -0,0 +1,116 @@
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    text::{Span, Spans},
+    widgets::{Clear, List, ListItem, ListState},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::App;
+
+use super::{block, highlight_symbol, COMMANDS};
+
+// Most completions shown at once
+const MAX_SHOWN: u16 = 8;
+
+// Completion is a way to finish what's being typed: what's shown for it in the popup,
+// and the text it becomes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub label: String,
+    pub text: String,
+}
+
+// Ways to finish what's typed: the commands starting with it, or the subscribed podcasts
+// with what's typed after `/load` in their title, as (title, feed URL) pairs
+pub fn completions(typed: &str, podcasts: &[(&str, &str)]) -> Vec<Completion> {
+    if let Some(query) = typed.strip_prefix("/load ") {
+        let query = query.trim().to_lowercase();
+        return podcasts
+            .iter()
+            .filter(|(title, _)| title.to_lowercase().contains(query.as_str()))
+            .map(|(title, url)| Completion {
+                label: title.to_string(),
+                text: format!("/load {}", url),
+            })
+            .collect();
+    }
+    if typed.contains(' ') {
+        return vec![];
+    }
+    COMMANDS
+        .iter()
+        // the filter isn't a command to finish
+        .filter(|(cmd, _)| !cmd.contains(' ') && cmd.starts_with(typed) && *cmd != typed)
+        .map(|(cmd, desc)| Completion {
+            label: format!("{}  {}", cmd, desc),
+            text: cmd.to_string(),
+        })
+        .collect()
+}
+
+// Show the completions in a popup under the input box, the one Tab accepts highlighted
+pub fn draw_completions<B: Backend>(
+    f: &mut Frame<B>,
+    app: &App,
+    input: Rect,
+    completions: &[Completion],
+    selected: usize,
+) {
+    if completions.is_empty() || app.config.accessible {
+        return;
+    }
+    let screen = f.size();
+    let y = input.bottom();
+    let height = (completions.len() as u16).min(MAX_SHOWN) + 2;
+    let widest = completions
+        .iter()
+        .map(|c| c.label.width() as u16)
+        .max()
+        .unwrap_or(0);
+    let rect = Rect {
+        x: input.x + 1,
+        y,
+        width: (widest + 5).min(screen.right().saturating_sub(input.x + 1)),
+        height: height.min(screen.bottom().saturating_sub(y)),
+    };
+    let items = completions
+        .iter()
+        .map(|c| ListItem::new(Spans::from(Span::raw(c.label.as_str()))))
+        .collect::<Vec<ListItem>>();
+    let list = List::new(items)
+        .block(block(app, "Tab completes").style(app.theme.base))
+        .highlight_style(app.theme.highlight)
+        .highlight_symbol(highlight_symbol(app));
+    let mut state = ListState::default();
+    state.select(Some(selected));
+    f.render_widget(Clear, rect);
+    f.render_stateful_widget(list, rect, &mut state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::completions;
+
+    #[test]
+    fn completes_commands_and_podcasts() {
+        let texts = |typed: &str| {
+            let podcasts = [
+                ("Wine About It", "https://example.com/wine"),
+                ("Beer Talk", "https://example.com/beer"),
+            ];
+            completions(typed, &podcasts)
+                .into_iter()
+                .map(|c| c.text)
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(texts("/sp"), ["/speak", "/speed"]);
+        // already typed in full
+        assert!(!texts("/library").contains(&"/library".to_string()));
+        assert_eq!(texts("/load wine"), ["/load https://example.com/wine"]);
+        assert_eq!(texts("/load ").len(), 2);
+        assert!(texts("/search wine").is_empty());
+        assert!(texts("/ wine").is_empty());
+    }
+}
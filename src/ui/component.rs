@@ -0,0 +1,334 @@
+use crossterm::event::{KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+use tui::{backend::Backend, layout::Rect, Frame};
+
+use crate::{message::DisplayAction, App};
+
+use super::{
+    details::Details,
+    episodes::EpisodeList,
+    help::Help,
+    input_box::InputBox,
+    playbar::Playbar,
+    sidebar::Sidebar,
+    views::{
+        Bookmarks, CleanUp, Diagnostics, Downloads, History, Library, NowPlaying, PodcastSettings,
+        Preview, Queue, Search, Starred, Storage,
+    },
+};
+
+// Component is a part of the screen that keeps track of its own state, draws itself, and
+// handles the keys meant for it. What it shows comes from the app, and what a key asks of
+// the app comes back as an action for the app to carry out.
+pub trait Component {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App);
+
+    // None when the key isn't for the component, leaving it to the rest of the screen
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action>;
+
+    // Same for clicks and the scroll wheel, which most components leave alone
+    fn handle_mouse(&mut self, _mouse: MouseEvent) -> Option<Action> {
+        None
+    }
+}
+
+// Whether the mouse is over the area
+pub fn over(mouse: &MouseEvent, area: Rect) -> bool {
+    (area.left()..area.right()).contains(&mouse.column)
+        && (area.top()..area.bottom()).contains(&mouse.row)
+}
+
+// What a key asks of the app
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    // the component took care of the key by itself
+    Consumed,
+    // run the command typed into the input box
+    Submit(String),
+    TogglePause,
+    SeekBack,
+    SeekForward,
+    Faster,
+    Slower,
+    Louder,
+    Quieter,
+    // select the previous or next episode
+    Previous,
+    Next,
+    // show the selected episode, or older episodes when that row is selected
+    Open,
+    // play, or open, the file of the episode described
+    OpenFile,
+    // the rest are about the selected episode, or the one described
+    Enqueue,
+    MarkPlayed,
+    Download,
+    DeleteDownload,
+    Star,
+    // start selecting a range of episodes, or stop
+    Visual,
+    // wait for the letter of a mark to set
+    SetMark,
+    Speak,
+    PodcastSettings,
+    // wait for the number of a link in the show notes to open
+    ChooseLink,
+    // load the podcast at this row of the sidebar
+    OpenPodcast(usize),
+    // select the episode at this row of the list
+    Select(usize),
+    // go to this far into the episode playing, from 0 to 1
+    SeekTo(f64),
+    // subscribe or just browse, as chosen in the preview
+    ChoosePreview,
+    // open the actions for the selected search result, or carry out the one chosen
+    ChooseResult,
+    // the rest are about the selected row of the list on screen
+    OpenSubscription,
+    SortLibrary,
+    // step the selected podcast setting by this many steps, or back to its default
+    ChangeSetting(i32),
+    ResetSetting,
+    PlayEntry,
+    MoveUp,
+    MoveDown,
+    MoveToTop,
+    MoveToBottom,
+    PlayBookmark,
+    DeleteBookmark,
+    PlayStar,
+    ResumeListen,
+    PlayDownload,
+    RemoveDownload,
+    PauseDownload,
+    CancelDownload,
+    ClearStorage,
+    CleanUp,
+    SortMetrics,
+}
+
+// Screen holds the components, and passes each key on to the one it's for
+#[derive(Default)]
+pub struct Screen {
+    pub input: InputBox,
+    pub episodes: EpisodeList,
+    pub details: Details,
+    pub playbar: Playbar,
+    pub sidebar: Sidebar,
+    pub help: Help,
+    // where the view is drawn, for the scroll wheel
+    pub display: Rect,
+}
+
+impl Screen {
+    // The help takes every key while it's open. Otherwise the input box gets the key
+    // first, then the sidebar, then the view on screen unless the sidebar has the focus,
+    // then the play bar. Keys with Ctrl or Alt are left to the app.
+    pub fn handle_event(&mut self, key: KeyEvent, display: &DisplayAction) -> Option<Action> {
+        if self.help.shown {
+            return self.help.handle_event(key);
+        }
+        // the input box takes its editing shortcuts, like Ctrl-W
+        if let Some(action) = self.input.handle_event(key) {
+            return Some(action);
+        }
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return None;
+        }
+        if let Some(action) = self.help.handle_event(key) {
+            return Some(action);
+        }
+        if let Some(action) = self.sidebar.handle_event(key) {
+            return Some(action);
+        }
+        if self.sidebar.focused {
+            return self.playbar.handle_event(key);
+        }
+        let view = match display {
+            DisplayAction::ListEpisodes => self.episodes.handle_event(key),
+            DisplayAction::DescribeEpisode => self.details.handle_event(key),
+            DisplayAction::Preview => Preview.handle_event(key),
+            DisplayAction::NowPlaying => NowPlaying.handle_event(key),
+            DisplayAction::Queue => Queue.handle_event(key),
+            DisplayAction::Bookmarks => Bookmarks.handle_event(key),
+            DisplayAction::Search => Search.handle_event(key),
+            DisplayAction::Diagnostics => Diagnostics.handle_event(key),
+            DisplayAction::Library => Library.handle_event(key),
+            DisplayAction::PodcastSettings => PodcastSettings.handle_event(key),
+            DisplayAction::Starred => Starred.handle_event(key),
+            DisplayAction::History => History.handle_event(key),
+            DisplayAction::Downloads => Downloads.handle_event(key),
+            DisplayAction::Storage => Storage.handle_event(key),
+            DisplayAction::CleanUp => CleanUp.handle_event(key),
+            DisplayAction::Input => None,
+        };
+        view.or_else(|| self.playbar.handle_event(key))
+    }
+
+    // The play bar gets clicks first, then the view on screen. Views without a component
+    // of their own are lists, which the scroll wheel moves through.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent, display: &DisplayAction) -> Option<Action> {
+        if self.help.shown {
+            return None;
+        }
+        if let Some(action) = self.playbar.handle_mouse(mouse) {
+            return Some(action);
+        }
+        match display {
+            DisplayAction::ListEpisodes => return self.episodes.handle_mouse(mouse),
+            DisplayAction::DescribeEpisode => return self.details.handle_mouse(mouse),
+            DisplayAction::Input | DisplayAction::NowPlaying => return None,
+            _ => {}
+        }
+        if !over(&mouse, self.display) {
+            return None;
+        }
+        match mouse.kind {
+            MouseEventKind::ScrollUp => Some(Action::Previous),
+            MouseEventKind::ScrollDown => Some(Action::Next),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{Action, Screen};
+    use crate::message::DisplayAction;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn routes_keys() {
+        let mut s = Screen::default();
+        let episodes = DisplayAction::ListEpisodes;
+        assert_eq!(
+            s.handle_event(key(KeyCode::Char('a')), &episodes),
+            Some(Action::Enqueue)
+        );
+        assert_eq!(
+            s.handle_event(key(KeyCode::Left), &episodes),
+            Some(Action::SeekBack)
+        );
+        assert_eq!(
+            s.handle_event(key(KeyCode::Left), &DisplayAction::PodcastSettings),
+            Some(Action::ChangeSetting(-1))
+        );
+
+        // once a command is being typed, the input box takes the keys
+        for c in "/load".chars() {
+            s.handle_event(key(KeyCode::Char(c)), &episodes);
+        }
+        assert_eq!(
+            s.handle_event(key(KeyCode::Char('a')), &episodes),
+            Some(Action::Consumed)
+        );
+        assert_eq!(
+            s.handle_event(key(KeyCode::Left), &episodes),
+            Some(Action::Consumed)
+        );
+        // Up recalls older commands instead of selecting
+        assert_eq!(
+            s.handle_event(key(KeyCode::Up), &episodes),
+            Some(Action::Consumed)
+        );
+        assert_eq!(
+            s.handle_event(key(KeyCode::Enter), &episodes),
+            Some(Action::Submit("/loada".to_string()))
+        );
+        assert_eq!(s.input.take(), "");
+        let ctrl_o = KeyEvent::new(KeyCode::Char('o'), KeyModifiers::CONTROL);
+        assert_eq!(s.handle_event(ctrl_o, &episodes), None);
+    }
+
+    #[test]
+    fn routes_keys_to_view() {
+        let mut s = Screen::default();
+        let cases = [
+            (
+                KeyCode::Enter,
+                DisplayAction::Queue,
+                Some(Action::PlayEntry),
+            ),
+            (
+                KeyCode::Char('K'),
+                DisplayAction::Queue,
+                Some(Action::MoveUp),
+            ),
+            (KeyCode::Down, DisplayAction::Queue, Some(Action::Next)),
+            (
+                KeyCode::Enter,
+                DisplayAction::Library,
+                Some(Action::OpenSubscription),
+            ),
+            (
+                KeyCode::Char('o'),
+                DisplayAction::Library,
+                Some(Action::PodcastSettings),
+            ),
+            (
+                KeyCode::Char('D'),
+                DisplayAction::Downloads,
+                Some(Action::RemoveDownload),
+            ),
+            (
+                KeyCode::Char('D'),
+                DisplayAction::Storage,
+                Some(Action::ClearStorage),
+            ),
+            (
+                KeyCode::Char('D'),
+                DisplayAction::CleanUp,
+                Some(Action::CleanUp),
+            ),
+            (
+                KeyCode::Char('d'),
+                DisplayAction::Bookmarks,
+                Some(Action::DeleteBookmark),
+            ),
+            (
+                KeyCode::Char('s'),
+                DisplayAction::Diagnostics,
+                Some(Action::SortMetrics),
+            ),
+            (
+                KeyCode::Enter,
+                DisplayAction::NowPlaying,
+                Some(Action::TogglePause),
+            ),
+            (
+                KeyCode::Enter,
+                DisplayAction::Preview,
+                Some(Action::ChoosePreview),
+            ),
+            (
+                KeyCode::Enter,
+                DisplayAction::Search,
+                Some(Action::ChooseResult),
+            ),
+            (
+                KeyCode::Backspace,
+                DisplayAction::PodcastSettings,
+                Some(Action::ResetSetting),
+            ),
+            // the play bar gets what the view leaves
+            (
+                KeyCode::Left,
+                DisplayAction::Library,
+                Some(Action::SeekBack),
+            ),
+            // and the app what neither wants
+            (KeyCode::Char('K'), DisplayAction::Library, None),
+            (KeyCode::Enter, DisplayAction::Storage, None),
+        ];
+        for (code, display, action) in cases {
+            assert_eq!(s.handle_event(key(code), &display), action, "{:?}", display);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    text::{Span, Spans},
+    widgets::{Clear, Paragraph, Wrap},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::App;
+
+use super::block;
+
+// Narrowest the popup gets, unless the screen is narrower
+const MIN_WIDTH: u16 = 30;
+
+// Where the popup goes: in the middle of the area, tall enough for the question once it
+// wraps, a blank line and the keys
+pub fn popup(area: Rect, question: &str) -> Rect {
+    let width = (area.width * 60 / 100).max(MIN_WIDTH).min(area.width);
+    let inner = width.saturating_sub(2).max(1);
+    let lines = (question.width() as u16).div_ceil(inner).max(1);
+    let height = (lines + 4).min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+// Ask about the destructive action waiting for a yes or no in a popup over the screen.
+// Accessible mode keeps to the status line, which already asks.
+pub fn draw_confirm<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    if app.config.accessible {
+        return;
+    }
+    let text = vec![
+        Spans::from(app.question.as_str()),
+        Spans::default(),
+        Spans::from(vec![
+            Span::styled("y", app.theme.key),
+            Span::raw(" yes, "),
+            Span::styled("any other key", app.theme.key),
+            Span::raw(" no"),
+        ]),
+    ];
+    let rect = popup(area, app.question.as_str());
+    let contents = Paragraph::new(text)
+        .block(block(app, "Confirm").style(app.theme.base))
+        .wrap(Wrap { trim: true });
+    f.render_widget(Clear, rect);
+    f.render_widget(contents, rect);
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::layout::Rect;
+
+    use super::popup;
+
+    #[test]
+    fn fits_question() {
+        let area = Rect::new(0, 0, 100, 40);
+        assert_eq!(popup(area, "Unsubscribe?"), Rect::new(20, 17, 60, 5));
+        // wraps over two lines
+        assert_eq!(popup(area, &"x".repeat(80)), Rect::new(20, 17, 60, 6));
+        // never bigger than the screen
+        assert_eq!(
+            popup(Rect::new(0, 0, 20, 4), "Unsubscribe?"),
+            Rect::new(0, 0, 20, 4)
+        );
+    }
+}
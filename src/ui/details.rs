@@ -0,0 +1,161 @@
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
+use tracing::{span, trace, Level};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    text::{Span, Spans},
+    widgets::{Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    feed::{episode_artwork, episode_id, private::redact},
+    App,
+};
+
+use super::{
+    block, chapter_lines,
+    component::{over, Action, Component},
+    notes, show_notes, title, with_image,
+};
+
+// Lines the notes scroll by with Page Up and Page Down
+const PAGE: u16 = 10;
+
+// Lines the notes scroll by with a turn of the scroll wheel
+const SCROLL: u16 = 3;
+
+// Details shows the open episode: its file, chapters and show notes, which scroll
+#[derive(Default, Debug)]
+pub struct Details {
+    // lines scrolled past
+    scroll: u16,
+    // the episode scrolled
+    shown: Option<String>,
+    // where it's drawn, for the scroll wheel
+    area: Rect,
+}
+
+impl Component for Details {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        self.area = area;
+        let span = span!(Level::TRACE, "render_episode");
+        let _entered = span.enter();
+        trace!("rendering episode details");
+
+        let episode_name = title(
+            app,
+            "Episode",
+            app.item
+                .as_ref()
+                .and_then(|i| i.title())
+                .unwrap_or("Episode Title"),
+        );
+        let description = app
+            .item
+            .as_ref()
+            .and_then(|i| i.description())
+            .unwrap_or("Description");
+        let audio_link = app
+            .item
+            .as_ref()
+            .and_then(|i| i.enclosure.as_ref())
+            .map(|e| match url::Url::parse(e.url()) {
+                Ok(u) => redact(&u),
+                Err(_) => e.url().to_string(),
+            })
+            .unwrap_or("[Audio URL]".to_string());
+
+        let audio_link = if app.config.accessible {
+            Span::raw(format!("Audio: {}", audio_link))
+        } else {
+            Span::styled(audio_link, app.theme.link)
+        };
+
+        let mut text = vec![Spans::from(audio_link)];
+        if let Some(chapters) = app.chapters.as_ref().filter(|c| c.len() > 0) {
+            text.push(Spans::from(Span::raw("")));
+            text.extend(chapter_lines(app, chapters, app.position));
+        }
+        text.push(Spans::from(Span::raw("")));
+        text.extend(show_notes(app, description, area.width));
+        // screen readers get the links listed at the end of the plain text already
+        let links = notes::links(description);
+        if !links.is_empty() && !app.config.accessible {
+            text.push(Spans::from(Span::raw("")));
+            text.push(Spans::from(Span::styled(
+                "Links, o and the number opens one",
+                app.theme.key,
+            )));
+            text.extend(links.into_iter().enumerate().map(|(i, url)| {
+                Spans::from(vec![
+                    Span::styled(format!("[{}] ", i + 1), app.theme.key),
+                    Span::styled(url, app.theme.link),
+                ])
+            }));
+        }
+
+        // show notes are already wrapped, and trimming would lose the indentation of lists
+        // a new episode starts at the top
+        let shown = app.item.as_ref().and_then(episode_id).map(str::to_string);
+        if shown != self.shown {
+            self.shown = shown;
+            self.scroll = 0;
+        }
+        self.scroll = self.scroll.min(text.len().saturating_sub(1) as u16);
+        let contents = Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .block(block(app, episode_name));
+
+        let images = [
+            app.chapters.as_ref().and_then(|c| c.image_at(app.position)),
+            app.item
+                .as_ref()
+                .and_then(|i| episode_artwork(i, app.channel.as_ref())),
+        ];
+        let area = with_image(f, app, images, area);
+        f.render_widget(contents, area);
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        let action = match key.code {
+            KeyCode::Up => {
+                self.scroll = self.scroll.saturating_sub(1);
+                Action::Consumed
+            }
+            KeyCode::Down => {
+                self.scroll = self.scroll.saturating_add(1);
+                Action::Consumed
+            }
+            KeyCode::PageUp => {
+                self.scroll = self.scroll.saturating_sub(PAGE);
+                Action::Consumed
+            }
+            KeyCode::PageDown => {
+                self.scroll = self.scroll.saturating_add(PAGE);
+                Action::Consumed
+            }
+            KeyCode::Enter => Action::OpenFile,
+            KeyCode::Char('d') => Action::Download,
+            KeyCode::Char('*') => Action::Star,
+            KeyCode::Char('m') => Action::SetMark,
+            KeyCode::Char('s') => Action::Speak,
+            KeyCode::Char('o') => Action::ChooseLink,
+            _ => return None,
+        };
+        Some(action)
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Option<Action> {
+        if !over(&mouse, self.area) {
+            return None;
+        }
+        match mouse.kind {
+            MouseEventKind::ScrollUp => self.scroll = self.scroll.saturating_sub(SCROLL),
+            MouseEventKind::ScrollDown => self.scroll = self.scroll.saturating_add(SCROLL),
+            _ => return None,
+        }
+        Some(Action::Consumed)
+    }
+}
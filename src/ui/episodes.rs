@@ -0,0 +1,381 @@
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use tracing::{debug, span, trace, Level};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Span, Spans},
+    widgets::{List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{
+    downloads,
+    feed::{duration, published},
+    App,
+};
+
+use super::{
+    block,
+    component::{over, Action, Component},
+    download_marker, episode_legend, fmt_duration, highlight_symbol, loading,
+    theme::EpisodeState,
+    title, OLDER_EPISODES,
+};
+
+// EpisodeList lists the episodes of the loaded podcast. The app keeps which one is
+// selected, the list how far it's scrolled, making only the rows that show.
+#[derive(Default, Debug)]
+pub struct EpisodeList {
+    state: ListState,
+    // where the rows are, and the first one shown, to tell which one is clicked
+    rows: Rect,
+    offset: usize,
+    len: usize,
+}
+
+// Widths of the date and length columns
+const DATE_WIDTH: usize = 10;
+const LENGTH_WIDTH: usize = 7;
+
+// Narrowest rows that still have room for the date, and for the length
+const DATE_FROM: usize = 70;
+const LENGTH_FROM: usize = 45;
+
+// Columns of the rows of the list, the date and length are left out of narrow rows
+#[derive(Debug, PartialEq)]
+struct Columns {
+    title: usize,
+    date: bool,
+    length: bool,
+}
+
+impl Columns {
+    // Fit the columns in rows this wide, after the state glyph and the number, which
+    // take `prefix`. The star and the download marker go after the length.
+    fn fit(width: usize, prefix: usize) -> Columns {
+        let date = width >= DATE_FROM;
+        let length = width >= LENGTH_FROM;
+        let mut taken = prefix + 4;
+        if date {
+            taken += DATE_WIDTH + 2;
+        }
+        if length {
+            taken += LENGTH_WIDTH + 2;
+        }
+        Columns {
+            title: width.saturating_sub(taken).max(10),
+            date,
+            length,
+        }
+    }
+}
+
+// Cut the text down to the width, ending it with an ellipsis when it's cut, and pad it
+// out to the width
+fn fit(s: &str, width: usize) -> String {
+    if s.width() <= width {
+        return format!("{}{}", s, " ".repeat(width - s.width()));
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in s.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    out.push('…');
+    format!("{}{}", out, " ".repeat(width.saturating_sub(used + 1)))
+}
+
+// When the episode came out, e.g. 2024-01-31
+fn date(item: &rss::Item) -> Option<String> {
+    let secs = published(item)?;
+    chrono::DateTime::from_timestamp(secs, 0).map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+// The first row shown of a list of one line rows, scrolled just enough for the selected
+// row to show, the way the list widget scrolls
+fn scrolled(offset: usize, selected: Option<usize>, height: usize, len: usize) -> usize {
+    let offset = offset.min(len.saturating_sub(1));
+    match selected {
+        Some(s) if s >= offset + height => s + 1 - height,
+        Some(s) if s < offset => s,
+        _ => offset,
+    }
+}
+
+impl Component for EpisodeList {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        self.state.select(app.state.selected());
+        let span = span!(Level::TRACE, "render_feed");
+        let _entered = span.enter();
+        trace!("rendering podcast episodes");
+        let items = app.channel.as_ref().map(|c| c.items()).unwrap_or_default();
+        let listed = app.episode_rows();
+        let older = app.has_older_episodes();
+        self.len = listed.len() + usize::from(older);
+
+        let mut podcast_name = title(
+            app,
+            "Podcast",
+            app.channel.as_ref().map(|c| c.title()).unwrap_or("Title"),
+        );
+        if let Some(l) = loading(app) {
+            podcast_name.push_str(&format!(" {}", l));
+        }
+        if let Some(f) = app.episode_filter.as_deref() {
+            podcast_name.push_str(&format!(
+                " {} of {} match \"{}\"",
+                listed.len(),
+                items.len(),
+                f
+            ));
+        }
+        let range = app.visual_rows();
+        if let Some(r) = range.as_ref() {
+            podcast_name.push_str(&format!(" {} selected", r.clone().count()));
+        }
+        debug!(num_episodes = self.len, name = podcast_name);
+
+        let b = block(app, podcast_name);
+        let list_area = if app.config.accessible {
+            area
+        } else {
+            Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+                .split(area)[0]
+        };
+        self.rows = b.inner(list_area);
+        self.offset = scrolled(
+            self.offset,
+            self.state.selected(),
+            self.rows.height as usize,
+            self.len,
+        );
+        // only the rows that fit are made, feeds can have thousands of episodes
+        let window = self.offset..(self.offset + self.rows.height as usize).min(self.len);
+
+        // numbers are padded to the same width, for the columns after them to line up
+        let number_width = listed.last().copied().unwrap_or(0).to_string().len();
+        let glyph_width = EpisodeState::ALL
+            .iter()
+            .map(|s| app.theme.episode(*s).glyph.width())
+            .max()
+            .unwrap_or(1);
+        let row_width = (area.width as usize).saturating_sub(2 + highlight_symbol(app).width());
+        let columns = Columns::fit(row_width, glyph_width + number_width + 3);
+        let mut contents = listed
+            .get(window.start.min(listed.len())..window.end.min(listed.len()))
+            .unwrap_or_default()
+            .iter()
+            .zip(window.clone())
+            .filter_map(|(idx, row)| items.get(*idx).map(|item| (*idx, row, item)))
+            .map(|(idx, row, item)| {
+                // in the range being selected
+                let picked = range.as_ref().is_some_and(|r| r.contains(&row));
+                let title = item.title.as_deref().unwrap_or("Title missing!");
+                let state = app.episode_state(item);
+                let starred = app.is_starred(item);
+                let download = app.download_of(item).map(|j| &j.status);
+                let date = date(item);
+                let length = duration(item).map(fmt_duration);
+                if app.config.accessible {
+                    // spelled out, no need for glyphs or colors
+                    let mut line = match state {
+                        EpisodeState::Unplayed => format!("{}: {}", idx, title),
+                        s => format!("{}: {}, {}", idx, title, s.name()),
+                    };
+                    if let Some(d) = date {
+                        line.push_str(&format!(", published {}", d));
+                    }
+                    if let Some(l) = length {
+                        line.push_str(&format!(", {} long", l));
+                    }
+                    if starred {
+                        line.push_str(", starred");
+                    }
+                    if picked {
+                        line.push_str(", selected");
+                    }
+                    match download {
+                        Some(downloads::Status::Queued) => line.push_str(", queued to download"),
+                        Some(d @ downloads::Status::Active(..)) => {
+                            line.push_str(&format!(", downloading {}", d.describe()))
+                        }
+                        Some(downloads::Status::Paused(..)) => line.push_str(", download paused"),
+                        Some(downloads::Status::Done) => line.push_str(", downloaded"),
+                        Some(downloads::Status::Failed(_)) => line.push_str(", download failed"),
+                        None => {}
+                    }
+                    return ListItem::new(line);
+                }
+                let s = app.theme.episode(state);
+                let mut row = format!(
+                    "{}{}{:>width$}: {}",
+                    fit(&s.glyph, glyph_width),
+                    if picked { '+' } else { ' ' },
+                    idx,
+                    fit(title, columns.title),
+                    width = number_width
+                );
+                if columns.date {
+                    row.push_str(&format!(
+                        "  {:<w$}",
+                        date.unwrap_or_default(),
+                        w = DATE_WIDTH
+                    ));
+                }
+                if columns.length {
+                    row.push_str(&format!(
+                        "  {:>w$}",
+                        length.unwrap_or_default(),
+                        w = LENGTH_WIDTH
+                    ));
+                }
+                row.push_str(if starred { " ★ " } else { "   " });
+                let line = vec![Span::raw(row), download_marker(app, download)];
+                let style = if picked { app.theme.highlight } else { s.style };
+                ListItem::new(Spans::from(line)).style(style)
+            })
+            .collect::<Vec<ListItem>>();
+        if older && window.contains(&listed.len()) {
+            contents.push(
+                ListItem::new(format!(
+                    "{} ({} more)",
+                    OLDER_EPISODES,
+                    items.len() - app.shown_episodes()
+                ))
+                .style(app.theme.key),
+            );
+        }
+
+        // the selection is always marked with a symbol, never by styling alone
+        let contents = List::new(contents)
+            .block(b)
+            .highlight_style(app.theme.highlight)
+            .highlight_symbol(highlight_symbol(app));
+        // the rows made start at the first one shown
+        let mut state = ListState::default();
+        state.select(
+            self.state
+                .selected()
+                .filter(|s| window.contains(s))
+                .map(|s| s - window.start),
+        );
+        f.render_stateful_widget(contents, list_area, &mut state);
+        if !app.config.accessible {
+            let legend = Rect {
+                y: list_area.bottom(),
+                height: area.bottom() - list_area.bottom(),
+                ..area
+            };
+            f.render_widget(Paragraph::new(episode_legend(app)), legend);
+        }
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        let action = match key.code {
+            KeyCode::Up => Action::Previous,
+            KeyCode::Down => Action::Next,
+            KeyCode::Enter => Action::Open,
+            KeyCode::Char('a') => Action::Enqueue,
+            KeyCode::Char('P') => Action::MarkPlayed,
+            KeyCode::Char('d') => Action::Download,
+            KeyCode::Char('D') => Action::DeleteDownload,
+            KeyCode::Char('V') => Action::Visual,
+            KeyCode::Char('*') => Action::Star,
+            KeyCode::Char('m') => Action::SetMark,
+            KeyCode::Char('o') => Action::PodcastSettings,
+            _ => return None,
+        };
+        Some(action)
+    }
+
+    // A click selects the row under it, and opens it when it's selected already
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Option<Action> {
+        if !over(&mouse, self.rows) {
+            return None;
+        }
+        let action = match mouse.kind {
+            MouseEventKind::ScrollUp => Action::Previous,
+            MouseEventKind::ScrollDown => Action::Next,
+            MouseEventKind::Down(MouseButton::Left) => {
+                let idx = self.offset + (mouse.row - self.rows.top()) as usize;
+                match idx {
+                    i if i >= self.len => Action::Consumed,
+                    i if self.state.selected() == Some(i) => Action::Open,
+                    i => Action::Select(i),
+                }
+            }
+            _ => return None,
+        };
+        Some(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    use tui::layout::Rect;
+
+    use super::{fit, scrolled, Columns, EpisodeList};
+    use crate::ui::component::{Action, Component};
+
+    #[test]
+    fn fits_columns() {
+        assert_eq!(
+            Columns::fit(80, 6),
+            Columns {
+                title: 49,
+                date: true,
+                length: true
+            }
+        );
+        assert_eq!(
+            Columns::fit(50, 6),
+            Columns {
+                title: 31,
+                date: false,
+                length: true
+            }
+        );
+        assert_eq!(fit("Wine About It", 8), "Wine Ab…");
+        assert_eq!(fit("Wine", 6), "Wine  ");
+        // wide characters take two columns
+        assert_eq!(fit("日本語の番組", 7), "日本語…");
+    }
+
+    #[test]
+    fn scrolls_like_the_list() {
+        assert_eq!(scrolled(0, Some(3), 5, 10), 0);
+        assert_eq!(scrolled(0, Some(7), 5, 10), 3);
+        assert_eq!(scrolled(3, Some(1), 5, 10), 1);
+        assert_eq!(scrolled(3, None, 5, 10), 3);
+    }
+
+    #[test]
+    fn selects_clicked_row() {
+        let mut list = EpisodeList {
+            rows: Rect::new(1, 1, 20, 5),
+            offset: 3,
+            len: 10,
+            ..Default::default()
+        };
+        let click = |row| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 5,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        assert_eq!(list.handle_mouse(click(2)), Some(Action::Select(4)));
+        list.state.select(Some(4));
+        assert_eq!(list.handle_mouse(click(2)), Some(Action::Open));
+        assert_eq!(list.handle_mouse(click(0)), None);
+    }
+}
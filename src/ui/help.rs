@@ -0,0 +1,268 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    text::{Span, Spans},
+    widgets::{Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{message::DisplayAction, App};
+
+use super::{
+    block,
+    component::{Action, Component},
+    episode_legend, COMMANDS,
+};
+
+// Keys that work on every screen, as (keys, description) pairs
+const GLOBAL_KEYS: [(&str, &str); 24] = [
+    ("Esc", "Quit"),
+    ("?", "Show or hide this help"),
+    ("1-5, Tab, Shift-Tab", "Switch tabs"),
+    ("h", "Focus the sidebar, l or Tab to leave it"),
+    ("Space, p", "Pause or resume"),
+    ("Left, [", "Seek back"),
+    ("Right, ]", "Seek forward"),
+    ("+, -", "Play faster or slower"),
+    ("9, 0", "Turn the volume down or up"),
+    ("n, N", "Go to the next or previous chapter"),
+    ("v", "Show or hide now playing"),
+    ("x", "Stop playing"),
+    ("X", "Stop after the episode playing"),
+    ("S", "Skip silence"),
+    ("r", "Repeat the episode or the queue"),
+    ("l", "Loop part of the episode"),
+    ("b", "Bookmark the moment playing"),
+    ("' + letter", "Jump to a mark"),
+    ("Ctrl-O", "Go back in the jump list"),
+    ("Ctrl-F", "Go forward in the jump list"),
+    (
+        "Left, Right, Home, End",
+        "Move through the command being typed",
+    ),
+    (
+        "Ctrl-W, Ctrl-U",
+        "Delete a word, or up to the start, of the command",
+    ),
+    ("Up, Down", "Recall commands run before, once typing"),
+    ("Tab, Ctrl-N, Ctrl-P", "Complete the command being typed"),
+];
+
+// Name of the screen and the keys of its own
+fn screen_keys(display: &DisplayAction) -> (&'static str, &'static [(&'static str, &'static str)]) {
+    match display {
+        DisplayAction::Input => ("Start", &[("/", "Type a command")]),
+        DisplayAction::Preview => ("Preview", &[("Enter", "Subscribe or just browse")]),
+        DisplayAction::ListEpisodes => (
+            "Episodes",
+            &[
+                ("Up, Down", "Select an episode"),
+                ("Enter", "Show the episode's details"),
+                ("a", "Add to the queue"),
+                ("P", "Mark as played"),
+                ("d", "Download"),
+                ("D", "Delete the download"),
+                ("V", "Select a range of episodes for a, P, d and D"),
+                ("*", "Star"),
+                ("m + letter", "Mark the episode"),
+                ("o", "Podcast settings"),
+                ("/ + space + text", "Filter by title"),
+                ("Esc", "Clear the filter"),
+            ],
+        ),
+        DisplayAction::DescribeEpisode => (
+            "Episode details",
+            &[
+                ("Up, Down", "Scroll"),
+                ("Page Up, Page Down", "Scroll a page"),
+                ("Enter", "Play or open the file"),
+                ("d", "Download"),
+                ("*", "Star"),
+                ("m + letter", "Mark the episode"),
+                ("s", "Read the show notes aloud"),
+                ("o + number", "Open a link in the show notes"),
+            ],
+        ),
+        DisplayAction::Queue => (
+            "Queue",
+            &[
+                ("Enter", "Play"),
+                ("K, J", "Move up or down"),
+                ("T, B", "Move to the top or bottom"),
+            ],
+        ),
+        DisplayAction::Search => (
+            "Search",
+            &[
+                ("Enter", "Show the actions, or run one"),
+                ("Esc", "Back to the results"),
+            ],
+        ),
+        DisplayAction::Diagnostics => ("Diagnostics", &[("s", "Change the order")]),
+        DisplayAction::Library => (
+            "Library",
+            &[
+                ("Enter", "Show the podcast's episodes"),
+                ("s", "Change the order"),
+                ("o", "Podcast settings"),
+            ],
+        ),
+        DisplayAction::PodcastSettings => (
+            "Podcast settings",
+            &[
+                ("Left, Right", "Change the setting"),
+                ("Backspace", "Reset the setting"),
+            ],
+        ),
+        DisplayAction::Starred => ("Starred", &[("Enter", "Play"), ("*", "Unstar")]),
+        DisplayAction::History => ("History", &[("Enter", "Play from where it was left")]),
+        DisplayAction::Downloads => (
+            "Downloads",
+            &[
+                ("Enter", "Play"),
+                ("d", "Try a failed download again"),
+                ("P", "Pause or resume"),
+                ("c", "Cancel"),
+                ("D", "Delete"),
+            ],
+        ),
+        DisplayAction::Storage => ("Storage", &[("D", "Delete downloads and cached feeds")]),
+        DisplayAction::CleanUp => ("Clean up", &[("D", "Delete them all")]),
+        DisplayAction::Bookmarks => ("Bookmarks", &[("Enter", "Play"), ("d", "Delete")]),
+        DisplayAction::NowPlaying => ("Now playing", &[("Enter", "Pause or resume")]),
+    }
+}
+
+// Help is a popup over the screen listing the keys of the screen, the ones that work
+// everywhere and the commands. It takes every key while it's open.
+#[derive(Default, Debug)]
+pub struct Help {
+    pub shown: bool,
+    // lines scrolled past
+    scroll: u16,
+    // lines in the popup, to stop scrolling at the end
+    len: u16,
+}
+
+impl Component for Help {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        let (name, keys) = screen_keys(&app.display_action);
+        let mut lines = vec![];
+        let heading = |lines: &mut Vec<Spans>, heading: String| {
+            if !lines.is_empty() {
+                lines.push(Spans::default());
+            }
+            lines.push(Spans::from(Span::styled(heading, app.theme.highlight)));
+        };
+        let section = |lines: &mut Vec<Spans>, title: String, rows: &[(&str, &str)]| {
+            heading(lines, title);
+            let width = rows.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+            for (k, desc) in rows {
+                lines.push(if app.config.accessible {
+                    Spans::from(format!("{}: {}", k, desc))
+                } else {
+                    Spans::from(vec![
+                        Span::styled(format!("{:width$}", k, width = width), app.theme.key),
+                        Span::raw(format!("  {}", desc)),
+                    ])
+                });
+            }
+        };
+        section(
+            &mut lines,
+            format!("Keys on the {} screen", name.to_lowercase()),
+            keys,
+        );
+        // also shown under the episode list, except in accessible mode
+        if app.display_action == DisplayAction::ListEpisodes {
+            heading(
+                &mut lines,
+                "What the marks in front of episodes mean".to_string(),
+            );
+            lines.push(episode_legend(app));
+        }
+        section(&mut lines, "Keys on every screen".to_string(), &GLOBAL_KEYS);
+        section(&mut lines, "Commands".to_string(), &COMMANDS);
+
+        self.len = lines.len() as u16;
+        self.scroll = self.scroll.min(self.len.saturating_sub(1));
+        let popup = centered(area, 80, 80);
+        let contents = Paragraph::new(lines)
+            .block(block(app, "Help, Esc closes").style(app.theme.base))
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0));
+        // the popup would show through to the screen under it otherwise
+        f.render_widget(Clear, popup);
+        f.render_widget(contents, popup);
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        if !self.shown {
+            if key.code == KeyCode::Char('?') {
+                self.shown = true;
+                self.scroll = 0;
+                return Some(Action::Consumed);
+            }
+            return None;
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('?' | 'q') => self.shown = false,
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll = (self.scroll + 1).min(self.len.saturating_sub(1)),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll = (self.scroll + 10).min(self.len.saturating_sub(1)),
+            _ => {}
+        }
+        Some(Action::Consumed)
+    }
+}
+
+// The part of the area this percentage of its width and height takes, in its middle
+pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let w = area.width * width / 100;
+    let h = area.height * height / 100;
+    Rect {
+        x: area.x + (area.width - w) / 2,
+        y: area.y + (area.height - h) / 2,
+        width: w,
+        height: h,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use tui::layout::Rect;
+
+    use super::{centered, Help};
+    use crate::ui::component::{Action, Component};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn opens_and_closes() {
+        let mut h = Help::default();
+        assert_eq!(h.handle_event(key(KeyCode::Char('a'))), None);
+        assert_eq!(
+            h.handle_event(key(KeyCode::Char('?'))),
+            Some(Action::Consumed)
+        );
+        // every key is taken while it's open
+        assert_eq!(
+            h.handle_event(key(KeyCode::Char('a'))),
+            Some(Action::Consumed)
+        );
+        assert!(h.shown);
+        h.handle_event(key(KeyCode::Esc));
+        assert!(!h.shown);
+    }
+
+    #[test]
+    fn centers_popup() {
+        let area = Rect::new(0, 0, 100, 50);
+        assert_eq!(centered(area, 80, 80), Rect::new(10, 5, 80, 40));
+    }
+}
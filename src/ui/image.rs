@@ -0,0 +1,46 @@
+use image::{imageops::FilterType, DynamicImage};
+use tui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+
+// Image renders a picture with unicode half blocks: each cell shows two vertically
+// stacked pixels, the upper one as the foreground and the lower one as the background.
+// This works on any terminal with true color support, no graphics protocol required.
+pub struct Image<'a> {
+    img: &'a DynamicImage,
+}
+
+impl<'a> Image<'a> {
+    pub fn new(img: &'a DynamicImage) -> Self {
+        Image { img }
+    }
+}
+
+impl<'a> Widget for Image<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        // keeps the aspect ratio, so the result may be smaller than the area
+        let img = self
+            .img
+            .resize(
+                area.width.into(),
+                u32::from(area.height) * 2,
+                FilterType::Nearest,
+            )
+            .to_rgb8();
+        // center horizontally
+        let offset = (u32::from(area.width) - img.width()) / 2;
+
+        for y in 0..img.height().div_ceil(2) {
+            for x in 0..img.width() {
+                let top = img.get_pixel(x, y * 2);
+                let cell = buf.get_mut(area.x + (offset + x) as u16, area.y + y as u16);
+                cell.set_symbol("▀")
+                    .set_fg(Color::Rgb(top[0], top[1], top[2]));
+                if let Some(bottom) = img.get_pixel_checked(x, y * 2 + 1) {
+                    cell.set_bg(Color::Rgb(bottom[0], bottom[1], bottom[2]));
+                }
+            }
+        }
+    }
+}
@@ -1,8 +1,77 @@
-#[derive(Default, Debug, PartialEq, Eq)]
+use std::time::Duration;
+
+#[derive(Default, Debug, PartialEq)]
 pub enum Command {
     #[default]
     NoOp,
     FetchPodcastFeed(String),
+    ToggleAccessibility,
+    SetTheme(String),
+    ShowQueue,
+    MoveQueueEntry(usize, usize),
+    Search(String),
+    MarkAllPlayed,
+    // mark the selected episode as played, or unplayed when false
+    MarkPlayed(bool),
+    Speak,
+    ShowDiagnostics,
+    ShowLibrary,
+    SetSpeed(f32),
+    // speed for the podcast's episodes, or back to the global speed when None
+    SetPodcastSpeed(Option<f32>),
+    SetVolume(u8),
+    // stop playback after a while, or at the end of the episode when None
+    Sleep(Option<Duration>),
+    CancelSleep,
+    // seconds to skip at the start of the podcast's episodes, or before their end
+    SkipIntro(u64),
+    SkipOutro(u64),
+    // skip the podcast's ad chapters, or stop skipping them
+    SkipAds(bool),
+    // bookmark the moment playing, with an optional note
+    Bookmark(Option<String>),
+    ShowBookmarks,
+    // go to the timestamp in the episode playing
+    Seek(Duration),
+    // play episodes with this command instead of in the app, or in the app again when None
+    OpenWith(Option<String>),
+    // add a feed to the library, or the loaded one when None
+    Subscribe(Option<String>),
+    // add a private feed, or explain how to find its URL when None
+    SubscribePrivate(Option<String>),
+    // take the selected podcast out of the library, or the loaded one
+    Unsubscribe,
+    // write the library to this file as OPML
+    ExportOpml(String),
+    // write the loaded feed's episodes to this file, as JSON or CSV
+    ExportEpisodes(String),
+    // write all of the user's data to this file, or replace it with the file's
+    Backup(String),
+    RestoreBackup(String),
+    // fetch every subscription again
+    Refresh,
+    // show the settings of the podcast playing, or the one loaded
+    ShowPodcastSettings,
+    ShowStarred,
+    ShowHistory,
+    ShowDownloads,
+    ShowStorage,
+    // list the downloads cleaning up would delete
+    ShowCleanUp,
+    // tag the selected podcast, or the loaded one, or take the tag off
+    TagPodcast(String),
+    UntagPodcast(String),
+    // only list podcasts with the tag in the library, or all of them when None
+    FilterLibrary(Option<String>),
+    ListTags,
+    // archive the selected podcast, or the loaded one, or bring it back when false
+    ArchivePodcast(bool),
+    ShowArchive,
+    // only list the loaded podcast's episodes with this in their title, or all of them
+    // again when empty
+    FilterEpisodes(String),
+    // open the link with this number in the show notes
+    OpenLink(usize),
 }
 
 pub fn parse(s: &str) -> Command {
@@ -18,12 +87,199 @@ pub fn parse(s: &str) -> Command {
 
     match op {
         "/load" => Command::FetchPodcastFeed(args.join("")),
+        // a slash on its own, commands always have a name after it
+        "/" => Command::FilterEpisodes(args.join(" ").trim().to_string()),
+        "/accessible" => Command::ToggleAccessibility,
+        "/theme" => Command::SetTheme(args.join(" ")),
+        "/queue" => match args.as_slice() {
+            [] => Command::ShowQueue,
+            [cmd, from, to] if cmd == "move" => match (from.parse(), to.parse()) {
+                (Ok(from), Ok(to)) => Command::MoveQueueEntry(from, to),
+                _ => Command::NoOp,
+            },
+            _ => Command::NoOp,
+        },
+        "/search" => Command::Search(args.join(" ")),
+        "/speak" => Command::Speak,
+        "/diagnostics" => Command::ShowDiagnostics,
+        "/library" => Command::ShowLibrary,
+        "/subscribe" => match args.join("").trim() {
+            "" => Command::Subscribe(None),
+            url => Command::Subscribe(Some(url.to_string())),
+        },
+        "/private" => match args.join("").trim() {
+            "" => Command::SubscribePrivate(None),
+            url => Command::SubscribePrivate(Some(url.to_string())),
+        },
+        "/unsubscribe" => Command::Unsubscribe,
+        "/refresh" => Command::Refresh,
+        "/settings" => Command::ShowPodcastSettings,
+        "/starred" => Command::ShowStarred,
+        "/history" => Command::ShowHistory,
+        "/downloads" => Command::ShowDownloads,
+        "/storage" => Command::ShowStorage,
+        "/cleanup" => Command::ShowCleanUp,
+        "/archive" => Command::ArchivePodcast(true),
+        "/unarchive" => Command::ArchivePodcast(false),
+        "/archived" => Command::ShowArchive,
+        "/tag" => {
+            let tag = args
+                .get(1..)
+                .unwrap_or_default()
+                .join(" ")
+                .trim()
+                .to_string();
+            match (args.first().map(String::as_str), tag.as_str()) {
+                (None, _) => Command::ListTags,
+                (Some("add"), t) if !t.is_empty() => Command::TagPodcast(tag),
+                (Some("remove"), t) if !t.is_empty() => Command::UntagPodcast(tag),
+                (Some("filter"), "" | "off") => Command::FilterLibrary(None),
+                (Some("filter"), _) => Command::FilterLibrary(Some(tag)),
+                _ => Command::NoOp,
+            }
+        }
+        "/backup" | "/restore" => match args.join(" ").trim() {
+            "" => Command::NoOp,
+            path if op == "/backup" => Command::Backup(path.to_string()),
+            path => Command::RestoreBackup(path.to_string()),
+        },
+        "/export" => match args.split_first() {
+            Some((what, path)) if !path.join(" ").trim().is_empty() => {
+                let path = path.join(" ").trim().to_string();
+                match what.as_str() {
+                    "opml" => Command::ExportOpml(path),
+                    "episodes" => Command::ExportEpisodes(path),
+                    _ => Command::NoOp,
+                }
+            }
+            _ => Command::NoOp,
+        },
+        "/speed" => match args.first().and_then(|s| parse_speed(s)) {
+            Some(speed) => Command::SetSpeed(speed),
+            None => Command::NoOp,
+        },
+        "/podcast-speed" => match args.first().map(String::as_str) {
+            Some("off") => Command::SetPodcastSpeed(None),
+            Some(s) => match parse_speed(s) {
+                Some(speed) => Command::SetPodcastSpeed(Some(speed)),
+                None => Command::NoOp,
+            },
+            None => Command::NoOp,
+        },
+        "/sleep" => match args.first().map(String::as_str) {
+            Some("eoe") => Command::Sleep(None),
+            Some("off") => Command::CancelSleep,
+            Some(d) => match parse_duration(d) {
+                Some(d) => Command::Sleep(Some(d)),
+                None => Command::NoOp,
+            },
+            None => Command::NoOp,
+        },
+        "/volume" => match args.first().map(|s| s.trim_end_matches('%').parse()) {
+            Some(Ok(volume)) if volume <= 100 => Command::SetVolume(volume),
+            _ => Command::NoOp,
+        },
+        "/bookmark" => match args.join(" ").trim() {
+            "" => Command::Bookmark(None),
+            note => Command::Bookmark(Some(note.to_string())),
+        },
+        "/bookmarks" => Command::ShowBookmarks,
+        "/skip" => {
+            let secs = match args.get(1).map(String::as_str) {
+                Some("off") => Some(0),
+                Some(t) => parse_timestamp(t).map(|d| d.as_secs()),
+                None => None,
+            };
+            match (args.first().map(String::as_str), secs) {
+                (Some("intro"), Some(secs)) => Command::SkipIntro(secs),
+                (Some("outro"), Some(secs)) => Command::SkipOutro(secs),
+                (Some("ads"), _) => match args.get(1).map(String::as_str) {
+                    Some("on") => Command::SkipAds(true),
+                    Some("off") => Command::SkipAds(false),
+                    _ => Command::NoOp,
+                },
+                _ => Command::NoOp,
+            }
+        }
+        "/link" => match args.first().and_then(|n| n.parse().ok()) {
+            Some(n) => Command::OpenLink(n),
+            None => Command::NoOp,
+        },
+        "/seek" => match args.first().and_then(|t| parse_timestamp(t)) {
+            Some(to) => Command::Seek(to),
+            None => Command::NoOp,
+        },
+        "/open-with" => match args.join(" ").trim() {
+            "" => Command::OpenWith(None),
+            cmd => Command::OpenWith(Some(cmd.to_string())),
+        },
+        "/mark" => match args.first().map(String::as_str) {
+            Some("all") => Command::MarkAllPlayed,
+            Some("played") => Command::MarkPlayed(true),
+            Some("unplayed") => Command::MarkPlayed(false),
+            _ => Command::NoOp,
+        },
         _ => Command::NoOp,
     }
 }
 
+// Parse a playback speed like "1.5" or "1.5x". Speeds that aren't numbers, like "nan",
+// would stop the player.
+fn parse_speed(s: &str) -> Option<f32> {
+    s.trim_end_matches('x')
+        .parse::<f32>()
+        .ok()
+        .filter(|speed| speed.is_finite())
+}
+
+// Parse a timestamp like "1:23:45", "23:45" or "45", as show notes write them
+fn parse_timestamp(s: &str) -> Option<Duration> {
+    let parts = s
+        .split(':')
+        .map(|p| p.parse::<u64>().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    // only the leading part can go past 59
+    if parts.len() > 3 || parts.iter().skip(1).any(|p| *p > 59) {
+        return None;
+    }
+    // one too long to count in seconds isn't a timestamp
+    let secs = parts
+        .iter()
+        .try_fold(0u64, |secs, p| secs.checked_mul(60)?.checked_add(*p))?;
+    Some(Duration::from_secs(secs))
+}
+
+// Parse a duration like "30m", "1h30m" or "45s". A bare number is in minutes. One too
+// long to count in seconds isn't a duration.
+fn parse_duration(s: &str) -> Option<Duration> {
+    if let Ok(minutes) = s.parse::<u64>() {
+        return Some(Duration::from_secs(minutes.checked_mul(60)?));
+    }
+    let mut secs: u64 = 0;
+    let mut number = String::new();
+    for c in s.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' | 's' => {
+                let n = number.parse::<u64>().ok()?;
+                number.clear();
+                let unit = match c {
+                    'h' => 3600,
+                    'm' => 60,
+                    _ => 1,
+                };
+                secs = secs.checked_add(n.checked_mul(unit)?)?;
+            }
+            _ => return None,
+        }
+    }
+    Some(Duration::from_secs(secs)).filter(|d| number.is_empty() && !d.is_zero())
+}
+
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use crate::ui::input::Command;
 
     use super::parse;
@@ -48,6 +304,232 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_open_link() {
+        assert_eq!(parse("/link 12"), Command::OpenLink(12));
+        assert_eq!(parse("/link"), Command::NoOp);
+    }
+
+    #[test]
+    fn parses_filter_episodes() {
+        assert_eq!(
+            parse("/ wine tasting "),
+            Command::FilterEpisodes("wine tasting".to_string())
+        );
+        assert_eq!(parse("/"), Command::FilterEpisodes(String::new()));
+    }
+
+    #[test]
+    fn parses_toggle_accessibility() {
+        assert_eq!(parse("/accessible"), Command::ToggleAccessibility);
+    }
+
+    #[test]
+    fn parses_set_theme() {
+        assert_eq!(
+            parse("/theme high-contrast"),
+            Command::SetTheme("high-contrast".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_show_queue() {
+        assert_eq!(parse("/queue"), Command::ShowQueue);
+        assert_eq!(parse("/queue move 3 0"), Command::MoveQueueEntry(3, 0));
+        assert_eq!(parse("/queue move 3"), Command::NoOp);
+        assert_eq!(parse("/queue move a b"), Command::NoOp);
+    }
+
+    #[test]
+    fn parses_search() {
+        assert_eq!(
+            parse("/search wine about it"),
+            Command::Search("wine about it".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_screens() {
+        assert_eq!(parse("/speak"), Command::Speak);
+        assert_eq!(parse("/diagnostics"), Command::ShowDiagnostics);
+        assert_eq!(parse("/library"), Command::ShowLibrary);
+        assert_eq!(parse("/refresh"), Command::Refresh);
+        assert_eq!(parse("/settings"), Command::ShowPodcastSettings);
+        assert_eq!(parse("/starred"), Command::ShowStarred);
+        assert_eq!(parse("/history"), Command::ShowHistory);
+        assert_eq!(parse("/downloads"), Command::ShowDownloads);
+        assert_eq!(parse("/storage"), Command::ShowStorage);
+        assert_eq!(parse("/cleanup"), Command::ShowCleanUp);
+    }
+
+    #[test]
+    fn parses_tag() {
+        assert_eq!(parse("/tag"), Command::ListTags);
+        assert_eq!(
+            parse("/tag add true crime"),
+            Command::TagPodcast("true crime".to_string())
+        );
+        assert_eq!(
+            parse("/tag remove news"),
+            Command::UntagPodcast("news".to_string())
+        );
+        assert_eq!(
+            parse("/tag filter tech"),
+            Command::FilterLibrary(Some("tech".to_string()))
+        );
+        assert_eq!(parse("/tag filter off"), Command::FilterLibrary(None));
+        assert_eq!(parse("/tag filter"), Command::FilterLibrary(None));
+        assert_eq!(parse("/tag add"), Command::NoOp);
+    }
+
+    #[test]
+    fn parses_backup() {
+        assert_eq!(
+            parse("/backup ~/podcasts.json"),
+            Command::Backup("~/podcasts.json".to_string())
+        );
+        assert_eq!(
+            parse("/restore ~/podcasts.json"),
+            Command::RestoreBackup("~/podcasts.json".to_string())
+        );
+        assert_eq!(parse("/restore"), Command::NoOp);
+    }
+
+    #[test]
+    fn parses_archive() {
+        assert_eq!(parse("/archive"), Command::ArchivePodcast(true));
+        assert_eq!(parse("/unarchive"), Command::ArchivePodcast(false));
+        assert_eq!(parse("/archived"), Command::ShowArchive);
+    }
+
+    #[test]
+    fn parses_subscribe() {
+        assert_eq!(
+            parse("/subscribe https://example.com/feed"),
+            Command::Subscribe(Some("https://example.com/feed".to_string()))
+        );
+        assert_eq!(parse("/subscribe"), Command::Subscribe(None));
+        assert_eq!(
+            parse("/private https://www.patreon.com/rss/show?auth=abc"),
+            Command::SubscribePrivate(Some(
+                "https://www.patreon.com/rss/show?auth=abc".to_string()
+            ))
+        );
+        assert_eq!(parse("/private"), Command::SubscribePrivate(None));
+        assert_eq!(parse("/unsubscribe"), Command::Unsubscribe);
+    }
+
+    #[test]
+    fn parses_export() {
+        assert_eq!(
+            parse("/export opml ~/My Podcasts.opml"),
+            Command::ExportOpml("~/My Podcasts.opml".to_string())
+        );
+        assert_eq!(parse("/export opml"), Command::NoOp);
+        assert_eq!(
+            parse("/export episodes wine.csv"),
+            Command::ExportEpisodes("wine.csv".to_string())
+        );
+        assert_eq!(parse("/export csv out.csv"), Command::NoOp);
+    }
+
+    #[test]
+    fn parses_speed() {
+        assert_eq!(parse("/speed 1.5"), Command::SetSpeed(1.5));
+        assert_eq!(parse("/speed 2x"), Command::SetSpeed(2.0));
+        assert_eq!(parse("/speed fast"), Command::NoOp);
+        assert_eq!(parse("/speed nan"), Command::NoOp);
+        assert_eq!(parse("/speed inf"), Command::NoOp);
+    }
+
+    #[test]
+    fn parses_podcast_speed() {
+        assert_eq!(
+            parse("/podcast-speed 1.5x"),
+            Command::SetPodcastSpeed(Some(1.5))
+        );
+        assert_eq!(parse("/podcast-speed off"), Command::SetPodcastSpeed(None));
+        assert_eq!(parse("/podcast-speed fast"), Command::NoOp);
+        assert_eq!(parse("/podcast-speed NaN"), Command::NoOp);
+    }
+
+    #[test]
+    fn parses_sleep() {
+        let minutes = |m: u64| Command::Sleep(Some(Duration::from_secs(m * 60)));
+        assert_eq!(parse("/sleep 30m"), minutes(30));
+        assert_eq!(parse("/sleep 45"), minutes(45));
+        assert_eq!(parse("/sleep 1h30m"), minutes(90));
+        assert_eq!(parse("/sleep eoe"), Command::Sleep(None));
+        assert_eq!(parse("/sleep off"), Command::CancelSleep);
+        assert_eq!(parse("/sleep 30x"), Command::NoOp);
+        assert_eq!(parse("/sleep 0m"), Command::NoOp);
+        assert_eq!(parse("/sleep 18446744073709551615"), Command::NoOp);
+        assert_eq!(parse("/sleep 5124095576030432h"), Command::NoOp);
+        assert_eq!(parse("/sleep 18446744073709551615s1s"), Command::NoOp);
+        assert_eq!(parse("/sleep"), Command::NoOp);
+    }
+
+    #[test]
+    fn parses_volume() {
+        assert_eq!(parse("/volume 80"), Command::SetVolume(80));
+        assert_eq!(parse("/volume 50%"), Command::SetVolume(50));
+        assert_eq!(parse("/volume 101"), Command::NoOp);
+        assert_eq!(parse("/volume"), Command::NoOp);
+    }
+
+    #[test]
+    fn parses_mark() {
+        assert_eq!(parse("/mark all"), Command::MarkAllPlayed);
+        assert_eq!(parse("/mark played"), Command::MarkPlayed(true));
+        assert_eq!(parse("/mark unplayed"), Command::MarkPlayed(false));
+        assert_eq!(parse("/mark"), Command::NoOp);
+    }
+
+    #[test]
+    fn parses_bookmark() {
+        assert_eq!(
+            parse("/bookmark the wine list"),
+            Command::Bookmark(Some("the wine list".to_string()))
+        );
+        assert_eq!(parse("/bookmark"), Command::Bookmark(None));
+        assert_eq!(parse("/bookmarks"), Command::ShowBookmarks);
+    }
+
+    #[test]
+    fn parses_skip() {
+        assert_eq!(parse("/skip intro 45"), Command::SkipIntro(45));
+        assert_eq!(parse("/skip intro 1:30"), Command::SkipIntro(90));
+        assert_eq!(parse("/skip outro 30"), Command::SkipOutro(30));
+        assert_eq!(parse("/skip outro off"), Command::SkipOutro(0));
+        assert_eq!(parse("/skip ads on"), Command::SkipAds(true));
+        assert_eq!(parse("/skip ads off"), Command::SkipAds(false));
+        assert_eq!(parse("/skip ads 30"), Command::NoOp);
+        assert_eq!(parse("/skip intro"), Command::NoOp);
+    }
+
+    #[test]
+    fn parses_seek() {
+        let secs = |s: u64| Command::Seek(Duration::from_secs(s));
+        assert_eq!(parse("/seek 1:23:45"), secs(5025));
+        assert_eq!(parse("/seek 23:05"), secs(1385));
+        assert_eq!(parse("/seek 90"), secs(90));
+        assert_eq!(parse("/seek 0:00"), secs(0));
+        assert_eq!(parse("/seek 1:75"), Command::NoOp);
+        assert_eq!(parse("/seek 1:2:3:4"), Command::NoOp);
+        assert_eq!(parse("/seek 1m"), Command::NoOp);
+        assert_eq!(parse("/seek 307445734561825861:00"), Command::NoOp);
+        assert_eq!(parse("/seek"), Command::NoOp);
+    }
+
+    #[test]
+    fn parses_open_with() {
+        assert_eq!(
+            parse("/open-with mpv --no-video"),
+            Command::OpenWith(Some("mpv --no-video".to_string()))
+        );
+        assert_eq!(parse("/open-with"), Command::OpenWith(None));
+    }
+
     #[test]
     fn parses_no_op() {
         let input = "something";
@@ -0,0 +1,374 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::{backend::Backend, layout::Rect, style::Style, widgets::Paragraph, Frame};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{commands::Commands, feed::private::redact_text, App};
+
+use super::{
+    block,
+    complete::{self, completions, Completion},
+    component::{Action, Component},
+    sidebar,
+};
+
+// InputBox is where commands are typed. Typing starts with a slash, other keys are
+// shortcuts while it's empty. Up and Down recall the commands typed before that start with
+// what was typed.
+#[derive(Default, Debug)]
+pub struct InputBox {
+    text: String,
+    // where the next character goes, as a byte offset into the text
+    cursor: usize,
+    pub commands: Commands,
+    // where the command recalled is in the history, and what was typed before recalling it
+    recalled: Option<(usize, String)>,
+    // ways to finish what was typed when last drawn, and the one Tab takes
+    completions: Vec<Completion>,
+    completed: String,
+    selected: usize,
+    // where it was last drawn, for the completions to go under
+    area: Rect,
+}
+
+impl InputBox {
+    // What was typed, emptying the box
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        std::mem::take(&mut self.text)
+    }
+
+    // Where the character before the cursor starts. Characters are what shows as one, like
+    // an emoji made of several or a letter with an accent added.
+    fn prev(&self) -> usize {
+        self.text[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    // Where the character after the cursor ends
+    fn next(&self) -> usize {
+        self.text[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map_or(self.cursor, |g| self.cursor + g.len())
+    }
+
+    // Where the word before the cursor starts, after any spaces up to the cursor
+    fn word_start(&self) -> usize {
+        let before = self.text[..self.cursor].trim_end();
+        before.rfind(' ').map_or(0, |i| i + 1)
+    }
+
+    // Cut the text from `start` up to the cursor
+    fn delete_back(&mut self, start: usize) {
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    // Go to an older or newer command in the history, back to what was typed past the latest
+    fn recall(&mut self, older: bool) {
+        let (at, typed) = match self.recalled.take() {
+            Some((at, typed)) => (Some(at), typed),
+            None => (None, self.text.clone()),
+        };
+        let found = match (older, at) {
+            (true, _) => self.commands.older(at, &typed),
+            (false, Some(at)) => self.commands.newer(at, &typed),
+            (false, None) => None,
+        };
+        match found {
+            Some((i, cmd)) => {
+                self.text = cmd.to_string();
+                self.recalled = Some((i, typed));
+            }
+            None if !older => self.text = typed,
+            // nothing older, the oldest stays
+            None => self.recalled = at.map(|at| (at, typed)),
+        }
+        self.cursor = self.text.len();
+    }
+
+    // The completions of what's in the box, none when it changed since they were drawn
+    fn current_completions(&self) -> &[Completion] {
+        if self.completed == self.text {
+            &self.completions
+        } else {
+            &[]
+        }
+    }
+
+    // Take the highlighted completion in place of what was typed
+    fn complete(&mut self) -> Option<Action> {
+        let c = self.current_completions().get(self.selected)?;
+        self.text = c.text.clone();
+        self.cursor = self.text.len();
+        self.selected = 0;
+        Some(Action::Consumed)
+    }
+
+    // Ctrl-W, Ctrl-U, and Ctrl-N and Ctrl-P to go through the completions, the only keys
+    // with modifiers it takes
+    fn handle_control(&mut self, key: KeyEvent) -> Option<Action> {
+        if self.text.is_empty() || key.modifiers != KeyModifiers::CONTROL {
+            return None;
+        }
+        let len = self.current_completions().len();
+        match key.code {
+            KeyCode::Char('w') => self.delete_back(self.word_start()),
+            KeyCode::Char('u') => self.delete_back(0),
+            KeyCode::Char('n') if len > 0 => self.selected = (self.selected + 1) % len,
+            KeyCode::Char('p') if len > 0 => self.selected = (self.selected + len - 1) % len,
+            _ => return None,
+        }
+        Some(Action::Consumed)
+    }
+
+    // The popup of completions goes over the rest of the screen, so it's drawn last
+    pub fn draw_completions<B: Backend>(&self, f: &mut Frame<B>, app: &App) {
+        let shown = self.current_completions();
+        complete::draw_completions(f, app, self.area, shown, self.selected);
+    }
+
+    // The filter for the episodes being typed, a slash and a space then the text
+    pub fn filter(&self) -> Option<&str> {
+        self.text.strip_prefix("/ ")
+    }
+}
+
+impl Component for InputBox {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        // there is no left border to skip over in accessible mode
+        let border = u16::from(!app.config.accessible);
+
+        // pasted private feed URLs shouldn't be readable over the user's shoulder
+        let text = redact_text(self.text.as_str());
+        let before = redact_text(&self.text[..self.cursor]);
+        let at = if text.starts_with(before.as_str()) {
+            before.len()
+        } else {
+            text.len()
+        };
+        // text too long for the box scrolls, keeping the cursor in it
+        let width = area.width.saturating_sub(2 * border) as usize;
+        let start = scrolled(&text, at, width);
+        let input = Paragraph::new(&text[start..])
+            .style(Style::default())
+            .block(block(app, "Input"));
+        f.render_widget(input, area);
+        self.area = area;
+
+        // commands, not the filter, get completed
+        if self.text != self.completed {
+            let podcasts = sidebar::podcasts(&app.subscriptions);
+            let podcasts = podcasts
+                .iter()
+                .map(|s| (s.title.as_str(), s.url.as_str()))
+                .collect::<Vec<(&str, &str)>>();
+            self.completions = match self.filter() {
+                None if self.text.starts_with('/') => completions(&self.text, &podcasts),
+                _ => vec![],
+            };
+            self.completed = self.text.clone();
+            self.selected = 0;
+        }
+
+        // Make the cursor visible and ask tui-rs to put it at the specified coordinates after rendering
+        f.set_cursor(
+            // Put cursor past the text before it, as wide as it shows
+            area.x + columns(&text[start..at]) as u16 + border,
+            // Move one line down, from the border (or title) to the input line
+            area.y + 1,
+        );
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return self.handle_control(key);
+        }
+        // editing starts over from what's in the box
+        if !matches!(key.code, KeyCode::Up | KeyCode::Down) || self.text.is_empty() {
+            self.recalled = None;
+        }
+        match key.code {
+            KeyCode::Tab if !self.text.is_empty() => return self.complete(),
+            // commands always start with a slash, other keys are shortcuts until then
+            KeyCode::Char(c) if !self.text.is_empty() || c == '/' => {
+                self.text.insert(self.cursor, c);
+                self.cursor += c.len_utf8();
+            }
+            KeyCode::Backspace if !self.text.is_empty() => self.delete_back(self.prev()),
+            KeyCode::Delete if !self.text.is_empty() => {
+                self.text.replace_range(self.cursor..self.next(), "");
+            }
+            KeyCode::Enter if !self.text.is_empty() => {
+                self.commands.push(self.text.as_str());
+                return Some(Action::Submit(self.take()));
+            }
+            // no seeking while typing
+            KeyCode::Left if !self.text.is_empty() => self.cursor = self.prev(),
+            KeyCode::Right if !self.text.is_empty() => self.cursor = self.next(),
+            KeyCode::Up if !self.text.is_empty() => self.recall(true),
+            KeyCode::Down if !self.text.is_empty() => self.recall(false),
+            KeyCode::Home if !self.text.is_empty() => self.cursor = 0,
+            KeyCode::End if !self.text.is_empty() => self.cursor = self.text.len(),
+            _ => return None,
+        }
+        Some(Action::Consumed)
+    }
+}
+
+// Where to start showing the text for the cursor, at the byte offset, to fit in the width.
+// As much of the text before the cursor is shown as fits.
+fn scrolled(text: &str, at: usize, width: usize) -> usize {
+    text[..at]
+        .grapheme_indices(true)
+        .map(|(i, _)| i)
+        .find(|i| columns(&text[*i..at]) < width)
+        .unwrap_or(at)
+}
+
+// How many columns the text takes, a grapheme at a time, so that an emoji joined from
+// several (e.g. a family) counts once
+fn columns(text: &str) -> usize {
+    text.graphemes(true).map(|g| g.width()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{columns, scrolled, InputBox};
+    use crate::ui::component::{Action, Component};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn typed(s: &str) -> InputBox {
+        let mut b = InputBox::default();
+        for c in s.chars() {
+            b.handle_event(key(KeyCode::Char(c)));
+        }
+        b
+    }
+
+    #[test]
+    fn edits_at_cursor() {
+        let mut b = typed("/lod");
+        b.handle_event(key(KeyCode::Left));
+        b.handle_event(key(KeyCode::Char('a')));
+        assert_eq!(b.text, "/load");
+        b.handle_event(key(KeyCode::Home));
+        b.handle_event(key(KeyCode::Delete));
+        assert_eq!(b.text, "load");
+        b.handle_event(key(KeyCode::End));
+        b.handle_event(key(KeyCode::Backspace));
+        assert_eq!(b.text, "loa");
+
+        let mut b = typed("/search café");
+        b.handle_event(key(KeyCode::Left));
+        b.handle_event(key(KeyCode::Backspace));
+        assert_eq!(b.text, "/search caé");
+        assert_eq!(
+            b.handle_event(key(KeyCode::Enter)),
+            Some(Action::Submit("/search caé".to_string()))
+        );
+        assert_eq!(b.cursor, 0);
+    }
+
+    #[test]
+    fn recalls_commands() {
+        let mut b = InputBox::default();
+        for cmd in ["/search wine", "/load x", "/search beer"] {
+            b.commands.push(cmd);
+        }
+        for c in "/s".chars() {
+            b.handle_event(key(KeyCode::Char(c)));
+        }
+        b.handle_event(key(KeyCode::Up));
+        assert_eq!(b.text, "/search beer");
+        b.handle_event(key(KeyCode::Up));
+        assert_eq!(b.text, "/search wine");
+        // the oldest stays
+        b.handle_event(key(KeyCode::Up));
+        assert_eq!(b.text, "/search wine");
+        b.handle_event(key(KeyCode::Down));
+        assert_eq!(b.text, "/search beer");
+        // back to what was typed
+        b.handle_event(key(KeyCode::Down));
+        assert_eq!(b.text, "/s");
+
+        b.handle_event(key(KeyCode::Up));
+        b.handle_event(key(KeyCode::Backspace));
+        b.handle_event(key(KeyCode::Enter));
+        assert_eq!(b.commands.older(None, "/"), Some((3, "/search bee")));
+        // Up only recalls while typing, the lists take it otherwise
+        assert_eq!(b.handle_event(key(KeyCode::Up)), None);
+    }
+
+    #[test]
+    fn takes_completion() {
+        let mut b = typed("/sp");
+        b.completions = super::completions("/sp", &[]);
+        b.completed = b.text.clone();
+        b.handle_event(ctrl('n'));
+        assert_eq!(b.handle_event(key(KeyCode::Tab)), Some(Action::Consumed));
+        assert_eq!(b.text, "/speed");
+
+        // nothing to complete, Tab goes on to switch tabs
+        assert_eq!(b.handle_event(key(KeyCode::Tab)), None);
+    }
+
+    #[test]
+    fn edits_whole_characters() {
+        // an accent added to the e, and a family made of three emoji
+        let mut b = typed("/search cafe\u{301} 👨\u{200d}👩\u{200d}👧");
+        b.handle_event(key(KeyCode::Backspace));
+        assert_eq!(b.text, "/search cafe\u{301} ");
+        b.handle_event(key(KeyCode::Left));
+        b.handle_event(key(KeyCode::Left));
+        b.handle_event(key(KeyCode::Delete));
+        assert_eq!(b.text, "/search caf ");
+    }
+
+    #[test]
+    fn scrolls_to_cursor() {
+        assert_eq!(scrolled("/abcdef", 7, 20), 0);
+        assert_eq!(scrolled("/abcdef", 7, 4), 4);
+        assert_eq!(scrolled("/abcdef", 3, 4), 0);
+        // two columns each
+        let wide = "/日本語テキスト";
+        assert_eq!(scrolled(wide, wide.len(), 5), wide.len() - 6);
+        // a family emoji is one grapheme, two columns wide
+        let family = "/\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(columns(family), 3);
+        assert_eq!(scrolled(family, family.len(), 4), 0);
+    }
+
+    #[test]
+    fn deletes_words() {
+        let mut b = typed("/search wine  about");
+        assert_eq!(b.handle_event(ctrl('w')), Some(Action::Consumed));
+        assert_eq!(b.text, "/search wine  ");
+        b.handle_event(ctrl('w'));
+        assert_eq!(b.text, "/search ");
+        b.handle_event(key(KeyCode::Char('x')));
+        b.handle_event(key(KeyCode::Left));
+        b.handle_event(ctrl('u'));
+        assert_eq!(b.text, "x");
+        assert_eq!(b.cursor, 0);
+
+        // other shortcuts go on to the app
+        assert_eq!(b.handle_event(ctrl('o')), None);
+        assert_eq!(InputBox::default().handle_event(ctrl('w')), None);
+    }
+}
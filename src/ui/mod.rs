@@ -1,25 +1,266 @@
+pub mod complete;
+pub mod component;
+pub mod confirm;
+pub mod details;
+pub mod episodes;
+pub mod help;
+pub mod image;
 pub mod input;
+pub mod input_box;
+pub mod notes;
+pub mod playbar;
+pub mod sidebar;
+pub mod tabs;
+pub mod theme;
+pub mod title;
+pub mod toast;
+pub mod views;
 
-use tracing::{debug, span, trace, Level};
+use std::time::Duration;
+
+use tracing::{span, Level};
 use tui::{
     backend::Backend,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
     style::{Modifier, Style},
     text::{Span, Spans, Text},
-    widgets::{Block, Borders, Cell, List, ListItem, Paragraph, Row, Table, Wrap},
+    widgets::{Block, Borders, Cell, LineGauge, List, ListItem, Paragraph, Row, Table, Wrap},
     Frame,
 };
 use unicode_width::UnicodeWidthStr;
 
-use crate::{message::DisplayAction, App};
+use crate::{
+    chapters::Chapters,
+    downloads,
+    feed::{artwork, episode_artwork},
+    history,
+    message::DisplayAction,
+    now, player,
+    subscriptions::settings::SETTINGS,
+    App,
+};
+
+use self::{
+    component::{Component, Screen},
+    image::Image,
+    sidebar::Sidebar,
+    theme::EpisodeState,
+    views::{
+        Bookmarks, CleanUp, Diagnostics, Downloads, History, Library, NowPlaying, PodcastSettings,
+        Preview, Queue, Search, Starred, Storage,
+    },
+};
+
+// Commands listed in the usage hint, as (command, description) pairs
+const COMMANDS: [(&str, &str); 35] = [
+    ("/load", "Load an RSS feed"),
+    ("/library", "Show the podcasts you subscribed to"),
+    ("/ <text>", "Filter the loaded podcast's episodes by title"),
+    (
+        "/subscribe",
+        "Add a feed, or the loaded one, to the library",
+    ),
+    ("/private", "Subscribe to a membership feed, like Patreon's"),
+    (
+        "/unsubscribe",
+        "Take the selected podcast out of the library",
+    ),
+    ("/refresh", "Check every podcast for new episodes"),
+    ("/settings", "Change the settings of a podcast"),
+    ("/export", "Export to a file: /export opml|episodes <path>"),
+    ("/backup", "Back up all of your data to a file"),
+    ("/restore", "Replace all of your data with a backup"),
+    ("/search", "Search for podcasts by name"),
+    ("/queue", "Show the play queue"),
+    ("/mark", "Mark episodes as played"),
+    ("/speak", "Read the show notes aloud"),
+    ("/link", "Open a link in the show notes by its number"),
+    ("/speed", "Set the playback speed"),
+    ("/podcast-speed", "Set the playback speed for a podcast"),
+    ("/skip", "Skip a podcast's intros, outros or ads"),
+    ("/volume", "Set the playback volume"),
+    ("/sleep", "Stop playing after a while"),
+    ("/seek", "Go to a timestamp, e.g. 1:23:45"),
+    ("/bookmark", "Bookmark the moment playing, with a note"),
+    ("/bookmarks", "Show bookmarks"),
+    ("/starred", "Show starred episodes of every podcast"),
+    ("/history", "Show episodes listened to"),
+    ("/downloads", "Show downloaded episodes"),
+    ("/storage", "Show disk space used by podcast"),
+    ("/cleanup", "Show downloads due to be deleted"),
+    ("/tag", "Tag podcasts and filter the library"),
+    ("/archive", "Archive a podcast, /archived lists them"),
+    ("/open-with", "Play episodes with another program"),
+    ("/diagnostics", "Show how long feeds take to fetch"),
+    ("/accessible", "Toggle screen reader friendly mode"),
+    ("/theme", "Switch color theme"),
+];
+
+// Columns taken by the volume gauge in the play bar
+const VOLUME_WIDTH: u16 = 20;
+
+// Columns taken by the progress bar of an episode downloading, in the episode list
+const DOWNLOAD_BAR: usize = 8;
+
+// Marks the episodes that stream, not having been downloaded
+const STREAMS: &str = "☁";
+
+// Last row of the episode list when there are older episodes than the ones listed
+pub const OLDER_EPISODES: &str = "Load older episodes";
+
+// Choices on the feed preview screen, in order
+pub const PREVIEW_CHOICES: [&str; 2] = ["Subscribe", "Just browse"];
+
+// Actions for a search result, in order
+pub const SEARCH_ACTIONS: [&str; 3] = ["Subscribe", "Preview", "Queue latest episode"];
+
+// Number of episodes shown on the feed preview screen
+const PREVIEW_EPISODES: usize = 5;
+
+// Frames of the spinner shown while a feed downloads, and how long each shows for
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const SPINNER_FRAME: Duration = Duration::from_millis(100);
+
+// "⠙ Loading feed…" while the feed on screen downloads. Screen readers get no spinner, it
+// would be read out with every frame.
+pub fn loading(app: &App) -> Option<String> {
+    let started = app.loading?;
+    if app.config.accessible {
+        return Some("Loading feed".to_string());
+    }
+    Some(format!("{} Loading feed…", spinner(started.elapsed())))
+}
+
+fn spinner(elapsed: Duration) -> &'static str {
+    let frame = elapsed.as_millis() / SPINNER_FRAME.as_millis();
+    SPINNER[frame as usize % SPINNER.len()]
+}
+
+// Create the surrounding block for a widget. Accessible mode drops the decorative
+// borders so that screen readers only see the title and the contents.
+fn block<'a, T: Into<Spans<'a>>>(app: &App, title: T) -> Block<'a> {
+    let b = Block::default().title(title);
+    if app.config.accessible {
+        b
+    } else {
+        b.borders(Borders::ALL).border_style(app.theme.border)
+    }
+}
+
+// Format a block title, e.g. "[Wine About It]" or "Podcast: Wine About It" in accessible mode
+fn title(app: &App, label: &str, value: &str) -> String {
+    if app.config.accessible {
+        format!("{}: {}", label, value)
+    } else {
+        format!("[{}]", value)
+    }
+}
+
+// Symbol that marks the selected entry of a list
+fn highlight_symbol(app: &App) -> &'static str {
+    if app.config.accessible {
+        "Selected: "
+    } else {
+        ">> "
+    }
+}
+
+// Format a duration as H:MM:SS, or M:SS when it is shorter than an hour
+pub fn fmt_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+// Milliseconds as e.g. "850 ms" or "1.2 s"
+fn fmt_millis(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{} ms", ms)
+    } else {
+        format!("{:.1} s", ms as f64 / 1000.0)
+    }
+}
+
+// Where an episode plays from, after its title: the cloud when it streams, how its
+// download is going, e.g. "⬇ ▰▰▰▱▱▱▱▱ 42%" or how much has downloaded when the server
+// didn't say how big the file is, or the downloaded glyph once it's on disk
+fn download_marker(app: &App, status: Option<&downloads::Status>) -> Span<'static> {
+    let Some(status) = status else {
+        return Span::styled(STREAMS, app.theme.status);
+    };
+    let marker = match status {
+        downloads::Status::Queued => "⬇ queued".to_string(),
+        downloads::Status::Active(done, Some(len)) if *len > 0 => {
+            let filled = (done * DOWNLOAD_BAR as u64 / len).min(DOWNLOAD_BAR as u64) as usize;
+            format!(
+                "⬇ {}{} {}",
+                "▰".repeat(filled),
+                "▱".repeat(DOWNLOAD_BAR - filled),
+                status.describe()
+            )
+        }
+        downloads::Status::Active(done, _) => format!("⬇ {}", fmt_bytes(*done)),
+        downloads::Status::Paused(..) => "⬇ paused".to_string(),
+        downloads::Status::Done => {
+            let s = app.theme.episode(EpisodeState::Downloaded);
+            return Span::styled(s.glyph.clone(), s.style);
+        }
+        downloads::Status::Failed(_) => {
+            let s = app.theme.episode(EpisodeState::Failed);
+            return Span::styled(format!("{} download failed", s.glyph), s.style);
+        }
+    };
+    Span::styled(marker, app.theme.key)
+}
+
+// Bytes as e.g. "120 KB" or "2.5 MB"
+pub fn fmt_bytes(bytes: u64) -> String {
+    if bytes < 1024 * 1024 {
+        format!("{} KB", bytes.div_ceil(1024))
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
 
-pub fn draw_main_layout<B>(f: &mut Frame<B>, app: &mut App)
+pub fn draw_main_layout<B>(f: &mut Frame<B>, app: &mut App, screen: &mut Screen)
 where
     B: Backend,
 {
     let span = span!(Level::TRACE, "render_main");
     let _entered = span.enter();
 
+    // paint the theme's base colors over the whole screen
+    f.render_widget(Block::default().style(app.theme.base), f.size());
+
+    // the now playing screen takes the place of the hint and the play bar, keeping the
+    // tabs and the input box for commands
+    if app.display_action == DisplayAction::NowPlaying {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .horizontal_margin(2)
+            .vertical_margin(1)
+            .constraints(
+                [
+                    Constraint::Length(1), // tabs
+                    Constraint::Length(3), // input box
+                    Constraint::Min(0),    // now playing
+                    Constraint::Length(1), // status line
+                ]
+                .as_ref(),
+            )
+            .split(f.size());
+        screen.sidebar.hide();
+        tabs::draw_tabs(f, app, chunks[0]);
+        screen.input.draw(f, chunks[1], app);
+        NowPlaying.draw(f, chunks[2], app);
+        draw_status_line(f, app, chunks[3]);
+        draw_popups(f, app, screen);
+        return;
+    }
+
     // draw the top bar and the main display area
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -28,30 +269,75 @@ where
         .constraints(
             [
                 // TODO: figure out the minimum size requirements
-                Constraint::Percentage(10), // help text
+                Constraint::Length(2),      // tabs and help text
                 Constraint::Percentage(15), // input box
                 Constraint::Percentage(65), // output contents
                 Constraint::Percentage(15), // play bar
+                Constraint::Length(1),      // status line
             ]
             .as_ref(),
         )
         .split(f.size());
 
-    draw_hint(f, app, chunks[0]);
-    draw_input_box(f, app, chunks[1]);
-    draw_display_area(f, app, chunks[2]);
-    draw_playbar(f, app, chunks[3]);
+    let top = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(chunks[0]);
+    tabs::draw_tabs(f, app, top[0]);
+    draw_hint(f, app, top[1]);
+    screen.input.draw(f, chunks[1], app);
+    // the sidebar goes next to the display area when there's room for both
+    let display = if Sidebar::fits(app, chunks[2].width) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(sidebar::WIDTH), Constraint::Min(0)].as_ref())
+            .split(chunks[2]);
+        screen.sidebar.draw(f, columns[0], app);
+        columns[1]
+    } else {
+        screen.sidebar.hide();
+        chunks[2]
+    };
+    screen.display = display;
+    draw_display_area(f, app, screen, display);
+    screen.playbar.draw(f, chunks[3], app);
+    draw_status_line(f, app, chunks[4]);
+    draw_popups(f, app, screen);
+}
+
+// Popups go over everything else, the question about a destructive action on top
+fn draw_popups<B: Backend>(f: &mut Frame<B>, app: &mut App, screen: &mut Screen) {
+    let margin = Margin {
+        vertical: 1,
+        horizontal: 2,
+    };
+    toast::draw_toasts(f, app, f.size().inner(&margin));
+    screen.input.draw_completions(f, app);
+    if screen.help.shown {
+        screen.help.draw(f, f.size(), app);
+    }
+    if app.confirm.is_some() {
+        confirm::draw_confirm(f, app, f.size());
+    }
 }
 
-pub fn draw_hint<B: Backend>(f: &mut Frame<B>, _app: &App, parent: Rect) {
+pub fn draw_hint<B: Backend>(f: &mut Frame<B>, app: &App, parent: Rect) {
+    if app.config.accessible {
+        let text = Text::raw("Press Escape to exit, Enter to submit, question mark for help");
+        f.render_widget(Paragraph::new(text), parent);
+        return;
+    }
+
     let (msg, style) = (
         vec![
-            Span::styled("Podcasts::", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled("Podcasts::", app.theme.key),
             Span::raw("Press "),
-            Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled("Esc", app.theme.key),
             Span::raw(" to exit, "),
-            Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw(" to input"),
+            Span::styled("Enter", app.theme.key),
+            Span::raw(" to input, "),
+            Span::styled("?", app.theme.key),
+            Span::raw(" for help"),
         ],
         Style::default(),
     );
@@ -61,145 +347,916 @@ pub fn draw_hint<B: Backend>(f: &mut Frame<B>, _app: &App, parent: Rect) {
     f.render_widget(help_message, parent);
 }
 
-pub fn draw_input_box<B: Backend>(f: &mut Frame<B>, app: &App, parent: Rect) {
-    let input = Paragraph::new(app.input.as_ref())
-        .style(Style::default())
-        .block(Block::default().borders(Borders::ALL).title("Input"));
-    f.render_widget(input, parent);
-
-    // Make the cursor visible and ask tui-rs to put it at the specified coordinates after rendering
-    f.set_cursor(
-        // Put cursor past the end of the input text
-        parent.x + app.input.width() as u16 + 1,
-        // Move one line down, from the border to the input line
-        parent.y + 1,
-    );
-}
-
-pub fn draw_display_area<B: Backend>(f: &mut Frame<B>, app: &mut App, parent: Rect) {
+pub fn draw_display_area<B: Backend>(
+    f: &mut Frame<B>,
+    app: &mut App,
+    screen: &mut Screen,
+    parent: Rect,
+) {
     let span = span!(Level::TRACE, "render_display_area");
     let _entered = span.enter();
 
     match app.display_action {
-        DisplayAction::ListEpisodes => draw_episode_list(f, app, parent),
-        DisplayAction::DescribeEpisode => draw_episode_details(f, app, parent),
+        DisplayAction::Preview => Preview.draw(f, parent, app),
+        DisplayAction::ListEpisodes => screen.episodes.draw(f, parent, app),
+        DisplayAction::DescribeEpisode => screen.details.draw(f, parent, app),
+        DisplayAction::Queue => Queue.draw(f, parent, app),
+        DisplayAction::Bookmarks => Bookmarks.draw(f, parent, app),
+        DisplayAction::Search => Search.draw(f, parent, app),
+        DisplayAction::Diagnostics => Diagnostics.draw(f, parent, app),
+        DisplayAction::Library => Library.draw(f, parent, app),
+        DisplayAction::PodcastSettings => PodcastSettings.draw(f, parent, app),
+        DisplayAction::Starred => Starred.draw(f, parent, app),
+        DisplayAction::Downloads => Downloads.draw(f, parent, app),
+        DisplayAction::Storage => Storage.draw(f, parent, app),
+        DisplayAction::CleanUp => CleanUp.draw(f, parent, app),
+        DisplayAction::History => History.draw(f, parent, app),
         _ => draw_usage_hint(f, app, parent),
     }
 }
 
-pub fn draw_usage_hint<B: Backend>(f: &mut Frame<B>, _app: &mut App, parent: Rect) {
+pub fn draw_usage_hint<B: Backend>(f: &mut Frame<B>, app: &mut App, parent: Rect) {
     let span = span!(Level::TRACE, "render_main_hints");
     let _entered = span.enter();
 
+    if app.config.accessible {
+        // a table reads cell by cell, so spell each command out on its own line
+        let text = COMMANDS
+            .iter()
+            .map(|(cmd, desc)| Spans::from(format!("{}: {}", cmd, desc)))
+            .collect::<Vec<Spans>>();
+        let contents = Paragraph::new(text).block(block(app, "Available commands"));
+        f.render_widget(contents, parent);
+        return;
+    }
+
     let constraints = [Constraint::Ratio(25, 100), Constraint::Length(parent.width)];
 
-    let t = Table::new(vec![Row::new(vec![
-        Cell::from("/load").style(Style::default().add_modifier(Modifier::BOLD)),
-        Cell::from("Load an RSS feed"),
-    ])
-    .bottom_margin(1)
-    .height(1)])
-    .header(
-        Row::new(vec!["Command", "Description"])
-            .bottom_margin(1)
-            .height(1),
-    )
-    .column_spacing(1)
-    .widths(&constraints)
-    .block(
-        Block::default()
-            .title("Available commands")
-            .borders(Borders::all()),
-    );
+    let rows = COMMANDS.iter().map(|(cmd, desc)| {
+        Row::new(vec![
+            Cell::from(*cmd).style(app.theme.key),
+            Cell::from(*desc),
+        ])
+        .bottom_margin(1)
+        .height(1)
+    });
+
+    let t = Table::new(rows)
+        .header(
+            Row::new(vec!["Command", "Description"])
+                .bottom_margin(1)
+                .height(1),
+        )
+        .column_spacing(1)
+        .widths(&constraints)
+        .block(
+            Block::default()
+                .title("Available commands")
+                .borders(Borders::all()),
+        );
 
     f.render_widget(t, parent);
 }
 
-pub fn draw_episode_list<B: Backend>(f: &mut Frame<B>, app: &mut App, parent: Rect) {
-    let span = span!(Level::TRACE, "render_feed");
+// Split off room for an image on the left of the area, if there is an image to show.
+// The first of the candidate images that has finished loading is shown. Images are
+// meaningless to screen readers, and need colors to be recognizable.
+fn with_image<'a, B: Backend, I>(f: &mut Frame<B>, app: &App, candidates: I, parent: Rect) -> Rect
+where
+    I: IntoIterator<Item = Option<&'a str>>,
+{
+    let image = candidates
+        .into_iter()
+        .flatten()
+        .find_map(|u| app.images.get(u))
+        .filter(|_| !app.config.accessible && !app.theme.is_monochrome());
+    match image {
+        Some(img) => {
+            // half blocks make each cell two pixels tall, so a square image is twice
+            // as wide as it is tall
+            let width = (parent.height * 2).min(parent.width / 3);
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(width), Constraint::Min(0)].as_ref())
+                .split(parent);
+            f.render_widget(Image::new(img), chunks[0]);
+            chunks[1]
+        }
+        None => parent,
+    }
+}
+
+pub fn draw_preview<B: Backend>(f: &mut Frame<B>, app: &mut App, parent: Rect) {
+    let span = span!(Level::TRACE, "render_preview");
     let _entered = span.enter();
-    trace!("rendering podcast episodes");
-    let contents = app
-        .channel
+
+    let Some(c) = app.channel.as_ref() else {
+        let text = loading(app).unwrap_or_else(|| "Loading feed".to_string());
+        let contents = Paragraph::new(text).block(block(app, "Preview"));
+        f.render_widget(contents, parent);
+        return;
+    };
+
+    let mut name = title(app, "Podcast", c.title());
+    // the copy from last time shows while the feed downloads
+    if let Some(l) = loading(app) {
+        name.push_str(&format!(" {}", l));
+    }
+    let b = block(app, name);
+    let inner = b.inner(parent);
+    f.render_widget(b, parent);
+    let area = with_image(f, app, [artwork(c)], inner);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Min(1),                                   // description
+                Constraint::Length(PREVIEW_EPISODES as u16 + 2),      // latest episodes
+                Constraint::Length(PREVIEW_CHOICES.len() as u16 + 1), // choices
+            ]
+            .as_ref(),
+        )
+        .split(area);
+
+    let description = html2text::from_read(c.description().as_bytes(), chunks[0].width.into());
+    f.render_widget(
+        Paragraph::new(description).wrap(Wrap { trim: true }),
+        chunks[0],
+    );
+
+    // feeds list their newest episodes first
+    let mut latest = vec![
+        Spans::from(Span::raw("")),
+        Spans::from(Span::styled("Latest episodes:", app.theme.key)),
+    ];
+    latest.extend(c.items().iter().take(PREVIEW_EPISODES).map(|item| {
+        Spans::from(Span::raw(format!(
+            "- {} ({})",
+            item.title().unwrap_or("Title missing!"),
+            item.pub_date().unwrap_or("date unknown")
+        )))
+    }));
+    f.render_widget(Paragraph::new(latest), chunks[1]);
+
+    let subscribed = app
+        .feed_url
         .as_ref()
-        .and_then(|c| Some(c.items()))
-        .unwrap_or_default()
+        .map(|u| app.subscriptions.contains(u.as_str()))
+        .unwrap_or_default();
+    let choices = PREVIEW_CHOICES
+        .iter()
+        .map(|choice| match *choice {
+            "Subscribe" if subscribed => ListItem::new("Subscribe (already subscribed)"),
+            _ => ListItem::new(*choice),
+        })
+        .collect::<Vec<ListItem>>();
+    let choices = List::new(choices)
+        .highlight_style(app.theme.highlight)
+        .highlight_symbol(highlight_symbol(app));
+    let mut area = chunks[2];
+    area.y += 1;
+    area.height = area.height.saturating_sub(1);
+    f.render_stateful_widget(choices, area, &mut app.preview_state);
+}
+
+// What the glyphs in front of episodes mean
+pub fn episode_legend(app: &App) -> Spans<'static> {
+    let spans = EpisodeState::ALL
+        .iter()
+        .flat_map(|state| {
+            let s = app.theme.episode(*state);
+            [
+                Span::styled(format!("{} {}", s.glyph, state.name()), s.style),
+                Span::raw("  "),
+            ]
+        })
+        .chain([Span::styled(
+            format!("{} not downloaded", STREAMS),
+            app.theme.status,
+        )])
+        .collect::<Vec<Span>>();
+    Spans::from(spans)
+}
+
+// The chapters with their start times, marking the one playing at the position
+fn chapter_lines(app: &App, chapters: &Chapters, position: Duration) -> Vec<Spans<'static>> {
+    let mut lines = vec![Spans::from(Span::styled(
+        "Chapters",
+        app.theme.key.add_modifier(Modifier::BOLD),
+    ))];
+    let current = chapters.at(position);
+    for (idx, chapter) in chapters.chapters.iter().enumerate() {
+        let start = fmt_duration(Duration::from_secs_f64(chapter.start_time));
+        let title = chapter.title.as_deref().unwrap_or("Untitled");
+        let playing = current.is_some_and(|c| std::ptr::eq(c, chapter));
+        lines.push(Spans::from(if app.config.accessible {
+            Span::raw(format!(
+                "Chapter {} of {} at {}: {}{}",
+                idx + 1,
+                chapters.len(),
+                start,
+                title,
+                if playing { ", current" } else { "" }
+            ))
+        } else if playing {
+            Span::styled(
+                format!("▸ {:>8}  {}", start, title),
+                Style::default().add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Span::raw(format!("  {:>8}  {}", start, title))
+        }));
+    }
+    lines
+}
+
+fn show_notes(app: &App, description: &str, width: u16) -> Vec<Spans<'static>> {
+    // screen readers get plain text, without the decorations used to show structure
+    if app.config.accessible {
+        vec![Spans::from(Span::raw(html2text::from_read(
+            description.as_bytes(),
+            width.into(),
+        )))]
+    } else {
+        notes::render(description, width.into(), &app.theme)
+    }
+}
+
+// Full screen view of the episode playing: its notes and chapters, how far along it is,
+// and what's up next in the queue
+pub fn draw_now_playing<B: Backend>(f: &mut Frame<B>, app: &App, parent: Rect) {
+    let span = span!(Level::TRACE, "render_now_playing");
+    let _entered = span.enter();
+
+    let (episode, external) = match (app.player.external.as_ref(), app.player.episode.as_ref()) {
+        (Some(e), _) => (&e.episode, Some(e.program.as_str())),
+        (None, Some(e)) => (e, None),
+        (None, None) => {
+            let contents =
+                Paragraph::new("Nothing playing. Press Enter on an episode's details to play it.")
+                    .block(block(app, "Now playing"));
+            f.render_widget(contents, parent);
+            return;
+        }
+    };
+    let podcast = episode.podcast.clone().unwrap_or_default();
+    let (ratio, progress) = match external {
+        Some(program) => (0.0, format!("Playing externally in {}", program)),
+        None => progress(app),
+    };
+    let description = episode.item.description().unwrap_or_default();
+    let chapters = app
+        .player
+        .chapters
+        .as_ref()
+        .filter(|c| c.len() > 0 && external.is_none());
+    let up_next = app
+        .queue
+        .entries()
+        .iter()
+        .map(|e| {
+            let left = e
+                .remaining(app.player.status.speed)
+                .map(|d| format!(" ({} left)", fmt_duration(d)))
+                .unwrap_or_default();
+            format!("{}{}", e.item.title().unwrap_or("Title missing!"), left)
+        })
+        .collect::<Vec<String>>();
+
+    let b = block(app, title(app, "Now playing", episode.title()));
+    let area = b.inner(parent);
+    f.render_widget(b, parent);
+
+    if app.config.accessible {
+        let mut text = vec![
+            Spans::from(Span::raw(format!("Podcast: {}", podcast))),
+            Spans::from(Span::raw(format!("Position: {}", progress))),
+        ];
+        if let Some(c) = chapters {
+            text.extend(chapter_lines(app, c, app.player.status.position));
+        }
+        text.extend(
+            up_next
+                .iter()
+                .enumerate()
+                .map(|(idx, e)| Spans::from(Span::raw(format!("Up next {}: {}", idx + 1, e)))),
+        );
+        text.extend(show_notes(app, description, area.width));
+        f.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), area);
+        return;
+    }
+
+    let images = [
+        chapters.and_then(|c| c.image_at(app.player.status.position)),
+        episode_artwork(&episode.item, None),
+        episode.artwork.as_deref(),
+    ];
+    let area = with_image(f, app, images, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+    f.render_widget(Paragraph::new(podcast), chunks[0]);
+    let gauge = LineGauge::default()
+        .ratio(ratio)
+        .label(progress)
+        .gauge_style(app.theme.playbar);
+    f.render_widget(gauge, chunks[1]);
+
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)].as_ref())
+        .split(chunks[3]);
+    let notes_block = block(app, "Show notes");
+    let width = notes_block.inner(body[0]).width;
+    let mut text = vec![];
+    if let Some(c) = chapters {
+        text.extend(chapter_lines(app, c, app.player.status.position));
+        text.push(Spans::from(Span::raw("")));
+    }
+    text.extend(show_notes(app, description, width));
+    let notes = Paragraph::new(text)
+        .wrap(Wrap { trim: false })
+        .block(notes_block);
+    f.render_widget(notes, body[0]);
+
+    let queue = if up_next.is_empty() {
+        vec![ListItem::new("The queue is empty")]
+    } else {
+        up_next
+            .into_iter()
+            .enumerate()
+            .map(|(idx, e)| ListItem::new(format!("{}. {}", idx + 1, e)))
+            .collect()
+    };
+    f.render_widget(List::new(queue).block(block(app, "Up next")), body[1]);
+}
+
+pub fn draw_queue<B: Backend>(f: &mut Frame<B>, app: &mut App, parent: Rect) {
+    let span = span!(Level::TRACE, "render_queue");
+    let _entered = span.enter();
+
+    let speed = app.player.status.speed;
+
+    let contents = app
+        .queue
+        .entries()
         .iter()
         .enumerate()
-        .map(|(idx, item)| {
-            let content = vec![Spans::from(Span::raw(format!(
-                "{}: {}",
+        .map(|(idx, e)| {
+            let left = e
+                .remaining(speed)
+                .map(|d| format!("{} left", fmt_duration(d)))
+                .unwrap_or("length unknown".to_string());
+            ListItem::new(Spans::from(Span::raw(format!(
+                "{}: {} ({})",
                 idx,
-                item.title.as_deref().unwrap_or("Title missing!")
-            )))];
-            ListItem::new(content)
+                e.item.title().unwrap_or("Title missing!"),
+                left
+            ))))
         })
         .collect::<Vec<ListItem>>();
 
-    let podcast_name = app
-        .channel
-        .as_ref()
-        .map(|c| format!("[{}]", c.title()))
-        .unwrap_or("[Title]".to_string());
+    let (total, unknown) = app.queue.remaining(speed);
+    let mut footer = if app.queue.is_empty() {
+        "The queue is empty, press a on an episode to add it".to_string()
+    } else {
+        format!(
+            "Total: {} across {} episodes",
+            fmt_duration(total),
+            app.queue.len()
+        )
+    };
+    if unknown > 0 {
+        footer.push_str(format!(", plus {} of unknown length", unknown).as_str());
+    }
+
+    let b = block(app, "Queue");
+    let inner = b.inner(parent);
+    f.render_widget(b, parent);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(inner);
+
+    let contents = List::new(contents)
+        .highlight_style(app.theme.highlight)
+        .highlight_symbol(highlight_symbol(app));
+    f.render_stateful_widget(contents, chunks[0], &mut app.queue_state);
+    f.render_widget(Paragraph::new(footer).style(app.theme.key), chunks[1]);
+}
+
+pub fn draw_bookmarks<B: Backend>(f: &mut Frame<B>, app: &mut App, parent: Rect) {
+    let span = span!(Level::TRACE, "render_bookmarks");
+    let _entered = span.enter();
+
+    if app.bookmarks.is_empty() {
+        let contents = Paragraph::new("No bookmarks yet, press b while an episode plays")
+            .block(block(app, "Bookmarks"));
+        f.render_widget(contents, parent);
+        return;
+    }
+    let contents = app
+        .bookmarks
+        .iter()
+        .map(|b| {
+            let mut lines = vec![Spans::from(vec![
+                Span::styled(format!("{:>8}  ", fmt_duration(b.position)), app.theme.key),
+                Span::raw(b.title().to_string()),
+            ])];
+            let mut detail = b.podcast.clone().unwrap_or_default();
+            if let Some(note) = b.note.as_deref() {
+                if !detail.is_empty() {
+                    detail.push_str(" — ");
+                }
+                detail.push_str(note);
+            }
+            if !detail.is_empty() {
+                lines.push(Spans::from(Span::styled(
+                    format!("          {}", detail),
+                    app.theme.status,
+                )));
+            }
+            ListItem::new(lines)
+        })
+        .collect::<Vec<ListItem>>();
+    let contents = List::new(contents)
+        .block(block(app, "Bookmarks"))
+        .highlight_style(app.theme.highlight)
+        .highlight_symbol(highlight_symbol(app));
+    f.render_stateful_widget(contents, parent, &mut app.bookmarks_state);
+}
+
+pub fn draw_starred<B: Backend>(f: &mut Frame<B>, app: &mut App, parent: Rect) {
+    let span = span!(Level::TRACE, "render_starred");
+    let _entered = span.enter();
+
+    if app.stars.is_empty() {
+        let contents = Paragraph::new("No starred episodes yet, press * on an episode")
+            .block(block(app, "Starred"));
+        f.render_widget(contents, parent);
+        return;
+    }
+    let contents = app
+        .stars
+        .iter()
+        .map(|s| {
+            let mut line = vec![Span::raw(s.title().to_string())];
+            if let Some(podcast) = s.podcast.as_deref() {
+                line.push(Span::styled(format!(" — {}", podcast), app.theme.status));
+            }
+            ListItem::new(Spans::from(line))
+        })
+        .collect::<Vec<ListItem>>();
+    let contents = List::new(contents)
+        .block(block(app, "Starred"))
+        .highlight_style(app.theme.highlight)
+        .highlight_symbol(highlight_symbol(app));
+    f.render_stateful_widget(contents, parent, &mut app.stars_state);
+}
 
-    debug!(num_episodes = contents.len(), name = podcast_name);
+pub fn draw_downloads<B: Backend>(f: &mut Frame<B>, app: &mut App, parent: Rect) {
+    let span = span!(Level::TRACE, "render_downloads");
+    let _entered = span.enter();
 
+    if app.downloads.is_empty() {
+        let contents = Paragraph::new("Nothing downloaded yet, press d on an episode")
+            .block(block(app, "Downloads"));
+        f.render_widget(contents, parent);
+        return;
+    }
+    let contents = app
+        .downloads
+        .iter()
+        .map(|j| {
+            let style = match j.status {
+                downloads::Status::Failed(_) => app.theme.error,
+                downloads::Status::Active(..) => app.theme.key,
+                _ => app.theme.status,
+            };
+            let mut line = vec![
+                Span::styled(format!("{:>12}  ", j.status.describe()), style),
+                Span::raw(j.title().to_string()),
+            ];
+            if let Some(podcast) = j.podcast.as_deref() {
+                line.push(Span::styled(format!(" — {}", podcast), app.theme.status));
+            }
+            ListItem::new(Spans::from(line))
+        })
+        .collect::<Vec<ListItem>>();
+    let title = format!(
+        "Downloads ({} active, {} queued)",
+        app.downloads.active(),
+        app.downloads.queued()
+    );
     let contents = List::new(contents)
-        .block(Block::default().borders(Borders::ALL).title(podcast_name))
-        .highlight_style(
-            Style::default()
-                .add_modifier(Modifier::BOLD)
-                .add_modifier(Modifier::ITALIC),
+        .block(block(app, title.as_str()))
+        .highlight_style(app.theme.highlight)
+        .highlight_symbol(highlight_symbol(app));
+    f.render_stateful_widget(contents, parent, &mut app.downloads_state);
+}
+
+pub fn draw_storage<B: Backend>(f: &mut Frame<B>, app: &mut App, parent: Rect) {
+    let span = span!(Level::TRACE, "render_storage");
+    let _entered = span.enter();
+
+    let rows = (0..app.storage.len())
+        .filter_map(|idx| app.storage.get(idx))
+        .map(|u| {
+            let downloads = match u.downloads {
+                1 => "1 download".to_string(),
+                n => format!("{} downloads", n),
+            };
+            let detail = format!(
+                "          {} in {}, {} of cached feed",
+                fmt_bytes(u.download_size),
+                downloads,
+                fmt_bytes(u.cache_size)
+            );
+            ListItem::new(vec![
+                Spans::from(vec![
+                    Span::styled(format!("{:>8}  ", fmt_bytes(u.total())), app.theme.key),
+                    Span::raw(u.title.clone()),
+                ]),
+                Spans::from(Span::styled(detail, app.theme.status)),
+            ])
+        })
+        .collect::<Vec<ListItem>>();
+    let title = format!(
+        "Storage ({} more of your data)",
+        fmt_bytes(app.storage.data_size)
+    );
+    let contents = List::new(rows)
+        .block(block(app, title.as_str()))
+        .highlight_style(app.theme.highlight)
+        .highlight_symbol(highlight_symbol(app));
+    f.render_stateful_widget(contents, parent, &mut app.storage_state);
+}
+
+pub fn draw_clean_up<B: Backend>(f: &mut Frame<B>, app: &mut App, parent: Rect) {
+    let span = span!(Level::TRACE, "render_clean_up");
+    let _entered = span.enter();
+
+    let rows = app
+        .clean_up
+        .iter()
+        .map(|(j, expiry)| {
+            let podcast = j.podcast.as_deref().unwrap_or("Unknown podcast");
+            ListItem::new(vec![
+                Spans::from(vec![
+                    Span::styled(format!("{}: ", podcast), app.theme.key),
+                    Span::raw(j.title().to_string()),
+                ]),
+                Spans::from(Span::styled(
+                    format!("          {}", expiry.describe()),
+                    app.theme.status,
+                )),
+            ])
+        })
+        .collect::<Vec<ListItem>>();
+    let title = match app.clean_up.len() {
+        0 => "Clean up (nothing to delete)".to_string(),
+        n => format!("Clean up ({} downloads to delete)", n),
+    };
+    let contents = List::new(rows)
+        .block(block(app, title.as_str()))
+        .highlight_style(app.theme.highlight)
+        .highlight_symbol(highlight_symbol(app));
+    f.render_stateful_widget(contents, parent, &mut app.clean_up_state);
+}
+
+pub fn draw_history<B: Backend>(f: &mut Frame<B>, app: &mut App, parent: Rect) {
+    let span = span!(Level::TRACE, "render_history");
+    let _entered = span.enter();
+
+    let now = now();
+    let episodes = app
+        .history
+        .episodes()
+        .into_iter()
+        .map(|l| {
+            let mut lines = vec![Spans::from(vec![
+                Span::styled(format!("{:>8}  ", fmt_duration(l.position)), app.theme.key),
+                Span::raw(l.title().to_string()),
+            ])];
+            let mut detail = history::ago(l.stopped, now);
+            if let Some(podcast) = l.podcast.as_deref() {
+                detail = format!("{} — {}", podcast, detail);
+            }
+            lines.push(Spans::from(Span::styled(
+                format!("          {}", detail),
+                app.theme.status,
+            )));
+            ListItem::new(lines)
+        })
+        .collect::<Vec<ListItem>>();
+    if episodes.is_empty() {
+        let contents = Paragraph::new("Nothing listened to yet").block(block(app, "History"));
+        f.render_widget(contents, parent);
+        return;
+    }
+    let contents = List::new(episodes)
+        .block(block(app, "History"))
+        .highlight_style(app.theme.highlight)
+        .highlight_symbol(highlight_symbol(app));
+    f.render_stateful_widget(contents, parent, &mut app.history_state);
+}
+
+pub fn draw_search<B: Backend>(f: &mut Frame<B>, app: &mut App, parent: Rect) {
+    let span = span!(Level::TRACE, "render_search");
+    let _entered = span.enter();
+
+    let results = app
+        .search_results
+        .iter()
+        .map(|r| {
+            let mut line = format!("{} by {}", r.title, r.author);
+            if let Some(g) = r.genre.as_ref() {
+                line.push_str(format!(" ({})", g).as_str());
+            }
+            let subscribed = r
+                .feed_url
+                .as_ref()
+                .map(|u| app.subscriptions.contains(u))
+                .unwrap_or_default();
+            if subscribed {
+                line.push_str(" [subscribed]");
+            }
+            ListItem::new(line)
+        })
+        .collect::<Vec<ListItem>>();
+
+    let results = List::new(results)
+        .block(block(app, "Search results"))
+        .highlight_style(app.theme.highlight)
+        .highlight_symbol(highlight_symbol(app));
+
+    if !app.search_menu {
+        f.render_stateful_widget(results, parent, &mut app.search_state);
+        return;
+    }
+
+    // actions for the selected result go right below the results
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Min(0),
+                Constraint::Length(SEARCH_ACTIONS.len() as u16 + 2),
+            ]
+            .as_ref(),
         )
-        .highlight_symbol(">> ");
+        .split(parent);
+    f.render_stateful_widget(results, chunks[0], &mut app.search_state);
+
+    let selected = app
+        .search_state
+        .selected()
+        .and_then(|idx| app.search_results.get(idx))
+        .map(|r| r.title.as_str())
+        .unwrap_or("Result");
+    let actions = SEARCH_ACTIONS
+        .iter()
+        .map(|a| ListItem::new(*a))
+        .collect::<Vec<ListItem>>();
+    let actions = List::new(actions)
+        .block(block(app, title(app, "Actions for", selected)))
+        .highlight_style(app.theme.highlight)
+        .highlight_symbol(highlight_symbol(app));
+    f.render_stateful_widget(actions, chunks[1], &mut app.search_menu_state);
+}
 
-    f.render_stateful_widget(contents, parent, &mut app.state);
+// Position in the episode playing, e.g. "12:34 / 45:00"
+fn position(app: &App) -> String {
+    let position = app.player.status.position;
+    match app.player.length() {
+        Some(d) => format!("{} / {}", fmt_duration(position), fmt_duration(d)),
+        None => fmt_duration(position),
+    }
 }
 
-pub fn draw_episode_details<B: Backend>(f: &mut Frame<B>, app: &App, parent: Rect) {
-    let span = span!(Level::TRACE, "render_episode");
+// How far along the episode playing is, and a label for its progress gauge with the
+// speed, download, loop, repeat mode, stop after this and sleep timer
+fn progress(app: &App) -> (f64, String) {
+    let status = app.player.status;
+    let ratio = app
+        .player
+        .length()
+        .filter(|d| !d.is_zero())
+        .map(|d| (status.position.as_secs_f64() / d.as_secs_f64()).min(1.0))
+        .unwrap_or_default();
+    let mut label = format!("{}  {:.1}x", position(app), status.speed);
+    if let Some(b) = status.buffered {
+        label.push_str(format!("  {}% buffered", b).as_str());
+    }
+    if status.skip_silence {
+        label.push_str(format!("  {} saved", fmt_duration(status.saved)).as_str());
+    }
+    if let Some(s) = app.player.segment.as_ref() {
+        label.push_str("  ");
+        label.push_str(s.describe().as_str());
+    }
+    if app.player.repeat != player::Repeat::Off {
+        label.push_str("  ");
+        label.push_str(app.player.repeat.describe());
+    }
+    if app.player.stop_after {
+        label.push_str("  stop after this");
+    }
+    if let Some(s) = app.player.sleep.as_ref() {
+        label.push_str("  ");
+        label.push_str(s.describe().as_str());
+    }
+    (ratio, label)
+}
+
+pub fn draw_status_line<B: Backend>(f: &mut Frame<B>, app: &App, parent: Rect) {
+    // questions need an answer, make them stand out
+    let contents = match app.error.as_deref() {
+        Some(e) => Paragraph::new(format!("Error: {}", e)).style(app.theme.error),
+        None if app.confirm.is_some() => Paragraph::new(app.status.as_str()).style(app.theme.key),
+        None => Paragraph::new(app.status.as_str()).style(app.theme.status),
+    };
+
+    // a newer release stays noted on the right, once its announcement is gone
+    let Some(update) = app.update.as_ref() else {
+        f.render_widget(contents, parent);
+        return;
+    };
+    let notice = format!(" {} available", update.version);
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(notice.width() as u16),
+        ])
+        .split(parent);
+    f.render_widget(contents, chunks[0]);
+    f.render_widget(Paragraph::new(notice).style(app.theme.status), chunks[1]);
+}
+
+pub fn draw_podcast_settings<B: Backend>(f: &mut Frame<B>, app: &mut App, parent: Rect) {
+    let span = span!(Level::TRACE, "render_podcast_settings");
     let _entered = span.enter();
-    trace!("rendering episode details");
 
-    let episode_name = app
-        .item
-        .as_ref()
-        .and_then(|i| i.title())
-        .map(|t| format!("[{}]", t))
-        .unwrap_or("[Episode Title]".to_string());
-    let description = app
-        .item
-        .as_ref()
-        .and_then(|i| i.description())
-        .unwrap_or("Description");
-    let description = html2text::from_read(description.as_bytes(), parent.width.into());
-    let audio_link = app
-        .item
-        .as_ref()
-        .and_then(|i| i.enclosure.as_ref())
-        .map(|e| e.url())
-        .unwrap_or("[Audio URL]");
-
-    let text = vec![
-        Spans::from(Span::styled(
-            audio_link,
-            Style::default()
-                .add_modifier(Modifier::ITALIC)
-                .add_modifier(Modifier::BOLD),
-        )),
-        Spans::from(Span::raw("")),
-        Spans::from(Span::raw(description)),
-    ];
+    let Some(s) = app
+        .settings_podcast
+        .as_deref()
+        .and_then(|u| app.subscriptions.get(u))
+    else {
+        let contents =
+            Paragraph::new("Not subscribed to this podcast anymore").block(block(app, "Settings"));
+        f.render_widget(contents, parent);
+        return;
+    };
+    let width = SETTINGS.iter().map(|s| s.name().len()).max().unwrap_or(0);
+    let rows = SETTINGS
+        .iter()
+        .map(|setting| {
+            ListItem::new(Spans::from(vec![
+                Span::raw(format!("{:width$}  ", setting.name(), width = width)),
+                Span::styled(setting.value(s, &app.config), app.theme.key),
+            ]))
+        })
+        .collect::<Vec<ListItem>>();
+    let label = format!("Settings for {}", s.title);
+    let contents = List::new(rows)
+        .block(block(app, label))
+        .highlight_style(app.theme.highlight)
+        .highlight_symbol(highlight_symbol(app));
+    f.render_stateful_widget(contents, parent, &mut app.settings_state);
+}
 
-    let contents = Paragraph::new(text)
-        .wrap(Wrap { trim: true })
-        .block(Block::default().borders(Borders::ALL).title(episode_name));
-    f.render_widget(contents, parent);
+pub fn draw_diagnostics<B: Backend>(f: &mut Frame<B>, app: &mut App, parent: Rect) {
+    let span = span!(Level::TRACE, "render_diagnostics");
+    let _entered = span.enter();
+
+    let label = format!("Feed fetches, by {}", app.metrics_sort.name());
+    if app.metrics.is_empty() {
+        let contents = Paragraph::new("No feeds fetched yet").block(block(app, label));
+        f.render_widget(contents, parent);
+        return;
+    }
+    let feeds = app.metrics.sorted(app.metrics_sort);
+
+    if app.config.accessible {
+        let text = feeds
+            .iter()
+            .map(|(_, s)| {
+                Spans::from(format!(
+                    "{}: average {}, last {}, slowest {}, {}, {} fetches, {} unchanged",
+                    s.title,
+                    fmt_millis(s.average_ms()),
+                    fmt_millis(s.last_ms),
+                    fmt_millis(s.slowest_ms),
+                    fmt_bytes(s.bytes),
+                    s.fetches,
+                    s.unchanged
+                ))
+            })
+            .collect::<Vec<Spans>>();
+        let contents = Paragraph::new(text).block(block(app, label));
+        f.render_widget(contents, parent);
+        return;
+    }
+
+    let rows = feeds.iter().map(|(_, s)| {
+        Row::new(vec![
+            Cell::from(s.title.clone()),
+            Cell::from(fmt_millis(s.average_ms())),
+            Cell::from(fmt_millis(s.last_ms)),
+            Cell::from(fmt_millis(s.slowest_ms)),
+            Cell::from(fmt_bytes(s.bytes)),
+            Cell::from(format!("{}/{}", s.unchanged, s.fetches)),
+        ])
+    });
+    let constraints = [
+        Constraint::Min(20),
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Length(9),
+    ];
+    let t = Table::new(rows)
+        .header(
+            Row::new(vec![
+                "Podcast",
+                "Average",
+                "Last",
+                "Slowest",
+                "Size",
+                "Unchanged",
+            ])
+            .style(app.theme.key)
+            .bottom_margin(1),
+        )
+        .column_spacing(1)
+        .widths(&constraints)
+        .block(block(app, label));
+    f.render_widget(t, parent);
 }
 
-// TODO: make this an actual play bar
-pub fn draw_playbar<B: Backend>(f: &mut Frame<B>, _app: &mut App, parent: Rect) {
-    let text = Spans::from(Span::raw("This is the playbar"));
-    let contents = Paragraph::new(text).block(Block::default().borders(Borders::all()));
-    f.render_widget(contents, parent);
+pub fn draw_library<B: Backend>(f: &mut Frame<B>, app: &mut App, parent: Rect) {
+    let span = span!(Level::TRACE, "render_library");
+    let _entered = span.enter();
+
+    let name = if app.subscriptions.archive {
+        "Archive"
+    } else {
+        "Library"
+    };
+    let sort = app.subscriptions.sort.name();
+    let label = match app.subscriptions.tag.as_ref() {
+        Some(t) => format!("{}, tagged {}, by {}", name, t, sort),
+        None => format!("{}, by {}", name, sort),
+    };
+    let feeds = app
+        .subscriptions
+        .sorted(&app.played)
+        .into_iter()
+        .map(|s| {
+            let unplayed = s.unplayed(&app.played);
+            let mut line = vec![Span::raw(if unplayed == 0 {
+                s.title.clone()
+            } else {
+                format!("{} ({} unplayed)", s.title, unplayed)
+            })];
+            if s.new > 0 {
+                line.push(Span::styled(format!(" • {} new", s.new), app.theme.key));
+            }
+            if !s.tags.is_empty() {
+                line.push(Span::styled(
+                    format!(" [{}]", s.tags.join(", ")),
+                    app.theme.status,
+                ));
+            }
+            ListItem::new(Spans::from(line))
+        })
+        .collect::<Vec<ListItem>>();
+    if feeds.is_empty() {
+        let msg = match app.subscriptions.tag.as_ref() {
+            _ if app.subscriptions.archive => "No archived podcasts".to_string(),
+            Some(t) => format!("No podcasts tagged {}, /tag filter off lists them all", t),
+            None => "No subscriptions yet, /load or /search for a podcast".to_string(),
+        };
+        let contents = Paragraph::new(msg).block(block(app, label));
+        f.render_widget(contents, parent);
+        return;
+    }
+
+    let contents = List::new(feeds)
+        .block(block(app, label))
+        .highlight_style(app.theme.highlight)
+        .highlight_symbol(highlight_symbol(app));
+    f.render_stateful_widget(contents, parent, &mut app.library_state);
 }
@@ -0,0 +1,271 @@
+use html2text::render::text_renderer::{RichAnnotation, TaggedLine};
+use tui::{
+    style::{Modifier, Style},
+    text::{Span, Spans},
+};
+
+use super::theme::Theme;
+
+// Render an episode's show notes, keeping their structure: headings, emphasis, lists,
+// quotes and links are styled instead of flattened into plain text. Styles only add
+// modifiers on top of the theme, so they stay readable in the monochrome theme. Links
+// are followed by their number, e.g. `[1]`, as in `links`.
+pub fn render(html: &str, width: usize, theme: &Theme) -> Vec<Spans<'static>> {
+    let mut links = Links::default();
+    html2text::from_read_rich(normalize(html).as_bytes(), width.max(1))
+        .into_iter()
+        .map(|line| render_line(line, theme, &mut links))
+        .collect()
+}
+
+// The links in the show notes, in the order they're numbered from 1
+pub fn links(html: &str) -> Vec<String> {
+    let mut links = Links::default();
+    for line in html2text::from_read_rich(normalize(html).as_bytes(), LINKS_WIDTH) {
+        for s in line.tagged_strings() {
+            links.see(&s.s, &s.tag);
+        }
+    }
+    links.urls
+}
+
+// Width the notes are laid out at to find their links, which doesn't change them
+const LINKS_WIDTH: usize = 1000;
+
+// Links numbers the links as they come up in the text
+#[derive(Default)]
+struct Links {
+    urls: Vec<String>,
+    // the link the text last seen is part of
+    last: Option<String>,
+}
+
+impl Links {
+    // The number of the link the text starts, if it does. A link that wraps comes in
+    // more than one piece, with the indentation and quote marks of the next line between
+    // them, which don't count as leaving the link.
+    fn see(&mut self, s: &str, tags: &[RichAnnotation]) -> Option<usize> {
+        let url = tags.iter().find_map(|t| match t {
+            RichAnnotation::Link(u) => Some(u),
+            _ => None,
+        });
+        if url.is_none() && s.chars().all(|c| " >*".contains(c)) {
+            return None;
+        }
+        let start = match url {
+            Some(u) if self.last.as_ref() != Some(u) => {
+                self.urls.push(u.clone());
+                Some(self.urls.len())
+            }
+            _ => None,
+        };
+        self.last = url.cloned();
+        start
+    }
+}
+
+// html2text only knows about <strong> and <em>, but feeds mostly use <b> and <i>
+fn normalize(html: &str) -> String {
+    [("b", "strong"), ("i", "em")]
+        .into_iter()
+        .fold(html.to_string(), |html, (from, to)| {
+            [("<", ">"), ("<", " "), ("</", ">")]
+                .into_iter()
+                .fold(html, |html, (open, close)| {
+                    html.replace(
+                        &format!("{open}{from}{close}"),
+                        &format!("{open}{to}{close}"),
+                    )
+                    .replace(
+                        &format!("{open}{}{close}", from.to_uppercase()),
+                        &format!("{open}{to}{close}"),
+                    )
+                })
+        })
+}
+
+fn render_line(
+    line: TaggedLine<Vec<RichAnnotation>>,
+    theme: &Theme,
+    links: &mut Links,
+) -> Spans<'static> {
+    // prefixes and text come as separate strings, so join the ones tagged the same
+    let mut strings: Vec<(String, Vec<RichAnnotation>)> = vec![];
+    for s in line.into_tagged_strings() {
+        match strings.last_mut() {
+            Some((last, tags)) if *tags == s.tag => last.push_str(&s.s),
+            _ => strings.push((s.s, s.tag)),
+        }
+    }
+    let numbers = strings
+        .iter()
+        .map(|(s, tags)| links.see(s, tags))
+        .collect::<Vec<Option<usize>>>();
+
+    // The structure shows up as prefixes on the first string of the line. Quotes and
+    // list items can be nested, so strip them in a loop.
+    let mut spans = vec![];
+    let mut line_style = Style::default();
+    if let Some((first, _)) = strings.first_mut().filter(|(_, t)| t.is_empty()) {
+        let mut rest = first.as_str();
+        let mut prefix = String::new();
+        loop {
+            let indent = rest.len() - rest.trim_start_matches(' ').len();
+            prefix.push_str(&rest[..indent]);
+            rest = &rest[indent..];
+            if let Some(r) = rest.strip_prefix("> ") {
+                prefix.push_str("│ ");
+                line_style = line_style.add_modifier(Modifier::ITALIC);
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("* ") {
+                prefix.push_str("• ");
+                rest = r;
+            } else {
+                break;
+            }
+        }
+        let hashes = rest.len() - rest.trim_start_matches('#').len();
+        if hashes > 0 && rest[hashes..].starts_with(' ') {
+            line_style = line_style.patch(theme.key).add_modifier(Modifier::BOLD);
+            rest = &rest[hashes + 1..];
+        }
+        if !prefix.is_empty() {
+            spans.push(Span::raw(prefix));
+        }
+        *first = rest.to_string();
+    }
+
+    for ((s, tags), number) in strings.into_iter().zip(numbers) {
+        if s.is_empty() {
+            continue;
+        }
+        let style = tags
+            .iter()
+            .fold(line_style, |style, t| style.patch(annotation(t, theme)));
+        spans.push(Span::styled(strip_markers(s, &tags), style));
+        if let Some(n) = number {
+            spans.push(Span::styled(format!("[{}]", n), theme.key));
+        }
+    }
+    Spans::from(spans)
+}
+
+fn annotation(tag: &RichAnnotation, theme: &Theme) -> Style {
+    match tag {
+        RichAnnotation::Default | RichAnnotation::Image(_) => Style::default(),
+        RichAnnotation::Link(_) => theme.link,
+        RichAnnotation::Emphasis => Style::default().add_modifier(Modifier::ITALIC),
+        RichAnnotation::Strong => Style::default().add_modifier(Modifier::BOLD),
+        RichAnnotation::Strikeout => Style::default().add_modifier(Modifier::CROSSED_OUT),
+        RichAnnotation::Code | RichAnnotation::Preformat(_) => {
+            Style::default().add_modifier(Modifier::DIM)
+        }
+    }
+}
+
+// html2text surrounds bold text with `*` and code with backticks, which the styles replace
+fn strip_markers(s: String, tags: &[RichAnnotation]) -> String {
+    let marker = match tags.last() {
+        Some(RichAnnotation::Strong) => '*',
+        Some(RichAnnotation::Code) => '`',
+        _ => return s,
+    };
+    s.trim_matches(marker).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::{
+        style::Modifier,
+        text::{Span, Spans},
+    };
+
+    use super::{links, render};
+    use crate::ui::theme::Theme;
+
+    fn text(line: &Spans) -> String {
+        line.0.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    fn span<'a>(lines: &'a [Spans], content: &str) -> &'a Span<'a> {
+        lines
+            .iter()
+            .flat_map(|l| l.0.iter())
+            .find(|s| s.content == content)
+            .unwrap()
+    }
+
+    #[test]
+    fn styles_inline_text() {
+        let theme = Theme::default();
+        let lines = render(
+            r#"<p>Some <b>bold</b>, <em>italic</em> and <a href="https://a.com">a link</a></p>"#,
+            80,
+            &theme,
+        );
+
+        assert_eq!(text(&lines[0]), "Some bold, italic and a link[1]");
+        assert!(span(&lines, "bold")
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+        assert!(span(&lines, "italic")
+            .style
+            .add_modifier
+            .contains(Modifier::ITALIC));
+        assert_eq!(span(&lines, "a link").style, theme.link);
+    }
+
+    #[test]
+    fn numbers_links() {
+        let html = r#"<p><a href="https://a.com">first</a> and <a href="https://b.com">a second link that wraps</a></p><blockquote><a href="https://a.com">again</a></blockquote>"#;
+        assert_eq!(
+            links(html),
+            ["https://a.com", "https://b.com", "https://a.com"]
+        );
+
+        let lines = render(html, 20, &Theme::default())
+            .iter()
+            .map(text)
+            .collect::<Vec<String>>()
+            .join(" ");
+        assert!(lines.contains("first[1]"));
+        // numbered once, where it starts
+        assert!(lines.contains("a second[2] link that wraps"));
+        assert!(!lines.contains("[4]"));
+        assert!(lines.contains("again[3]"));
+    }
+
+    #[test]
+    fn keeps_structure() {
+        let lines = render(
+            "<h2>Topics</h2><ul><li>one<ul><li>nested</li></ul></li></ul><blockquote>quoted</blockquote>",
+            80,
+            &Theme::default(),
+        )
+        .iter()
+        .map(text)
+        .filter(|l| !l.trim().is_empty())
+        .collect::<Vec<String>>();
+
+        assert_eq!(lines, ["Topics", "• one", "  • nested", "│ quoted"]);
+    }
+
+    #[test]
+    fn styles_headings_and_quotes() {
+        let lines = render(
+            "<h1>Title</h1><blockquote>quoted</blockquote>",
+            80,
+            &Theme::default(),
+        );
+
+        assert!(span(&lines, "Title")
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+        assert!(span(&lines, "quoted")
+            .style
+            .add_modifier
+            .contains(Modifier::ITALIC));
+    }
+}
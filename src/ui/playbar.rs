@@ -0,0 +1,185 @@
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Span, Spans},
+    widgets::{LineGauge, Paragraph, Sparkline},
+    Frame,
+};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{player, App};
+
+use super::{
+    block,
+    component::{over, Action, Component},
+    fmt_duration, position, progress, title, VOLUME_WIDTH,
+};
+
+// Playbar shows the episode playing and how far along it is, and takes the keys that
+// control playback
+#[derive(Default, Debug)]
+pub struct Playbar {
+    // where the progress line is, and the width of its label, which comes first
+    gauge: Option<(Rect, u16)>,
+}
+
+impl Playbar {
+    // How far into the episode a click at this column is, None when it's not on the line
+    fn ratio_at(&self, column: u16) -> Option<f64> {
+        let (area, label) = self.gauge?;
+        // the line starts a space after the label
+        let start = area.left() + label + 1;
+        if column < start || start >= area.right() {
+            return None;
+        }
+        Some(f64::from(column - start) / f64::from(area.right() - start))
+    }
+}
+
+impl Component for Playbar {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        self.gauge = None;
+        // the app can't tell how far along another program is
+        if let Some(e) = app.player.external.as_ref() {
+            let state = format!("Playing externally in {}", e.program);
+            let text = vec![
+                Spans::from(Span::raw(e.episode.podcast.clone().unwrap_or_default())),
+                Spans::from(Span::styled("Press x to stop it", app.theme.status)),
+            ];
+            let contents =
+                Paragraph::new(text).block(block(app, title(app, &state, e.episode.title())));
+            f.render_widget(contents, area);
+            return;
+        }
+        let Some(episode) = app.player.episode.as_ref() else {
+            let contents =
+                Paragraph::new("Nothing playing. Press Enter on an episode's details to play it.")
+                    .style(app.theme.status)
+                    .block(block(app, ""));
+            f.render_widget(contents, area);
+            return;
+        };
+
+        let status = app.player.status;
+        let at = position(app);
+        let speed = format!("{:.1}x", status.speed);
+        let sleep = app.player.sleep.as_ref().map(|s| s.describe());
+        let b = block(app, title(app, status.name(), episode.title()));
+        let inner = b.inner(area);
+        f.render_widget(b, area);
+
+        let podcast = episode.podcast.clone().unwrap_or_default();
+        if app.config.accessible {
+            let mut text = vec![
+                Spans::from(Span::raw(format!("Podcast: {}", podcast))),
+                Spans::from(Span::raw(format!("Position: {}", at))),
+                Spans::from(Span::raw(format!("Speed: {}", speed))),
+                Spans::from(Span::raw(format!("Volume: {}%", status.volume))),
+            ];
+            if let Some(b) = status.buffered {
+                text.push(Spans::from(Span::raw(format!("Downloaded: {}%", b))));
+            }
+            if status.skip_silence {
+                text.push(Spans::from(Span::raw(format!(
+                    "Skipping silences: {} saved",
+                    fmt_duration(status.saved)
+                ))));
+            }
+            if let Some(s) = app.player.segment.as_ref() {
+                text.push(Spans::from(Span::raw(format!(
+                    "Loop: {}",
+                    s.describe().trim_start_matches("loop ")
+                ))));
+            }
+            if app.player.repeat != player::Repeat::Off {
+                text.push(Spans::from(Span::raw(format!(
+                    "Repeat: {}",
+                    app.player.repeat.describe().trim_start_matches("repeat ")
+                ))));
+            }
+            if app.player.stop_after {
+                text.push(Spans::from(Span::raw("Stopping after this episode")));
+            }
+            if let Some(s) = sleep {
+                text.push(Spans::from(Span::raw(format!("Timer: {}", s))));
+            }
+            f.render_widget(Paragraph::new(text), inner);
+            return;
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Length(1)].as_ref())
+            .split(inner);
+        f.render_widget(Paragraph::new(podcast), chunks[0]);
+        let (ratio, label) = progress(app);
+        let label_width = label.width() as u16;
+        let gauge = LineGauge::default()
+            .ratio(ratio)
+            .label(label)
+            .gauge_style(app.theme.playbar);
+        let line = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Min(0),
+                    Constraint::Length(player::LEVELS as u16 + 2),
+                    Constraint::Length(VOLUME_WIDTH),
+                ]
+                .as_ref(),
+            )
+            .split(chunks[1]);
+        f.render_widget(gauge, line[0]);
+        self.gauge = Some((line[0], label_width));
+        // how loud it played lately, which shows that sound is coming out
+        if status.state == player::State::Playing {
+            let levels = status.levels.map(u64::from);
+            let meter = Sparkline::default()
+                .data(&levels)
+                .max(100)
+                .style(app.theme.playbar);
+            let area = Rect {
+                x: line[1].x + 1,
+                width: line[1].width.saturating_sub(2),
+                ..line[1]
+            };
+            f.render_widget(meter, area);
+        }
+        let volume = LineGauge::default()
+            .ratio(status.volume as f64 / 100.0)
+            .label(format!(" Vol {:>3}%", status.volume))
+            .gauge_style(app.theme.key);
+        f.render_widget(volume, line[2]);
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        let action = match key.code {
+            KeyCode::Char(' ' | 'p') => Action::TogglePause,
+            KeyCode::Char('[') | KeyCode::Left => Action::SeekBack,
+            KeyCode::Char(']') | KeyCode::Right => Action::SeekForward,
+            KeyCode::Char('+' | '=') => Action::Faster,
+            KeyCode::Char('-') => Action::Slower,
+            KeyCode::Char('9') => Action::Quieter,
+            KeyCode::Char('0') => Action::Louder,
+            _ => return None,
+        };
+        Some(action)
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Option<Action> {
+        if mouse.kind != MouseEventKind::Down(MouseButton::Left) {
+            return None;
+        }
+        let (area, _) = self.gauge?;
+        if !over(&mouse, area) {
+            return None;
+        }
+        // a click on the label is taken too, so that it doesn't reach what's under it
+        Some(
+            self.ratio_at(mouse.column)
+                .map_or(Action::Consumed, Action::SeekTo),
+        )
+    }
+}
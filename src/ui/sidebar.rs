@@ -0,0 +1,184 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    widgets::{List, ListItem, ListState},
+    Frame,
+};
+
+use crate::{
+    subscriptions::{Subscription, Subscriptions},
+    App,
+};
+
+use super::{
+    block,
+    component::{Action, Component},
+    highlight_symbol,
+};
+
+// Narrowest terminal the sidebar is shown in, leaving the rest for the display area
+pub const MIN_WIDTH: u16 = 80;
+
+// Columns the sidebar takes
+pub const WIDTH: u16 = 24;
+
+// The podcasts in the sidebar: the ones not archived, by title
+pub fn podcasts(subscriptions: &Subscriptions) -> Vec<&Subscription> {
+    let mut feeds = subscriptions.active().collect::<Vec<&Subscription>>();
+    feeds.sort_by_key(|s| s.title.to_lowercase());
+    feeds
+}
+
+// Sidebar lists the subscribed podcasts next to the display area. It takes the keys
+// while it has the focus.
+#[derive(Default, Debug)]
+pub struct Sidebar {
+    state: ListState,
+    pub focused: bool,
+    // whether it was drawn, it can't take the focus otherwise
+    shown: bool,
+    len: usize,
+}
+
+impl Sidebar {
+    // Whether the sidebar fits in this width
+    pub fn fits(app: &App, width: u16) -> bool {
+        app.config.display.sidebar && width >= MIN_WIDTH
+    }
+
+    // Called when the sidebar isn't drawn, so that it gives up the focus
+    pub fn hide(&mut self) {
+        self.shown = false;
+        self.focused = false;
+    }
+
+    fn select(&mut self, down: bool) {
+        if self.len == 0 {
+            return;
+        }
+        let idx = match (self.state.selected(), down) {
+            (None, _) => 0,
+            (Some(i), true) => (i + 1).min(self.len - 1),
+            (Some(i), false) => i.saturating_sub(1),
+        };
+        self.state.select(Some(idx));
+    }
+}
+
+impl Component for Sidebar {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        self.shown = true;
+        let feeds = podcasts(&app.subscriptions);
+        self.len = feeds.len();
+        if self.state.selected().is_some_and(|i| i >= self.len) {
+            self.state.select(self.len.checked_sub(1));
+        }
+        let loaded = app.feed_url.as_ref().map(|u| u.as_str());
+        let items = feeds
+            .iter()
+            .map(|s| {
+                // the podcast whose episodes are loaded
+                let open = loaded == Some(s.url.as_str());
+                if app.config.accessible {
+                    let suffix = if open { ", loaded" } else { "" };
+                    return ListItem::new(format!("{}{}", s.title, suffix));
+                }
+                let mark = if open { "▸ " } else { "  " };
+                ListItem::new(format!("{}{}", mark, s.title))
+            })
+            .collect::<Vec<ListItem>>();
+
+        let title = match (self.focused, app.config.accessible) {
+            (true, true) => "Podcasts, focused",
+            (true, false) => "[Podcasts]*",
+            (false, true) => "Podcasts",
+            (false, false) => "[Podcasts]",
+        };
+        let mut b = block(app, title);
+        if self.focused && !app.config.accessible {
+            b = b.border_style(app.theme.highlight);
+        }
+        // the selection only shows while the sidebar has the focus
+        let mut list = List::new(items).block(b);
+        if self.focused {
+            list = list
+                .highlight_style(app.theme.highlight)
+                .highlight_symbol(highlight_symbol(app));
+        }
+        f.render_stateful_widget(list, area, &mut self.state);
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        if !self.focused {
+            if key.code == KeyCode::Char('h') && self.shown {
+                self.focused = true;
+                if self.state.selected().is_none() {
+                    self.select(true);
+                }
+                return Some(Action::Consumed);
+            }
+            return None;
+        }
+        let action = match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.select(false);
+                Action::Consumed
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.select(true);
+                Action::Consumed
+            }
+            KeyCode::Enter => {
+                // the episodes take the focus once they show
+                self.focused = false;
+                Action::OpenPodcast(self.state.selected()?)
+            }
+            KeyCode::Char('l') | KeyCode::Tab => {
+                self.focused = false;
+                Action::Consumed
+            }
+            _ => return None,
+        };
+        Some(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::Sidebar;
+    use crate::ui::component::{Action, Component};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn takes_focus() {
+        let mut s = Sidebar::default();
+        // not drawn, so it can't take the focus
+        assert_eq!(s.handle_event(key(KeyCode::Char('h'))), None);
+
+        s.shown = true;
+        s.len = 2;
+        assert_eq!(
+            s.handle_event(key(KeyCode::Char('h'))),
+            Some(Action::Consumed)
+        );
+        assert!(s.focused);
+        s.handle_event(key(KeyCode::Down));
+        s.handle_event(key(KeyCode::Down));
+        assert_eq!(
+            s.handle_event(key(KeyCode::Enter)),
+            Some(Action::OpenPodcast(1))
+        );
+        assert!(!s.focused);
+
+        s.handle_event(key(KeyCode::Char('h')));
+        assert_eq!(s.handle_event(key(KeyCode::Char(' '))), None);
+        assert_eq!(s.handle_event(key(KeyCode::Tab)), Some(Action::Consumed));
+        assert!(!s.focused);
+    }
+}
@@ -0,0 +1,109 @@
+use tui::{backend::Backend, layout::Rect, text::Spans, widgets::Tabs, Frame};
+
+use crate::{message::DisplayAction, App};
+
+// Tab is one of the top level screens, each standing for the views that belong to it
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tab {
+    #[default]
+    Podcasts,
+    Episodes,
+    Queue,
+    Downloads,
+    NowPlaying,
+}
+
+impl Tab {
+    // In the order they are shown, and numbered from 1 by
+    pub const ALL: [Tab; 5] = [
+        Tab::Podcasts,
+        Tab::Episodes,
+        Tab::Queue,
+        Tab::Downloads,
+        Tab::NowPlaying,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tab::Podcasts => "Podcasts",
+            Tab::Episodes => "Episodes",
+            Tab::Queue => "Queue",
+            Tab::Downloads => "Downloads",
+            Tab::NowPlaying => "Now Playing",
+        }
+    }
+
+    // The tab a view belongs to, if any. Views reached by command only, like the
+    // history, show in whichever tab they're opened from.
+    pub fn of(display: &DisplayAction) -> Option<Tab> {
+        match display {
+            DisplayAction::Library
+            | DisplayAction::Search
+            | DisplayAction::Preview
+            | DisplayAction::PodcastSettings => Some(Tab::Podcasts),
+            DisplayAction::ListEpisodes | DisplayAction::DescribeEpisode => Some(Tab::Episodes),
+            DisplayAction::Queue => Some(Tab::Queue),
+            DisplayAction::Downloads | DisplayAction::Storage | DisplayAction::CleanUp => {
+                Some(Tab::Downloads)
+            }
+            DisplayAction::NowPlaying => Some(Tab::NowPlaying),
+            _ => None,
+        }
+    }
+
+    // The tab numbered n, from 1
+    pub fn nth(n: u32) -> Option<Tab> {
+        Tab::ALL.get((n as usize).checked_sub(1)?).copied()
+    }
+
+    fn index(&self) -> usize {
+        Tab::ALL.iter().position(|t| t == self).unwrap_or(0)
+    }
+
+    // The tab after this one, or before it when going back, wrapping around
+    pub fn cycle(&self, back: bool) -> Tab {
+        let len = Tab::ALL.len();
+        let step = if back { len - 1 } else { 1 };
+        Tab::ALL[(self.index() + step) % len]
+    }
+}
+
+// Show the tabs, the current one bracketed so that it doesn't stand out by color alone
+pub fn draw_tabs<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let titles = Tab::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, t)| {
+            let label = format!("{} {}", i + 1, t.name());
+            Spans::from(if app.tab == *t {
+                format!("[{}]", label)
+            } else {
+                format!(" {} ", label)
+            })
+        })
+        .collect();
+    let tabs = Tabs::new(titles)
+        .style(app.theme.base)
+        .select(app.tab.index())
+        .highlight_style(app.theme.highlight);
+    f.render_widget(tabs, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::DisplayAction;
+
+    use super::Tab;
+
+    #[test]
+    fn cycles_through_tabs() {
+        assert_eq!(Tab::Podcasts.cycle(false), Tab::Episodes);
+        assert_eq!(Tab::NowPlaying.cycle(false), Tab::Podcasts);
+        assert_eq!(Tab::Podcasts.cycle(true), Tab::NowPlaying);
+        assert_eq!(Tab::nth(3), Some(Tab::Queue));
+        assert_eq!(Tab::nth(0), None);
+        assert_eq!(Tab::nth(6), None);
+        assert_eq!(Tab::of(&DisplayAction::Storage), Some(Tab::Downloads));
+        assert_eq!(Tab::of(&DisplayAction::History), None);
+    }
+}
@@ -0,0 +1,512 @@
+use serde::Deserialize;
+use std::{env, error::Error, fs};
+
+use tracing::error;
+use tui::style::{Color, Modifier, Style};
+
+use crate::config::{self, Config};
+
+// What has happened to an episode, as shown in the episode list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EpisodeState {
+    Unplayed,
+    InProgress,
+    Played,
+    Downloaded,
+    Failed,
+}
+
+impl EpisodeState {
+    // In the order they are listed in the legend
+    pub const ALL: [EpisodeState; 5] = [
+        EpisodeState::Unplayed,
+        EpisodeState::InProgress,
+        EpisodeState::Played,
+        EpisodeState::Downloaded,
+        EpisodeState::Failed,
+    ];
+
+    // Also the key used for it under `[episode_states]` in the config, with `-` as `_`
+    pub fn name(&self) -> &'static str {
+        match self {
+            EpisodeState::Unplayed => "unplayed",
+            EpisodeState::InProgress => "in progress",
+            EpisodeState::Played => "played",
+            EpisodeState::Downloaded => "downloaded",
+            EpisodeState::Failed => "failed",
+        }
+    }
+
+    fn by_key(key: &str) -> Option<EpisodeState> {
+        EpisodeState::ALL
+            .into_iter()
+            .find(|s| s.name().replace(' ', "_") == key)
+    }
+}
+
+// How an episode state is drawn: a glyph in front of the title, and a style for the line.
+// The glyph is what carries the state, the style only reinforces it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateStyle {
+    pub glyph: String,
+    pub style: Style,
+}
+
+fn state_styles(styles: [(&str, Style); 5]) -> [StateStyle; 5] {
+    styles.map(|(glyph, style)| StateStyle {
+        glyph: glyph.to_string(),
+        style,
+    })
+}
+
+// Glyphs used by every built-in theme, in the order of `EpisodeState::ALL`
+const GLYPHS: [&str; 5] = ["•", "◐", "✓", "↓", "✗"];
+
+// Names of the built-in themes, in the order they are listed to the user
+pub const THEMES: [&str; 3] = ["default", "high-contrast", "monochrome"];
+
+// Theme holds the styles used when rendering. State must never be conveyed by color
+// alone: every theme keeps the bold/italic modifiers and the selection symbol, so the
+// monochrome theme is readable on terminals without color support.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    // applied to the whole screen before anything else is drawn
+    pub base: Style,
+    pub border: Style,
+    // key names and commands in hints
+    pub key: Style,
+    // the selected row in lists
+    pub highlight: Style,
+    pub link: Style,
+    pub status: Style,
+    // errors in the status line
+    pub error: Style,
+    // the progress of the episode playing
+    pub playbar: Style,
+    // episode states, in the order of `EpisodeState::ALL`
+    pub episodes: [StateStyle; 5],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            name: "default".to_string(),
+            base: Style::default(),
+            border: Style::default(),
+            key: Style::default().add_modifier(Modifier::BOLD),
+            highlight: Style::default()
+                .fg(Color::LightCyan)
+                .add_modifier(Modifier::BOLD)
+                .add_modifier(Modifier::ITALIC),
+            link: Style::default()
+                .fg(Color::LightBlue)
+                .add_modifier(Modifier::ITALIC)
+                .add_modifier(Modifier::BOLD),
+            status: Style::default().fg(Color::Gray),
+            error: Style::default()
+                .fg(Color::LightRed)
+                .add_modifier(Modifier::BOLD),
+            playbar: Style::default().fg(Color::LightCyan),
+            episodes: state_styles([
+                (GLYPHS[0], Style::default().add_modifier(Modifier::BOLD)),
+                (GLYPHS[1], Style::default().fg(Color::Yellow)),
+                (GLYPHS[2], Style::default().fg(Color::DarkGray)),
+                (GLYPHS[3], Style::default().fg(Color::Green)),
+                (GLYPHS[4], Style::default().fg(Color::Red)),
+            ]),
+        }
+    }
+}
+
+impl Theme {
+    pub fn high_contrast() -> Self {
+        let base = Style::default().fg(Color::White).bg(Color::Black);
+        Theme {
+            name: "high-contrast".to_string(),
+            base,
+            border: base.add_modifier(Modifier::BOLD),
+            key: Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+            highlight: Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+            link: Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::UNDERLINED)
+                .add_modifier(Modifier::BOLD),
+            status: base.add_modifier(Modifier::BOLD),
+            error: base.fg(Color::LightRed).add_modifier(Modifier::BOLD),
+            playbar: base.fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            episodes: state_styles([
+                (GLYPHS[0], base.add_modifier(Modifier::BOLD)),
+                (GLYPHS[1], base.fg(Color::Yellow)),
+                (GLYPHS[2], base),
+                (GLYPHS[3], base.fg(Color::LightGreen)),
+                (
+                    GLYPHS[4],
+                    base.fg(Color::LightRed).add_modifier(Modifier::BOLD),
+                ),
+            ]),
+        }
+    }
+
+    // No colors at all, only text modifiers
+    pub fn monochrome() -> Self {
+        Theme {
+            name: "monochrome".to_string(),
+            base: Style::default(),
+            border: Style::default(),
+            key: Style::default().add_modifier(Modifier::BOLD),
+            highlight: Style::default()
+                .add_modifier(Modifier::REVERSED)
+                .add_modifier(Modifier::BOLD),
+            link: Style::default().add_modifier(Modifier::UNDERLINED),
+            status: Style::default(),
+            error: Style::default()
+                .add_modifier(Modifier::REVERSED)
+                .add_modifier(Modifier::BOLD),
+            playbar: Style::default().add_modifier(Modifier::BOLD),
+            episodes: state_styles([
+                (GLYPHS[0], Style::default().add_modifier(Modifier::BOLD)),
+                (GLYPHS[1], Style::default()),
+                (GLYPHS[2], Style::default().add_modifier(Modifier::DIM)),
+                (GLYPHS[3], Style::default()),
+                (GLYPHS[4], Style::default().add_modifier(Modifier::BOLD)),
+            ]),
+        }
+    }
+
+    pub fn is_monochrome(&self) -> bool {
+        self.name == "monochrome"
+    }
+
+    pub fn episode(&self, state: EpisodeState) -> &StateStyle {
+        let idx = EpisodeState::ALL
+            .iter()
+            .position(|s| *s == state)
+            .unwrap_or_default();
+        &self.episodes[idx]
+    }
+
+    // Apply the user's `[episode_states]` on top of the theme. Colors are left out of
+    // the monochrome theme.
+    pub fn customize(mut self, config: &Config) -> Self {
+        for (key, custom) in config.episode_states.iter() {
+            let Some(state) = EpisodeState::by_key(key) else {
+                error!("unknown episode state {:?} in config", key);
+                continue;
+            };
+            let monochrome = self.is_monochrome();
+            let idx = EpisodeState::ALL
+                .iter()
+                .position(|s| *s == state)
+                .unwrap_or_default();
+            let s = &mut self.episodes[idx];
+            if let Some(g) = custom.glyph.as_ref() {
+                s.glyph = g.clone();
+            }
+            match custom.color.as_deref().map(parse_color) {
+                Some(Some(c)) if !monochrome => s.style = s.style.fg(c),
+                Some(None) => error!("unknown color for episode state {}", key),
+                _ => {}
+            }
+        }
+        self
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Theme::default()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "monochrome" | "no-color" => Some(Theme::monochrome()),
+            _ => None,
+        }
+    }
+
+    // A built-in theme, or one from the user's themes directory
+    pub fn load(name: &str) -> Result<Self, Box<dyn Error>> {
+        if let Some(t) = Theme::by_name(name) {
+            return Ok(t);
+        }
+        // the name becomes a file name, so it can't lead out of the themes directory
+        let ok = name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if name.is_empty() || !ok {
+            return Err(format!(
+                "Invalid theme name {:?}, use letters, digits, - and _",
+                name
+            )
+            .into());
+        }
+        match config::themes_dir().map(|d| d.join(format!("{}.toml", name))) {
+            Some(p) if p.exists() => parse_theme(name, fs::read_to_string(p)?.as_str()),
+            _ => Err(format!("Unknown theme {:?}", name).into()),
+        }
+    }
+
+    // Pick the theme at startup. A non-empty NO_COLOR always wins (see https://no-color.org).
+    pub fn from_config(config: &Config) -> Self {
+        if no_color(env::var("NO_COLOR").ok().as_deref()) {
+            return Theme::monochrome().customize(config);
+        }
+        let theme = match config.theme.as_str() {
+            "" => Ok(Theme::default()),
+            name => Theme::load(name),
+        };
+        theme
+            .unwrap_or_else(|e| {
+                error!("could not load theme: {}", e);
+                Theme::default()
+            })
+            .customize(config)
+    }
+}
+
+// The user's themes, by the name of their file in the themes directory
+pub fn user_themes() -> Vec<String> {
+    let Some(entries) = config::themes_dir().and_then(|d| fs::read_dir(d).ok()) else {
+        return vec![];
+    };
+    let mut names = entries
+        .filter_map(|e| {
+            let p = e.ok()?.path();
+            if p.extension()? != "toml" {
+                return None;
+            }
+            Some(p.file_stem()?.to_string_lossy().to_string())
+        })
+        .collect::<Vec<String>>();
+    names.sort();
+    names
+}
+
+// A style in a theme file. Anything left out is taken from the theme it extends.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StyleFile {
+    fg: Option<String>,
+    bg: Option<String>,
+    bold: Option<bool>,
+    italic: Option<bool>,
+    underlined: Option<bool>,
+    reversed: Option<bool>,
+}
+
+impl StyleFile {
+    fn apply(&self, mut style: Style) -> Result<Style, Box<dyn Error>> {
+        let color = |c: &str| parse_color(c).ok_or(format!("unknown color {:?}", c));
+        if let Some(c) = self.fg.as_deref() {
+            style = style.fg(color(c)?);
+        }
+        if let Some(c) = self.bg.as_deref() {
+            style = style.bg(color(c)?);
+        }
+        let modifiers = [
+            (self.bold, Modifier::BOLD),
+            (self.italic, Modifier::ITALIC),
+            (self.underlined, Modifier::UNDERLINED),
+            (self.reversed, Modifier::REVERSED),
+        ];
+        for (on, m) in modifiers {
+            style = match on {
+                Some(true) => style.add_modifier(m),
+                Some(false) => style.remove_modifier(m),
+                None => style,
+            };
+        }
+        Ok(style)
+    }
+}
+
+// A theme file, `$XDG_CONFIG_HOME/podcasts/themes/<name>.toml`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile {
+    // the built-in theme to start from, `default` when not set
+    extends: Option<String>,
+    base: StyleFile,
+    border: StyleFile,
+    key: StyleFile,
+    highlight: StyleFile,
+    link: StyleFile,
+    status: StyleFile,
+    error: StyleFile,
+    playbar: StyleFile,
+}
+
+pub fn parse_theme(name: &str, s: &str) -> Result<Theme, Box<dyn Error>> {
+    let file: ThemeFile = toml::from_str(s)?;
+    let mut t = match file.extends.as_deref() {
+        Some(b) => Theme::by_name(b).ok_or(format!("unknown theme {:?} to extend", b))?,
+        None => Theme::default(),
+    };
+    t.name = name.to_string();
+    t.base = file.base.apply(t.base)?;
+    t.border = file.border.apply(t.border)?;
+    t.key = file.key.apply(t.key)?;
+    t.highlight = file.highlight.apply(t.highlight)?;
+    t.link = file.link.apply(t.link)?;
+    t.status = file.status.apply(t.status)?;
+    t.error = file.error.apply(t.error)?;
+    t.playbar = file.playbar.apply(t.playbar)?;
+    Ok(t)
+}
+
+// A color by name (as in `tui::style::Color`, e.g. "lightblue") or as "#rrggbb"
+pub fn parse_color(s: &str) -> Option<Color> {
+    let s = s.trim().to_ascii_lowercase();
+    if let Some(hex) = s.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let n = u32::from_str_radix(hex, 16).ok()?;
+        return Some(Color::Rgb((n >> 16) as u8, (n >> 8) as u8, n as u8));
+    }
+    let c = match s.replace(['-', '_', ' '], "").as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    };
+    Some(c)
+}
+
+fn no_color(var: Option<&str>) -> bool {
+    var.map(|v| !v.is_empty()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::style::{Color, Modifier};
+
+    use super::{no_color, parse_color, parse_theme, EpisodeState, Theme, THEMES};
+    use crate::config::parse;
+
+    #[test]
+    fn all_listed_themes_exist() {
+        for name in THEMES {
+            assert_eq!(Theme::by_name(name).map(|t| t.name), Some(name.to_string()));
+        }
+    }
+
+    #[test]
+    fn unknown_theme() {
+        assert_eq!(Theme::by_name("solarized"), None);
+    }
+
+    #[test]
+    fn keeps_to_themes_dir() {
+        for name in ["../secret", "a/b", "..", ""] {
+            let e = Theme::load(name).unwrap_err();
+            assert!(e.to_string().starts_with("Invalid theme name"), "{}", name);
+        }
+    }
+
+    #[test]
+    fn monochrome_has_no_colors() {
+        let t = Theme::monochrome();
+        let episodes = t.episodes.iter().map(|s| s.style);
+        for style in [
+            t.base,
+            t.border,
+            t.key,
+            t.highlight,
+            t.link,
+            t.status,
+            t.playbar,
+        ]
+        .into_iter()
+        .chain(episodes)
+        {
+            assert!(style.fg.is_none() || style.fg == Some(Color::Reset));
+            assert!(style.bg.is_none() || style.bg == Some(Color::Reset));
+        }
+    }
+
+    #[test]
+    fn parses_colors() {
+        assert_eq!(parse_color("Light-Blue"), Some(Color::LightBlue));
+        assert_eq!(parse_color("#ff8000"), Some(Color::Rgb(255, 128, 0)));
+        assert_eq!(parse_color("#ff80"), None);
+        assert_eq!(parse_color("mauve"), None);
+    }
+
+    #[test]
+    fn customizes_episode_states() {
+        let config = parse(
+            r##"[episode_states]
+            played = { glyph = "x", color = "#00ff00" }
+            in_progress = { color = "cyan" }
+            "##,
+        )
+        .unwrap();
+
+        let t = Theme::default().customize(&config);
+        assert_eq!(t.episode(EpisodeState::Played).glyph, "x");
+        assert_eq!(
+            t.episode(EpisodeState::Played).style.fg,
+            Some(Color::Rgb(0, 255, 0))
+        );
+        assert_eq!(t.episode(EpisodeState::InProgress).glyph, "◐");
+        assert_eq!(
+            t.episode(EpisodeState::InProgress).style.fg,
+            Some(Color::Cyan)
+        );
+
+        // glyphs still apply without colors
+        let t = Theme::monochrome().customize(&config);
+        assert_eq!(t.episode(EpisodeState::Played).glyph, "x");
+        assert_eq!(t.episode(EpisodeState::Played).style.fg, None);
+    }
+
+    #[test]
+    fn parses_theme_file() {
+        let t = parse_theme(
+            "dusk",
+            r##"extends = "high-contrast"
+            border = { fg = "#303030" }
+            highlight = { fg = "black", bg = "lightmagenta", bold = false }
+            playbar = { fg = "magenta" }
+            "##,
+        )
+        .unwrap();
+        let base = Theme::high_contrast();
+        assert_eq!(t.name, "dusk");
+        assert_eq!(t.border.fg, Some(Color::Rgb(48, 48, 48)));
+        assert_eq!(t.highlight.bg, Some(Color::LightMagenta));
+        assert!(!t.highlight.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(t.playbar.fg, Some(Color::Magenta));
+        // what the file leaves out comes from the theme it extends
+        assert_eq!(t.status, base.status);
+        assert_eq!(t.episodes, base.episodes);
+
+        assert!(parse_theme("x", "status = { fg = \"mauve\" }").is_err());
+        assert!(parse_theme("x", "extends = \"solarized\"").is_err());
+        assert!(parse_theme("x", "borders = {}").is_err());
+    }
+
+    #[test]
+    fn respects_no_color() {
+        assert!(no_color(Some("1")));
+        assert!(!no_color(Some("")));
+        assert!(!no_color(None));
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+
+use crossterm::Command;
+
+use crate::{player, profile, App};
+
+// Title used when nothing is open
+const APP_TITLE: &str = "podcasts";
+
+// SaveTitle pushes the terminal's current window title onto its title stack (xterm's
+// XTWINOPS 22), so that it can be put back with RestoreTitle on exit. Terminals don't let
+// programs read the title, so this is the only way to restore it.
+pub struct SaveTitle;
+
+impl Command for SaveTitle {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1b[22;0t")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> crossterm::Result<()> {
+        Ok(())
+    }
+}
+
+// RestoreTitle pops the title saved by SaveTitle
+pub struct RestoreTitle;
+
+impl Command for RestoreTitle {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1b[23;0t")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> crossterm::Result<()> {
+        Ok(())
+    }
+}
+
+// Window title for the current state: the episode playing and its podcast, or else the
+// open episode and its podcast
+pub fn window_title(app: &App) -> String {
+    let playing = match (app.player.external.as_ref(), app.player.episode.as_ref()) {
+        (Some(e), _) => Some(&e.episode),
+        (None, Some(e)) if app.player.status.state == player::State::Playing => Some(e),
+        _ => None,
+    };
+    let title = match playing {
+        Some(e) => match e.podcast.as_deref() {
+            Some(p) => format!("▶ {} — {}", e.title(), p),
+            None => format!("▶ {}", e.title()),
+        },
+        None => open_title(app),
+    };
+    // titles come from the feed, don't let them smuggle escape sequences into the terminal
+    title.chars().filter(|c| !c.is_control()).collect()
+}
+
+fn open_title(app: &App) -> String {
+    let episode = app.item.as_ref().and_then(|i| i.title());
+    let podcast = app.channel.as_ref().map(|c| c.title());
+    match (episode, podcast) {
+        (Some(e), Some(p)) => format!("{} — {}", e, p),
+        (Some(e), None) => e.to_string(),
+        (None, Some(p)) => p.to_string(),
+        (None, None) => match profile::current() {
+            Some(p) => format!("{} ({})", APP_TITLE, p),
+            None => APP_TITLE.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rss::{Channel, Item};
+
+    use super::window_title;
+    use crate::{player, App};
+
+    #[test]
+    fn episode_and_podcast() {
+        let mut app = App::default();
+        assert_eq!(window_title(&app), "podcasts");
+
+        app.channel = Some(Channel {
+            title: "Wine About It".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(window_title(&app), "Wine About It");
+
+        app.item = Some(Item {
+            title: Some("Episode 1".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(window_title(&app), "Episode 1 — Wine About It");
+    }
+
+    #[test]
+    fn episode_playing() {
+        let mut app = App {
+            item: Some(Item {
+                title: Some("Episode 2".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        app.player.episode = Some(player::Episode {
+            item: Item {
+                title: Some("Episode 1".to_string()),
+                ..Default::default()
+            },
+            podcast: Some("Wine About It".to_string()),
+            feed: None,
+            artwork: None,
+        });
+        app.player.status.state = player::State::Playing;
+        assert_eq!(window_title(&app), "▶ Episode 1 — Wine About It");
+
+        // the open episode once it's paused
+        app.player.status.state = player::State::Paused;
+        assert_eq!(window_title(&app), "Episode 2");
+    }
+
+    #[test]
+    fn strips_control_characters() {
+        let app = App {
+            item: Some(Item {
+                title: Some("Evil\x1b]0;pwned\x07 episode".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(window_title(&app), "Evil]0;pwned episode");
+    }
+}
@@ -0,0 +1,103 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    widgets::{Clear, Paragraph},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::App;
+
+use super::block;
+
+// How long a toast shows for
+pub const LIFETIME: Duration = Duration::from_secs(4);
+
+// Most toasts shown at once, older ones make room for newer ones
+const MAX: usize = 3;
+
+// Widest a toast gets, as a percentage of the screen
+const MAX_WIDTH: u16 = 40;
+
+// Toasts are short notices of things that happened in the background, like a download
+// finishing, shown in the top right corner for a few seconds. Several stack up.
+#[derive(Default, Debug)]
+pub struct Toasts {
+    // messages and when they were pushed, oldest first
+    shown: VecDeque<(String, Instant)>,
+}
+
+impl Toasts {
+    pub fn push(&mut self, msg: String, at: Instant) {
+        self.shown.retain(|(_, t)| at.duration_since(*t) < LIFETIME);
+        if self.shown.len() == MAX {
+            self.shown.pop_front();
+        }
+        self.shown.push_back((msg, at));
+    }
+
+    // The toasts still showing at this time, oldest first
+    pub fn current(&self, now: Instant) -> impl Iterator<Item = &str> {
+        self.shown
+            .iter()
+            .filter(move |(_, t)| now.duration_since(*t) < LIFETIME)
+            .map(|(msg, _)| msg.as_str())
+    }
+}
+
+// Stack the toasts down from the top right corner of the area. Accessible mode has them in
+// the status line only.
+pub fn draw_toasts<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    if app.config.accessible {
+        return;
+    }
+    let max_width = area.width * MAX_WIDTH / 100;
+    let mut y = area.y;
+    for msg in app.toasts.current(Instant::now()) {
+        let width = (msg.width() as u16 + 4).min(max_width);
+        if y + 3 > area.bottom() {
+            break;
+        }
+        let rect = Rect {
+            x: area.right().saturating_sub(width),
+            y,
+            width,
+            height: 3,
+        };
+        let toast = Paragraph::new(msg).block(block(app, "").style(app.theme.status));
+        f.render_widget(Clear, rect);
+        f.render_widget(toast, rect);
+        y += 3;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{Toasts, LIFETIME};
+
+    #[test]
+    fn expires_and_stacks() {
+        let start = Instant::now();
+        let mut t = Toasts::default();
+        t.push("one".to_string(), start);
+        t.push("two".to_string(), start + Duration::from_secs(1));
+        assert_eq!(t.current(start).collect::<Vec<&str>>(), ["one", "two"]);
+        assert_eq!(t.current(start + LIFETIME).collect::<Vec<&str>>(), ["two"]);
+
+        // the oldest make room
+        t.push("three".to_string(), start + Duration::from_secs(2));
+        t.push("four".to_string(), start + Duration::from_secs(2));
+        assert_eq!(
+            t.current(start + Duration::from_secs(2))
+                .collect::<Vec<&str>>(),
+            ["two", "three", "four"]
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::{backend::Backend, layout::Rect, Frame};
+
+use crate::App;
+
+use super::{
+    component::{Action, Component},
+    draw_bookmarks, draw_clean_up, draw_diagnostics, draw_downloads, draw_history, draw_library,
+    draw_now_playing, draw_podcast_settings, draw_preview, draw_queue, draw_search, draw_starred,
+    draw_storage,
+};
+
+// The views below are lists the app keeps the selection of, so they have no state of their
+// own. Each draws its list and turns its keys into actions.
+
+// Up and Down move through any of the lists
+fn select(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Up => Some(Action::Previous),
+        KeyCode::Down => Some(Action::Next),
+        _ => None,
+    }
+}
+
+pub struct Preview;
+
+impl Component for Preview {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        draw_preview(f, app, area)
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Enter => Some(Action::ChoosePreview),
+            _ => select(key),
+        }
+    }
+}
+
+pub struct NowPlaying;
+
+impl Component for NowPlaying {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        draw_now_playing(f, app, area)
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Enter => Some(Action::TogglePause),
+            _ => None,
+        }
+    }
+}
+
+pub struct Queue;
+
+impl Component for Queue {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        draw_queue(f, app, area)
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        let action = match key.code {
+            KeyCode::Enter => Action::PlayEntry,
+            KeyCode::Char('K') => Action::MoveUp,
+            KeyCode::Char('J') => Action::MoveDown,
+            KeyCode::Char('T') => Action::MoveToTop,
+            KeyCode::Char('B') => Action::MoveToBottom,
+            _ => return select(key),
+        };
+        Some(action)
+    }
+}
+
+pub struct Bookmarks;
+
+impl Component for Bookmarks {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        draw_bookmarks(f, app, area)
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        let action = match key.code {
+            KeyCode::Enter => Action::PlayBookmark,
+            KeyCode::Char('d') => Action::DeleteBookmark,
+            _ => return select(key),
+        };
+        Some(action)
+    }
+}
+
+pub struct Search;
+
+impl Component for Search {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        draw_search(f, app, area)
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Enter => Some(Action::ChooseResult),
+            _ => select(key),
+        }
+    }
+}
+
+pub struct Diagnostics;
+
+impl Component for Diagnostics {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        draw_diagnostics(f, app, area)
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Char('s') => Some(Action::SortMetrics),
+            _ => select(key),
+        }
+    }
+}
+
+pub struct Library;
+
+impl Component for Library {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        draw_library(f, app, area)
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        let action = match key.code {
+            KeyCode::Enter => Action::OpenSubscription,
+            KeyCode::Char('s') => Action::SortLibrary,
+            KeyCode::Char('o') => Action::PodcastSettings,
+            _ => return select(key),
+        };
+        Some(action)
+    }
+}
+
+pub struct PodcastSettings;
+
+impl Component for PodcastSettings {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        draw_podcast_settings(f, app, area)
+    }
+
+    // Left and Right change the setting instead of seeking
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        let action = match key.code {
+            KeyCode::Enter | KeyCode::Right => Action::ChangeSetting(1),
+            KeyCode::Left => Action::ChangeSetting(-1),
+            KeyCode::Backspace => Action::ResetSetting,
+            _ => return select(key),
+        };
+        Some(action)
+    }
+}
+
+pub struct Starred;
+
+impl Component for Starred {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        draw_starred(f, app, area)
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        let action = match key.code {
+            KeyCode::Enter => Action::PlayStar,
+            KeyCode::Char('*') => Action::Star,
+            _ => return select(key),
+        };
+        Some(action)
+    }
+}
+
+pub struct History;
+
+impl Component for History {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        draw_history(f, app, area)
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Enter => Some(Action::ResumeListen),
+            _ => select(key),
+        }
+    }
+}
+
+pub struct Downloads;
+
+impl Component for Downloads {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        draw_downloads(f, app, area)
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        let action = match key.code {
+            KeyCode::Enter => Action::PlayDownload,
+            KeyCode::Char('d') => Action::Download,
+            KeyCode::Char('D') => Action::RemoveDownload,
+            KeyCode::Char('P') => Action::PauseDownload,
+            KeyCode::Char('c') => Action::CancelDownload,
+            _ => return select(key),
+        };
+        Some(action)
+    }
+}
+
+pub struct Storage;
+
+impl Component for Storage {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        draw_storage(f, app, area)
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Char('D') => Some(Action::ClearStorage),
+            _ => select(key),
+        }
+    }
+}
+
+pub struct CleanUp;
+
+impl Component for CleanUp {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, app: &mut App) {
+        draw_clean_up(f, app, area)
+    }
+
+    fn handle_event(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Char('D') => Some(Action::CleanUp),
+            _ => select(key),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use serde::Deserialize;
+use std::error::Error;
+
+// Latest release of the app on GitHub
+const RELEASES_URL: &str = "https://api.github.com/repos/SaxyPandaBear/PodcastsTUI/releases/latest";
+
+// Longest changelog summary shown in the status line
+const SUMMARY_LEN: usize = 80;
+
+// Release is a published version of the app
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Release {
+    // e.g. `v0.2.0`
+    #[serde(rename = "tag_name")]
+    pub version: String,
+    // release notes, in markdown
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+impl Release {
+    // First line of the release notes that isn't a heading, without list markers
+    pub fn summary(&self) -> Option<String> {
+        let line = self
+            .body
+            .as_deref()?
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty() && !l.starts_with('#'))?;
+        let line = line.trim_start_matches(['-', '*', ' ']);
+        Some(match line.char_indices().nth(SUMMARY_LEN) {
+            Some((idx, _)) => format!("{}…", &line[..idx]),
+            None => line.to_string(),
+        })
+    }
+}
+
+pub fn parse(s: &[u8]) -> Result<Release, Box<dyn Error>> {
+    Ok(serde_json::from_slice(s)?)
+}
+
+// Parse `v1.2.3` into its numbers, ignoring any pre-release suffix
+fn version(v: &str) -> Vec<u64> {
+    v.trim_start_matches('v')
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|n| n.parse().unwrap_or_default())
+        .collect()
+}
+
+pub fn is_newer(release: &str, current: &str) -> bool {
+    version(release) > version(current)
+}
+
+// The latest release, if it's newer than this build
+pub async fn check() -> Result<Option<Release>, Box<dyn Error>> {
+    // GitHub turns away requests without a User-Agent
+    let res = reqwest::Client::new()
+        .get(RELEASES_URL)
+        .header(reqwest::header::USER_AGENT, env!("CARGO_PKG_NAME"))
+        .send()
+        .await?
+        .error_for_status()?;
+    let release = parse(&res.bytes().await?)?;
+    Ok(Some(release).filter(|r| is_newer(r.version.as_str(), env!("CARGO_PKG_VERSION"))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_newer, parse};
+
+    #[test]
+    fn parses_release() {
+        let release = parse(
+            br#"{
+                "tag_name": "v0.2.0",
+                "name": "0.2.0",
+                "html_url": "https://github.com/SaxyPandaBear/PodcastsTUI/releases/tag/v0.2.0",
+                "body": "\n## What's new\n\n- Episodes can be played\n- Faster feeds"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(release.version, "v0.2.0");
+        assert_eq!(release.summary().as_deref(), Some("Episodes can be played"));
+    }
+
+    #[test]
+    fn compares_versions() {
+        assert!(is_newer("v0.2.0", "0.1.0"));
+        assert!(is_newer("0.10.0", "0.9.3"));
+        assert!(is_newer("v1.0", "0.9.9"));
+        assert!(!is_newer("v0.1.0", "0.1.0"));
+        assert!(!is_newer("v0.1.0-rc.1", "0.1.0"));
+        assert!(!is_newer("v0.0.9", "0.1.0"));
+    }
+}